@@ -3,25 +3,221 @@
 //! All gpui::actions! used across multiple crates are defined here
 //! to avoid circular dependencies.
 
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 
 use gpui::actions;
 
+mod keymap;
+mod registry;
+
+pub use keymap::{DefaultBinding, DEFAULT_KEYBINDINGS};
+pub use registry::{
+    find_action, parse_action, ActionSpec, ArgumentKind, ParseActionError, ACTIONS,
+};
+
 // Application lifecycle
-actions!(humanssh, [Quit, OpenSettings]);
+actions!(
+    humanssh,
+    [Quit, OpenSettings, ShowEffectiveConfig, NewWindow]
+);
 
 // Tab management
 actions!(humanssh, [NewTab, CloseTab, NextTab, PrevTab]);
 
+// Tab customization and ordering
+actions!(
+    humanssh,
+    [
+        RenameTab,
+        TogglePinTab,
+        MoveTabLeft,
+        MoveTabRight,
+        GoToLastTab
+    ]
+);
+
+/// Color marking a tab in the tab bar, drawn from the theme's ANSI palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabColor {
+    Red,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Magenta,
+}
+
+impl TabColor {
+    /// Every color, in menu order.
+    pub const ALL: [TabColor; 6] = [
+        TabColor::Red,
+        TabColor::Yellow,
+        TabColor::Green,
+        TabColor::Cyan,
+        TabColor::Blue,
+        TabColor::Magenta,
+    ];
+
+    /// Lowercase name, as used in action names (`tab-color-red`).
+    pub fn name(self) -> &'static str {
+        match self {
+            TabColor::Red => "red",
+            TabColor::Yellow => "yellow",
+            TabColor::Green => "green",
+            TabColor::Cyan => "cyan",
+            TabColor::Blue => "blue",
+            TabColor::Magenta => "magenta",
+        }
+    }
+
+    /// The color called `name`, as returned by [`TabColor::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.name() == name)
+    }
+}
+
+/// Color the active tab, or clear its color with `None`.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct SetTabColor {
+    pub color: Option<TabColor>,
+}
+
+/// Connect to the named profile in a new tab.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct NewTabWithProfile {
+    pub profile: String,
+}
+
+/// Where [`RunCommand`] opens the command's pane.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RunTarget {
+    #[default]
+    Tab,
+    /// Split beside the active pane.
+    Split,
+    /// The active tab's floating pane, or a split if the tab already
+    /// has one.
+    Floating,
+}
+
+/// Run a shell command line in a new pane that stays open after the
+/// command exits, showing its exit code.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct RunCommand {
+    pub command: String,
+    pub target: RunTarget,
+}
+
+/// Reopen a closed tab or pane; `index` 0 is the most recently closed.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct ReopenClosedTab {
+    pub index: usize,
+}
+
+/// Switch to the tab at `index` (0-based).
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct GoToTab {
+    pub index: usize,
+}
+
+// Tab groups (named sets of tabs within a window)
+actions!(
+    humanssh,
+    [NewTabGroup, NextTabGroup, PrevTabGroup, RenameTabGroup]
+);
+
+/// Show the tab group at `index` (0-based).
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct GoToTabGroup {
+    pub index: usize,
+}
+
 // Split management
 actions!(humanssh, [SplitVertical, SplitHorizontal, ClosePane]);
 
 // Focus navigation
 actions!(humanssh, [FocusNextPane, FocusPrevPane]);
 
+// Split sizing and layout
+actions!(
+    humanssh,
+    [EqualizePanes, RotatePanes, MovePaneToNewTab, TogglePaneZoom]
+);
+
+/// A direction from the active pane: which divider to move, or which
+/// neighbour to focus or swap with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaneDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Move the divider next to the active pane by `amount`, a share of the split
+/// (0.05 moves it by 5%).
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct ResizePane {
+    pub direction: PaneDirection,
+    pub amount: f32,
+}
+
+impl ResizePane {
+    /// Share of the split one keyboard resize moves the divider.
+    pub const STEP: f32 = 0.05;
+
+    /// Resize by [`ResizePane::STEP`].
+    pub fn step(direction: PaneDirection) -> Self {
+        Self {
+            direction,
+            amount: Self::STEP,
+        }
+    }
+}
+
+/// Focus the nearest pane on screen in `direction`.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct FocusPane {
+    pub direction: PaneDirection,
+}
+
+/// Swap the active pane with its nearest neighbour in `direction`.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct SwapPane {
+    pub direction: PaneDirection,
+}
+
+/// Move the active pane into the tab at `index` (0-based), beside its
+/// active pane.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct MovePaneToTab {
+    pub index: usize,
+}
+
+// Floating terminal over the split layout
+actions!(humanssh, [ToggleFloatingPane]);
+
 // macOS native features
 actions!(humanssh, [ToggleSecureInput, ToggleOptionAsAlt]);
 
+/// Type `text` into the active terminal, as if entered at the keyboard.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct SendText {
+    pub text: String,
+}
+
 // Search
 actions!(
     humanssh,
@@ -34,14 +230,60 @@ actions!(humanssh, [EnterCopyMode, ExitCopyMode]);
 // Command palette
 actions!(humanssh, [ToggleCommandPalette]);
 
+// Tab switcher (every tab and pane, most recently used first)
+actions!(humanssh, [ToggleTabSwitcher]);
+
+// Keybinding cheat sheet (every effective binding, by context)
+actions!(humanssh, [ToggleKeybindings]);
+
 // Scratchpad (persistent notes overlay)
 actions!(humanssh, [ToggleScratchpad]);
 
+// SSH port forwarding manager
+actions!(humanssh, [TogglePortForwards]);
+
+/// Start a port forward written like `L 8080:localhost:80 [user@host]`,
+/// through the active tab's SSH session unless a host is given.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct AddPortForward {
+    pub forward: String,
+}
+
+// File browser pane (local or SFTP)
+actions!(humanssh, [OpenFileBrowser]);
+
+// Serial console
+actions!(humanssh, [SendBreak, ToggleLocalEcho]);
+
+// Remote session reconnect
+actions!(humanssh, [Reconnect]);
+
+// Broadcast input to several panes
+actions!(
+    humanssh,
+    [ToggleBroadcastTab, ToggleBroadcastPane, StopBroadcast]
+);
+
+// Cluster open (grid of sessions in one tab)
+actions!(humanssh, [OpenCluster]);
+
 // Recording & replay
 actions!(humanssh, [StartRecording, StopRecording, OpenReplay]);
 
-// Terminal-specific actions to capture keys before GPUI's focus system
-actions!(terminal, [SendTab, SendShiftTab]);
+/// Replay the asciicast recording at `path` in a new tab.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct ReplayRecording {
+    pub path: PathBuf,
+}
+
+/// Switch to the theme called `name` and save it as the configured theme.
+#[derive(Clone, Debug, PartialEq, gpui::Action)]
+#[action(namespace = humanssh, no_json)]
+pub struct SelectTheme {
+    pub name: String,
+}
 
 /// When true (default), macOS Option key is treated as Alt for terminal input.
 /// When false, Option key is stripped from modifier set, allowing macOS to insert special characters.
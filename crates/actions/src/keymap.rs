@@ -0,0 +1,153 @@
+//! Default keybindings.
+//!
+//! The one list of built-in shortcuts: the app binds these on startup, before
+//! the user's `[[keybindings]]`, and the keybinding cheat sheet shows them.
+//! Actions are written as in the config (see [`crate::parse_action`]).
+
+/// A built-in keybinding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DefaultBinding {
+    /// Key chord, such as `cmd-shift-t`
+    pub keys: &'static str,
+    /// Action name with its argument, if any
+    pub action: &'static str,
+    /// Key context the binding applies in; `None` for everywhere
+    pub context: Option<&'static str>,
+}
+
+impl DefaultBinding {
+    const fn global(keys: &'static str, action: &'static str) -> Self {
+        Self {
+            keys,
+            action,
+            context: None,
+        }
+    }
+
+    const fn terminal(keys: &'static str, action: &'static str) -> Self {
+        Self {
+            keys,
+            action,
+            context: Some("terminal"),
+        }
+    }
+}
+
+/// Every built-in keybinding, in the order they are bound.
+pub const DEFAULT_KEYBINDINGS: &[DefaultBinding] = &[
+    // Quit
+    DefaultBinding::global("cmd-q", "quit"),
+    DefaultBinding::global("ctrl-q", "quit"),
+    // New window
+    DefaultBinding::global("cmd-shift-n", "new-window"),
+    DefaultBinding::global("ctrl-shift-n", "new-window"),
+    // Close tab
+    DefaultBinding::global("cmd-w", "close-tab"),
+    DefaultBinding::global("ctrl-w", "close-tab"),
+    // Settings
+    DefaultBinding::global("cmd-,", "open-settings"),
+    DefaultBinding::global("ctrl-,", "open-settings"),
+    // macOS: Toggle secure keyboard entry
+    DefaultBinding::global("cmd-shift-s", "toggle-secure-input"),
+    // Terminal-specific: Tab key
+    DefaultBinding::terminal("tab", r#"send-text "\t""#),
+    DefaultBinding::terminal("shift-tab", r#"send-text "\e[Z""#),
+    // Tab navigation
+    DefaultBinding::global("cmd-t", "new-tab"),
+    DefaultBinding::global("ctrl-shift-t", "new-tab"),
+    DefaultBinding::global("cmd-shift-t", "reopen-closed-tab"),
+    DefaultBinding::global("cmd-shift-]", "next-tab"),
+    DefaultBinding::global("ctrl-tab", "next-tab"),
+    DefaultBinding::global("cmd-shift-[", "prev-tab"),
+    DefaultBinding::global("ctrl-shift-tab", "prev-tab"),
+    DefaultBinding::global("cmd-1", "activate-tab 1"),
+    DefaultBinding::global("cmd-2", "activate-tab 2"),
+    DefaultBinding::global("cmd-3", "activate-tab 3"),
+    DefaultBinding::global("cmd-4", "activate-tab 4"),
+    DefaultBinding::global("cmd-5", "activate-tab 5"),
+    DefaultBinding::global("cmd-6", "activate-tab 6"),
+    DefaultBinding::global("cmd-7", "activate-tab 7"),
+    DefaultBinding::global("cmd-8", "activate-tab 8"),
+    DefaultBinding::global("cmd-9", "go-to-last-tab"),
+    // Tab management
+    DefaultBinding::global("cmd-shift-r", "rename-tab"),
+    DefaultBinding::global("ctrl-shift-pageup", "move-tab-left"),
+    DefaultBinding::global("ctrl-shift-pagedown", "move-tab-right"),
+    // Tab groups
+    DefaultBinding::global("cmd-alt-n", "new-tab-group"),
+    DefaultBinding::global("cmd-alt-]", "next-tab-group"),
+    DefaultBinding::global("cmd-alt-[", "prev-tab-group"),
+    // Splits
+    DefaultBinding::global("cmd-d", "split-vertical"),
+    DefaultBinding::global("cmd-shift-d", "split-horizontal"),
+    // Focus navigation
+    DefaultBinding::global("cmd-alt-left", "focus-pane-left"),
+    DefaultBinding::global("cmd-alt-right", "focus-pane-right"),
+    DefaultBinding::global("cmd-alt-up", "focus-pane-up"),
+    DefaultBinding::global("cmd-alt-down", "focus-pane-down"),
+    // Pane layout
+    DefaultBinding::global("cmd-alt-shift-left", "swap-pane-left"),
+    DefaultBinding::global("cmd-alt-shift-right", "swap-pane-right"),
+    DefaultBinding::global("cmd-alt-shift-up", "swap-pane-up"),
+    DefaultBinding::global("cmd-alt-shift-down", "swap-pane-down"),
+    // Split sizing
+    DefaultBinding::global("cmd-ctrl-left", "resize-pane-left"),
+    DefaultBinding::global("cmd-ctrl-right", "resize-pane-right"),
+    DefaultBinding::global("cmd-ctrl-up", "resize-pane-up"),
+    DefaultBinding::global("cmd-ctrl-down", "resize-pane-down"),
+    DefaultBinding::global("cmd-ctrl-=", "equalize-panes"),
+    DefaultBinding::global("cmd-shift-enter", "toggle-pane-zoom"),
+    // Floating terminal
+    DefaultBinding::global("cmd-alt-f", "toggle-floating-pane"),
+    // Search
+    DefaultBinding::terminal("cmd-f", "search"),
+    DefaultBinding::terminal("ctrl-f", "search"),
+    DefaultBinding::terminal("cmd-alt-r", "search-toggle-regex"),
+    DefaultBinding::terminal("alt-r", "search-toggle-regex"),
+    DefaultBinding::terminal("cmd-g", "search-next"),
+    DefaultBinding::terminal("cmd-shift-g", "search-prev"),
+    // Copy mode
+    DefaultBinding::terminal("cmd-shift-c", "enter-copy-mode"),
+    // Command palette
+    DefaultBinding::global("cmd-shift-p", "toggle-command-palette"),
+    DefaultBinding::global("ctrl-shift-p", "toggle-command-palette"),
+    // Tab switcher
+    DefaultBinding::global("cmd-p", "toggle-tab-switcher"),
+    // Keybinding cheat sheet
+    DefaultBinding::global("cmd-/", "toggle-keybindings"),
+    DefaultBinding::global("ctrl-shift-/", "toggle-keybindings"),
+    // Scratchpad (persistent notes overlay)
+    DefaultBinding::global("ctrl-`", "toggle-scratchpad"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn every_default_action_parses() {
+        for binding in DEFAULT_KEYBINDINGS {
+            assert!(
+                parse_action(binding.action).is_ok(),
+                "{} -> {}",
+                binding.keys,
+                binding.action
+            );
+        }
+    }
+
+    #[test]
+    fn defaults_build_the_expected_actions() {
+        let action = |keys: &str| {
+            let binding = DEFAULT_KEYBINDINGS.iter().find(|b| b.keys == keys).unwrap();
+            parse_action(binding.action).unwrap()
+        };
+        assert!(action("cmd-3").partial_eq(&GoToTab { index: 2 }));
+        assert!(action("cmd-shift-t").partial_eq(&ReopenClosedTab { index: 0 }));
+        assert!(action("cmd-ctrl-up").partial_eq(&ResizePane::step(PaneDirection::Up)));
+        assert!(action("shift-tab").partial_eq(&SendText {
+            text: "\x1b[Z".to_string()
+        }));
+    }
+}
@@ -0,0 +1,554 @@
+//! Registry of named actions.
+//!
+//! Every action users can refer to by name — in `[[keybindings]]`, in the
+//! command palette, or from anywhere else that takes an action as text — is
+//! listed once in [`ACTIONS`]. An action spec is a name, optionally followed
+//! by one argument:
+//!
+//! ```text
+//! new-tab
+//! activate-tab 3
+//! send-text "ls -la\n"
+//! new-tab-with-profile prod
+//! ```
+//!
+//! Numbered actions also accept the argument as a suffix (`activate-tab-3`,
+//! `go-to-tab-group-2`), the spelling older configs use.
+
+use std::fmt;
+
+use gpui::Action;
+
+use crate::*;
+
+/// The argument an action takes after its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgumentKind {
+    /// No argument.
+    None,
+    /// A number counting from 1, such as a tab position.
+    Number,
+    /// Free text; quote it to use escapes like `\n`, `\e` or `\x03`.
+    Text,
+}
+
+/// How a registry entry builds its action.
+#[derive(Clone, Copy)]
+enum Builder {
+    Unit(fn() -> Box<dyn Action>),
+    Number(fn(usize) -> Box<dyn Action>),
+    Text(fn(String) -> Box<dyn Action>),
+}
+
+/// A named action in the registry.
+#[derive(Clone, Copy)]
+pub struct ActionSpec {
+    /// Name used in config files, e.g. `new-tab`.
+    pub name: &'static str,
+    /// Title shown in the command palette.
+    pub label: &'static str,
+    build: Builder,
+}
+
+impl ActionSpec {
+    const fn unit(name: &'static str, label: &'static str, build: fn() -> Box<dyn Action>) -> Self {
+        Self {
+            name,
+            label,
+            build: Builder::Unit(build),
+        }
+    }
+
+    const fn number(
+        name: &'static str,
+        label: &'static str,
+        build: fn(usize) -> Box<dyn Action>,
+    ) -> Self {
+        Self {
+            name,
+            label,
+            build: Builder::Number(build),
+        }
+    }
+
+    const fn text(
+        name: &'static str,
+        label: &'static str,
+        build: fn(String) -> Box<dyn Action>,
+    ) -> Self {
+        Self {
+            name,
+            label,
+            build: Builder::Text(build),
+        }
+    }
+
+    /// The argument this action takes.
+    pub fn argument(&self) -> ArgumentKind {
+        match self.build {
+            Builder::Unit(_) => ArgumentKind::None,
+            Builder::Number(_) => ArgumentKind::Number,
+            Builder::Text(_) => ArgumentKind::Text,
+        }
+    }
+
+    /// The action itself, for entries that take no argument.
+    pub fn action(&self) -> Option<Box<dyn Action>> {
+        match self.build {
+            Builder::Unit(build) => Some(build()),
+            Builder::Number(_) | Builder::Text(_) => None,
+        }
+    }
+
+    /// Build the action from the text after its name.
+    fn build(&self, argument: &str) -> Result<Box<dyn Action>, ParseActionError> {
+        match self.build {
+            Builder::Unit(build) if argument.is_empty() => Ok(build()),
+            Builder::Unit(_) => Err(ParseActionError::UnexpectedArgument(self.name)),
+            Builder::Number(_) | Builder::Text(_) if argument.is_empty() => {
+                Err(ParseActionError::MissingArgument(self.name))
+            }
+            Builder::Number(build) => match argument.parse::<usize>() {
+                Ok(n) if n >= 1 => Ok(build(n - 1)),
+                _ => Err(ParseActionError::InvalidNumber(
+                    self.name,
+                    argument.to_string(),
+                )),
+            },
+            Builder::Text(build) => Ok(build(parse_text(argument)?)),
+        }
+    }
+}
+
+/// Why an action spec could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseActionError {
+    Empty,
+    Unknown(String),
+    MissingArgument(&'static str),
+    UnexpectedArgument(&'static str),
+    InvalidNumber(&'static str, String),
+    UnterminatedString,
+    InvalidEscape(String),
+}
+
+impl fmt::Display for ParseActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("no action given"),
+            Self::Unknown(name) => write!(f, "unknown action '{}'", name),
+            Self::MissingArgument(name) => write!(f, "'{}' needs an argument", name),
+            Self::UnexpectedArgument(name) => write!(f, "'{}' takes no argument", name),
+            Self::InvalidNumber(name, value) => {
+                write!(f, "'{}' needs a number from 1, not '{}'", name, value)
+            }
+            Self::UnterminatedString => f.write_str("missing closing quote"),
+            Self::InvalidEscape(escape) => write!(f, "unknown escape '\\{}'", escape),
+        }
+    }
+}
+
+impl std::error::Error for ParseActionError {}
+
+/// Older names still accepted for registry entries.
+const ALIASES: &[(&str, &str)] = &[("go-to-tab", "activate-tab")];
+
+/// Every named action, in command palette order.
+pub static ACTIONS: &[ActionSpec] = &[
+    ActionSpec::unit("new-window", "New Window", || Box::new(NewWindow)),
+    ActionSpec::unit("new-tab", "New Tab", || Box::new(NewTab)),
+    ActionSpec::text("new-tab-with-profile", "New Tab with Profile", |profile| {
+        Box::new(NewTabWithProfile { profile })
+    }),
+    ActionSpec::text("run-command", "Run Command", |command| {
+        Box::new(RunCommand {
+            command,
+            target: RunTarget::Tab,
+        })
+    }),
+    ActionSpec::text("run-command-split", "Run Command in Split", |command| {
+        Box::new(RunCommand {
+            command,
+            target: RunTarget::Split,
+        })
+    }),
+    ActionSpec::text(
+        "run-command-floating",
+        "Run Command in Floating Pane",
+        |command| {
+            Box::new(RunCommand {
+                command,
+                target: RunTarget::Floating,
+            })
+        },
+    ),
+    ActionSpec::unit("close-tab", "Close Tab", || Box::new(CloseTab)),
+    ActionSpec::unit("reopen-closed-tab", "Reopen Closed Tab", || {
+        Box::new(ReopenClosedTab { index: 0 })
+    }),
+    ActionSpec::unit("next-tab", "Next Tab", || Box::new(NextTab)),
+    ActionSpec::unit("prev-tab", "Previous Tab", || Box::new(PrevTab)),
+    ActionSpec::unit("toggle-tab-switcher", "Switch Tab or Pane", || {
+        Box::new(ToggleTabSwitcher)
+    }),
+    ActionSpec::number("activate-tab", "Go to Tab", |index| {
+        Box::new(GoToTab { index })
+    }),
+    ActionSpec::unit("go-to-last-tab", "Go to Last Tab", || Box::new(GoToLastTab)),
+    ActionSpec::unit("rename-tab", "Rename Tab", || Box::new(RenameTab)),
+    ActionSpec::unit("toggle-pin-tab", "Toggle Pin Tab", || {
+        Box::new(TogglePinTab)
+    }),
+    ActionSpec::unit("move-tab-left", "Move Tab Left", || Box::new(MoveTabLeft)),
+    ActionSpec::unit("move-tab-right", "Move Tab Right", || {
+        Box::new(MoveTabRight)
+    }),
+    ActionSpec::unit("tab-color-red", "Tab Color: Red", || {
+        Box::new(SetTabColor {
+            color: Some(TabColor::Red),
+        })
+    }),
+    ActionSpec::unit("tab-color-yellow", "Tab Color: Yellow", || {
+        Box::new(SetTabColor {
+            color: Some(TabColor::Yellow),
+        })
+    }),
+    ActionSpec::unit("tab-color-green", "Tab Color: Green", || {
+        Box::new(SetTabColor {
+            color: Some(TabColor::Green),
+        })
+    }),
+    ActionSpec::unit("tab-color-cyan", "Tab Color: Cyan", || {
+        Box::new(SetTabColor {
+            color: Some(TabColor::Cyan),
+        })
+    }),
+    ActionSpec::unit("tab-color-blue", "Tab Color: Blue", || {
+        Box::new(SetTabColor {
+            color: Some(TabColor::Blue),
+        })
+    }),
+    ActionSpec::unit("tab-color-magenta", "Tab Color: Magenta", || {
+        Box::new(SetTabColor {
+            color: Some(TabColor::Magenta),
+        })
+    }),
+    ActionSpec::unit("clear-tab-color", "Clear Tab Color", || {
+        Box::new(SetTabColor { color: None })
+    }),
+    ActionSpec::unit("new-tab-group", "New Tab Group", || Box::new(NewTabGroup)),
+    ActionSpec::unit("next-tab-group", "Next Tab Group", || {
+        Box::new(NextTabGroup)
+    }),
+    ActionSpec::unit("prev-tab-group", "Previous Tab Group", || {
+        Box::new(PrevTabGroup)
+    }),
+    ActionSpec::number("go-to-tab-group", "Go to Tab Group", |index| {
+        Box::new(GoToTabGroup { index })
+    }),
+    ActionSpec::unit("rename-tab-group", "Rename Tab Group", || {
+        Box::new(RenameTabGroup)
+    }),
+    ActionSpec::unit("split-vertical", "Split Vertical", || {
+        Box::new(SplitVertical)
+    }),
+    ActionSpec::unit("split-horizontal", "Split Horizontal", || {
+        Box::new(SplitHorizontal)
+    }),
+    ActionSpec::unit("close-pane", "Close Pane", || Box::new(ClosePane)),
+    ActionSpec::unit("focus-pane-left", "Focus Pane Left", || {
+        Box::new(FocusPane {
+            direction: PaneDirection::Left,
+        })
+    }),
+    ActionSpec::unit("focus-pane-right", "Focus Pane Right", || {
+        Box::new(FocusPane {
+            direction: PaneDirection::Right,
+        })
+    }),
+    ActionSpec::unit("focus-pane-up", "Focus Pane Up", || {
+        Box::new(FocusPane {
+            direction: PaneDirection::Up,
+        })
+    }),
+    ActionSpec::unit("focus-pane-down", "Focus Pane Down", || {
+        Box::new(FocusPane {
+            direction: PaneDirection::Down,
+        })
+    }),
+    ActionSpec::unit("focus-next-pane", "Focus Next Pane", || {
+        Box::new(FocusNextPane)
+    }),
+    ActionSpec::unit("focus-prev-pane", "Focus Previous Pane", || {
+        Box::new(FocusPrevPane)
+    }),
+    ActionSpec::unit("swap-pane-left", "Swap Pane Left", || {
+        Box::new(SwapPane {
+            direction: PaneDirection::Left,
+        })
+    }),
+    ActionSpec::unit("swap-pane-right", "Swap Pane Right", || {
+        Box::new(SwapPane {
+            direction: PaneDirection::Right,
+        })
+    }),
+    ActionSpec::unit("swap-pane-up", "Swap Pane Up", || {
+        Box::new(SwapPane {
+            direction: PaneDirection::Up,
+        })
+    }),
+    ActionSpec::unit("swap-pane-down", "Swap Pane Down", || {
+        Box::new(SwapPane {
+            direction: PaneDirection::Down,
+        })
+    }),
+    ActionSpec::unit("toggle-floating-pane", "Toggle Floating Terminal", || {
+        Box::new(ToggleFloatingPane)
+    }),
+    ActionSpec::unit("toggle-pane-zoom", "Toggle Pane Zoom", || {
+        Box::new(TogglePaneZoom)
+    }),
+    ActionSpec::unit("rotate-panes", "Rotate Panes", || Box::new(RotatePanes)),
+    ActionSpec::unit("move-pane-to-new-tab", "Move Pane to New Tab", || {
+        Box::new(MovePaneToNewTab)
+    }),
+    ActionSpec::number("move-pane-to-tab", "Move Pane to Tab", |index| {
+        Box::new(MovePaneToTab { index })
+    }),
+    ActionSpec::unit("resize-pane-left", "Resize Pane Left", || {
+        Box::new(ResizePane::step(PaneDirection::Left))
+    }),
+    ActionSpec::unit("resize-pane-right", "Resize Pane Right", || {
+        Box::new(ResizePane::step(PaneDirection::Right))
+    }),
+    ActionSpec::unit("resize-pane-up", "Resize Pane Up", || {
+        Box::new(ResizePane::step(PaneDirection::Up))
+    }),
+    ActionSpec::unit("resize-pane-down", "Resize Pane Down", || {
+        Box::new(ResizePane::step(PaneDirection::Down))
+    }),
+    ActionSpec::unit("equalize-panes", "Equalize Splits", || {
+        Box::new(EqualizePanes)
+    }),
+    ActionSpec::text("send-text", "Send Text", |text| Box::new(SendText { text })),
+    ActionSpec::unit("search", "Search", || Box::new(SearchToggle)),
+    ActionSpec::unit("search-next", "Search Next", || Box::new(SearchNext)),
+    ActionSpec::unit("search-prev", "Search Previous", || Box::new(SearchPrev)),
+    ActionSpec::unit("search-toggle-regex", "Toggle Regex Search", || {
+        Box::new(SearchToggleRegex)
+    }),
+    ActionSpec::unit("enter-copy-mode", "Enter Copy Mode", || {
+        Box::new(EnterCopyMode)
+    }),
+    ActionSpec::unit("exit-copy-mode", "Exit Copy Mode", || {
+        Box::new(ExitCopyMode)
+    }),
+    ActionSpec::unit("open-settings", "Open Settings", || Box::new(OpenSettings)),
+    ActionSpec::unit("show-effective-config", "Show Effective Config", || {
+        Box::new(ShowEffectiveConfig)
+    }),
+    ActionSpec::unit("toggle-secure-input", "Toggle Secure Input", || {
+        Box::new(ToggleSecureInput)
+    }),
+    ActionSpec::unit("toggle-option-as-alt", "Toggle Option as Alt", || {
+        Box::new(ToggleOptionAsAlt)
+    }),
+    ActionSpec::unit("toggle-command-palette", "Toggle Command Palette", || {
+        Box::new(ToggleCommandPalette)
+    }),
+    ActionSpec::unit("toggle-keybindings", "Show Keybindings", || {
+        Box::new(ToggleKeybindings)
+    }),
+    ActionSpec::unit("toggle-scratchpad", "Toggle Scratchpad", || {
+        Box::new(ToggleScratchpad)
+    }),
+    ActionSpec::unit("toggle-port-forwards", "Port Forwards", || {
+        Box::new(TogglePortForwards)
+    }),
+    ActionSpec::text("add-port-forward", "Add Port Forward", |forward| {
+        Box::new(AddPortForward { forward })
+    }),
+    ActionSpec::unit("open-file-browser", "Open File Browser", || {
+        Box::new(OpenFileBrowser)
+    }),
+    ActionSpec::unit("reconnect", "Reconnect", || Box::new(Reconnect)),
+    ActionSpec::unit("send-break", "Send Break", || Box::new(SendBreak)),
+    ActionSpec::unit("toggle-local-echo", "Toggle Local Echo", || {
+        Box::new(ToggleLocalEcho)
+    }),
+    ActionSpec::unit("open-cluster", "Open Cluster", || Box::new(OpenCluster)),
+    ActionSpec::unit("toggle-broadcast-tab", "Broadcast Input to Tab", || {
+        Box::new(ToggleBroadcastTab)
+    }),
+    ActionSpec::unit("toggle-broadcast-pane", "Toggle Pane in Broadcast", || {
+        Box::new(ToggleBroadcastPane)
+    }),
+    ActionSpec::unit("stop-broadcast", "Stop Broadcast", || {
+        Box::new(StopBroadcast)
+    }),
+    ActionSpec::unit("start-recording", "Start Recording", || {
+        Box::new(StartRecording)
+    }),
+    ActionSpec::unit("stop-recording", "Stop Recording", || {
+        Box::new(StopRecording)
+    }),
+    ActionSpec::unit("open-replay", "Open Replay", || Box::new(OpenReplay)),
+    ActionSpec::text("replay-recording", "Replay Recording", |path| {
+        Box::new(ReplayRecording { path: path.into() })
+    }),
+    ActionSpec::text("select-theme", "Select Theme", |name| {
+        Box::new(SelectTheme { name })
+    }),
+    ActionSpec::unit("quit", "Quit", || Box::new(Quit)),
+];
+
+/// The registry entry called `name`, or one of its older names.
+pub fn find_action(name: &str) -> Option<&'static ActionSpec> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, name)| name);
+    ACTIONS.iter().find(|spec| spec.name == name)
+}
+
+/// Parse an action spec such as `new-tab`, `activate-tab 3` or
+/// `send-text "echo hi\n"`.
+pub fn parse_action(input: &str) -> Result<Box<dyn Action>, ParseActionError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ParseActionError::Empty);
+    }
+    let (name, argument) = input
+        .split_once(char::is_whitespace)
+        .map_or((input, ""), |(name, rest)| (name, rest.trim()));
+
+    if let Some(spec) = find_action(name) {
+        return spec.build(argument);
+    }
+
+    // `activate-tab-3`: the number as part of the name
+    if let Some((prefix, number)) = name.rsplit_once('-') {
+        if let Some(spec) = find_action(prefix) {
+            if spec.argument() == ArgumentKind::Number && argument.is_empty() {
+                return spec.build(number);
+            }
+        }
+    }
+
+    Err(ParseActionError::Unknown(name.to_string()))
+}
+
+/// A text argument: everything after the name, or the contents of a quoted
+/// string with its escapes resolved.
+fn parse_text(argument: &str) -> Result<String, ParseActionError> {
+    let Some(quoted) = argument.strip_prefix('"') else {
+        return Ok(argument.to_string());
+    };
+
+    let mut text = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                return if chars.as_str().trim().is_empty() {
+                    Ok(text)
+                } else {
+                    Err(ParseActionError::UnterminatedString)
+                };
+            }
+            '\\' => {
+                let escape = chars.next().ok_or(ParseActionError::UnterminatedString)?;
+                text.push(match escape {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'e' => '\x1b',
+                    '0' => '\0',
+                    '\\' => '\\',
+                    '"' => '"',
+                    'x' => {
+                        let hex: String = chars.by_ref().take(2).collect();
+                        u8::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|byte| byte.is_ascii())
+                            .map(char::from)
+                            .ok_or_else(|| ParseActionError::InvalidEscape(format!("x{}", hex)))?
+                    }
+                    other => return Err(ParseActionError::InvalidEscape(other.to_string())),
+                });
+            }
+            c => text.push(c),
+        }
+    }
+    Err(ParseActionError::UnterminatedString)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_numbered_actions() {
+        assert!(parse_action("new-tab").unwrap().partial_eq(&NewTab));
+        assert!(parse_action("activate-tab 3")
+            .unwrap()
+            .partial_eq(&GoToTab { index: 2 }));
+        assert!(parse_action("go-to-tab-3")
+            .unwrap()
+            .partial_eq(&GoToTab { index: 2 }));
+        assert!(parse_action("go-to-tab-group-2")
+            .unwrap()
+            .partial_eq(&GoToTabGroup { index: 1 }));
+    }
+
+    #[test]
+    fn parses_text_arguments() {
+        assert!(parse_action(r#"send-text "ls -la\n""#)
+            .unwrap()
+            .partial_eq(&SendText {
+                text: "ls -la\n".into()
+            }));
+        assert!(parse_action(r#"send-text "\x03""#)
+            .unwrap()
+            .partial_eq(&SendText {
+                text: "\x03".into()
+            }));
+        assert!(parse_action("new-tab-with-profile prod")
+            .unwrap()
+            .partial_eq(&NewTabWithProfile {
+                profile: "prod".into()
+            }));
+    }
+
+    #[test]
+    fn rejects_bad_specs() {
+        assert_eq!(
+            parse_action("no-such-action").unwrap_err(),
+            ParseActionError::Unknown("no-such-action".into())
+        );
+        assert_eq!(
+            parse_action("new-tab 2").unwrap_err(),
+            ParseActionError::UnexpectedArgument("new-tab")
+        );
+        assert_eq!(
+            parse_action("activate-tab").unwrap_err(),
+            ParseActionError::MissingArgument("activate-tab")
+        );
+        assert_eq!(
+            parse_action("activate-tab 0").unwrap_err(),
+            ParseActionError::InvalidNumber("activate-tab", "0".into())
+        );
+        assert_eq!(
+            parse_action(r#"send-text "oops"#).unwrap_err(),
+            ParseActionError::UnterminatedString
+        );
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut names: Vec<&str> = ACTIONS.iter().map(|spec| spec.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), ACTIONS.len());
+    }
+}
@@ -2,19 +2,19 @@
 //!
 //! Main entry point for the application.
 
-use actions::{
-    ClosePane, CloseTab, EnterCopyMode, ExitCopyMode, FocusNextPane, FocusPrevPane, NewTab,
-    NextTab, OpenSettings, PrevTab, Quit, SearchNext, SearchPrev, SearchToggle, SearchToggleRegex,
-    SendShiftTab, SendTab, SplitHorizontal, SplitVertical, ToggleCommandPalette, ToggleOptionAsAlt,
-    ToggleScratchpad, ToggleSecureInput,
-};
+use actions::{NewWindow, Quit};
 use anyhow::{Context, Result};
 use gpui::*;
 use gpui_component_assets::Assets;
-use humanssh_workspace::Workspace;
+use humanssh_workspace::{
+    check_config_without_app, open_workspace_window, refresh_config_diagnostics, restore_session,
+    send_remote_action, set_key_sequences, set_reopen_with_scrollback, shadowing_bindings,
+    start_remote_control, KeySequence, WindowOptionsBuilder, Workspace,
+};
 use once_cell::sync::Lazy;
+use std::rc::Rc;
 use std::time::Instant;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Application startup time for performance monitoring
 static STARTUP_TIME: Lazy<Instant> = Lazy::new(Instant::now);
@@ -195,12 +195,47 @@ fn build_titlebar_options() -> TitlebarOptions {
     }
 }
 
-/// Open the main application window.
-fn open_main_window(cx: &mut App) -> Result<()> {
-    let options = build_window_options(cx);
-    cx.open_window(options, |window, cx| {
+/// What to open on startup, from the command line.
+enum Launch {
+    /// A profile name, a serial device (`/dev/ttyUSB0@9600,8N1`), a
+    /// `telnet://` or `tcp://` URL, or `[user@]host[:port]`.
+    Connect(String),
+    /// `--cluster <group | host,host,...>`: a grid of sessions in one tab.
+    Cluster(String),
+}
+
+fn launch_from_args() -> Option<Launch> {
+    let mut args = std::env::args().skip(1);
+    match args.next()?.as_str() {
+        "--cluster" => {
+            let spec = args.next();
+            if spec.is_none() {
+                warn!("--cluster needs a profile group or a comma-separated host list");
+            }
+            spec.map(Launch::Cluster)
+        }
+        destination => Some(Launch::Connect(destination.to_string())),
+    }
+}
+
+/// Open the main application window, optionally connecting to a destination.
+fn open_main_window(cx: &mut App, launch: Option<Launch>) -> Result<()> {
+    open_workspace_window(cx, None, |_window, cx| {
         let app_view = cx.new(Workspace::new);
-        cx.new(|cx| gpui_component::Root::new(app_view, window, cx))
+        match launch {
+            Some(Launch::Connect(destination)) => {
+                app_view.update(cx, |workspace, cx| workspace.connect(&destination, cx));
+            }
+            Some(Launch::Cluster(spec)) => {
+                app_view.update(cx, |workspace, cx| {
+                    if let Err(error) = workspace.open_cluster(&spec, cx) {
+                        error!("Cannot open cluster: {}", error);
+                    }
+                });
+            }
+            None => {}
+        }
+        app_view
     })
     .context("Failed to open main window")?;
 
@@ -214,50 +249,24 @@ fn register_keybindings(cx: &mut App) {
         info!("Application quit requested (fallback)");
         cx.quit();
     });
+    cx.on_action(|_: &NewWindow, cx| {
+        if let Err(e) = open_main_window(cx, None) {
+            error!("Failed to open window: {}", e);
+        }
+    });
 
-    cx.bind_keys([
-        // Quit
-        KeyBinding::new("cmd-q", Quit, None),
-        KeyBinding::new("ctrl-q", Quit, None),
-        // Close tab
-        KeyBinding::new("cmd-w", CloseTab, None),
-        KeyBinding::new("ctrl-w", CloseTab, None),
-        // Settings
-        KeyBinding::new("cmd-,", OpenSettings, None),
-        KeyBinding::new("ctrl-,", OpenSettings, None),
-        // macOS: Toggle secure keyboard entry
-        KeyBinding::new("cmd-shift-s", ToggleSecureInput, None),
-        // Terminal-specific: Tab key
-        KeyBinding::new("tab", SendTab, Some("terminal")),
-        KeyBinding::new("shift-tab", SendShiftTab, Some("terminal")),
-        // Tab navigation
-        KeyBinding::new("cmd-t", NewTab, None),
-        KeyBinding::new("ctrl-shift-t", NewTab, None),
-        KeyBinding::new("cmd-shift-]", NextTab, None),
-        KeyBinding::new("ctrl-tab", NextTab, None),
-        KeyBinding::new("cmd-shift-[", PrevTab, None),
-        KeyBinding::new("ctrl-shift-tab", PrevTab, None),
-        // Splits
-        KeyBinding::new("cmd-d", SplitVertical, None),
-        KeyBinding::new("cmd-shift-d", SplitHorizontal, None),
-        // Focus navigation
-        KeyBinding::new("cmd-alt-right", FocusNextPane, None),
-        KeyBinding::new("cmd-alt-left", FocusPrevPane, None),
-        // Search
-        KeyBinding::new("cmd-f", SearchToggle, Some("terminal")),
-        KeyBinding::new("ctrl-f", SearchToggle, Some("terminal")),
-        KeyBinding::new("cmd-alt-r", SearchToggleRegex, Some("terminal")),
-        KeyBinding::new("alt-r", SearchToggleRegex, Some("terminal")),
-        KeyBinding::new("cmd-g", SearchNext, Some("terminal")),
-        KeyBinding::new("cmd-shift-g", SearchPrev, Some("terminal")),
-        // Copy mode
-        KeyBinding::new("cmd-shift-c", EnterCopyMode, Some("terminal")),
-        // Command palette
-        KeyBinding::new("cmd-shift-p", ToggleCommandPalette, None),
-        KeyBinding::new("ctrl-shift-p", ToggleCommandPalette, None),
-        // Scratchpad (persistent notes overlay)
-        KeyBinding::new("ctrl-`", ToggleScratchpad, None),
-    ]);
+    let bindings: Vec<KeyBinding> = actions::DEFAULT_KEYBINDINGS
+        .iter()
+        .filter_map(|binding| {
+            let action = actions::parse_action(binding.action)
+                .map_err(|e| warn!("Bad default binding '{}': {}", binding.action, e))
+                .ok()?;
+            load_binding(binding.keys, action, binding.context)
+                .map_err(|e| warn!("Bad default binding '{}': {:#}", binding.keys, e))
+                .ok()
+        })
+        .collect();
+    cx.bind_keys(bindings);
 
     // Apply user custom keybindings (these override defaults since GPUI uses last-wins)
     let config = settings::load_config();
@@ -267,48 +276,53 @@ fn register_keybindings(cx: &mut App) {
 }
 
 /// Apply custom keybindings from user config.
-/// Maps action name strings to GPUI KeyBinding registrations.
+/// Action names are looked up in the action registry (see `actions::ACTIONS`).
+/// Multi-chord keys (`ctrl-a v`) become key sequences, matched by the workspace;
+/// one whose first chord is also bound on its own is reported, as it can't start.
 fn apply_custom_keybindings(config: &settings::Config, cx: &mut App) {
     let mut bindings: Vec<KeyBinding> = Vec::new();
+    let mut sequences: Vec<KeySequence> = Vec::new();
 
     for entry in &config.keybindings {
-        let context = entry.context.as_deref();
-        let keys = entry.keys.as_str();
-
-        // Map action name to concrete action type
-        match entry.action.as_str() {
-            "quit" => bindings.push(KeyBinding::new(keys, Quit, context)),
-            "new-tab" => bindings.push(KeyBinding::new(keys, NewTab, context)),
-            "close-tab" => bindings.push(KeyBinding::new(keys, CloseTab, context)),
-            "next-tab" => bindings.push(KeyBinding::new(keys, NextTab, context)),
-            "prev-tab" => bindings.push(KeyBinding::new(keys, PrevTab, context)),
-            "split-vertical" => bindings.push(KeyBinding::new(keys, SplitVertical, context)),
-            "split-horizontal" => bindings.push(KeyBinding::new(keys, SplitHorizontal, context)),
-            "close-pane" => bindings.push(KeyBinding::new(keys, ClosePane, context)),
-            "focus-next-pane" => bindings.push(KeyBinding::new(keys, FocusNextPane, context)),
-            "focus-prev-pane" => bindings.push(KeyBinding::new(keys, FocusPrevPane, context)),
-            "open-settings" => bindings.push(KeyBinding::new(keys, OpenSettings, context)),
-            "toggle-secure-input" => {
-                bindings.push(KeyBinding::new(keys, ToggleSecureInput, context))
+        let action = match actions::parse_action(&entry.action) {
+            Ok(action) => action,
+            Err(error) => {
+                warn!(
+                    "Ignoring keybinding '{}' -> '{}': {}",
+                    entry.keys, entry.action, error
+                );
+                continue;
             }
-            "toggle-option-as-alt" => {
-                bindings.push(KeyBinding::new(keys, ToggleOptionAsAlt, context))
+        };
+        if entry.keys.split_whitespace().nth(1).is_some() {
+            let context = entry.context.as_deref();
+            match KeySequence::parse(&entry.keys, action, entry.action.clone(), context) {
+                Some(sequence) => sequences.push(sequence),
+                None => {
+                    warn!(
+                        "Ignoring keybinding '{}' -> '{}': invalid keys or context",
+                        entry.keys, entry.action
+                    );
+                    continue;
+                }
             }
-            "search" => bindings.push(KeyBinding::new(keys, SearchToggle, context)),
-            "search-next" => bindings.push(KeyBinding::new(keys, SearchNext, context)),
-            "search-prev" => bindings.push(KeyBinding::new(keys, SearchPrev, context)),
-            "search-toggle-regex" => {
-                bindings.push(KeyBinding::new(keys, SearchToggleRegex, context))
-            }
-            "enter-copy-mode" => bindings.push(KeyBinding::new(keys, EnterCopyMode, context)),
-            "exit-copy-mode" => bindings.push(KeyBinding::new(keys, ExitCopyMode, context)),
-            "toggle-scratchpad" => bindings.push(KeyBinding::new(keys, ToggleScratchpad, context)),
-            "toggle-command-palette" => {
-                bindings.push(KeyBinding::new(keys, ToggleCommandPalette, context))
-            }
-            other => {
-                tracing::warn!("Unknown keybinding action: '{}'", other);
+            let shadowed_by = shadowing_bindings(&entry.keys, context, config);
+            if !shadowed_by.is_empty() {
+                warn!(
+                    "Key sequence '{}' -> '{}' can't start: its first chord is bound to {}",
+                    entry.keys,
+                    entry.action,
+                    shadowed_by.join(", ")
+                );
             }
+            continue;
+        }
+        match load_binding(&entry.keys, action, entry.context.as_deref()) {
+            Ok(binding) => bindings.push(binding),
+            Err(error) => warn!(
+                "Ignoring keybinding '{}' -> '{}': {:#}",
+                entry.keys, entry.action, error
+            ),
         }
     }
 
@@ -316,11 +330,25 @@ fn apply_custom_keybindings(config: &settings::Config, cx: &mut App) {
         tracing::info!("Applying {} custom keybinding(s)", bindings.len());
         cx.bind_keys(bindings);
     }
+    set_key_sequences(sequences, config.key_sequence_timeout_ms, cx);
 }
 
-/// Callback for config file changes — re-applies custom keybindings.
-fn on_keybinding_config_apply(config: &settings::Config, cx: &mut App) {
+/// A keybinding for an action built at runtime, which `KeyBinding::new`
+/// cannot take.
+fn load_binding(keys: &str, action: Box<dyn Action>, context: Option<&str>) -> Result<KeyBinding> {
+    let predicate = context
+        .map(KeyBindingContextPredicate::parse)
+        .transpose()?
+        .map(Rc::new);
+    KeyBinding::load(keys, action, predicate, false, None, &DummyKeyboardMapper)
+        .map_err(|_| anyhow::anyhow!("invalid keys '{}'", keys))
+}
+
+/// Callback for config file changes — re-applies custom keybindings and the
+/// workspace's settings.
+fn on_config_apply(config: &settings::Config, cx: &mut App) {
     apply_custom_keybindings(config, cx);
+    set_reopen_with_scrollback(config.reopen_with_scrollback, cx);
 }
 
 /// Initialize subsystems.
@@ -331,13 +359,87 @@ fn initialize_subsystems(cx: &mut App) {
     theme::init(cx);
     debug!("Theme system initialized");
 
+    cx.set_global(WindowOptionsBuilder(build_window_options));
+
     register_keybindings(cx);
     debug!("Keybindings registered");
 
-    // Watch config for keybinding changes (re-applies custom bindings on reload)
-    if let Some(debouncer) = settings::watch_config(cx, on_keybinding_config_apply) {
+    set_reopen_with_scrollback(settings::load_config().reopen_with_scrollback, cx);
+
+    // Watch config for changes (re-applies custom bindings and workspace
+    // settings on reload) and re-check it for the config problems banner
+    refresh_config_diagnostics(cx);
+    if let Some(debouncer) = settings::watch_config(cx, on_config_apply, refresh_config_diagnostics)
+    {
         Box::leak(Box::new(debouncer));
     }
+
+    start_remote_control(cx);
+}
+
+/// `humanssh --check-config`: print the config files' problems and exit,
+/// non-zero if any is an error. Runs without starting the app.
+fn run_config_check() -> i32 {
+    let files: Vec<_> = settings::layers::load_layers()
+        .files
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect();
+    if files.is_empty() {
+        println!("No config files, using defaults");
+        return 0;
+    }
+
+    let diagnostics = check_config_without_app();
+    for (path, diagnostic) in &diagnostics {
+        println!("{}:{}", path.display(), diagnostic);
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|(_, diagnostic)| diagnostic.severity == settings::validate::Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    if diagnostics.is_empty() {
+        for path in &files {
+            println!("{}: OK", path.display());
+        }
+    } else {
+        println!("{} error(s), {} warning(s)", errors, warnings);
+    }
+    i32::from(errors > 0)
+}
+
+/// `humanssh --remote <action>`: run an action in the app whose control
+/// socket `HUMANSSH_CONTROL` names, e.g. from a shell in one of its tabs.
+fn run_remote(spec: &str) -> i32 {
+    if spec.is_empty() {
+        println!("Usage: humanssh --remote <action> [argument]\n\nActions:");
+        for action in actions::ACTIONS {
+            match action.argument() {
+                actions::ArgumentKind::None => println!("  {}", action.name),
+                actions::ArgumentKind::Number => println!("  {} <n>", action.name),
+                actions::ArgumentKind::Text => println!("  {} <text>", action.name),
+            }
+        }
+        return 2;
+    }
+    if let Err(e) = actions::parse_action(spec) {
+        eprintln!("{}", e);
+        return 2;
+    }
+    match send_remote_action(spec) {
+        Ok(reply) => match reply.strip_prefix("error: ") {
+            Some(message) => {
+                eprintln!("{}", message);
+                1
+            }
+            None => 0,
+        },
+        Err(e) => {
+            eprintln!("{:#}", e);
+            1
+        }
+    }
 }
 
 fn main() {
@@ -345,6 +447,18 @@ fn main() {
 
     init_logging();
 
+    if std::env::args().nth(1).as_deref() == Some("--effective-config") {
+        print!("{}", settings::layers::load_layers().render());
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("--check-config") {
+        std::process::exit(run_config_check());
+    }
+    if std::env::args().nth(1).as_deref() == Some("--remote") {
+        let spec = std::env::args().skip(2).collect::<Vec<_>>().join(" ");
+        std::process::exit(run_remote(&spec));
+    }
+
     if let Err(e) = init_paths() {
         error!("Failed to initialize paths: {}", e);
     }
@@ -353,7 +467,7 @@ fn main() {
 
     app.on_reopen(|cx| {
         if cx.windows().is_empty() {
-            if let Err(e) = open_main_window(cx) {
+            if let Err(e) = open_main_window(cx, None) {
                 error!("Failed to reopen window: {}", e);
             }
         }
@@ -363,9 +477,17 @@ fn main() {
         cx.activate(true);
         initialize_subsystems(cx);
 
-        if let Err(e) = open_main_window(cx) {
-            error!("Failed to open main window: {}", e);
-            cx.quit();
+        // Windows from the last session come back alongside anything asked for
+        // on the command line
+        let launch = launch_from_args();
+        let restored = restore_session(cx);
+        if launch.is_some() || !restored {
+            if let Err(e) = open_main_window(cx, launch) {
+                error!("Failed to open main window: {}", e);
+                if !restored {
+                    cx.quit();
+                }
+            }
         }
 
         info!(
@@ -0,0 +1,1102 @@
+//! SSH port forwarding (`-L`, `-R`, `-D`) with per-forward byte counters.
+//!
+//! Each forward runs its own `ssh -N` connection as a control master. Every
+//! port is listened on by HumanSSH itself, never handed to `ssh` by number,
+//! and every byte passes a counting relay thread:
+//!
+//! ```text
+//! Local:    client → [relay on bind port] → ssh -W host:port (via the master) → remote
+//! Dynamic:  client → [SOCKS relay on bind port] → ssh -W host:port (via the master) → remote
+//! Remote:   remote → ssh -R → [relay on 127.0.0.1:ephemeral] → destination
+//! ```
+//!
+//! `ssh` runs with `BatchMode=yes`, so forwards need key or agent auth. It
+//! checks the host key the way the session it belongs to did: strictly once
+//! HumanSSH has checked the key, otherwise as the ssh config says, which in
+//! batch mode refuses keys that aren't known yet.
+//! Windows' OpenSSH has no connection sharing, so there each connection
+//! through a local or dynamic forward opens its own `ssh -W`.
+
+use crate::ssh::SshTarget;
+use anyhow::{bail, Context as _, Result};
+use parking_lot::Mutex;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often blocked relay threads wake up to check for shutdown.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Relay copy buffer size.
+const BUFFER_SIZE: usize = 16 * 1024;
+
+/// Which side listens and where traffic goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardKind {
+    /// `-L`: listen locally, connect from the remote host.
+    Local,
+    /// `-R`: listen on the remote host, connect from here.
+    Remote,
+    /// `-D`: local SOCKS proxy, connect from the remote host.
+    Dynamic,
+}
+
+/// A forward definition, parsed from ssh-style text.
+///
+/// ```text
+/// L [bind_address:]port:host:hostport
+/// R [bind_address:]port:host:hostport
+/// D [bind_address:]port
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardSpec {
+    pub kind: ForwardKind,
+    pub bind_address: Option<String>,
+    pub bind_port: u16,
+    /// Destination host and port (`None` for dynamic forwards).
+    pub destination: Option<(String, u16)>,
+}
+
+impl ForwardSpec {
+    /// Parse `L 8080:db:5432`, `-L 8080:db:5432`, `R 9000:localhost:3000`, `D 1080`.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (kind_str, rest) = input.split_once(char::is_whitespace).context(
+            "Expected a forward like 'L 8080:host:80', 'R 9000:localhost:3000' or 'D 1080'",
+        )?;
+        let kind = match kind_str
+            .trim_start_matches('-')
+            .to_ascii_uppercase()
+            .as_str()
+        {
+            "L" => ForwardKind::Local,
+            "R" => ForwardKind::Remote,
+            "D" => ForwardKind::Dynamic,
+            other => bail!("Unknown forward type '{}': use L, R or D", other),
+        };
+
+        let parts = split_forward_fields(rest.trim())?;
+        let parse_port = |s: &str| -> Result<u16> {
+            s.parse::<u16>()
+                .ok()
+                .filter(|p| *p != 0)
+                .with_context(|| format!("Invalid port '{}'", s))
+        };
+
+        let (bind_address, bind_port, destination) = match (kind, parts.as_slice()) {
+            (ForwardKind::Dynamic, [port]) => (None, parse_port(port)?, None),
+            (ForwardKind::Dynamic, [addr, port]) => (Some(addr.clone()), parse_port(port)?, None),
+            (ForwardKind::Local | ForwardKind::Remote, [port, host, host_port]) => (
+                None,
+                parse_port(port)?,
+                Some((host.clone(), parse_port(host_port)?)),
+            ),
+            (ForwardKind::Local | ForwardKind::Remote, [addr, port, host, host_port]) => (
+                Some(addr.clone()),
+                parse_port(port)?,
+                Some((host.clone(), parse_port(host_port)?)),
+            ),
+            (ForwardKind::Dynamic, _) => bail!("Dynamic forwards take '[bind_address:]port'"),
+            _ => bail!("Expected '[bind_address:]port:host:hostport'"),
+        };
+
+        if destination
+            .as_ref()
+            .is_some_and(|(host, _)| host.is_empty())
+        {
+            bail!("Destination host is empty");
+        }
+
+        Ok(Self {
+            kind,
+            bind_address,
+            bind_port,
+            destination,
+        })
+    }
+
+    /// Address the listening side binds to.
+    fn bind_host(&self) -> &str {
+        self.bind_address.as_deref().unwrap_or(match self.kind {
+            ForwardKind::Remote => "localhost",
+            _ => "127.0.0.1",
+        })
+    }
+}
+
+impl std::fmt::Display for ForwardSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ForwardKind::Local => "L",
+            ForwardKind::Remote => "R",
+            ForwardKind::Dynamic => "D",
+        };
+        write!(f, "{} ", kind)?;
+        if let Some(addr) = &self.bind_address {
+            write!(f, "{}:", bracket_ipv6(addr))?;
+        }
+        write!(f, "{}", self.bind_port)?;
+        if let Some((host, port)) = &self.destination {
+            write!(f, ":{}:{}", bracket_ipv6(host), port)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split on `:` while keeping bracketed IPv6 addresses intact.
+fn split_forward_fields(input: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_brackets = false;
+    for ch in input.chars() {
+        match ch {
+            '[' if !in_brackets => in_brackets = true,
+            ']' if in_brackets => in_brackets = false,
+            ':' if !in_brackets => fields.push(std::mem::take(&mut current)),
+            c if c.is_whitespace() => bail!("Unexpected whitespace in '{}'", input),
+            c => current.push(c),
+        }
+    }
+    if in_brackets {
+        bail!("Unclosed '[' in '{}'", input);
+    }
+    fields.push(current);
+    Ok(fields)
+}
+
+fn bracket_ipv6(host: &str) -> String {
+    if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// Live traffic counters for one forward.
+#[derive(Debug, Default)]
+struct Counters {
+    /// Bytes sent towards the remote side.
+    sent: AtomicU64,
+    /// Bytes received from the remote side.
+    received: AtomicU64,
+    active_connections: AtomicUsize,
+    total_connections: AtomicUsize,
+}
+
+impl Counters {
+    fn bytes(&self, sent: bool) -> &AtomicU64 {
+        if sent {
+            &self.sent
+        } else {
+            &self.received
+        }
+    }
+}
+
+/// Snapshot of a forward's traffic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ForwardStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub active_connections: usize,
+    pub total_connections: usize,
+}
+
+/// Lifecycle of a forward as shown in the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForwardState {
+    Active,
+    /// `ssh` exited on its own; holds its last error line.
+    Failed(String),
+    Stopped,
+}
+
+/// A running port forward. Stops (and kills its `ssh`) on drop.
+pub struct PortForward {
+    id: u64,
+    spec: ForwardSpec,
+    target: SshTarget,
+    counters: Arc<Counters>,
+    stop: Arc<AtomicBool>,
+    child: Mutex<Option<Child>>,
+    last_error: Arc<Mutex<String>>,
+    failure: Mutex<Option<String>>,
+    /// Private directory holding the control socket, removed on drop
+    control_dir: Option<PathBuf>,
+}
+
+impl PortForward {
+    /// Start a forward through `target`. Unless `host_key_checked`, `ssh`
+    /// checks the host key as the ssh config says.
+    ///
+    /// Binding the listening side happens synchronously, so "address already
+    /// in use" is reported here. SSH connection failures show up later
+    /// through [`PortForward::state`].
+    pub fn start(target: SshTarget, spec: ForwardSpec, host_key_checked: bool) -> Result<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        // Built first so that failing below stops and cleans up on drop
+        let forward = Self {
+            id,
+            spec,
+            target,
+            counters: Arc::new(Counters::default()),
+            stop: Arc::new(AtomicBool::new(false)),
+            child: Mutex::new(None),
+            last_error: Arc::new(Mutex::new(String::new())),
+            failure: Mutex::new(None),
+            control_dir: create_control_dir(id)?,
+        };
+        let spec = &forward.spec;
+        let tunnel = Arc::new(Tunnel {
+            target: forward.target.clone(),
+            host_key_checked,
+            control_path: forward.control_dir.as_ref().map(|dir| dir.join("ctl")),
+        });
+
+        let mut master_options = Vec::new();
+        let (listener, upstream, direction) = match spec.kind {
+            ForwardKind::Local | ForwardKind::Dynamic => {
+                let listener =
+                    TcpListener::bind((spec.bind_host(), spec.bind_port)).with_context(|| {
+                        format!("Cannot listen on {}:{}", spec.bind_host(), spec.bind_port)
+                    })?;
+                let upstream = match &spec.destination {
+                    Some(destination) => Upstream::Tunnel(tunnel.clone(), destination.clone()),
+                    None => Upstream::Socks(tunnel.clone()),
+                };
+                (listener, upstream, Direction::ListenerIsLocal)
+            }
+            ForwardKind::Remote => {
+                let listener = TcpListener::bind(("127.0.0.1", 0))
+                    .context("Cannot open loopback relay port")?;
+                let relay_port = listener.local_addr()?.port();
+                let (host, port) = spec
+                    .destination
+                    .clone()
+                    .context("Remote forward needs a destination")?;
+                let bind = match &spec.bind_address {
+                    Some(addr) => format!("{}:{}", bracket_ipv6(addr), spec.bind_port),
+                    None => spec.bind_port.to_string(),
+                };
+                master_options.push("-R".to_string());
+                master_options.push(format!("{}:127.0.0.1:{}", bind, relay_port));
+                (
+                    listener,
+                    Upstream::Tcp(host, port),
+                    Direction::ListenerIsRemote,
+                )
+            }
+        };
+        spawn_relay(
+            listener,
+            upstream,
+            direction,
+            forward.counters.clone(),
+            forward.stop.clone(),
+        )?;
+
+        let mut options = vec![
+            "-N".to_string(),
+            "-T".to_string(),
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            "ExitOnForwardFailure=yes".to_string(),
+            "-o".to_string(),
+            "ServerAliveInterval=30".to_string(),
+        ];
+        if let Some(control_path) = &tunnel.control_path {
+            options.push("-M".to_string());
+            options.push("-S".to_string());
+            options.push(control_path.to_string_lossy().into_owned());
+        }
+        options.extend(master_options);
+        let args = ssh_args(&forward.target, host_key_checked, &options);
+
+        #[allow(clippy::disallowed_methods)] // Long-lived ssh process owned by this forward
+        let mut child = std::process::Command::new("ssh")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start ssh")?;
+
+        if let Some(stderr) = child.stderr.take() {
+            let last_error = forward.last_error.clone();
+            let host = forward.target.display_name();
+            thread::Builder::new()
+                .name("forward-stderr".into())
+                .spawn(move || {
+                    let mut key_changed = false;
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        let line = line.trim();
+                        if line.is_empty() {
+                            continue;
+                        }
+                        tracing::debug!("ssh forward: {}", line);
+                        key_changed |= line.contains("REMOTE HOST IDENTIFICATION HAS CHANGED");
+                        *last_error.lock() = explain_ssh_error(line, key_changed, &host);
+                    }
+                })
+                .context("Failed to spawn stderr reader")?;
+        }
+
+        *forward.child.lock() = Some(child);
+        tracing::info!(
+            "Started forward {} via {}",
+            forward.spec,
+            forward.target.display_name()
+        );
+        Ok(forward)
+    }
+
+    /// Process-unique id, stable for the lifetime of the forward.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn spec(&self) -> &ForwardSpec {
+        &self.spec
+    }
+
+    pub fn target(&self) -> &SshTarget {
+        &self.target
+    }
+
+    pub fn stats(&self) -> ForwardStats {
+        ForwardStats {
+            bytes_sent: self.counters.sent.load(Ordering::Relaxed),
+            bytes_received: self.counters.received.load(Ordering::Relaxed),
+            active_connections: self.counters.active_connections.load(Ordering::Relaxed),
+            total_connections: self.counters.total_connections.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Current state. Polls the `ssh` process; a dead process stops the relay.
+    pub fn state(&self) -> ForwardState {
+        if let Some(reason) = self.failure.lock().clone() {
+            return ForwardState::Failed(reason);
+        }
+        if self.stop.load(Ordering::Relaxed) {
+            return ForwardState::Stopped;
+        }
+
+        let mut child = self.child.lock();
+        let exited = child
+            .as_mut()
+            .and_then(|c| c.try_wait().ok().flatten())
+            .map(|status| status.code());
+        match exited {
+            Some(code) => {
+                *child = None;
+                self.stop.store(true, Ordering::Relaxed);
+                let last_error = self.last_error.lock().clone();
+                let reason = if last_error.is_empty() {
+                    format!(
+                        "ssh exited ({})",
+                        code.map_or("signal".to_string(), |c| c.to_string())
+                    )
+                } else {
+                    last_error
+                };
+                tracing::warn!("Forward {} failed: {}", self.spec, reason);
+                *self.failure.lock() = Some(reason.clone());
+                ForwardState::Failed(reason)
+            }
+            None => ForwardState::Active,
+        }
+    }
+
+    /// Stop relaying and kill the `ssh` process.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(mut child) = self.child.lock().take() {
+            let _ = child.kill();
+            let _ = child.wait();
+            tracing::info!("Stopped forward {}", self.spec);
+        }
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        self.stop();
+        remove_control_dir(self.control_dir.as_ref());
+    }
+}
+
+/// `ssh` arguments for `target`: strict about the host key once HumanSSH
+/// checked it, otherwise leaving the check to the ssh config.
+fn ssh_args(target: &SshTarget, host_key_checked: bool, options: &[String]) -> Vec<String> {
+    if host_key_checked {
+        target.ssh_args_with(options)
+    } else {
+        target.ssh_args_unverified_with(options)
+    }
+}
+
+/// Say what an `ssh` error line means for a forward, which can't prompt:
+/// host key failures say which way the key failed, and refused passwords
+/// point at key auth.
+fn explain_ssh_error(line: &str, key_changed: bool, host: &str) -> String {
+    if line.starts_with("Host key verification failed") {
+        if key_changed {
+            format!(
+                "The host key for {} has changed, so the forward was blocked",
+                host
+            )
+        } else {
+            format!(
+                "The host key for {} isn't known yet: connect to it in a tab to check \
+                 the key, then start the forward again",
+                host
+            )
+        }
+    } else if line.starts_with("Permission denied") {
+        format!(
+            "{} Forwards can't ask for a password: {} needs key or agent authentication",
+            line, host
+        )
+    } else {
+        line.to_string()
+    }
+}
+
+/// Make a directory only this user can enter for a forward's control socket,
+/// so no one else can reach the shared connection. `None` on Windows, whose
+/// OpenSSH can't share connections.
+fn create_control_dir(id: u64) -> Result<Option<PathBuf>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        let dir = std::env::temp_dir().join(format!("humanssh-{}-{}", std::process::id(), id));
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&dir)
+            .with_context(|| format!("Cannot create {}", dir.display()))?;
+        Ok(Some(dir))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = id;
+        Ok(None)
+    }
+}
+
+fn remove_control_dir(dir: Option<&PathBuf>) {
+    if let Some(dir) = dir {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+/// The SSH connection that local and dynamic forwards open channels through.
+struct Tunnel {
+    target: SshTarget,
+    /// Whether HumanSSH checked the host key first, so ssh can insist on it
+    host_key_checked: bool,
+    /// The forward's control master socket; `None` connects afresh each time
+    control_path: Option<PathBuf>,
+}
+
+impl Tunnel {
+    /// Start an `ssh -W` that connects its stdin and stdout to `host:port`,
+    /// as seen from the remote host.
+    fn open(&self, host: &str, port: u16) -> std::io::Result<Child> {
+        let mut options = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+        if let Some(control_path) = &self.control_path {
+            options.push("-S".to_string());
+            options.push(control_path.to_string_lossy().into_owned());
+        }
+        options.push("-W".to_string());
+        options.push(format!("{}:{}", bracket_ipv6(host), port));
+
+        #[allow(clippy::disallowed_methods)] // Lives as long as the relayed connection
+        std::process::Command::new("ssh")
+            .args(ssh_args(&self.target, self.host_key_checked, &options))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+    }
+}
+
+/// Where a relay sends the connections it accepts.
+#[derive(Clone)]
+enum Upstream {
+    /// A TCP address reachable from here.
+    Tcp(String, u16),
+    /// A fixed destination through the tunnel.
+    Tunnel(Arc<Tunnel>, (String, u16)),
+    /// Wherever each SOCKS client asks, through the tunnel.
+    Socks(Arc<Tunnel>),
+}
+
+/// Which end of the relay faces the remote host (for byte accounting).
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    /// Accepted connections come from local clients; upstream is the tunnel.
+    ListenerIsLocal,
+    /// Accepted connections come through the tunnel; upstream is local.
+    ListenerIsRemote,
+}
+
+/// Accept connections on `listener` and pipe each one to `upstream`, counting bytes.
+fn spawn_relay(
+    listener: TcpListener,
+    upstream: Upstream,
+    direction: Direction,
+    counters: Arc<Counters>,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    listener.set_nonblocking(true)?;
+    thread::Builder::new()
+        .name("forward-relay".into())
+        .spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((client, _)) => {
+                        let upstream = upstream.clone();
+                        let counters = counters.clone();
+                        let stop = stop.clone();
+                        let _ =
+                            thread::Builder::new()
+                                .name("forward-conn".into())
+                                .spawn(move || {
+                                    if let Err(e) = relay_connection(
+                                        client, &upstream, direction, &counters, &stop,
+                                    ) {
+                                        tracing::debug!("Forward connection ended: {}", e);
+                                    }
+                                });
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        tracing::warn!("Forward listener error: {}", e);
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+        })
+        .context("Failed to spawn relay thread")?;
+    Ok(())
+}
+
+fn relay_connection(
+    mut client: TcpStream,
+    upstream: &Upstream,
+    direction: Direction,
+    counters: &Arc<Counters>,
+    stop: &Arc<AtomicBool>,
+) -> std::io::Result<()> {
+    client.set_nonblocking(false)?;
+    let (tunnel, (host, port)) = match upstream {
+        Upstream::Tcp(host, port) => {
+            return relay_tcp(client, (host, *port), direction, counters, stop)
+        }
+        Upstream::Tunnel(tunnel, destination) => (tunnel, destination.clone()),
+        Upstream::Socks(tunnel) => (tunnel, socks_handshake(&mut client)?),
+    };
+    let mut channel = tunnel.open(&host, port)?;
+    let (Some(to_remote), Some(from_remote)) = (channel.stdin.take(), channel.stdout.take()) else {
+        let _ = channel.kill();
+        return Err(std::io::Error::other("ssh -W has no stdio"));
+    };
+
+    counters.total_connections.fetch_add(1, Ordering::Relaxed);
+    counters.active_connections.fetch_add(1, Ordering::Relaxed);
+
+    // The remote side's output has no read timeout; it ends when the client
+    // side below stops and kills the channel, or the remote end closes
+    let client_write = client.try_clone()?;
+    let download_counters = counters.clone();
+    let download_stop = stop.clone();
+    let download = thread::Builder::new()
+        .name("forward-pump".into())
+        .spawn(move || {
+            let mut client_write = client_write;
+            let result = pump(
+                from_remote,
+                &mut client_write,
+                download_counters.bytes(false),
+                &download_stop,
+            );
+            let _ = client_write.shutdown(Shutdown::Write);
+            result
+        })?;
+
+    client.set_read_timeout(Some(POLL_INTERVAL))?;
+    // Dropping the channel's stdin when the client is done half-closes it
+    let result = pump(client.try_clone()?, to_remote, counters.bytes(true), stop);
+    if result.is_err() || stop.load(Ordering::Relaxed) {
+        let _ = channel.kill();
+        let _ = client.shutdown(Shutdown::Both);
+    }
+    let _ = download.join();
+    let _ = channel.kill();
+    let _ = channel.wait();
+
+    counters.active_connections.fetch_sub(1, Ordering::Relaxed);
+    result
+}
+
+/// Relay `client` to a TCP address, such as a remote forward's destination.
+fn relay_tcp(
+    client: TcpStream,
+    upstream: (&str, u16),
+    direction: Direction,
+    counters: &Arc<Counters>,
+    stop: &Arc<AtomicBool>,
+) -> std::io::Result<()> {
+    let addr = upstream
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, "no address"))?;
+    let server = TcpStream::connect_timeout(&addr, Duration::from_secs(10))?;
+
+    counters.total_connections.fetch_add(1, Ordering::Relaxed);
+    counters.active_connections.fetch_add(1, Ordering::Relaxed);
+
+    // Client → server is "sent" when the client is local, "received" when it
+    // arrived through the tunnel
+    let upload_is_sent = matches!(direction, Direction::ListenerIsLocal);
+
+    let client_read = client.try_clone()?;
+    let server_write = server.try_clone()?;
+    let upload_counters = counters.clone();
+    let upload_stop = stop.clone();
+    let upload = thread::Builder::new()
+        .name("forward-pump".into())
+        .spawn(move || {
+            pump_tcp(
+                client_read,
+                server_write,
+                upload_counters.bytes(upload_is_sent),
+                &upload_stop,
+            )
+        })?;
+
+    let result = pump_tcp(server, client, counters.bytes(!upload_is_sent), stop);
+    let _ = upload.join();
+
+    counters.active_connections.fetch_sub(1, Ordering::Relaxed);
+    result
+}
+
+/// Copy `from` → `to` between sockets until EOF or shutdown, adding to
+/// `counter`, then close the side(s) that are done.
+fn pump_tcp(
+    from: TcpStream,
+    to: TcpStream,
+    counter: &AtomicU64,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    from.set_read_timeout(Some(POLL_INTERVAL))?;
+    let result = pump(from.try_clone()?, to.try_clone()?, counter, stop);
+    if stop.load(Ordering::Relaxed) {
+        let _ = from.shutdown(Shutdown::Both);
+        let _ = to.shutdown(Shutdown::Both);
+    } else {
+        let _ = to.shutdown(Shutdown::Write);
+    }
+    result
+}
+
+/// Copy `from` → `to` until EOF, an error or shutdown, adding to `counter`.
+/// `from` should time out its reads so shutdown is noticed.
+fn pump(
+    mut from: impl Read,
+    mut to: impl Write,
+    counter: &AtomicU64,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    let mut buf = vec![0u8; BUFFER_SIZE];
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        match from.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                to.write_all(&buf[..n])?;
+                to.flush()?;
+                counter.fetch_add(n as u64, Ordering::Relaxed);
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read a SOCKS4, 4a or 5 `CONNECT` request from `client` and accept it,
+/// returning the host and port asked for. Only unauthenticated `CONNECT` is
+/// supported, as with `ssh -D`. The client hears of success before the
+/// channel opens; a destination that can't be reached closes the connection.
+fn socks_handshake(client: &mut (impl Read + Write)) -> std::io::Result<(String, u16)> {
+    let invalid = |what: &str| std::io::Error::new(ErrorKind::InvalidData, what.to_string());
+    let mut version = [0u8; 1];
+    client.read_exact(&mut version)?;
+    match version[0] {
+        4 => {
+            // VN=4 CD PORT(2) IP(4) USERID NUL [HOST NUL for 4a]
+            let mut header = [0u8; 7];
+            client.read_exact(&mut header)?;
+            if header[0] != 1 {
+                client.write_all(&[0, 91, 0, 0, 0, 0, 0, 0])?;
+                return Err(invalid("SOCKS4 command other than CONNECT"));
+            }
+            let port = u16::from_be_bytes([header[1], header[2]]);
+            let ip = [header[3], header[4], header[5], header[6]];
+            read_nul_terminated(client)?;
+            // 0.0.0.x (x != 0) means the host name follows: SOCKS4a
+            let host = if ip[..3] == [0, 0, 0] && ip[3] != 0 {
+                read_nul_terminated(client)?
+            } else {
+                Ipv4Addr::from(ip).to_string()
+            };
+            client.write_all(&[0, 90, 0, 0, 0, 0, 0, 0])?;
+            Ok((host, port))
+        }
+        5 => {
+            let mut count = [0u8; 1];
+            client.read_exact(&mut count)?;
+            let mut methods = vec![0u8; usize::from(count[0])];
+            client.read_exact(&mut methods)?;
+            if !methods.contains(&0) {
+                client.write_all(&[5, 0xff])?;
+                return Err(invalid("SOCKS5 client needs authentication"));
+            }
+            client.write_all(&[5, 0])?;
+
+            let mut request = [0u8; 4];
+            client.read_exact(&mut request)?;
+            if request[1] != 1 {
+                client.write_all(&[5, 7, 0, 1, 0, 0, 0, 0, 0, 0])?;
+                return Err(invalid("SOCKS5 command other than CONNECT"));
+            }
+            let host = match request[3] {
+                1 => {
+                    let mut ip = [0u8; 4];
+                    client.read_exact(&mut ip)?;
+                    Ipv4Addr::from(ip).to_string()
+                }
+                3 => {
+                    let mut len = [0u8; 1];
+                    client.read_exact(&mut len)?;
+                    let mut name = vec![0u8; usize::from(len[0])];
+                    client.read_exact(&mut name)?;
+                    String::from_utf8(name).map_err(|_| invalid("SOCKS5 host is not UTF-8"))?
+                }
+                4 => {
+                    let mut ip = [0u8; 16];
+                    client.read_exact(&mut ip)?;
+                    Ipv6Addr::from(ip).to_string()
+                }
+                _ => {
+                    client.write_all(&[5, 8, 0, 1, 0, 0, 0, 0, 0, 0])?;
+                    return Err(invalid("unknown SOCKS5 address type"));
+                }
+            };
+            let mut port = [0u8; 2];
+            client.read_exact(&mut port)?;
+            client.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])?;
+            Ok((host, u16::from_be_bytes(port)))
+        }
+        _ => Err(invalid("not a SOCKS request")),
+    }
+}
+
+/// Read a SOCKS4 user id or host name, up to its NUL.
+fn read_nul_terminated(reader: &mut impl Read) -> std::io::Result<String> {
+    let mut bytes = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        if byte[0] == 0 {
+            break;
+        }
+        if bytes.len() == 255 {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                "SOCKS4 field too long",
+            ));
+        }
+        bytes.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_local_forward() {
+        let spec = ForwardSpec::parse("L 8080:db.internal:5432").unwrap();
+        assert_eq!(spec.kind, ForwardKind::Local);
+        assert_eq!(spec.bind_address, None);
+        assert_eq!(spec.bind_port, 8080);
+        assert_eq!(spec.destination, Some(("db.internal".to_string(), 5432)));
+    }
+
+    #[test]
+    fn parses_ssh_style_flags_and_bind_address() {
+        let spec = ForwardSpec::parse("-L 0.0.0.0:8080:localhost:80").unwrap();
+        assert_eq!(spec.bind_address.as_deref(), Some("0.0.0.0"));
+        assert_eq!(spec.to_string(), "L 0.0.0.0:8080:localhost:80");
+    }
+
+    #[test]
+    fn parses_remote_and_dynamic() {
+        let remote = ForwardSpec::parse("r 9000:localhost:3000").unwrap();
+        assert_eq!(remote.kind, ForwardKind::Remote);
+        assert_eq!(remote.bind_host(), "localhost");
+
+        let dynamic = ForwardSpec::parse("D 1080").unwrap();
+        assert_eq!(dynamic.kind, ForwardKind::Dynamic);
+        assert_eq!(dynamic.destination, None);
+        assert_eq!(dynamic.to_string(), "D 1080");
+    }
+
+    #[test]
+    fn parses_bracketed_ipv6() {
+        let spec = ForwardSpec::parse("L [::1]:8080:[fd00::5]:22").unwrap();
+        assert_eq!(spec.bind_address.as_deref(), Some("::1"));
+        assert_eq!(spec.destination, Some(("fd00::5".to_string(), 22)));
+        assert_eq!(spec.to_string(), "L [::1]:8080:[fd00::5]:22");
+    }
+
+    #[test]
+    fn rejects_invalid_specs() {
+        assert!(ForwardSpec::parse("").is_err());
+        assert!(ForwardSpec::parse("X 80:a:80").is_err());
+        assert!(ForwardSpec::parse("L 8080").is_err());
+        assert!(ForwardSpec::parse("L 8080::80").is_err());
+        assert!(ForwardSpec::parse("L 0:host:80").is_err());
+        assert!(ForwardSpec::parse("L 70000:host:80").is_err());
+        assert!(ForwardSpec::parse("D 1080:host:80").is_err());
+        assert!(ForwardSpec::parse("L [::1:8080:host:80").is_err());
+    }
+
+    /// A SOCKS client's bytes, with room for the proxy's replies.
+    struct FakeClient {
+        input: std::io::Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl FakeClient {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: std::io::Cursor::new(input.to_vec()),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for FakeClient {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for FakeClient {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn socks5_connect_by_name() {
+        let mut request = vec![5, 1, 0, 5, 1, 0, 3, 11];
+        request.extend_from_slice(b"db.internal");
+        request.extend_from_slice(&5432u16.to_be_bytes());
+        let mut client = FakeClient::new(&request);
+        assert_eq!(
+            socks_handshake(&mut client).unwrap(),
+            ("db.internal".to_string(), 5432)
+        );
+        assert_eq!(client.output, vec![5, 0, 5, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn socks5_rejects_authentication_only_clients() {
+        let mut client = FakeClient::new(&[5, 1, 2]);
+        assert!(socks_handshake(&mut client).is_err());
+        assert_eq!(client.output, vec![5, 0xff]);
+    }
+
+    #[test]
+    fn socks4_and_4a_connect() {
+        let mut client = FakeClient::new(&[4, 1, 0, 80, 10, 0, 0, 5, b'm', b'e', 0]);
+        assert_eq!(
+            socks_handshake(&mut client).unwrap(),
+            ("10.0.0.5".to_string(), 80)
+        );
+        assert_eq!(client.output, vec![0, 90, 0, 0, 0, 0, 0, 0]);
+
+        let mut request = vec![4, 1, 0x1f, 0x90, 0, 0, 0, 1, 0];
+        request.extend_from_slice(b"example.com\0");
+        let mut client = FakeClient::new(&request);
+        assert_eq!(
+            socks_handshake(&mut client).unwrap(),
+            ("example.com".to_string(), 8080)
+        );
+    }
+
+    /// Echo server: bounces every byte back to the sender.
+    fn echo_server() -> u16 {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().map_while(Result::ok) {
+                thread::spawn(move || {
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n) = stream.read(&mut buf) {
+                        if n == 0 || stream.write_all(&buf[..n]).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    fn wait_for(mut condition: impl FnMut() -> bool) {
+        for _ in 0..100 {
+            if condition() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("condition not met in time");
+    }
+
+    #[test]
+    fn relay_counts_bytes_in_both_directions() {
+        let echo_port = echo_server();
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let relay_port = listener.local_addr().unwrap().port();
+        let counters = Arc::new(Counters::default());
+        let stop = Arc::new(AtomicBool::new(false));
+        spawn_relay(
+            listener,
+            Upstream::Tcp("127.0.0.1".to_string(), echo_port),
+            Direction::ListenerIsLocal,
+            counters.clone(),
+            stop.clone(),
+        )
+        .unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", relay_port)).unwrap();
+        client.write_all(b"hello tunnel").unwrap();
+        let mut reply = [0u8; 12];
+        client.read_exact(&mut reply).unwrap();
+        assert_eq!(&reply, b"hello tunnel");
+
+        // The reply can arrive before the relay threads add up what they copied
+        wait_for(|| {
+            counters.received.load(Ordering::Relaxed) == 12
+                && counters.sent.load(Ordering::Relaxed) == 12
+        });
+        assert_eq!(counters.total_connections.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.active_connections.load(Ordering::Relaxed), 1);
+
+        drop(client);
+        wait_for(|| counters.active_connections.load(Ordering::Relaxed) == 0);
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn remote_direction_swaps_counters() {
+        let echo_port = echo_server();
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let relay_port = listener.local_addr().unwrap().port();
+        let counters = Arc::new(Counters::default());
+        let stop = Arc::new(AtomicBool::new(false));
+        spawn_relay(
+            listener,
+            Upstream::Tcp("127.0.0.1".to_string(), echo_port),
+            Direction::ListenerIsRemote,
+            counters.clone(),
+            stop.clone(),
+        )
+        .unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", relay_port)).unwrap();
+        client.write_all(b"abc").unwrap();
+        client.write_all(b"defg").unwrap();
+        let mut reply = [0u8; 7];
+        client.read_exact(&mut reply).unwrap();
+
+        wait_for(|| {
+            counters.sent.load(Ordering::Relaxed) == 7
+                && counters.received.load(Ordering::Relaxed) == 7
+        });
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn host_key_mode_follows_the_session() {
+        let target = SshTarget::parse("me@web").unwrap();
+        let options = vec!["-N".to_string()];
+        assert!(ssh_args(&target, true, &options).contains(&"StrictHostKeyChecking=yes".into()));
+        assert_eq!(
+            ssh_args(&target, false, &options),
+            ["-N", "-l", "me", "--", "web"]
+        );
+    }
+
+    #[test]
+    fn ssh_errors_are_explained() {
+        let unknown = explain_ssh_error("Host key verification failed.", false, "web");
+        assert!(unknown.contains("isn't known yet"));
+        let changed = explain_ssh_error("Host key verification failed.", true, "web");
+        assert!(changed.contains("has changed"));
+        let denied = explain_ssh_error("Permission denied (publickey).", false, "web");
+        assert!(denied.starts_with("Permission denied (publickey)."));
+        assert!(denied.contains("key or agent authentication"));
+        assert_eq!(
+            explain_ssh_error("Connection refused", false, "web"),
+            "Connection refused"
+        );
+    }
+
+    #[test]
+    fn stop_closes_open_connections() {
+        let echo_port = echo_server();
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let relay_port = listener.local_addr().unwrap().port();
+        let counters = Arc::new(Counters::default());
+        let stop = Arc::new(AtomicBool::new(false));
+        spawn_relay(
+            listener,
+            Upstream::Tcp("127.0.0.1".to_string(), echo_port),
+            Direction::ListenerIsLocal,
+            counters.clone(),
+            stop.clone(),
+        )
+        .unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", relay_port)).unwrap();
+        wait_for(|| counters.active_connections.load(Ordering::Relaxed) == 1);
+
+        stop.store(true, Ordering::Relaxed);
+        wait_for(|| counters.active_connections.load(Ordering::Relaxed) == 0);
+        let mut buf = [0u8; 1];
+        assert!(matches!(client.read(&mut buf), Ok(0) | Err(_)));
+    }
+}
@@ -0,0 +1,738 @@
+//! OpenSSH `known_hosts` parsing and trust-on-first-use bookkeeping.
+//!
+//! Supports plain and hashed (`|1|salt|hash`) host fields, `*`/`?` wildcards,
+//! `!` negation, and the `@revoked` marker. `@cert-authority` lines are
+//! skipped; certificate validation is left to `ssh` itself.
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Magic prefix OpenSSH uses for hashed host names.
+const HASH_MAGIC: &str = "|1|";
+
+/// Length of the HMAC-SHA1 salt written for new hashed entries.
+const SALT_LEN: usize = 20;
+
+/// Key type preference when a server offers several host keys.
+/// Mirrors OpenSSH's default `HostKeyAlgorithms` order.
+const KEY_TYPE_PREFERENCE: &[&str] = &[
+    "ssh-ed25519",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "rsa-sha2-512",
+    "rsa-sha2-256",
+    "ssh-rsa",
+];
+
+/// A public host key: algorithm name plus base64-encoded key blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostKey {
+    pub key_type: String,
+    pub key_data: String,
+}
+
+impl HostKey {
+    pub fn new(key_type: impl Into<String>, key_data: impl Into<String>) -> Self {
+        Self {
+            key_type: key_type.into(),
+            key_data: key_data.into(),
+        }
+    }
+
+    /// OpenSSH-style SHA256 fingerprint (`SHA256:<unpadded base64>`).
+    ///
+    /// Falls back to hashing the raw text if the key blob is not valid base64,
+    /// so a malformed key still gets a stable (if unusual) fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let blob = STANDARD
+            .decode(self.key_data.as_bytes())
+            .unwrap_or_else(|_| self.key_data.as_bytes().to_vec());
+        let digest = Sha256::digest(&blob);
+        format!("SHA256:{}", STANDARD_NO_PAD.encode(digest))
+    }
+
+    /// Rank used to pick the key `ssh` will most likely negotiate (lower is preferred).
+    fn preference(&self) -> usize {
+        KEY_TYPE_PREFERENCE
+            .iter()
+            .position(|t| *t == self.key_type)
+            .unwrap_or(KEY_TYPE_PREFERENCE.len())
+    }
+}
+
+/// Result of checking a server's host key against `known_hosts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostKeyStatus {
+    /// The key matches a known entry for this host.
+    Trusted,
+    /// No entry exists for this host and key type.
+    Unknown,
+    /// An entry of the same key type exists but with a different key.
+    /// This is what a man-in-the-middle attack looks like.
+    Changed {
+        /// 1-based line number of the conflicting entry.
+        line: usize,
+        known_fingerprint: String,
+    },
+    /// The key is explicitly marked `@revoked`.
+    Revoked { line: usize },
+}
+
+impl HostKeyStatus {
+    /// Which status to report when keys or files disagree (lower wins): a
+    /// revoked key, then a trusted one, then a changed one.
+    fn rank(&self) -> u8 {
+        match self {
+            HostKeyStatus::Revoked { .. } => 0,
+            HostKeyStatus::Trusted => 1,
+            HostKeyStatus::Changed { .. } => 2,
+            HostKeyStatus::Unknown => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    None,
+    Revoked,
+    CertAuthority,
+}
+
+#[derive(Debug, Clone)]
+enum HostField {
+    /// `|1|base64(salt)|base64(hmac_sha1(salt, host))`
+    Hashed { salt: Vec<u8>, hash: Vec<u8> },
+    /// Comma-separated patterns, possibly negated or wildcarded.
+    Patterns(Vec<String>),
+}
+
+impl HostField {
+    fn parse(field: &str) -> Option<Self> {
+        if let Some(rest) = field.strip_prefix(HASH_MAGIC) {
+            let (salt, hash) = rest.split_once('|')?;
+            return Some(HostField::Hashed {
+                salt: STANDARD.decode(salt).ok()?,
+                hash: STANDARD.decode(hash).ok()?,
+            });
+        }
+        Some(HostField::Patterns(
+            field.split(',').map(|p| p.to_string()).collect(),
+        ))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            HostField::Hashed { salt, hash } => hash_host_name(salt, name) == *hash,
+            HostField::Patterns(patterns) => {
+                let mut matched = false;
+                for pattern in patterns {
+                    if let Some(negated) = pattern.strip_prefix('!') {
+                        if wildcard_matches(negated, name) {
+                            return false;
+                        }
+                    } else if wildcard_matches(pattern, name) {
+                        matched = true;
+                    }
+                }
+                matched
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    marker: Marker,
+    hosts: HostField,
+    key: HostKey,
+    line: usize,
+}
+
+/// In-memory view of a `known_hosts` file.
+#[derive(Debug, Clone)]
+pub struct KnownHosts {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl KnownHosts {
+    /// `~/.ssh/known_hosts`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".ssh").join("known_hosts"))
+    }
+
+    /// Load a known_hosts file. A missing file is treated as empty.
+    pub fn load(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self::parse(path, &content))
+    }
+
+    /// Parse known_hosts content. Malformed lines are skipped, as `ssh` does.
+    pub fn parse(path: impl Into<PathBuf>, content: &str) -> Self {
+        let entries = content
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| parse_line(line, index + 1))
+            .collect();
+        Self {
+            path: path.into(),
+            entries,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check a single host key for `host:port`.
+    pub fn check(&self, host: &str, port: u16, key: &HostKey) -> HostKeyStatus {
+        let name = host_name_for_lookup(host, port);
+        let matching: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|e| e.hosts.matches(&name))
+            .collect();
+
+        // Revocation takes priority over any positive match, wherever it appears
+        if let Some(entry) = matching
+            .iter()
+            .find(|e| e.marker == Marker::Revoked && e.key == *key)
+        {
+            return HostKeyStatus::Revoked { line: entry.line };
+        }
+
+        let mut changed: Option<&Entry> = None;
+        for entry in matching.into_iter().filter(|e| e.marker == Marker::None) {
+            if entry.key == *key {
+                return HostKeyStatus::Trusted;
+            }
+            if entry.key.key_type == key.key_type && changed.is_none() {
+                changed = Some(entry);
+            }
+        }
+
+        match changed {
+            Some(entry) => HostKeyStatus::Changed {
+                line: entry.line,
+                known_fingerprint: entry.key.fingerprint(),
+            },
+            None => HostKeyStatus::Unknown,
+        }
+    }
+
+    /// Check every key a server offered and decide what to tell the user.
+    ///
+    /// A revoked key always wins, then any trusted key (`ssh` prefers host key
+    /// algorithms it already knows). Otherwise a changed key is reported before
+    /// an unknown one, so a partial match can never hide a conflict.
+    /// The returned key is the one the status refers to. Returns `None` if
+    /// the server offered no keys.
+    pub fn verify(
+        &self,
+        host: &str,
+        port: u16,
+        offered: &[HostKey],
+    ) -> Option<(HostKeyStatus, HostKey)> {
+        let mut keys: Vec<&HostKey> = offered.iter().collect();
+        keys.sort_by_key(|k| k.preference());
+
+        // The first of equally ranked results is the most preferred key
+        keys.iter()
+            .map(|k| (self.check(host, port, k), (*k).clone()))
+            .min_by_key(|(status, _)| status.rank())
+    }
+
+    /// [`KnownHosts::verify`] across several files, as `ssh` checks its user
+    /// and global known_hosts files together. The same priorities apply
+    /// across files, so a key trusted in one file isn't reported unknown
+    /// because another file lacks it. Also returns the file the status
+    /// refers to.
+    pub fn verify_all<'a>(
+        files: &'a [KnownHosts],
+        host: &str,
+        port: u16,
+        offered: &[HostKey],
+    ) -> Option<(HostKeyStatus, HostKey, &'a Path)> {
+        files
+            .iter()
+            .filter_map(|file| {
+                let (status, key) = file.verify(host, port, offered)?;
+                Some((status, key, file.path()))
+            })
+            .min_by_key(|(status, _, _)| status.rank())
+    }
+
+    /// Append a trusted key for `host:port`, creating the file (and `~/.ssh`) if needed.
+    ///
+    /// With `hash` set, the host name is written as an HMAC-SHA1 hash, matching
+    /// OpenSSH's `HashKnownHosts yes`.
+    pub fn add(&mut self, host: &str, port: u16, key: &HostKey, hash: bool) -> std::io::Result<()> {
+        let name = host_name_for_lookup(host, port);
+        let host_field = if hash {
+            let salt = random_salt()?;
+            let digest = hash_host_name(&salt, &name);
+            format!(
+                "{}{}|{}",
+                HASH_MAGIC,
+                STANDARD.encode(salt),
+                STANDARD.encode(digest)
+            )
+        } else {
+            name
+        };
+        let line = format!("{} {} {}", host_field, key.key_type, key.key_data);
+
+        if let Some(parent) = self.path.parent() {
+            if !parent.exists() {
+                std::fs::create_dir_all(parent)?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(parent, std::fs::Permissions::from_mode(0o700))?;
+                }
+            }
+        }
+
+        // Make sure the new entry starts on its own line, and number it after
+        // whatever the file ends with (comments and markers included)
+        let existing = std::fs::read(&self.path).unwrap_or_default();
+        let needs_newline = !existing.is_empty() && !existing.ends_with(b"\n");
+        let existing_lines = existing.iter().filter(|&&b| b == b'\n').count();
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if needs_newline {
+            writeln!(file)?;
+        }
+        writeln!(file, "{}", line)?;
+
+        let line_number = existing_lines + usize::from(needs_newline) + 1;
+        if let Some(entry) = parse_line(&line, line_number) {
+            self.entries.push(entry);
+        }
+        tracing::info!(
+            "Added {} host key for {} to {:?}",
+            key.key_type,
+            host,
+            self.path
+        );
+        Ok(())
+    }
+}
+
+/// The name OpenSSH stores for a host: bare for port 22, `[host]:port` otherwise.
+pub fn host_name_for_lookup(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+fn parse_line(line: &str, line_number: usize) -> Option<Entry> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut fields = line.split_whitespace();
+    let mut first = fields.next()?;
+
+    let marker = match first {
+        "@revoked" => Marker::Revoked,
+        "@cert-authority" => Marker::CertAuthority,
+        m if m.starts_with('@') => return None,
+        _ => Marker::None,
+    };
+    if marker != Marker::None {
+        first = fields.next()?;
+    }
+
+    let hosts = HostField::parse(first)?;
+    let key_type = fields.next()?;
+    let key_data = fields.next()?;
+
+    Some(Entry {
+        marker,
+        hosts,
+        key: HostKey::new(key_type, key_data),
+        line: line_number,
+    })
+}
+
+fn hash_host_name(salt: &[u8], name: &str) -> Vec<u8> {
+    let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(salt).expect("HMAC accepts any key length");
+    mac.update(name.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// A fresh salt for a hashed host name, from the OS random number generator.
+fn random_salt() -> std::io::Result<[u8; SALT_LEN]> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(salt)
+}
+
+/// `*` and `?` wildcard matching, case-insensitive like OpenSSH host patterns.
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<usize> = None;
+    let mut star_t = 0;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            star_t = t;
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some(star_p) = star {
+            p = star_p + 1;
+            star_t += 1;
+            t = star_t;
+        } else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+    p == pattern.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const ED25519: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl";
+    const ED25519_OTHER: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4";
+    const RSA: &str = "AAAAB3NzaC1yc2EAAAADAQABAAABAQC7";
+
+    fn ed25519() -> HostKey {
+        HostKey::new("ssh-ed25519", ED25519)
+    }
+
+    fn hashed_line(name: &str, key: &HostKey) -> String {
+        let salt = b"0123456789abcdefghij".to_vec();
+        format!(
+            "|1|{}|{} {} {}",
+            STANDARD.encode(&salt),
+            STANDARD.encode(hash_host_name(&salt, name)),
+            key.key_type,
+            key.key_data
+        )
+    }
+
+    #[test]
+    fn fingerprint_matches_openssh_format() {
+        // ssh-keygen -lf on this key prints SHA256:+DiY3wvvV6TuJJhbpZisF/zLDA0zPMSvHdkr4UvCOqU
+        let key = HostKey::new("ssh-ed25519", ED25519);
+        assert_eq!(
+            key.fingerprint(),
+            "SHA256:+DiY3wvvV6TuJJhbpZisF/zLDA0zPMSvHdkr4UvCOqU"
+        );
+    }
+
+    #[test]
+    fn unknown_host_in_empty_file() {
+        let known = KnownHosts::parse("/dev/null", "");
+        assert_eq!(
+            known.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn plain_entry_is_trusted() {
+        let content = format!("example.com,192.0.2.1 ssh-ed25519 {}\n", ED25519);
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(
+            known.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known.check("192.0.2.1", 22, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known.check("other.com", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn non_default_port_uses_bracket_form() {
+        let content = format!("[example.com]:2222 ssh-ed25519 {}\n", ED25519);
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(
+            known.check("example.com", 2222, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn hashed_entry_is_trusted() {
+        let known = KnownHosts::parse("/dev/null", &hashed_line("example.com", &ed25519()));
+        assert_eq!(
+            known.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known.check("example.org", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn hashed_host_name_matches_hmac_sha1() {
+        // Reference value from Python's hmac.new(salt, b"example.com", sha1)
+        let digest = hash_host_name(b"0123456789abcdefghij", "example.com");
+        assert_eq!(STANDARD.encode(digest), "jaHXoMQTU/+rEgquOJTQzPGCF4I=");
+    }
+
+    #[test]
+    fn changed_key_is_detected() {
+        let content = format!("example.com ssh-ed25519 {}\n", ED25519_OTHER);
+        let known = KnownHosts::parse("/dev/null", &content);
+        match known.check("example.com", 22, &ed25519()) {
+            HostKeyStatus::Changed {
+                line,
+                known_fingerprint,
+            } => {
+                assert_eq!(line, 1);
+                assert_eq!(
+                    known_fingerprint,
+                    HostKey::new("ssh-ed25519", ED25519_OTHER).fingerprint()
+                );
+            }
+            other => panic!("expected Changed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn different_key_type_is_unknown_not_changed() {
+        let content = format!("example.com ssh-rsa {}\n", RSA);
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(
+            known.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn revoked_key_is_reported() {
+        let content = format!(
+            "# comment\nexample.com ssh-ed25519 {}\n@revoked * ssh-ed25519 {}\n",
+            ED25519, ED25519
+        );
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(
+            known.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Revoked { line: 3 }
+        );
+    }
+
+    #[test]
+    fn cert_authority_lines_are_ignored() {
+        let content = format!("@cert-authority *.example.com ssh-ed25519 {}\n", ED25519);
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(
+            known.check("a.example.com", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn wildcards_and_negation() {
+        let content = format!("*.example.com,!bad.example.com ssh-ed25519 {}\n", ED25519);
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(
+            known.check("a.example.com", 22, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known.check("A.EXAMPLE.COM", 22, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known.check("bad.example.com", 22, &ed25519()),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let content = format!(
+            "garbage\n|1|not-base64 ssh-ed25519 {}\n@unknown-marker x y z\nexample.com ssh-ed25519 {}\n",
+            ED25519, ED25519
+        );
+        let known = KnownHosts::parse("/dev/null", &content);
+        assert_eq!(known.entries.len(), 1);
+        assert_eq!(known.entries[0].line, 4);
+    }
+
+    #[test]
+    fn verify_prefers_trusted_key() {
+        let content = format!("example.com ssh-rsa {}\n", RSA);
+        let known = KnownHosts::parse("/dev/null", &content);
+        let offered = vec![ed25519(), HostKey::new("ssh-rsa", RSA)];
+        let (status, key) = known.verify("example.com", 22, &offered).unwrap();
+        assert_eq!(status, HostKeyStatus::Trusted);
+        assert_eq!(key.key_type, "ssh-rsa");
+    }
+
+    #[test]
+    fn verify_reports_change_over_unknown() {
+        let content = format!("example.com ssh-ed25519 {}\n", ED25519_OTHER);
+        let known = KnownHosts::parse("/dev/null", &content);
+        let offered = vec![HostKey::new("ssh-rsa", RSA), ed25519()];
+        let (status, key) = known.verify("example.com", 22, &offered).unwrap();
+        assert!(matches!(status, HostKeyStatus::Changed { .. }));
+        assert_eq!(key, ed25519());
+    }
+
+    #[test]
+    fn verify_unknown_picks_preferred_key_type() {
+        let known = KnownHosts::parse("/dev/null", "");
+        let offered = vec![HostKey::new("ssh-rsa", RSA), ed25519()];
+        let (status, key) = known.verify("example.com", 22, &offered).unwrap();
+        assert_eq!(status, HostKeyStatus::Unknown);
+        assert_eq!(key.key_type, "ssh-ed25519");
+    }
+
+    #[test]
+    fn verify_with_no_keys_is_none() {
+        let known = KnownHosts::parse("/dev/null", "");
+        assert!(known.verify("example.com", 22, &[]).is_none());
+    }
+
+    #[test]
+    fn verify_all_checks_every_file() {
+        let offered = vec![ed25519()];
+        let user = KnownHosts::parse("/home/me/.ssh/known_hosts", "");
+        let global = KnownHosts::parse(
+            "/etc/ssh/ssh_known_hosts",
+            &format!("example.com ssh-ed25519 {}\n", ED25519),
+        );
+        let files = [user, global];
+        let (status, _, path) =
+            KnownHosts::verify_all(&files, "example.com", 22, &offered).unwrap();
+        assert_eq!(status, HostKeyStatus::Trusted);
+        assert_eq!(path, Path::new("/etc/ssh/ssh_known_hosts"));
+
+        // A revocation anywhere wins over a trusted entry elsewhere
+        let revoked = KnownHosts::parse(
+            "/work/known_hosts",
+            &format!("@revoked * ssh-ed25519 {}\n", ED25519),
+        );
+        let files = [files[0].clone(), files[1].clone(), revoked];
+        let (status, _, path) =
+            KnownHosts::verify_all(&files, "example.com", 22, &offered).unwrap();
+        assert_eq!(status, HostKeyStatus::Revoked { line: 1 });
+        assert_eq!(path, Path::new("/work/known_hosts"));
+    }
+
+    #[test]
+    fn add_hashed_entry_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ssh").join("known_hosts");
+
+        let mut known = KnownHosts::load(&path).unwrap();
+        known.add("example.com", 2222, &ed25519(), true).unwrap();
+        assert_eq!(
+            known.check("example.com", 2222, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("|1|"));
+        assert!(!content.contains("example.com"), "host name must not leak");
+
+        let reloaded = KnownHosts::load(&path).unwrap();
+        assert_eq!(
+            reloaded.check("example.com", 2222, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+    }
+
+    #[test]
+    fn add_plain_entry_appends_on_new_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known_hosts");
+        std::fs::write(&path, format!("other.com ssh-rsa {}", RSA)).unwrap();
+
+        let mut known = KnownHosts::load(&path).unwrap();
+        known.add("example.com", 22, &ed25519(), false).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], format!("example.com ssh-ed25519 {}", ED25519));
+
+        let reloaded = KnownHosts::load(&path).unwrap();
+        assert_eq!(
+            reloaded.check("other.com", 22, &HostKey::new("ssh-rsa", RSA)),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            reloaded.check("example.com", 22, &ed25519()),
+            HostKeyStatus::Trusted
+        );
+    }
+
+    #[test]
+    fn add_numbers_entry_after_trailing_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("known_hosts");
+        std::fs::write(
+            &path,
+            format!(
+                "other.com ssh-rsa {}\n\n# retired hosts\n@revoked * ssh-rsa {}",
+                RSA, RSA
+            ),
+        )
+        .unwrap();
+
+        let mut known = KnownHosts::load(&path).unwrap();
+        known.add("example.com", 22, &ed25519(), false).unwrap();
+        assert_eq!(known.entries.last().unwrap().line, 5);
+        assert_eq!(
+            KnownHosts::load(&path)
+                .unwrap()
+                .entries
+                .last()
+                .unwrap()
+                .line,
+            5
+        );
+    }
+
+    #[test]
+    fn load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let known = KnownHosts::load(dir.path().join("nope")).unwrap();
+        assert!(known.entries.is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+//! Remote session support.
+//!
+//! SSH targets, host key verification against `~/.ssh/known_hosts`, port
+//! forwarding, SFTP, and the helpers used to launch `ssh` inside a terminal pane.
+//! This crate has no GPUI dependency — it's the pure logic layer.
+
+pub mod forward;
+pub mod known_hosts;
+pub mod sftp;
+pub mod ssh;
+
+pub use forward::{ForwardSpec, ForwardState, ForwardStats, PortForward};
+pub use known_hosts::{HostKey, HostKeyStatus, KnownHosts};
+pub use sftp::{FileEntry, SftpClient, Transfer, TransferDirection, TransferStatus};
+pub use ssh::{resolve_host, scan_host_keys, ResolvedHost, SshTarget};
@@ -0,0 +1,719 @@
+//! SFTP file operations and transfers via the system `sftp` binary.
+//!
+//! Directory operations run `sftp -b -`: in batch mode any failing command
+//! aborts with a non-zero exit, so errors surface reliably. Transfers run
+//! interactively inside a PTY instead, because `sftp` only prints its progress
+//! meter to a terminal (and disables it entirely in batch mode).
+//!
+//! Like port forwards, `sftp` runs with `BatchMode=yes`, so it needs key or
+//! agent authentication.
+
+use crate::ssh::{SshTarget, DEFAULT_PORT};
+use anyhow::{bail, Context as _, Result};
+use parking_lot::Mutex;
+use portable_pty::{native_pty_system, Child, MasterPty, PtySize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::thread;
+
+/// A directory entry, from `ls -la` (remote) or `std::fs` (local).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub size: u64,
+    /// `ls`-style permission string, e.g. `drwxr-xr-x`.
+    pub permissions: String,
+    /// Modification time as `ls` prints it, e.g. `Jan  5 12:00`.
+    pub modified: String,
+}
+
+/// Runs one-shot `sftp` batches against a host.
+#[derive(Debug, Clone)]
+pub struct SftpClient {
+    target: SshTarget,
+}
+
+impl SftpClient {
+    pub fn new(target: SshTarget) -> Self {
+        Self { target }
+    }
+
+    pub fn target(&self) -> &SshTarget {
+        &self.target
+    }
+
+    /// The remote login directory.
+    pub fn home_dir(&self) -> Result<String> {
+        let output = self.run_batch(&["pwd".to_string()])?;
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix("Remote working directory: "))
+            .map(|dir| dir.trim().to_string())
+            .context("sftp did not report a working directory")
+    }
+
+    /// List a remote directory, directories first, then by name.
+    pub fn list(&self, path: &str) -> Result<Vec<FileEntry>> {
+        let output = self.run_batch(&[format!("ls -la {}", quote(path, true))])?;
+        let mut entries = parse_ls_output(&output);
+        sort_entries(&mut entries);
+        Ok(entries)
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        self.run_batch(&[format!(
+            "rename {} {}",
+            quote(from, false),
+            quote(to, false)
+        )])
+        .map(drop)
+    }
+
+    /// Delete a file, or an empty directory.
+    pub fn remove(&self, path: &str, is_dir: bool) -> Result<()> {
+        let command = if is_dir {
+            format!("rmdir {}", quote(path, false))
+        } else {
+            format!("rm {}", quote(path, true))
+        };
+        self.run_batch(&[command]).map(drop)
+    }
+
+    pub fn mkdir(&self, path: &str) -> Result<()> {
+        self.run_batch(&[format!("mkdir {}", quote(path, false))])
+            .map(drop)
+    }
+
+    /// Run commands with `sftp -b -`, returning stdout.
+    ///
+    /// Blocking — call from a background executor.
+    fn run_batch(&self, commands: &[String]) -> Result<String> {
+        let mut args = vec!["-q".to_string(), "-b".to_string(), "-".to_string()];
+        args.extend(sftp_args(&self.target));
+
+        #[allow(clippy::disallowed_methods)] // stdin must be written while sftp runs
+        let mut child = std::process::Command::new("sftp")
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run sftp")?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let script = commands.join("\n") + "\n";
+            stdin
+                .write_all(script.as_bytes())
+                .context("Failed to send commands to sftp")?;
+        }
+
+        let output = child.wait_with_output().context("sftp did not finish")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr
+                .lines()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("sftp failed");
+            bail!("{}", reason.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Arguments that connect `sftp` to `target`.
+fn sftp_args(target: &SshTarget) -> Vec<String> {
+    let host = if target.host.contains(':') {
+        format!("[{}]", target.host)
+    } else {
+        target.host.clone()
+    };
+    let destination = match &target.user {
+        Some(user) => format!("{}@{}", user, host),
+        None => host,
+    };
+    let mut args = vec![
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        "StrictHostKeyChecking=yes".to_string(),
+    ];
+    // Leave the default port to the ssh config, which may set its own
+    if target.port != DEFAULT_PORT {
+        args.push("-P".to_string());
+        args.push(target.port.to_string());
+    }
+    args.push("--".to_string());
+    args.push(destination);
+    args
+}
+
+/// Quote a path for an sftp command line.
+///
+/// sftp splits on whitespace and honours double quotes with backslash escapes.
+/// Commands that glob (`ls`, `rm`, `get`, `put`) also need `*?[]` escaped.
+fn quote(path: &str, globbing: bool) -> String {
+    let mut quoted = String::with_capacity(path.len() + 2);
+    quoted.push('"');
+    for c in path.chars() {
+        let escape = matches!(c, '"' | '\\') || (globbing && matches!(c, '*' | '?' | '[' | ']'));
+        if escape {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Parse `ls -la` output, skipping `.`, `..` and echoed `sftp>` commands.
+pub fn parse_ls_output(output: &str) -> Vec<FileEntry> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with("sftp>"))
+        .filter_map(parse_ls_line)
+        .filter(|entry| entry.name != "." && entry.name != "..")
+        .collect()
+}
+
+/// Parse one long-listing line:
+/// `drwxr-xr-x    5 user  group   4096 Jan  5 12:00 name with spaces`.
+fn parse_ls_line(line: &str) -> Option<FileEntry> {
+    let mut rest = line.trim_start();
+    let mut fields = Vec::with_capacity(8);
+    for _ in 0..8 {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    if rest.is_empty() {
+        return None;
+    }
+
+    let permissions = fields[0];
+    let kind = permissions.chars().next()?;
+    if !matches!(kind, 'd' | '-' | 'l' | 'c' | 'b' | 'p' | 's') || permissions.len() < 10 {
+        return None;
+    }
+
+    // Some servers append the link target; names never contain '/'
+    let name = rest.split(" -> ").next().unwrap_or(rest);
+    let name = name.rsplit('/').next().unwrap_or(name);
+
+    Some(FileEntry {
+        name: name.to_string(),
+        is_dir: kind == 'd',
+        is_symlink: kind == 'l',
+        size: fields[4].parse().ok()?,
+        permissions: permissions.to_string(),
+        modified: fields[5..8].join(" "),
+    })
+}
+
+/// Directories first, then case-insensitive by name.
+pub fn sort_entries(entries: &mut [FileEntry]) {
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+}
+
+/// List a local directory in the same shape as a remote listing.
+pub fn list_local(path: &Path) -> Result<Vec<FileEntry>> {
+    let mut entries = Vec::new();
+    for entry in
+        std::fs::read_dir(path).with_context(|| format!("Cannot read {}", path.display()))?
+    {
+        let entry = entry?;
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+        entries.push(FileEntry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_dir: metadata.is_dir(),
+            is_symlink,
+            size: metadata.len(),
+            permissions: String::new(),
+            modified: String::new(),
+        });
+    }
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// Join a remote directory and an entry name.
+pub fn join_remote(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Parent of a remote path (`/` stays `/`).
+pub fn parent_remote(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => trimmed[..index].to_string(),
+    }
+}
+
+/// Which way a transfer goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+/// Progress of a transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferStatus {
+    /// Percentage of the current file, once sftp starts reporting it.
+    Running(Option<u8>),
+    Done,
+    Failed(String),
+}
+
+/// An SFTP upload or download, running in the background.
+///
+/// Dropping a transfer does not cancel it; call [`Transfer::cancel`].
+pub struct Transfer {
+    direction: TransferDirection,
+    local: PathBuf,
+    remote: String,
+    status: Arc<Mutex<TransferStatus>>,
+    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    _master: Box<dyn MasterPty + Send>,
+}
+
+impl Transfer {
+    /// Start copying `local` to `remote` (upload) or `remote` to `local` (download).
+    /// Directories are copied recursively.
+    pub fn start(
+        target: &SshTarget,
+        direction: TransferDirection,
+        local: PathBuf,
+        remote: String,
+        recursive: bool,
+    ) -> Result<Self> {
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: 24,
+                // Wide enough that sftp doesn't truncate the progress line
+                cols: 200,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .context("Failed to open PTY for sftp")?;
+
+        let mut cmd = portable_pty::CommandBuilder::new("sftp");
+        for arg in sftp_args(target) {
+            cmd.arg(arg);
+        }
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .context("Failed to run sftp")?;
+        // Without our copy of the slave, reads hit EOF once sftp exits
+        drop(pair.slave);
+
+        let local_arg = quote(&local.to_string_lossy(), true);
+        let remote_arg = quote(&remote, true);
+        let flag = if recursive { "-r " } else { "" };
+        let command = match direction {
+            TransferDirection::Upload => format!("put {}-- {} {}", flag, local_arg, remote_arg),
+            TransferDirection::Download => format!("get {}-- {} {}", flag, remote_arg, local_arg),
+        };
+
+        let mut writer = pair
+            .master
+            .take_writer()
+            .context("Failed to get PTY writer")?;
+        writer
+            .write_all(format!("{}\nbye\n", command).as_bytes())
+            .context("Failed to send command to sftp")?;
+
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .context("Failed to get PTY reader")?;
+        let status = Arc::new(Mutex::new(TransferStatus::Running(None)));
+        let child = Arc::new(Mutex::new(child));
+
+        let command_echo = command.clone();
+        let thread_status = status.clone();
+        let thread_child = child.clone();
+        let thread_local = local.clone();
+        thread::Builder::new()
+            .name("humanssh-sftp-transfer".into())
+            .spawn(move || {
+                let _writer = writer;
+                let mut monitor = TransferMonitor::new(vec![command_echo, "bye".to_string()]);
+                let mut buf = [0u8; 4096];
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => {
+                            monitor.feed(&String::from_utf8_lossy(&buf[..n]));
+                            if let Some(percent) = monitor.percent {
+                                let mut status = thread_status.lock();
+                                if matches!(*status, TransferStatus::Running(_)) {
+                                    *status = TransferStatus::Running(Some(percent));
+                                }
+                            }
+                        }
+                    }
+                }
+
+                let exited_ok = thread_child
+                    .lock()
+                    .wait()
+                    .map(|status| status.success())
+                    .unwrap_or(false);
+                let finished = {
+                    let mut status = thread_status.lock();
+                    if matches!(*status, TransferStatus::Running(_)) {
+                        *status = monitor.finish(exited_ok);
+                    }
+                    *status == TransferStatus::Done
+                };
+                // A failed or cancelled download leaves nothing half-written
+                if !finished && direction == TransferDirection::Download {
+                    remove_partial_download(&thread_local);
+                }
+            })
+            .context("Failed to spawn sftp transfer thread")?;
+
+        tracing::info!("Started sftp {:?}: {}", direction, command);
+        Ok(Self {
+            direction,
+            local,
+            remote,
+            status,
+            child,
+            _master: pair.master,
+        })
+    }
+
+    pub fn direction(&self) -> TransferDirection {
+        self.direction
+    }
+
+    pub fn local(&self) -> &Path {
+        &self.local
+    }
+
+    pub fn remote(&self) -> &str {
+        &self.remote
+    }
+
+    /// File name being transferred, for display.
+    pub fn name(&self) -> String {
+        match self.direction {
+            TransferDirection::Upload => self
+                .local
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            TransferDirection::Download => self
+                .remote
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    pub fn status(&self) -> TransferStatus {
+        self.status.lock().clone()
+    }
+
+    /// Kill sftp. A partially downloaded file or folder is removed once
+    /// sftp exits.
+    pub fn cancel(&self) {
+        {
+            let mut status = self.status.lock();
+            if !matches!(*status, TransferStatus::Running(_)) {
+                return;
+            }
+            *status = TransferStatus::Failed("Cancelled".to_string());
+        }
+        if let Err(error) = self.child.lock().kill() {
+            tracing::warn!("Failed to kill sftp: {}", error);
+        }
+    }
+}
+
+/// Remove what a download wrote before it failed. Downloads always go to a
+/// fresh path, so nothing else lives there.
+fn remove_partial_download(local: &Path) {
+    let removed = match std::fs::symlink_metadata(local) {
+        Ok(metadata) if metadata.is_dir() => std::fs::remove_dir_all(local),
+        Ok(_) => std::fs::remove_file(local),
+        Err(_) => return,
+    };
+    if let Err(error) = removed {
+        tracing::warn!(
+            "Failed to remove partial download {}: {}",
+            local.display(),
+            error
+        );
+    }
+}
+
+/// Interprets the output of an interactive sftp session.
+#[derive(Debug, Default)]
+struct TransferMonitor {
+    /// Commands we typed; the PTY echoes them back.
+    sent: Vec<String>,
+    /// Latest progress meter percentage.
+    percent: Option<u8>,
+    /// First line that looks like an error.
+    error: Option<String>,
+    /// Incomplete trailing line from the previous chunk.
+    partial: String,
+}
+
+impl TransferMonitor {
+    /// Lines sftp prints during a normal transfer.
+    const INFO_PREFIXES: &'static [&'static str] = &[
+        "sftp>",
+        "Connected to",
+        "Fetching",
+        "Uploading",
+        "Entering",
+        "Connection to",
+    ];
+
+    fn new(sent: Vec<String>) -> Self {
+        Self {
+            sent,
+            ..Self::default()
+        }
+    }
+
+    fn feed(&mut self, chunk: &str) {
+        self.partial.push_str(chunk);
+        // The progress meter redraws with '\r'; commands end with '\n'
+        while let Some(end) = self.partial.find(['\r', '\n']) {
+            let line: String = self.partial.drain(..=end).collect();
+            self.line(line.trim());
+        }
+        // A meter line may sit unterminated until the next redraw
+        if let Some(percent) = parse_progress(&self.partial) {
+            self.percent = Some(percent);
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        if line.is_empty() {
+            return;
+        }
+        if let Some(percent) = parse_progress(line) {
+            self.percent = Some(percent);
+        } else if self.error.is_none()
+            && !Self::INFO_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+            && !self
+                .sent
+                .iter()
+                .any(|command| line.ends_with(command.as_str()))
+        {
+            self.error = Some(line.to_string());
+        }
+    }
+
+    fn finish(mut self, exited_ok: bool) -> TransferStatus {
+        let rest = std::mem::take(&mut self.partial);
+        self.line(rest.trim());
+        match self.error {
+            Some(error) => TransferStatus::Failed(error),
+            None if exited_ok => TransferStatus::Done,
+            None => TransferStatus::Failed("sftp exited with an error".to_string()),
+        }
+    }
+}
+
+/// Parse an sftp progress meter line:
+/// `file.tar.gz     45%  123MB  11.2MB/s   00:09 ETA`.
+fn parse_progress(line: &str) -> Option<u8> {
+    let mut tokens = line.split_whitespace();
+    let percent = tokens.find_map(|token| token.strip_suffix('%')?.parse::<u8>().ok())?;
+    // Require a rate column so a file literally named "50%" isn't mistaken for progress
+    tokens
+        .any(|token| token.ends_with("/s"))
+        .then_some(percent.min(100))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_long_listing() {
+        let output = "\
+sftp> ls -la \"/srv\"
+drwxr-xr-x    5 root     root         4096 Jan  5 12:00 .
+drwxr-xr-x   20 root     root         4096 Dec 31  2024 ..
+-rw-r--r--    1 deploy   staff       12345 Mar 14 09:26 release notes.txt
+lrwxrwxrwx    1 deploy   staff          11 Mar 14 09:26 current -> releases/42
+drwxr-x---    3 deploy   staff        4096 Mar 14 09:26 releases
+";
+        let entries = parse_ls_output(output);
+        assert_eq!(
+            entries,
+            vec![
+                FileEntry {
+                    name: "release notes.txt".to_string(),
+                    is_dir: false,
+                    is_symlink: false,
+                    size: 12345,
+                    permissions: "-rw-r--r--".to_string(),
+                    modified: "Mar 14 09:26".to_string(),
+                },
+                FileEntry {
+                    name: "current".to_string(),
+                    is_dir: false,
+                    is_symlink: true,
+                    size: 11,
+                    permissions: "lrwxrwxrwx".to_string(),
+                    modified: "Mar 14 09:26".to_string(),
+                },
+                FileEntry {
+                    name: "releases".to_string(),
+                    is_dir: true,
+                    is_symlink: false,
+                    size: 4096,
+                    permissions: "drwxr-x---".to_string(),
+                    modified: "Mar 14 09:26".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn listing_strips_directory_prefix() {
+        let entries = parse_ls_output("-rw-r--r-- 1 a b 1 Jan 1 2024 /etc/hosts\n");
+        assert_eq!(entries[0].name, "hosts");
+    }
+
+    #[test]
+    fn listing_ignores_noise() {
+        assert!(parse_ls_output("Can't ls: \"/nope\" not found\n\ntotal 8\n").is_empty());
+    }
+
+    #[test]
+    fn sorts_directories_first() {
+        let mut entries = parse_ls_output(
+            "-rw-r--r-- 1 a b 1 Jan 1 2024 b.txt\n\
+             drwxr-xr-x 1 a b 1 Jan 1 2024 zeta\n\
+             -rw-r--r-- 1 a b 1 Jan 1 2024 A.txt\n",
+        );
+        sort_entries(&mut entries);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["zeta", "A.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn quotes_paths() {
+        assert_eq!(quote("/a b/c", false), "\"/a b/c\"");
+        assert_eq!(quote("say \"hi\"", false), "\"say \\\"hi\\\"\"");
+        assert_eq!(quote("*.log", true), "\"\\*.log\"");
+        assert_eq!(quote("*.log", false), "\"*.log\"");
+    }
+
+    #[test]
+    fn sftp_args_bracket_ipv6() {
+        let target = SshTarget::parse("me@[::1]:2222").unwrap();
+        let args = sftp_args(&target);
+        assert_eq!(args[args.len() - 3..], ["2222", "--", "me@[::1]"]);
+    }
+
+    #[test]
+    fn sftp_args_leave_default_port_to_ssh_config() {
+        let target = SshTarget::parse("me@web").unwrap();
+        let args = sftp_args(&target);
+        assert!(!args.contains(&"-P".to_string()));
+        assert_eq!(args[args.len() - 2..], ["--", "me@web"]);
+    }
+
+    #[test]
+    fn partial_downloads_are_removed() {
+        let downloads = tempfile::tempdir().unwrap();
+        let file = downloads.path().join("app.log");
+        std::fs::write(&file, b"half").unwrap();
+        remove_partial_download(&file);
+        assert!(!file.exists());
+
+        let folder = downloads.path().join("site");
+        std::fs::create_dir_all(folder.join("assets")).unwrap();
+        std::fs::write(folder.join("assets/app.css"), b"body").unwrap();
+        remove_partial_download(&folder);
+        assert!(!folder.exists());
+
+        // Nothing was written yet
+        remove_partial_download(&downloads.path().join("missing"));
+    }
+
+    #[test]
+    fn remote_path_helpers() {
+        assert_eq!(join_remote("/srv", "app"), "/srv/app");
+        assert_eq!(join_remote("/", "srv"), "/srv");
+        assert_eq!(parent_remote("/srv/app/"), "/srv");
+        assert_eq!(parent_remote("/srv"), "/");
+        assert_eq!(parent_remote("/"), "/");
+    }
+
+    #[test]
+    fn parses_progress_meter() {
+        assert_eq!(
+            parse_progress("backup.tar.gz     45%  123MB  11.2MB/s   00:09 ETA"),
+            Some(45)
+        );
+        assert_eq!(
+            parse_progress("notes.txt        100%   12KB 340.1KB/s   00:00"),
+            Some(100)
+        );
+        assert_eq!(parse_progress("Fetching /srv/50% to 50%"), None);
+    }
+
+    #[test]
+    fn monitor_tracks_progress_and_success() {
+        let mut monitor = TransferMonitor::new(vec!["get -- \"/a\" \"/tmp/a\"".to_string()]);
+        monitor.feed("Connected to web1.\r\nsftp> get -- \"/a\" \"/tmp/a\"\r\n");
+        monitor.feed("Fetching /a to /tmp/a\r\na    10%  1MB 1.0MB/s 00:09 ETA\ra   60%");
+        monitor.feed("  6MB 1.0MB/s 00:04 ETA\ra  100%  10MB 1.0MB/s 00:10\r\nsftp> bye\r\n");
+        assert_eq!(monitor.percent, Some(100));
+        assert_eq!(monitor.finish(true), TransferStatus::Done);
+    }
+
+    #[test]
+    fn monitor_reports_first_error() {
+        let mut monitor = TransferMonitor::new(vec![
+            "get -- \"/nope\" \"/tmp/nope\"".to_string(),
+            "bye".to_string(),
+        ]);
+        // Canonical-mode PTYs echo typed input before sftp prints its prompt
+        monitor.feed("get -- \"/nope\" \"/tmp/nope\"\r\nbye\r\n");
+        monitor.feed("File \"/nope\" not found.\r\nsftp> bye\r\n");
+        assert_eq!(
+            monitor.finish(true),
+            TransferStatus::Failed("File \"/nope\" not found.".to_string())
+        );
+    }
+
+    #[test]
+    fn monitor_fails_on_bad_exit_without_output() {
+        let monitor = TransferMonitor::default();
+        assert!(matches!(monitor.finish(false), TransferStatus::Failed(_)));
+    }
+}
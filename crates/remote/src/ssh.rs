@@ -0,0 +1,448 @@
+//! SSH connection targets and host key scanning.
+//!
+//! Sessions run the system `ssh` binary inside a PTY. Before launching, the
+//! target is resolved through the user's ssh config (`ssh -G`) and the host's
+//! keys are fetched with `ssh-keyscan` so HumanSSH can run its own
+//! trust-on-first-use prompt; `ssh` itself is then started with
+//! `StrictHostKeyChecking=yes` so it never falls back to a terminal prompt.
+//! Keys are checked against the `UserKnownHostsFile` and
+//! `GlobalKnownHostsFile` entries the config names, as `ssh` would. Hosts
+//! behind a `ProxyJump` or `ProxyCommand` can't be scanned, so `ssh` checks
+//! their keys itself.
+
+use crate::known_hosts::{HostKey, KnownHosts};
+use anyhow::{bail, Context as _, Result};
+use std::path::{Path, PathBuf};
+
+/// Default SSH port.
+pub const DEFAULT_PORT: u16 = 22;
+
+/// Seconds `ssh-keyscan` waits for a host before giving up.
+const KEYSCAN_TIMEOUT_SECS: u32 = 5;
+
+/// Where to connect: `[user@]host[:port]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshTarget {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: u16,
+}
+
+impl SshTarget {
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            user: None,
+            host: host.into(),
+            port: DEFAULT_PORT,
+        }
+    }
+
+    /// Parse `[ssh://][user@]host[:port]`. IPv6 hosts use brackets: `[::1]:2222`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let input = input.strip_prefix("ssh://").unwrap_or(input);
+        let input = input.trim_end_matches('/');
+
+        let (user, rest) = match input.rsplit_once('@') {
+            Some((user, rest)) if !user.is_empty() => (Some(user.to_string()), rest),
+            Some(_) => return None,
+            None => (None, input),
+        };
+
+        let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']')?;
+            let port = match after.strip_prefix(':') {
+                Some(p) => p.parse().ok()?,
+                None if after.is_empty() => DEFAULT_PORT,
+                None => return None,
+            };
+            (host, port)
+        } else if rest.matches(':').count() == 1 {
+            let (host, port) = rest.split_once(':')?;
+            (host, port.parse().ok()?)
+        } else {
+            // Bare host, or an unbracketed IPv6 address
+            (rest, DEFAULT_PORT)
+        };
+
+        if host.is_empty() || host.starts_with('-') || host.chars().any(char::is_whitespace) {
+            return None;
+        }
+
+        Some(Self {
+            user,
+            host: host.to_string(),
+            port,
+        })
+    }
+
+    /// Human-readable label, e.g. `deploy@web1:2222` (port omitted when default).
+    pub fn display_name(&self) -> String {
+        let mut name = match &self.user {
+            Some(user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        };
+        if self.port != DEFAULT_PORT {
+            name.push_str(&format!(":{}", self.port));
+        }
+        name
+    }
+
+    /// Arguments for the `ssh` binary.
+    ///
+    /// Host key checking is strict because HumanSSH has already verified (or
+    /// the user has just accepted) the key before `ssh` runs.
+    pub fn ssh_args(&self) -> Vec<String> {
+        self.ssh_args_with(&[])
+    }
+
+    /// Like [`SshTarget::ssh_args`], with extra options placed before the host.
+    pub fn ssh_args_with(&self, options: &[String]) -> Vec<String> {
+        let mut args = vec!["-o".to_string(), "StrictHostKeyChecking=yes".to_string()];
+        args.extend(self.ssh_args_unverified_with(options));
+        args
+    }
+
+    /// Arguments for a target whose host key HumanSSH couldn't check first.
+    /// `ssh` checks the key itself, asking in the terminal about a new one.
+    pub fn ssh_args_unverified(&self) -> Vec<String> {
+        self.ssh_args_unverified_with(&[])
+    }
+
+    /// [`SshTarget::ssh_args_unverified`] with extra `ssh` options before the target.
+    pub fn ssh_args_unverified_with(&self, options: &[String]) -> Vec<String> {
+        let mut args = Vec::new();
+        // Leave the default port to the ssh config, which may set its own
+        if self.port != DEFAULT_PORT {
+            args.push("-p".to_string());
+            args.push(self.port.to_string());
+        }
+        args.extend_from_slice(options);
+        if let Some(user) = &self.user {
+            args.push("-l".to_string());
+            args.push(user.clone());
+        }
+        // `--` stops option parsing so a hostile host string can't inject flags
+        args.push("--".to_string());
+        args.push(self.host.clone());
+        args
+    }
+}
+
+/// Where `ssh` really connects for a target, once `~/.ssh/config` is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedHost {
+    /// `HostName`, or the target's host when the config doesn't set one
+    pub hostname: String,
+    pub port: u16,
+    /// `HostKeyAlias`: the name the host's key is recorded under instead
+    pub host_key_alias: Option<String>,
+    /// Reached through `ProxyJump` or `ProxyCommand`, which `ssh-keyscan` can't follow
+    pub proxied: bool,
+    /// `UserKnownHostsFile`: the files `ssh` checks keys against; new keys
+    /// go in the first
+    pub user_known_hosts_files: Vec<PathBuf>,
+    /// `GlobalKnownHostsFile`: system-wide files `ssh` also checks
+    pub global_known_hosts_files: Vec<PathBuf>,
+}
+
+impl ResolvedHost {
+    /// Name and port the host's key is recorded under in `known_hosts`.
+    pub fn known_hosts_name(&self) -> (&str, u16) {
+        match &self.host_key_alias {
+            // ssh looks an alias up as-is, without a port
+            Some(alias) => (alias, DEFAULT_PORT),
+            None => (&self.hostname, self.port),
+        }
+    }
+
+    /// Every file `ssh` checks the host's key against, user files first.
+    pub fn known_hosts_files(&self) -> impl Iterator<Item = &Path> {
+        self.user_known_hosts_files
+            .iter()
+            .chain(&self.global_known_hosts_files)
+            .map(PathBuf::as_path)
+    }
+
+    /// The file a trusted key is saved to, where `ssh` will find it.
+    pub fn trusted_keys_file(&self) -> Option<&Path> {
+        self.user_known_hosts_files.first().map(PathBuf::as_path)
+    }
+}
+
+/// Resolve `target` through the user's ssh config with `ssh -G`, so aliases,
+/// `HostName`, `Port`, proxies and `HostKeyAlias` are honored.
+///
+/// Blocking — call from a background executor.
+pub fn resolve_host(target: &SshTarget) -> Result<ResolvedHost> {
+    let mut args = vec!["-G".to_string()];
+    args.extend(target.ssh_args_unverified());
+    let output = std::process::Command::new("ssh")
+        .args(&args)
+        .output()
+        .context("Failed to run ssh -G")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Could not read the ssh config for {}: {}",
+            target.display_name(),
+            stderr.lines().next().unwrap_or("ssh -G failed").trim()
+        );
+    }
+    Ok(parse_resolved_config(
+        &String::from_utf8_lossy(&output.stdout),
+        target,
+    ))
+}
+
+/// Parse `ssh -G` output (`keyword value` lines, keywords lowercased),
+/// falling back to `target` for anything it doesn't set.
+pub fn parse_resolved_config(output: &str, target: &SshTarget) -> ResolvedHost {
+    let mut resolved = ResolvedHost {
+        hostname: target.host.clone(),
+        port: target.port,
+        host_key_alias: None,
+        proxied: false,
+        user_known_hosts_files: KnownHosts::default_path().into_iter().collect(),
+        global_known_hosts_files: Vec::new(),
+    };
+    for line in output.lines() {
+        let Some((keyword, value)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("none") {
+            continue;
+        }
+        match keyword.to_ascii_lowercase().as_str() {
+            "hostname" => resolved.hostname = value.to_string(),
+            "port" => {
+                if let Ok(port) = value.parse() {
+                    resolved.port = port;
+                }
+            }
+            "hostkeyalias" => resolved.host_key_alias = Some(value.to_string()),
+            "proxyjump" | "proxycommand" => resolved.proxied = true,
+            "userknownhostsfile" => resolved.user_known_hosts_files = known_hosts_paths(value),
+            "globalknownhostsfile" => resolved.global_known_hosts_files = known_hosts_paths(value),
+            _ => {}
+        }
+    }
+    resolved
+}
+
+/// Paths from a `UserKnownHostsFile` or `GlobalKnownHostsFile` value. Newer
+/// `ssh -G` expands `~` itself; older versions print it as written.
+fn known_hosts_paths(value: &str) -> Vec<PathBuf> {
+    value
+        .split_whitespace()
+        .filter_map(|path| match path.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+            None => Some(PathBuf::from(path)),
+        })
+        .collect()
+}
+
+/// Fetch the host keys a server offers, via `ssh-keyscan`.
+///
+/// Blocking — call from a background executor.
+pub fn scan_host_keys(host: &ResolvedHost) -> Result<Vec<HostKey>> {
+    let output = std::process::Command::new("ssh-keyscan")
+        .args([
+            "-T",
+            &KEYSCAN_TIMEOUT_SECS.to_string(),
+            "-p",
+            &host.port.to_string(),
+            "--",
+            &host.hostname,
+        ])
+        .output()
+        .context("Failed to run ssh-keyscan")?;
+
+    let keys = parse_keyscan_output(&String::from_utf8_lossy(&output.stdout));
+    if keys.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .find(|l| !l.starts_with('#'))
+            .unwrap_or("no host keys returned");
+        bail!(
+            "Could not scan host keys for {}:{}: {}",
+            host.hostname,
+            host.port,
+            reason.trim()
+        );
+    }
+    Ok(keys)
+}
+
+/// Parse `ssh-keyscan` output lines: `host key-type base64 [comment]`.
+pub fn parse_keyscan_output(output: &str) -> Vec<HostKey> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _host = fields.next()?;
+            let key_type = fields.next()?;
+            let key_data = fields.next()?;
+            Some(HostKey::new(key_type, key_data))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_bare_host() {
+        assert_eq!(
+            SshTarget::parse("example.com"),
+            Some(SshTarget::new("example.com"))
+        );
+    }
+
+    #[test]
+    fn parse_user_host_port() {
+        let target = SshTarget::parse("deploy@web1:2222").unwrap();
+        assert_eq!(target.user.as_deref(), Some("deploy"));
+        assert_eq!(target.host, "web1");
+        assert_eq!(target.port, 2222);
+    }
+
+    #[test]
+    fn parse_ssh_url() {
+        let target = SshTarget::parse("ssh://root@10.0.0.1:22/").unwrap();
+        assert_eq!(target.user.as_deref(), Some("root"));
+        assert_eq!(target.host, "10.0.0.1");
+        assert_eq!(target.port, 22);
+    }
+
+    #[test]
+    fn parse_ipv6() {
+        let target = SshTarget::parse("admin@[::1]:2200").unwrap();
+        assert_eq!(target.host, "::1");
+        assert_eq!(target.port, 2200);
+
+        let bare = SshTarget::parse("fe80::1").unwrap();
+        assert_eq!(bare.host, "fe80::1");
+        assert_eq!(bare.port, DEFAULT_PORT);
+    }
+
+    #[test]
+    fn parse_rejects_invalid() {
+        assert_eq!(SshTarget::parse(""), None);
+        assert_eq!(SshTarget::parse("@host"), None);
+        assert_eq!(SshTarget::parse("host:notaport"), None);
+        assert_eq!(SshTarget::parse("-oProxyCommand=evil"), None);
+        assert_eq!(SshTarget::parse("host name"), None);
+    }
+
+    #[test]
+    fn display_name_omits_default_port() {
+        let mut target = SshTarget::parse("me@host").unwrap();
+        assert_eq!(target.display_name(), "me@host");
+        target.port = 2222;
+        assert_eq!(target.display_name(), "me@host:2222");
+    }
+
+    #[test]
+    fn ssh_args_are_strict_and_terminated() {
+        let target = SshTarget::parse("me@host:2222").unwrap();
+        assert_eq!(
+            target.ssh_args(),
+            vec![
+                "-o",
+                "StrictHostKeyChecking=yes",
+                "-p",
+                "2222",
+                "-l",
+                "me",
+                "--",
+                "host"
+            ]
+        );
+    }
+
+    #[test]
+    fn ssh_args_leave_default_port_to_config() {
+        let target = SshTarget::parse("host").unwrap();
+        assert_eq!(
+            target.ssh_args(),
+            vec!["-o", "StrictHostKeyChecking=yes", "--", "host"]
+        );
+        assert_eq!(target.ssh_args_unverified(), vec!["--", "host"]);
+    }
+
+    #[test]
+    fn parses_resolved_config() {
+        let target = SshTarget::parse("web").unwrap();
+        let output = "\
+user deploy
+hostname web1.internal.example.com
+port 2222
+hostkeyalias web-cluster
+proxycommand none
+";
+        let resolved = parse_resolved_config(output, &target);
+        assert_eq!(resolved.hostname, "web1.internal.example.com");
+        assert_eq!(resolved.port, 2222);
+        assert!(!resolved.proxied);
+        assert_eq!(resolved.known_hosts_name(), ("web-cluster", DEFAULT_PORT));
+    }
+
+    #[test]
+    fn resolved_config_lists_known_hosts_files() {
+        let target = SshTarget::parse("web").unwrap();
+        let output = "\
+globalknownhostsfile /etc/ssh/ssh_known_hosts /etc/ssh/ssh_known_hosts2
+userknownhostsfile /work/keys/known_hosts /home/me/.ssh/known_hosts
+";
+        let resolved = parse_resolved_config(output, &target);
+        assert_eq!(
+            resolved.trusted_keys_file(),
+            Some(Path::new("/work/keys/known_hosts"))
+        );
+        assert_eq!(
+            resolved.known_hosts_files().collect::<Vec<_>>(),
+            [
+                Path::new("/work/keys/known_hosts"),
+                Path::new("/home/me/.ssh/known_hosts"),
+                Path::new("/etc/ssh/ssh_known_hosts"),
+                Path::new("/etc/ssh/ssh_known_hosts2"),
+            ]
+        );
+
+        // Without the keywords, ssh's own default
+        let resolved = parse_resolved_config("", &target);
+        assert_eq!(
+            resolved.trusted_keys_file(),
+            KnownHosts::default_path().as_deref()
+        );
+    }
+
+    #[test]
+    fn resolved_config_notes_proxies() {
+        let target = SshTarget::parse("inner:2200").unwrap();
+        let resolved = parse_resolved_config("proxyjump bastion\n", &target);
+        assert!(resolved.proxied);
+        assert_eq!(resolved.known_hosts_name(), ("inner", 2200));
+    }
+
+    #[test]
+    fn parses_keyscan_output() {
+        let output = "\
+# host:22 SSH-2.0-OpenSSH_9.6
+host ssh-rsa AAAAB3NzaC1yc2E
+host ssh-ed25519 AAAAC3NzaC1lZDI1NTE5
+
+host ecdsa-sha2-nistp256 AAAAE2VjZHNh
+";
+        let keys = parse_keyscan_output(output);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[1], HostKey::new("ssh-ed25519", "AAAAC3NzaC1lZDI1NTE5"));
+    }
+}
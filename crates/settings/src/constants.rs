@@ -89,6 +89,20 @@ pub mod scrollback {
     pub const MAX_LINES: usize = 100_000;
 }
 
+/// Closed tabs kept for Reopen Closed Tab.
+pub mod closed_tabs {
+    /// Most closed tabs remembered per window; older ones are forgotten.
+    pub const MAX_REMEMBERED: usize = 10;
+    /// Most lines of a closed pane's text kept to show when it reopens.
+    pub const MAX_HISTORY_LINES: usize = 10_000;
+}
+
+/// Saved histories (recently used commands).
+pub mod history {
+    /// Most entries kept per history; older ones are forgotten.
+    pub const MAX_ENTRIES: usize = 50;
+}
+
 /// Settings file validation limits.
 pub mod settings {
     /// Maximum settings file size in bytes (64 KB).
@@ -97,6 +111,9 @@ pub mod settings {
 
     /// Maximum length for string fields (theme name, font family).
     pub const MAX_STRING_LENGTH: usize = 256;
+
+    /// How deep config files may `include` one another.
+    pub const MAX_INCLUDE_DEPTH: usize = 8;
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 //! TOML config file support with live reload.
 //!
-//! Config location: `~/.config/humanssh/config.toml`
+//! Config location: `~/.config/humanssh/config.toml`, layered over the
+//! system-wide file and any includes (see [`crate::layers`]).
 
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -32,6 +33,235 @@ pub struct Profile {
     pub working_directory: Option<String>,
     #[serde(default)]
     pub auto_switch: Option<AutoSwitchRule>,
+    /// SSH destination (`[user@]host[:port]`) for remote profiles.
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Port forwards started with this profile (e.g. "L 5432:db.internal:5432").
+    #[serde(default)]
+    pub forwards: Vec<String>,
+    /// Serial console settings for serial profiles.
+    #[serde(default)]
+    pub serial: Option<SerialConfig>,
+    /// Telnet or raw TCP settings for network device profiles.
+    #[serde(default)]
+    pub telnet: Option<TelnetConfig>,
+    /// Cluster group; Open Cluster with this name opens every profile in it.
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// Serial line parity.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+/// Serial line flow control.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlowControl {
+    #[default]
+    None,
+    /// XON/XOFF.
+    Software,
+    /// RTS/CTS.
+    Hardware,
+}
+
+/// Serial port settings. Defaults to 115200 baud, 8N1, no flow control.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SerialConfig {
+    /// Device path, e.g. `/dev/ttyUSB0` or `COM3`.
+    pub device: String,
+    pub baud_rate: u32,
+    /// 5 to 8.
+    pub data_bits: u8,
+    pub parity: Parity,
+    /// 1 or 2.
+    pub stop_bits: u8,
+    pub flow_control: FlowControl,
+    /// Show typed characters locally, for devices that don't echo.
+    pub local_echo: bool,
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            device: String::new(),
+            baud_rate: 115_200,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+            flow_control: FlowControl::None,
+            local_echo: false,
+        }
+    }
+}
+
+impl SerialConfig {
+    /// Parse `DEVICE[@BAUD][,FRAMING]`, e.g. `/dev/ttyUSB0@9600,7E1` or `COM3`.
+    ///
+    /// Only `/dev/...` paths and `COMn` names are accepted as devices.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let (rest, framing) = match input.split_once(',') {
+            Some((rest, framing)) => (rest, Some(framing)),
+            None => (input, None),
+        };
+        let (device, baud_rate) = match rest.split_once('@') {
+            Some((device, baud)) => (device, Some(baud.parse().ok().filter(|&b| b > 0)?)),
+            None => (rest, None),
+        };
+
+        let is_com_port = device.len() > 3
+            && device[..3].eq_ignore_ascii_case("com")
+            && device[3..].bytes().all(|b| b.is_ascii_digit());
+        let is_device_path = device.len() > "/dev/".len() && device.starts_with("/dev/");
+        if !(is_com_port || is_device_path) || device.chars().any(char::is_whitespace) {
+            return None;
+        }
+
+        let mut config = Self {
+            device: device.to_string(),
+            ..Self::default()
+        };
+        if let Some(baud_rate) = baud_rate {
+            config.baud_rate = baud_rate;
+        }
+        if let Some(framing) = framing {
+            let &[data_bits, parity, stop_bits] = framing.as_bytes() else {
+                return None;
+            };
+            config.data_bits = match data_bits {
+                b'5'..=b'8' => data_bits - b'0',
+                _ => return None,
+            };
+            config.parity = match parity.to_ascii_uppercase() {
+                b'N' => Parity::None,
+                b'O' => Parity::Odd,
+                b'E' => Parity::Even,
+                _ => return None,
+            };
+            config.stop_bits = match stop_bits {
+                b'1' | b'2' => stop_bits - b'0',
+                _ => return None,
+            };
+        }
+        Some(config)
+    }
+
+    /// Human-readable label, e.g. `ttyUSB0 115200 8N1`.
+    pub fn display_name(&self) -> String {
+        let device = self.device.rsplit('/').next().unwrap_or(&self.device);
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        format!(
+            "{} {} {}{}{}",
+            device, self.baud_rate, self.data_bits, parity, self.stop_bits
+        )
+    }
+}
+
+/// How a [`TelnetConfig`] session treats the byte stream.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TelnetMode {
+    /// Telnet protocol with option negotiation.
+    #[default]
+    Telnet,
+    /// Plain TCP socket, bytes passed through untouched (netcat-style).
+    Raw,
+}
+
+/// Telnet or raw TCP connection settings.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TelnetConfig {
+    pub host: String,
+    /// Defaults to 23.
+    pub port: u16,
+    pub mode: TelnetMode,
+}
+
+impl Default for TelnetConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 23,
+            mode: TelnetMode::Telnet,
+        }
+    }
+}
+
+impl TelnetConfig {
+    /// Parse `telnet://HOST[:PORT]` or `tcp://HOST:PORT`.
+    ///
+    /// IPv6 addresses go in brackets: `telnet://[::1]:2323`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let (mode, rest) = if let Some(rest) = input.strip_prefix("telnet://") {
+            (TelnetMode::Telnet, rest)
+        } else if let Some(rest) = input.strip_prefix("tcp://") {
+            (TelnetMode::Raw, rest)
+        } else {
+            return None;
+        };
+        let rest = rest.strip_suffix('/').unwrap_or(rest);
+
+        let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']')?;
+            match after {
+                "" => (host, None),
+                _ => (host, Some(after.strip_prefix(':')?)),
+            }
+        } else {
+            match rest.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (rest, None),
+            }
+        };
+        if host.is_empty() || host.contains(['/', '@']) || host.chars().any(char::is_whitespace) {
+            return None;
+        }
+        let port = match port {
+            Some(port) => port.parse().ok().filter(|&p| p > 0)?,
+            // Raw TCP has no well-known port
+            None if mode == TelnetMode::Raw => return None,
+            None => 23,
+        };
+
+        Some(Self {
+            host: host.to_string(),
+            port,
+            mode,
+        })
+    }
+
+    /// Human-readable label, e.g. `telnet switch1` or `tcp 10.0.0.5:9000`.
+    pub fn display_name(&self) -> String {
+        let scheme = match self.mode {
+            TelnetMode::Telnet => "telnet",
+            TelnetMode::Raw => "tcp",
+        };
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        if self.mode == TelnetMode::Telnet && self.port == 23 {
+            format!("{} {}", scheme, host)
+        } else {
+            format!("{} {}:{}", scheme, host, self.port)
+        }
+    }
 }
 
 /// Merged view of config defaults with profile overrides applied.
@@ -50,7 +280,8 @@ pub struct MergedProfileConfig {
 pub struct KeybindingEntry {
     /// Key chord (e.g., "cmd-shift-t", "ctrl-l")
     pub keys: String,
-    /// Action name (e.g., "new-tab", "close-tab", "quit")
+    /// Action name with an optional argument (e.g., "new-tab", "activate-tab 3",
+    /// `send-text "ls\n"`)
     pub action: String,
     /// Optional context scope (e.g., "terminal")
     pub context: Option<String>,
@@ -82,6 +313,13 @@ pub struct Config {
     pub windows_shell: Option<String>,
     /// Reverse scroll direction ("natural" scrolling).
     pub scroll_reverse: bool,
+    /// Show a reopened tab's old text above its new shells.
+    pub reopen_with_scrollback: bool,
+    /// Reopen the last session's windows, tab groups and splits on launch.
+    pub restore_session: bool,
+    /// How long a multi-stroke keybinding (`ctrl-a v`) waits for its next
+    /// key, in milliseconds; 0 waits until a key is pressed.
+    pub key_sequence_timeout_ms: u64,
     /// Fallback font families for glyphs not in the primary font.
     #[serde(default)]
     pub font_fallbacks: Vec<String>,
@@ -110,6 +348,9 @@ impl Default for Config {
             linux_decorations: None,
             windows_shell: None,
             scroll_reverse: false,
+            reopen_with_scrollback: true,
+            restore_session: false,
+            key_sequence_timeout_ms: 2000,
             font_fallbacks: Vec::new(),
             keybindings: Vec::new(),
             profiles: Vec::new(),
@@ -223,8 +464,26 @@ fn glob_matches(pattern: &str, text: &str) -> bool {
 }
 
 /// Default config file content with comments (generated on first launch).
-const DEFAULT_CONFIG: &str = r#"# HumanSSH Configuration
+pub(crate) const DEFAULT_CONFIG: &str = r#"# HumanSSH Configuration
 # Changes are applied live — just save this file.
+# Mistakes show in a banner; `humanssh --check-config` lists them all.
+
+# ─── Layers ───────────────────────────────────────────────────────────
+#
+# Settings are merged from, lowest precedence first, the system-wide
+# /etc/humanssh/config.toml (%ProgramData%\humanssh\config.toml on Windows)
+# and this file. Either can include shared files, which sit under the file
+# that includes them; paths are relative to it. Put this above any [table]:
+# include = ["team.toml"]
+#
+# An [os.<name>] section (macos, linux, windows) overrides the rest of its
+# file on that OS only:
+# [os.linux]
+# font-size = 13
+#
+# Keybindings from every layer are kept, and profiles with the same name
+# merge. Show Effective Config (or `humanssh --effective-config`) prints the
+# merged result with the file each value came from.
 
 # ─── Appearance ───────────────────────────────────────────────────────
 
@@ -248,12 +507,20 @@ scrollback-lines = 10000
 # Reverse scroll direction ("natural" scrolling like macOS trackpad)
 # scroll-reverse = false
 
+# Keep a closed tab's text (its last 10000 lines) and show it above the new
+# shell when the tab is reopened (Reopen Closed Tab)
+# reopen-with-scrollback = true
+
 # ─── Window ───────────────────────────────────────────────────────────
 
 # Window dimensions (auto-managed; uncomment to override)
 # window-width = 1200
 # window-height = 800
 
+# Reopen the last session's windows, tab groups and splits on launch
+# (each pane starts a fresh shell in its old directory)
+# restore-session = false
+
 # ─── Platform ─────────────────────────────────────────────────────────
 
 # macOS: treat Option key as Alt for terminal input
@@ -273,27 +540,78 @@ option-as-alt = true
 # Override default keybindings. User bindings take priority over defaults.
 # Add "context" to restrict to terminal-focused panes.
 #
+# "keys" may be a sequence of chords separated by spaces ("ctrl-a v"): press
+# the leader, then the next key. While a sequence is pending the status bar
+# shows it and a hint lists the keys that can follow; any other key ends it
+# and goes to the terminal. Sequences work anywhere in the window.
+# key-sequence-timeout-ms = 2000     # 0 waits for the next key forever
+#
 # Available actions:
-#   quit, new-tab, close-tab, next-tab, prev-tab,
+#   quit, new-window, new-tab, close-tab, reopen-closed-tab, next-tab, prev-tab,
+#   go-to-last-tab,
+#   rename-tab, toggle-pin-tab, move-tab-left, move-tab-right,
+#   new-tab-group, next-tab-group, prev-tab-group, rename-tab-group,
+#   tab-color-red, tab-color-yellow, tab-color-green, tab-color-cyan,
+#   tab-color-blue, tab-color-magenta, clear-tab-color,
 #   split-vertical, split-horizontal, close-pane,
-#   focus-next-pane, focus-prev-pane, open-settings,
+#   focus-next-pane, focus-prev-pane, open-settings, show-effective-config,
+#   resize-pane-left, resize-pane-right, resize-pane-up, resize-pane-down,
+#   equalize-panes,
+#   focus-pane-left, focus-pane-right, focus-pane-up, focus-pane-down,
+#   swap-pane-left, swap-pane-right, swap-pane-up, swap-pane-down,
+#   toggle-pane-zoom, toggle-floating-pane, rotate-panes, move-pane-to-new-tab,
 #   toggle-secure-input, toggle-option-as-alt,
 #   search, search-next, search-prev, search-toggle-regex,
 #   enter-copy-mode, exit-copy-mode,
-#   toggle-scratchpad
+#   toggle-scratchpad, toggle-port-forwards, open-file-browser, toggle-tab-switcher,
+#   toggle-keybindings,
+#   send-break, toggle-local-echo, reconnect,
+#   toggle-broadcast-tab, toggle-broadcast-pane, stop-broadcast, open-cluster,
+#   start-recording, stop-recording, open-replay, toggle-command-palette
 #
-# Default shortcuts:
+# Actions with an argument (N counts from 1; "go-to-tab-3" also works):
+#   activate-tab N, go-to-tab-group N, move-pane-to-tab N,
+#   send-text "TEXT"           — type TEXT (escapes: \n \r \t \e \xHH)
+#   new-tab-with-profile NAME  — connect to a profile in a new tab
+#   run-command "COMMAND"      — run a shell command line in a new tab; the
+#                                pane stays open and shows the exit code
+#   run-command-split "COMMAND", run-command-floating "COMMAND"
+#                              — the same, in a split or the floating pane
+#   add-port-forward "SPEC"    — start a forward, e.g. "L 8080:localhost:80",
+#                                through the active SSH tab or "... user@host"
+#   select-theme "NAME"        — switch theme and save it as `theme`
+#   replay-recording "PATH"    — replay an asciicast recording in a new tab
+#
+# Default shortcuts (Cmd+/ or Ctrl+Shift+/ lists every binding in effect,
+# including yours, and marks conflicts and overridden defaults):
+#   Cmd+Shift+N / Ctrl+Shift+N — new window (drag a tab out to detach it)
 #   Cmd+T / Ctrl+Shift+T  — new tab
 #   Cmd+W / Ctrl+W        — close tab
+#   Cmd+Shift+T           — reopen the last closed tab or pane
 #   Cmd+Shift+] / Ctrl+Tab — next tab
 #   Cmd+Shift+[ / Ctrl+Shift+Tab — prev tab
+#   Cmd+1 ... Cmd+8       — go to tab 1-8 (Cmd+9 goes to the last tab)
+#   Cmd+Shift+R           — rename tab (or double-click it)
+#   Ctrl+Shift+PageUp/PageDown — move tab left/right
+#   Cmd+Alt+N             — new tab group (double-click a group to rename it)
+#   Cmd+Alt+] / Cmd+Alt+[ — next/prev tab group
+#   Cmd+P                 — switch to a tab or pane (most recently used first)
+#   Cmd+Shift+P / Ctrl+Shift+P — command palette
+#   Cmd+/ / Ctrl+Shift+/  — keybinding cheat sheet
+#   Ctrl+`                — scratchpad
 #   Cmd+D                 — split vertical
 #   Cmd+Shift+D           — split horizontal
-#   Cmd+Alt+Right/Left    — focus next/prev pane
+#   Cmd+Alt+Arrows        — focus the pane in that direction
+#   Cmd+Alt+Shift+Arrows  — swap with the pane in that direction
+#   Cmd+Ctrl+Arrows       — resize pane (or drag the divider)
+#   Cmd+Ctrl+=            — equalize splits
+#   Cmd+Shift+Enter       — zoom the active pane (again to restore)
+#   Cmd+Alt+F             — show, focus or hide the tab's floating terminal
 #   Cmd+F / Ctrl+F        — search
 #   Cmd+G / Cmd+Shift+G   — search next/prev
 #   Cmd+Alt+R / Alt+R     — toggle regex search
 #   Cmd+Shift+C           — enter copy mode
+#   Cmd+Shift+S           — toggle secure keyboard entry (macOS)
 #   Cmd+, / Ctrl+,        — open settings
 #   Cmd+Q / Ctrl+Q        — quit
 
@@ -305,6 +623,15 @@ option-as-alt = true
 # keys = "ctrl-shift-f"
 # action = "search"
 # context = "terminal"
+#
+# [[keybindings]]
+# keys = "ctrl-a v"
+# action = "split-vertical"
+#
+# [[keybindings]]
+# keys = "ctrl-alt-c"
+# action = 'send-text "\x03"'
+# context = "terminal"
 
 # ─── Profiles ─────────────────────────────────────────────────────────
 # Named configurations that override defaults. Profiles can auto-switch
@@ -332,6 +659,54 @@ option-as-alt = true
 #
 # [profiles.auto-switch]
 # directory-pattern = "*/projects/*"
+#
+# SSH profile: `humanssh bastion` connects and starts its port forwards.
+# Forwards use ssh syntax: "L port:host:hostport", "R port:host:hostport", "D port"
+#
+# [[profiles]]
+# name = "bastion"
+# host = "deploy@bastion.example.com:22"
+# forwards = ["L 5432:db.internal:5432", "D 1080"]
+#
+# Serial profile: `humanssh router` opens the console. Also works without a
+# profile: `humanssh /dev/ttyUSB0@9600,8N1`.
+#
+# [[profiles]]
+# name = "router"
+#
+# [profiles.serial]
+# device = "/dev/ttyUSB0"
+# baud-rate = 9600
+# data-bits = 8
+# parity = "none"          # none, odd, even
+# stop-bits = 1
+# flow-control = "none"    # none, software, hardware
+# local-echo = false
+#
+# Telnet profile for gear without SSH. `mode = "raw"` skips option negotiation
+# for netcat-style debugging. Also works without a profile:
+# `humanssh telnet://switch1` or `humanssh tcp://10.0.0.5:9000`.
+#
+# [[profiles]]
+# name = "switch1"
+#
+# [profiles.telnet]
+# host = "switch1.example.com"
+# port = 23
+# mode = "telnet"          # telnet, raw
+#
+# Cluster group: `humanssh --cluster web` (or Open Cluster, "web") opens every
+# profile in the group as a grid in one tab, with broadcast input on.
+#
+# [[profiles]]
+# name = "web1"
+# host = "deploy@web1.example.com"
+# group = "web"
+#
+# [[profiles]]
+# name = "web2"
+# host = "deploy@web2.example.com"
+# group = "web"
 "#;
 
 /// Return the config file path.
@@ -389,35 +764,13 @@ pub fn ensure_config_file() -> Option<PathBuf> {
     Some(path)
 }
 
-/// Load and parse the config file. Returns default on any error.
+/// Load and merge the config layers (see [`crate::layers`]). Files that
+/// can't be read or parsed are skipped; returns default if nothing is left.
 pub fn load_config() -> Config {
-    let Some(path) = config_path() else {
-        return Config::default();
-    };
-
-    let content = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(e) => {
-            if e.kind() != std::io::ErrorKind::NotFound {
-                tracing::warn!("Failed to read config: {}", e);
-            }
-            return Config::default();
-        }
-    };
-
-    // Size guard
-    if content.len() > crate::constants::settings::MAX_FILE_SIZE as usize {
-        tracing::warn!(
-            "Config file too large ({} bytes), using defaults",
-            content.len()
-        );
-        return Config::default();
-    }
-
-    match toml::from_str(&content) {
+    match crate::layers::load_layers().config() {
         Ok(cfg) => cfg,
         Err(e) => {
-            tracing::warn!("Failed to parse config.toml: {}", e);
+            tracing::warn!("Failed to load config: {}", e);
             Config::default()
         }
     }
@@ -443,6 +796,25 @@ pub fn save_window_bounds(width: f32, height: f32) {
     }
 }
 
+/// Set the theme in the config file (preserving comments/formatting).
+pub fn save_theme(name: &str) {
+    let Some(path) = config_path() else {
+        return;
+    };
+
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut doc = match content.parse::<toml_edit::DocumentMut>() {
+        Ok(d) => d,
+        Err(_) => return,
+    };
+
+    doc["theme"] = toml_edit::value(name);
+
+    if let Err(e) = std::fs::write(&path, doc.to_string()) {
+        tracing::warn!("Failed to save theme: {}", e);
+    }
+}
+
 use gpui::App;
 use gpui_component::theme::{Theme, ThemeRegistry};
 
@@ -471,46 +843,62 @@ pub fn apply_config(config: &Config, cx: &mut App, on_apply: impl FnOnce(&Config
     cx.refresh_windows();
 }
 
-/// Start watching the config file for changes.
+/// Guard returned by [`watch_config`]; watching stops when it is dropped.
+pub struct ConfigWatcher {
+    _debouncer: std::sync::Arc<
+        parking_lot::Mutex<notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>>,
+    >,
+}
+
+/// Start watching the config files (every layer and include) for changes.
 /// Returns a guard that stops watching on drop.
 /// `on_apply` is called when the config changes to apply cross-crate side effects.
+/// `on_reload` is called after every write to a file, changed or not (a
+/// file that no longer parses is skipped, which may be no change).
 pub fn watch_config(
     cx: &mut App,
     on_apply: fn(&Config, &mut App),
-) -> Option<notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>> {
+    on_reload: fn(&mut App),
+) -> Option<ConfigWatcher> {
     use notify_debouncer_mini::new_debouncer;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
     use std::time::Duration;
 
-    let path = config_path()?;
-    let watch_dir = path.parent()?.to_path_buf();
-
-    let current = std::sync::Arc::new(parking_lot::Mutex::new(load_config()));
-    let current_clone = current.clone();
-    let path_clone = path.clone();
+    let layers = crate::layers::load_layers();
+    let mut current = layers.config().unwrap_or_default();
+    let files = Arc::new(Mutex::new(layers.files.clone()));
+    let files_clone = files.clone();
 
     let (tx, rx) = std::sync::mpsc::channel();
 
-    let mut debouncer = new_debouncer(
+    let debouncer = new_debouncer(
         Duration::from_millis(100),
         move |res: Result<Vec<notify_debouncer_mini::DebouncedEvent>, _>| {
             if let Ok(events) = res {
-                for event in &events {
-                    if event.path == path_clone {
-                        let _ = tx.send(());
-                        break;
-                    }
+                let files = files_clone.lock();
+                let is_config = |path: &std::path::Path| {
+                    files.iter().any(|file| file == path)
+                        || path.canonicalize().is_ok_and(|path| files.contains(&path))
+                };
+                if events.iter().any(|event| is_config(&event.path)) {
+                    let _ = tx.send(());
                 }
             }
         },
     )
     .ok()?;
+    let debouncer = Arc::new(Mutex::new(debouncer));
 
-    debouncer
-        .watcher()
-        .watch(&watch_dir, notify::RecursiveMode::NonRecursive)
-        .ok()?;
+    let mut watched_dirs = Vec::new();
+    watch_dirs(&mut debouncer.lock(), &layers.files, &mut watched_dirs);
+    if watched_dirs.is_empty() {
+        return None;
+    }
+    tracing::info!("Watching config files: {:?}", layers.files);
 
     // Poll channel on a timer to apply changes on the main thread
+    let weak_debouncer = Arc::downgrade(&debouncer);
     cx.spawn(async move |cx: &mut gpui::AsyncApp| {
         loop {
             cx.background_executor()
@@ -520,22 +908,55 @@ pub fn watch_config(
                 // Drain extra events
                 while rx.try_recv().is_ok() {}
 
-                let new_config = load_config();
-                let mut prev = current_clone.lock();
-                if new_config != *prev {
+                // Includes may have changed: watch whatever is included now
+                let layers = crate::layers::load_layers();
+                let Some(debouncer) = weak_debouncer.upgrade() else {
+                    break;
+                };
+                watch_dirs(&mut debouncer.lock(), &layers.files, &mut watched_dirs);
+                *files.lock() = layers.files.clone();
+
+                let new_config = layers.config().unwrap_or_else(|e| {
+                    tracing::warn!("Failed to load config: {}", e);
+                    Config::default()
+                });
+                if new_config != current {
                     tracing::info!("Config file changed, reloading...");
-                    *prev = new_config.clone();
                     let _ = cx.update(|cx| {
                         apply_config(&new_config, cx, on_apply);
                     });
+                    current = new_config;
                 }
+                let _ = cx.update(on_reload);
             }
         }
     })
     .detach();
 
-    tracing::info!("Watching config file: {:?}", path);
-    Some(debouncer)
+    Some(ConfigWatcher {
+        _debouncer: debouncer,
+    })
+}
+
+/// Watch the directories of `files` not watched yet. Missing directories
+/// are skipped.
+fn watch_dirs(
+    debouncer: &mut notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>,
+    files: &[PathBuf],
+    watched_dirs: &mut Vec<PathBuf>,
+) {
+    for dir in files.iter().filter_map(|file| file.parent()) {
+        if watched_dirs.iter().any(|watched| watched == dir) || !dir.is_dir() {
+            continue;
+        }
+        match debouncer
+            .watcher()
+            .watch(dir, notify::RecursiveMode::NonRecursive)
+        {
+            Ok(()) => watched_dirs.push(dir.to_path_buf()),
+            Err(e) => tracing::warn!("Failed to watch {:?}: {}", dir, e),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -694,6 +1115,27 @@ context = "terminal"
         assert!(cfg.scroll_reverse);
     }
 
+    #[test]
+    fn parses_key_sequence_timeout() {
+        let cfg: Config = toml::from_str("key-sequence-timeout-ms = 0").unwrap();
+        assert_eq!(cfg.key_sequence_timeout_ms, 0);
+        assert_eq!(Config::default().key_sequence_timeout_ms, 2000);
+    }
+
+    #[test]
+    fn parses_reopen_with_scrollback() {
+        let cfg: Config = toml::from_str("reopen-with-scrollback = false").unwrap();
+        assert!(!cfg.reopen_with_scrollback);
+        assert!(Config::default().reopen_with_scrollback);
+    }
+
+    #[test]
+    fn parses_restore_session() {
+        let cfg: Config = toml::from_str("restore-session = true").unwrap();
+        assert!(cfg.restore_session);
+        assert!(!Config::default().restore_session);
+    }
+
     #[test]
     fn scroll_reverse_defaults_to_false() {
         let cfg: Config = toml::from_str("").unwrap();
@@ -777,6 +1219,161 @@ font-fallbacks = ["Nerd Font", "Apple Color Emoji"]
         );
     }
 
+    #[test]
+    fn parses_ssh_profile_with_forwards() {
+        let toml_str = r#"
+[[profiles]]
+name = "bastion"
+host = "deploy@bastion:2222"
+forwards = ["L 5432:db.internal:5432", "D 1080"]
+"#;
+        let cfg: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(cfg.profiles[0].host.as_deref(), Some("deploy@bastion:2222"));
+        assert_eq!(
+            cfg.profiles[0].forwards,
+            vec!["L 5432:db.internal:5432".to_string(), "D 1080".to_string()]
+        );
+    }
+
+    #[test]
+    fn parses_serial_profile() {
+        let toml_str = r#"
+[[profiles]]
+name = "router"
+
+[profiles.serial]
+device = "/dev/ttyUSB0"
+baud-rate = 9600
+parity = "even"
+flow-control = "hardware"
+local-echo = true
+"#;
+        let cfg: Config = toml::from_str(toml_str).unwrap();
+        let serial = cfg.profiles[0].serial.as_ref().unwrap();
+        assert_eq!(serial.device, "/dev/ttyUSB0");
+        assert_eq!(serial.baud_rate, 9600);
+        assert_eq!(serial.data_bits, 8);
+        assert_eq!(serial.parity, Parity::Even);
+        assert_eq!(serial.stop_bits, 1);
+        assert_eq!(serial.flow_control, FlowControl::Hardware);
+        assert!(serial.local_echo);
+    }
+
+    #[test]
+    fn parses_serial_spec() {
+        let config = SerialConfig::parse("/dev/ttyUSB0").unwrap();
+        assert_eq!(config.device, "/dev/ttyUSB0");
+        assert_eq!(config.baud_rate, 115_200);
+        assert_eq!(config.display_name(), "ttyUSB0 115200 8N1");
+
+        let config = SerialConfig::parse("COM3@9600,7e2").unwrap();
+        assert_eq!(config.device, "COM3");
+        assert_eq!(config.baud_rate, 9600);
+        assert_eq!(config.data_bits, 7);
+        assert_eq!(config.parity, Parity::Even);
+        assert_eq!(config.stop_bits, 2);
+    }
+
+    #[test]
+    fn rejects_invalid_serial_specs() {
+        for spec in [
+            "ttyUSB0",
+            "/dev/",
+            "web1.example.com",
+            "COMX",
+            "/dev/ttyUSB0@fast",
+            "/dev/ttyUSB0@0",
+            "/dev/ttyUSB0,9N1",
+            "/dev/ttyUSB0,8X1",
+            "/dev/ttyUSB0,8N3",
+            "/dev/ttyUSB0,8N",
+        ] {
+            assert!(SerialConfig::parse(spec).is_none(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn parses_telnet_profile() {
+        let toml_str = r#"
+[[profiles]]
+name = "switch1"
+
+[profiles.telnet]
+host = "switch1.example.com"
+
+[[profiles]]
+name = "debug"
+
+[profiles.telnet]
+host = "10.0.0.5"
+port = 9000
+mode = "raw"
+"#;
+        let cfg: Config = toml::from_str(toml_str).unwrap();
+        let telnet = cfg.profiles[0].telnet.as_ref().unwrap();
+        assert_eq!(telnet.host, "switch1.example.com");
+        assert_eq!(telnet.port, 23);
+        assert_eq!(telnet.mode, TelnetMode::Telnet);
+        let raw = cfg.profiles[1].telnet.as_ref().unwrap();
+        assert_eq!(raw.port, 9000);
+        assert_eq!(raw.mode, TelnetMode::Raw);
+    }
+
+    #[test]
+    fn parses_profile_group() {
+        let toml_str = r#"
+[[profiles]]
+name = "web1"
+host = "web1.example.com"
+group = "web"
+
+[[profiles]]
+name = "local"
+"#;
+        let cfg: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(cfg.profiles[0].group.as_deref(), Some("web"));
+        assert_eq!(cfg.profiles[1].group, None);
+    }
+
+    #[test]
+    fn parses_telnet_urls() {
+        let config = TelnetConfig::parse("telnet://switch1").unwrap();
+        assert_eq!(config.host, "switch1");
+        assert_eq!(config.port, 23);
+        assert_eq!(config.mode, TelnetMode::Telnet);
+        assert_eq!(config.display_name(), "telnet switch1");
+
+        let config = TelnetConfig::parse("tcp://10.0.0.5:9000").unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.mode, TelnetMode::Raw);
+        assert_eq!(config.display_name(), "tcp 10.0.0.5:9000");
+
+        let config = TelnetConfig::parse("telnet://[::1]:2323/").unwrap();
+        assert_eq!(config.host, "::1");
+        assert_eq!(config.port, 2323);
+        assert_eq!(config.display_name(), "telnet [::1]:2323");
+
+        for spec in [
+            "switch1",
+            "telnet://",
+            "tcp://10.0.0.5",
+            "telnet://host:0",
+            "telnet://host:telnet",
+            "telnet://user@host",
+            "telnet://[::1",
+            "ssh://host",
+        ] {
+            assert!(TelnetConfig::parse(spec).is_none(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn local_profile_has_no_host_or_forwards() {
+        let cfg: Config = toml::from_str("[[profiles]]\nname = \"local\"\n").unwrap();
+        assert!(cfg.profiles[0].host.is_none());
+        assert!(cfg.profiles[0].forwards.is_empty());
+    }
+
     #[test]
     fn empty_profiles_default_to_empty_vec() {
         let cfg: Config = toml::from_str("").unwrap();
@@ -0,0 +1,86 @@
+//! Recently used items, most recent first, kept across restarts.
+//!
+//! Each kind of history (`commands`, ...) is a JSON list stored at
+//! `<data-dir>/humanssh/history/<kind>.json`.
+
+use std::path::PathBuf;
+
+use crate::constants::history::MAX_ENTRIES;
+
+fn history_path(kind: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|d| {
+        d.join("humanssh")
+            .join("history")
+            .join(format!("{}.json", kind))
+    })
+}
+
+/// The saved `kind` history, most recent first.
+pub fn load_history(kind: &str) -> Vec<String> {
+    let Some(content) = history_path(kind).and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return Vec::new();
+    };
+    match serde_json::from_str(&content) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable {} history: {}", kind, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Move `entry` to the front of `history`, dropping the oldest entries past
+/// the limit.
+pub fn push_history(history: &mut Vec<String>, entry: &str) {
+    history.retain(|existing| existing != entry);
+    history.insert(0, entry.to_string());
+    history.truncate(MAX_ENTRIES);
+}
+
+/// Save the `kind` history for the next launch.
+pub fn save_history(kind: &str, history: &[String]) {
+    let Some(path) = history_path(kind) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            tracing::warn!("Failed to create history directory: {}", e);
+            return;
+        }
+    }
+    let content = match serde_json::to_string_pretty(history) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("Failed to serialize {} history: {}", kind, e);
+            return;
+        }
+    };
+    if let Err(e) = std::fs::write(&path, content) {
+        tracing::warn!("Failed to save {} history: {}", kind, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_moves_entry_to_front() {
+        let mut history = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        push_history(&mut history, "c");
+        assert_eq!(history, vec!["c", "a", "b"]);
+        push_history(&mut history, "d");
+        assert_eq!(history, vec!["d", "c", "a", "b"]);
+    }
+
+    #[test]
+    fn push_drops_oldest_past_limit() {
+        let mut history = Vec::new();
+        for i in 0..MAX_ENTRIES + 5 {
+            push_history(&mut history, &i.to_string());
+        }
+        assert_eq!(history.len(), MAX_ENTRIES);
+        assert_eq!(history[0], (MAX_ENTRIES + 4).to_string());
+    }
+}
@@ -0,0 +1,570 @@
+//! Layered config: a system-wide file under the user's, `include`d files and
+//! per-OS sections, merged into one table.
+//!
+//! Layers, lowest precedence first:
+//! 1. the system file (`/etc/humanssh/config.toml`)
+//! 2. the user file (`~/.config/humanssh/config.toml`)
+//!
+//! Within a file, its `include = [...]` files (relative to it, in order) come
+//! first, then its own values, then its `[os.<name>]` section for the running
+//! OS (`macos`, `linux`, `windows`). Tables merge key by key and the higher
+//! layer's value wins, except that `[[keybindings]]` from every layer are kept
+//! (higher ones bound later, so they win) and `[[profiles]]` merge by `name`.
+
+use crate::constants::settings::{MAX_FILE_SIZE, MAX_INCLUDE_DEPTH};
+use crate::Config;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Where a value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub path: PathBuf,
+    /// From the file's `[os.<name>]` section rather than its top level.
+    pub os_section: bool,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", display_path(&self.path))?;
+        if self.os_section {
+            write!(f, " [os.{}]", std::env::consts::OS)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every config layer merged, with where each value came from.
+#[derive(Debug, Default)]
+pub struct LayeredConfig {
+    /// Every file looked at, whether or not it exists, lowest precedence
+    /// first: what the config watcher watches.
+    pub files: Vec<PathBuf>,
+    /// The merged settings, without `include` and `os`.
+    pub table: Table,
+    /// Source of each value in `table`, by dotted key: `font-size`,
+    /// `profiles.work.host`, `keybindings[2]`.
+    pub sources: BTreeMap<String, Source>,
+}
+
+/// Return the system-wide config file path, layered under the user's.
+pub fn system_config_path() -> Option<PathBuf> {
+    #[cfg(unix)]
+    {
+        Some(PathBuf::from("/etc/humanssh/config.toml"))
+    }
+    #[cfg(windows)]
+    {
+        std::env::var_os("ProgramData")
+            .map(|dir| PathBuf::from(dir).join("humanssh").join("config.toml"))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+/// Read and merge the system and user config files.
+pub fn load_layers() -> LayeredConfig {
+    let roots: Vec<PathBuf> = system_config_path()
+        .into_iter()
+        .chain(crate::config_path())
+        .collect();
+    load_layers_from(&roots)
+}
+
+/// Read and merge config files, lowest precedence first.
+pub fn load_layers_from(roots: &[PathBuf]) -> LayeredConfig {
+    let mut layers = LayeredConfig::default();
+    for root in roots {
+        layers.load_file(root, &mut Vec::new());
+    }
+    layers
+}
+
+/// Where an `include` entry points: `~/` is the home directory and a
+/// relative path is relative to the including file's directory.
+pub fn resolve_include(dir: &Path, include: &str) -> PathBuf {
+    match include.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => dir.join(include),
+    }
+}
+
+/// The `include` entries of a config table, which may be a string or an
+/// array of them.
+pub(crate) fn include_entries(include: &Value) -> Vec<&str> {
+    match include {
+        Value::String(path) => vec![path.as_str()],
+        Value::Array(paths) => paths.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl LayeredConfig {
+    /// Deserialize the merged settings.
+    pub fn config(&self) -> Result<Config, toml::de::Error> {
+        Value::Table(self.table.clone()).try_into()
+    }
+
+    fn load_file(&mut self, path: &Path, including: &mut Vec<PathBuf>) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if including.contains(&path) {
+            tracing::warn!("Ignoring include cycle through {:?}", path);
+            return;
+        }
+        if including.len() > MAX_INCLUDE_DEPTH {
+            tracing::warn!("Ignoring {:?}: includes nested too deeply", path);
+            return;
+        }
+        let Some(mut table) = read_table(&path) else {
+            self.note_file(&path);
+            return;
+        };
+        let include = table.remove("include");
+        let os_section = match table.remove("os") {
+            Some(Value::Table(mut sections)) => match sections.remove(std::env::consts::OS) {
+                Some(Value::Table(section)) => section,
+                _ => Table::new(),
+            },
+            _ => Table::new(),
+        };
+
+        if let Some(include) = include {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            including.push(path.clone());
+            for entry in include_entries(&include) {
+                self.load_file(&resolve_include(dir, entry), including);
+            }
+            including.pop();
+        }
+        self.note_file(&path);
+
+        // A file whose values don't fit is skipped as a whole, like one that
+        // doesn't parse, rather than taking the other layers down with it
+        let mut own = table.clone();
+        merge_table(&mut own, os_section.clone());
+        if let Err(e) = Value::Table(own).try_into::<Config>() {
+            tracing::warn!("Ignoring {:?}: {}", path, e.message());
+            return;
+        }
+
+        let source = Source {
+            path: path.clone(),
+            os_section: false,
+        };
+        self.merge(table, &source);
+        let source = Source {
+            os_section: true,
+            ..source
+        };
+        self.merge(os_section, &source);
+    }
+
+    fn note_file(&mut self, path: &Path) {
+        if !self.files.iter().any(|file| file == path) {
+            self.files.push(path.to_path_buf());
+        }
+    }
+
+    fn merge(&mut self, layer: Table, source: &Source) {
+        for (key, value) in layer {
+            match (key.as_str(), value) {
+                ("keybindings", Value::Array(keybindings)) => {
+                    let merged = array_entry(&mut self.table, &key);
+                    for keybinding in keybindings {
+                        self.sources
+                            .insert(format!("keybindings[{}]", merged.len()), source.clone());
+                        merged.push(keybinding);
+                    }
+                }
+                ("profiles", Value::Array(profiles)) => {
+                    for profile in profiles {
+                        self.merge_profile(profile, source);
+                    }
+                }
+                (_, value) => {
+                    let target = self
+                        .table
+                        .entry(key.clone())
+                        .or_insert(Value::Boolean(false));
+                    merge_value(target, value, &key, &mut self.sources, source);
+                }
+            }
+        }
+    }
+
+    fn merge_profile(&mut self, profile: Value, source: &Source) {
+        let name = profile
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let profiles = array_entry(&mut self.table, "profiles");
+        let existing = name.as_ref().and_then(|name| {
+            profiles
+                .iter()
+                .position(|p| p.get("name").and_then(Value::as_str) == Some(name))
+        });
+        let path = match &name {
+            Some(name) => format!("profiles.{}", name),
+            None => format!("profiles[{}]", profiles.len()),
+        };
+        match existing {
+            Some(index) => merge_value(
+                &mut profiles[index],
+                profile,
+                &path,
+                &mut self.sources,
+                source,
+            ),
+            None => {
+                record(&mut self.sources, &path, &profile, source);
+                profiles.push(profile);
+            }
+        }
+    }
+
+    /// Where the value at a dotted key came from: its own source, or that of
+    /// the nearest enclosing value set as a whole.
+    pub fn source_of(&self, key: &str) -> Option<&Source> {
+        let mut key = key;
+        loop {
+            if let Some(source) = self.sources.get(key) {
+                return Some(source);
+            }
+            key = &key[..key.rfind(['.', '['])?];
+        }
+    }
+
+    /// The merged config as TOML, each value commented with its source.
+    pub fn render(&self) -> String {
+        let mut out = String::from("# Effective config; layers, lowest precedence first:\n");
+        for file in &self.files {
+            let missing = if file.exists() { "" } else { " (not found)" };
+            out.push_str(&format!("#   {}{}\n", display_path(file), missing));
+        }
+        self.render_table(&mut out, None, "", &self.table);
+        out
+    }
+
+    fn render_table(&self, out: &mut String, header: Option<&str>, prefix: &str, table: &Table) {
+        let path_of = |key: &str| match prefix {
+            "" => key.to_string(),
+            _ => format!("{}.{}", prefix, key),
+        };
+        if let Some(header) = header {
+            out.push_str(&format!("\n{}\n", header));
+        }
+        for (key, value) in table {
+            if value.is_table() || is_array_of_tables(value) {
+                continue;
+            }
+            let source = self
+                .source_of(&path_of(key))
+                .map(|source| format!("  # {}", source))
+                .unwrap_or_default();
+            out.push_str(&format!("{} = {}{}\n", toml_key(key), value, source));
+        }
+        for (key, value) in table {
+            let name = match header {
+                Some(header) => format!(
+                    "{}.{}",
+                    header.trim_matches(|c| c == '[' || c == ']'),
+                    toml_key(key)
+                ),
+                None => toml_key(key),
+            };
+            match value {
+                Value::Table(table) => {
+                    self.render_table(out, Some(&format!("[{}]", name)), &path_of(key), table)
+                }
+                Value::Array(items) if is_array_of_tables(value) => {
+                    for (index, item) in items.iter().enumerate() {
+                        let Value::Table(table) = item else {
+                            continue;
+                        };
+                        let prefix = match (key.as_str(), item.get("name").and_then(Value::as_str))
+                        {
+                            ("profiles", Some(profile)) => format!("profiles.{}", profile),
+                            _ => format!("{}[{}]", path_of(key), index),
+                        };
+                        self.render_table(out, Some(&format!("[[{}]]", name)), &prefix, table);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Read one file as a TOML table, or `None` (with a warning unless it's just
+/// missing) if it can't be used.
+fn read_table(path: &Path) -> Option<Table> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to read {:?}: {}", path, e);
+            }
+            return None;
+        }
+    };
+    if content.len() > MAX_FILE_SIZE as usize {
+        tracing::warn!("Ignoring {:?}: too large ({} bytes)", path, content.len());
+        return None;
+    }
+    match toml::from_str(&content) {
+        Ok(table) => Some(table),
+        Err(e) => {
+            tracing::warn!("Failed to parse {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Merge `value` into `target` at dotted key `path`: tables key by key,
+/// anything else replaced.
+fn merge_value(
+    target: &mut Value,
+    value: Value,
+    path: &str,
+    sources: &mut BTreeMap<String, Source>,
+    source: &Source,
+) {
+    match (target, value) {
+        (Value::Table(target), Value::Table(table)) => {
+            for (key, value) in table {
+                let path = format!("{}.{}", path, key);
+                let target = target.entry(key).or_insert(Value::Boolean(false));
+                merge_value(target, value, &path, sources, source);
+            }
+        }
+        (target, value) => {
+            let nested = format!("{}.", path);
+            sources.retain(|key, _| !key.starts_with(&nested));
+            record(sources, path, &value, source);
+            *target = value;
+        }
+    }
+}
+
+/// Merge without tracking sources, for checking a single file.
+fn merge_table(target: &mut Table, table: Table) {
+    let mut target_value = Value::Table(std::mem::take(target));
+    merge_value(
+        &mut target_value,
+        Value::Table(table),
+        "",
+        &mut BTreeMap::new(),
+        &Source {
+            path: PathBuf::new(),
+            os_section: false,
+        },
+    );
+    if let Value::Table(table) = target_value {
+        *target = table;
+    }
+}
+
+/// Record the source of a value set as a whole, down to each table key so a
+/// later layer can override part of it.
+fn record(sources: &mut BTreeMap<String, Source>, path: &str, value: &Value, source: &Source) {
+    sources.insert(path.to_string(), source.clone());
+    if let Value::Table(table) = value {
+        for (key, value) in table {
+            record(sources, &format!("{}.{}", path, key), value, source);
+        }
+    }
+}
+
+/// The array at `key`, replacing whatever else is there.
+fn array_entry<'a>(table: &'a mut Table, key: &str) -> &'a mut Vec<Value> {
+    let entry = table
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if !entry.is_array() {
+        *entry = Value::Array(Vec::new());
+    }
+    match entry {
+        Value::Array(array) => array,
+        _ => unreachable!("replaced with an array above"),
+    }
+}
+
+fn is_array_of_tables(value: &Value) -> bool {
+    value
+        .as_array()
+        .is_some_and(|items| !items.is_empty() && items.iter().all(Value::is_table))
+}
+
+/// A key as written in TOML: bare if it can be, quoted otherwise.
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+/// A path with the home directory shown as `~`.
+fn display_path(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let dir = TempDir::new().unwrap();
+        let system = write(&dir, "system.toml", "theme = \"Nord\"\nfont-size = 12\n");
+        let user = write(&dir, "user.toml", "font-size = 16\n");
+
+        let layers = load_layers_from(&[system.clone(), user.clone()]);
+        let config = layers.config().unwrap();
+        assert_eq!(config.theme, "Nord");
+        assert_eq!(config.font_size, 16.0);
+        assert_eq!(layers.source_of("theme").unwrap().path, system);
+        assert_eq!(layers.source_of("font-size").unwrap().path, user);
+        assert_eq!(layers.files, vec![system, user]);
+    }
+
+    #[test]
+    fn includes_sit_under_the_including_file() {
+        let dir = TempDir::new().unwrap();
+        let team = write(
+            &dir,
+            "team.toml",
+            "font-size = 12\nscroll-reverse = true\n\n[[keybindings]]\nkeys = \"cmd-k\"\naction = \"new-tab\"\n",
+        );
+        let user = write(
+            &dir,
+            "config.toml",
+            "include = [\"team.toml\", \"missing.toml\"]\nfont-size = 16\n\n[[keybindings]]\nkeys = \"cmd-j\"\naction = \"new-tab\"\n",
+        );
+
+        let layers = load_layers_from(std::slice::from_ref(&user));
+        let config = layers.config().unwrap();
+        assert_eq!(config.font_size, 16.0);
+        assert!(config.scroll_reverse);
+        // Both files' keybindings, the including file's last
+        let keys: Vec<&str> = config.keybindings.iter().map(|k| k.keys.as_str()).collect();
+        assert_eq!(keys, ["cmd-k", "cmd-j"]);
+        assert_eq!(layers.source_of("keybindings[0].keys").unwrap().path, team);
+        assert_eq!(layers.source_of("keybindings[1]").unwrap().path, user);
+        assert!(!layers.table.contains_key("include"));
+        // Missing includes are still watched, so creating one takes effect
+        assert_eq!(layers.files.len(), 3);
+        assert_eq!(layers.files[0], team);
+        assert!(layers.files[1].ends_with("missing.toml"));
+        assert_eq!(layers.files[2], user);
+    }
+
+    #[test]
+    fn include_cycles_are_cut() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "a.toml", "include = \"b.toml\"\nfont-size = 10\n");
+        write(&dir, "b.toml", "include = [\"a.toml\"]\nfont-size = 20\n");
+
+        let layers = load_layers_from(&[dir.path().join("a.toml")]);
+        assert_eq!(layers.config().unwrap().font_size, 10.0);
+        assert_eq!(layers.files.len(), 2);
+    }
+
+    #[test]
+    fn os_section_overrides_its_file() {
+        let dir = TempDir::new().unwrap();
+        let content = format!(
+            "font-size = 12\n\n[os.{}]\nfont-size = 18\n\n[os.plan9]\nfont-size = 9\n",
+            std::env::consts::OS
+        );
+        let user = write(&dir, "config.toml", &content);
+
+        let layers = load_layers_from(&[user]);
+        assert_eq!(layers.config().unwrap().font_size, 18.0);
+        assert!(layers.source_of("font-size").unwrap().os_section);
+        assert!(!layers.table.contains_key("os"));
+    }
+
+    #[test]
+    fn profiles_merge_by_name() {
+        let dir = TempDir::new().unwrap();
+        let team = write(
+            &dir,
+            "team.toml",
+            "[[profiles]]\nname = \"db\"\nhost = \"db.internal\"\nfont-size = 12\n\n[[profiles]]\nname = \"web\"\nhost = \"web.internal\"\n",
+        );
+        let user = write(
+            &dir,
+            "user.toml",
+            "[[profiles]]\nname = \"db\"\nfont-size = 16\n\n[[profiles]]\nname = \"mine\"\n",
+        );
+
+        let layers = load_layers_from(&[team.clone(), user.clone()]);
+        let config = layers.config().unwrap();
+        let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["db", "web", "mine"]);
+        assert_eq!(config.profiles[0].host.as_deref(), Some("db.internal"));
+        assert_eq!(config.profiles[0].font_size, Some(16.0));
+        assert_eq!(layers.source_of("profiles.db.host").unwrap().path, team);
+        assert_eq!(
+            layers.source_of("profiles.db.font-size").unwrap().path,
+            user
+        );
+    }
+
+    #[test]
+    fn broken_layer_is_skipped() {
+        let dir = TempDir::new().unwrap();
+        let system = write(&dir, "system.toml", "theme = \"Nord\"\n");
+        let bad_type = write(&dir, "type.toml", "font-size = \"big\"\n");
+        let bad_syntax = write(&dir, "syntax.toml", "font-size = = 3\n");
+
+        let layers = load_layers_from(&[system, bad_type, bad_syntax]);
+        let config = layers.config().unwrap();
+        assert_eq!(config.theme, "Nord");
+        assert_eq!(config.font_size, 14.0);
+    }
+
+    #[test]
+    fn render_names_each_source() {
+        let dir = TempDir::new().unwrap();
+        let system = write(&dir, "system.toml", "theme = \"Nord\"\n");
+        let user = write(
+            &dir,
+            "user.toml",
+            "font-size = 16\n\n[[profiles]]\nname = \"db\"\nserial = { device = \"COM1\" }\n",
+        );
+
+        let rendered = load_layers_from(&[system.clone(), user.clone()]).render();
+        let system = display_path(&system);
+        let user = display_path(&user);
+        assert!(rendered.contains(&format!("#   {}\n#   {}\n", system, user)));
+        assert!(rendered.contains(&format!("theme = \"Nord\"  # {}\n", system)));
+        assert!(rendered.contains(&format!("font-size = 16  # {}\n", user)));
+        assert!(rendered.contains(&format!(
+            "\n[[profiles]]\nname = \"db\"  # {}\n\n[profiles.serial]\ndevice = \"COM1\"  # {}\n",
+            user, user
+        )));
+        // The output is itself a config file
+        let reparsed: Config = toml::from_str(&rendered).unwrap();
+        assert_eq!(reparsed.profiles[0].serial.as_ref().unwrap().device, "COM1");
+    }
+}
@@ -0,0 +1,150 @@
+//! Saved window layout for session restore.
+//!
+//! Records each window's tab groups, their tabs and split layouts, and the
+//! directory of every pane. Running programs aren't saved: restored panes
+//! start a fresh shell in their old directory.
+//!
+//! Stored as JSON at `<data-dir>/humanssh/session.json`.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Every open window, in the order they were opened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub windows: Vec<WindowSnapshot>,
+}
+
+/// One window's tab groups.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    pub groups: Vec<TabGroupSnapshot>,
+    /// Index of the group that was shown
+    pub active_group: usize,
+}
+
+/// A named group of tabs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabGroupSnapshot {
+    pub name: String,
+    pub tabs: Vec<TabSnapshot>,
+    pub active_tab: usize,
+}
+
+/// A tab and its split layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabSnapshot {
+    /// Name given by the user, if any
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Tab color name (`red`, `blue`, ...)
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    pub layout: LayoutSnapshot,
+}
+
+/// A pane, or a split of two layouts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum LayoutSnapshot {
+    Pane {
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+    Split {
+        /// Panes side by side (`true`) or stacked
+        horizontal: bool,
+        /// Share of the space given to `first`
+        ratio: f32,
+        first: Box<LayoutSnapshot>,
+        second: Box<LayoutSnapshot>,
+    },
+}
+
+fn session_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("humanssh").join("session.json"))
+}
+
+/// Load the last saved session, if there is one.
+pub fn load_session() -> Option<SessionSnapshot> {
+    let content = std::fs::read_to_string(session_path()?).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(session) => Some(session),
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable session file: {}", e);
+            None
+        }
+    }
+}
+
+/// Save the session for the next launch.
+pub fn save_session(session: &SessionSnapshot) {
+    let Some(path) = session_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            tracing::warn!("Failed to create session directory: {}", e);
+            return;
+        }
+    }
+    let content = match serde_json::to_string_pretty(session) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("Failed to serialize session: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = std::fs::write(&path, content) {
+        tracing::warn!("Failed to save session: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_round_trips_through_json() {
+        let session = SessionSnapshot {
+            windows: vec![WindowSnapshot {
+                groups: vec![TabGroupSnapshot {
+                    name: "api".to_string(),
+                    tabs: vec![TabSnapshot {
+                        title: Some("logs".to_string()),
+                        color: Some("green".to_string()),
+                        pinned: true,
+                        layout: LayoutSnapshot::Split {
+                            horizontal: true,
+                            ratio: 0.3,
+                            first: Box::new(LayoutSnapshot::Pane {
+                                cwd: Some(PathBuf::from("/srv/api")),
+                            }),
+                            second: Box::new(LayoutSnapshot::Pane { cwd: None }),
+                        },
+                    }],
+                    active_tab: 0,
+                }],
+                active_group: 0,
+            }],
+        };
+
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains(r#""kind":"split""#));
+        let parsed: SessionSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, session);
+    }
+
+    #[test]
+    fn missing_optional_fields_default() {
+        let json = r#"{"windows":[{"groups":[{"name":"scratch","active_tab":0,
+            "tabs":[{"layout":{"kind":"pane"}}]}],"active_group":0}]}"#;
+        let parsed: SessionSnapshot = serde_json::from_str(json).unwrap();
+        let tab = &parsed.windows[0].groups[0].tabs[0];
+        assert_eq!(tab.title, None);
+        assert!(!tab.pinned);
+        assert_eq!(tab.layout, LayoutSnapshot::Pane { cwd: None });
+    }
+}
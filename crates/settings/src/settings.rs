@@ -4,8 +4,13 @@
 
 pub mod constants;
 pub mod file;
+pub mod history;
+pub mod layers;
+pub mod session;
+pub mod validate;
 
 pub use file::{
     apply_config, config_path, ensure_config_file, load_config, watch_config, AutoSwitchRule,
-    Config, KeybindingEntry, MergedProfileConfig, Profile,
+    Config, ConfigWatcher, FlowControl, KeybindingEntry, MergedProfileConfig, Parity, Profile,
+    SerialConfig, TelnetConfig, TelnetMode,
 };
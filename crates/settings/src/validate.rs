@@ -0,0 +1,843 @@
+//! Config file validation.
+//!
+//! [`load_config`](crate::load_config) is forgiving: a file that doesn't
+//! parse falls back to the defaults, unknown keys are ignored and a bad
+//! keybinding is skipped. This pass reports each of those with its line and
+//! column, for the in-app banner and `humanssh --check-config`. Each layer
+//! file (see [`crate::layers`]) is checked on its own.
+
+use crate::constants::{scrollback, settings, terminal};
+use crate::layers::resolve_include;
+use crate::Config;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use toml_edit::{ImDocument, Item, Table, TableLike, Value};
+
+/// How much a problem matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The setting (or the whole file) is ignored.
+    Error,
+    /// The setting is ignored or applies, but probably not as intended.
+    Warning,
+}
+
+/// One problem in the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, in characters.
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, severity, self.message
+        )
+    }
+}
+
+/// What the config is checked against that this crate can't see: loaded
+/// themes, installed fonts and the action and keymap parsers. A `None`
+/// skips that check.
+#[derive(Default)]
+pub struct Environment<'a> {
+    pub themes: Option<&'a [String]>,
+    pub fonts: Option<&'a [String]>,
+    /// Why an action (`activate-tab 3`) is rejected.
+    pub check_action: Option<&'a dyn Fn(&str) -> Result<(), String>>,
+    /// Why a key chord (`cmd-shift-t`) is rejected.
+    pub check_chord: Option<&'a dyn Fn(&str) -> Result<(), String>>,
+    /// Why a key context (`terminal`) is rejected.
+    pub check_context: Option<&'a dyn Fn(&str) -> Result<(), String>>,
+    /// Why a key sequence (`ctrl-a v`) can't start in its context, such as
+    /// a single-chord binding that takes its first chord.
+    pub check_sequence: Option<&'a dyn Fn(&str, Option<&str>) -> Result<(), String>>,
+    /// Profile names from every layer, for `default-profile`; `None` uses
+    /// the file's own.
+    pub profiles: Option<&'a [String]>,
+}
+
+/// Keys that only make sense at the top of a file, not in an OS section.
+const LAYER_KEYS: &[&str] = &["include", "os"];
+const SETTING_KEYS: &[&str] = &[
+    "theme",
+    "font-family",
+    "font-size",
+    "option-as-alt",
+    "secure-keyboard-entry",
+    "scrollback-lines",
+    "window-width",
+    "window-height",
+    "linux-decorations",
+    "windows-shell",
+    "scroll-reverse",
+    "reopen-with-scrollback",
+    "restore-session",
+    "key-sequence-timeout-ms",
+    "font-fallbacks",
+    "keybindings",
+    "profiles",
+    "default-profile",
+];
+const KEYBINDING_KEYS: &[&str] = &["keys", "action", "context"];
+const PROFILE_KEYS: &[&str] = &[
+    "name",
+    "font-family",
+    "font-size",
+    "font-fallbacks",
+    "shell",
+    "working-directory",
+    "auto-switch",
+    "host",
+    "forwards",
+    "serial",
+    "telnet",
+    "group",
+];
+const AUTO_SWITCH_KEYS: &[&str] = &["hostname-pattern", "directory-pattern"];
+const SERIAL_KEYS: &[&str] = &[
+    "device",
+    "baud-rate",
+    "data-bits",
+    "parity",
+    "stop-bits",
+    "flow-control",
+    "local-echo",
+];
+const TELNET_KEYS: &[&str] = &["host", "port", "mode"];
+const OS_NAMES: &[&str] = &["macos", "linux", "windows"];
+
+/// Every `[os.<name>]` section, read as settings to catch type errors there.
+#[derive(Deserialize)]
+struct OsSections {
+    #[serde(default)]
+    #[allow(dead_code)] // Only deserialized for its errors
+    os: BTreeMap<String, Config>,
+}
+
+/// Check the config file at `path`. A missing file has no problems.
+pub fn check_config_file(path: &Path, env: &Environment) -> Vec<Diagnostic> {
+    let at_start = |message: String| {
+        vec![Diagnostic {
+            line: 1,
+            column: 1,
+            severity: Severity::Error,
+            message,
+        }]
+    };
+    match std::fs::read_to_string(path) {
+        Ok(content) if content.len() > settings::MAX_FILE_SIZE as usize => at_start(format!(
+            "file is too large ({} bytes, at most {}); this file is ignored",
+            content.len(),
+            settings::MAX_FILE_SIZE
+        )),
+        Ok(content) => validate_file(&content, path.parent(), env),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => at_start(format!("cannot read the file: {}", e)),
+    }
+}
+
+/// Check config file contents, returning problems in file order.
+pub fn validate(content: &str, env: &Environment) -> Vec<Diagnostic> {
+    validate_file(content, None, env)
+}
+
+/// [`validate`], also checking that included files exist when the file's
+/// directory is known.
+fn validate_file(content: &str, dir: Option<&Path>, env: &Environment) -> Vec<Diagnostic> {
+    let mut report = Report {
+        content,
+        diagnostics: Vec::new(),
+    };
+
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            report.error(
+                e.span(),
+                format!("{}; this file is ignored", one_line(e.message())),
+            );
+            return report.diagnostics;
+        }
+    };
+    // Syntax is fine but a value has the wrong type: serde gives up on the
+    // whole file
+    if let Err(e) = toml::from_str::<Config>(content) {
+        report.error(
+            e.span(),
+            format!("{}; this file is ignored", one_line(e.message())),
+        );
+    } else if let Err(e) = toml::from_str::<OsSections>(content) {
+        report.error(
+            e.span(),
+            format!("{}; this file is ignored on that OS", one_line(e.message())),
+        );
+    }
+
+    check_config(&mut report, document.as_table(), dir, env);
+    report
+        .diagnostics
+        .sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    report.diagnostics
+}
+
+struct Report<'a> {
+    content: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report<'_> {
+    fn push(&mut self, span: Option<Range<usize>>, severity: Severity, message: String) {
+        let offset = span.map_or(0, |span| span.start).min(self.content.len());
+        let before = &self.content[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        self.diagnostics.push(Diagnostic {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            severity,
+            message,
+        });
+    }
+
+    fn error(&mut self, span: Option<Range<usize>>, message: String) {
+        self.push(span, Severity::Error, message);
+    }
+
+    fn warning(&mut self, span: Option<Range<usize>>, message: String) {
+        self.push(span, Severity::Warning, message);
+    }
+}
+
+fn check_config(report: &mut Report, config: &Table, dir: Option<&Path>, env: &Environment) {
+    let known: Vec<&str> = SETTING_KEYS.iter().chain(LAYER_KEYS).copied().collect();
+    check_known_keys(report, config, &known, "");
+
+    let os_sections: Vec<(&str, &dyn TableLike)> = config
+        .get("os")
+        .and_then(Item::as_table_like)
+        .map(|sections| {
+            sections
+                .iter()
+                .filter_map(|(name, section)| Some((name, section.as_table_like()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let profile_names: Vec<String> = match env.profiles {
+        Some(profiles) => profiles.to_vec(),
+        None => std::iter::once(config as &dyn TableLike)
+            .chain(os_sections.iter().map(|(_, section)| *section))
+            .flat_map(|table| tables(table.get("profiles")))
+            .filter_map(|profile| profile.get("name")?.as_str().map(str::to_string))
+            .collect(),
+    };
+
+    check_settings(report, config, env, &profile_names);
+    if let Some(item) = config.get("include") {
+        check_includes(report, item, dir);
+    }
+    if let Some(sections) = config.get("os").and_then(Item::as_table_like) {
+        for (name, _) in sections.iter().filter(|(name, _)| !OS_NAMES.contains(name)) {
+            report.warning(
+                sections.key(name).and_then(|key| key.span()),
+                format!(
+                    "unknown OS '{}' in os; expected one of {}",
+                    name,
+                    OS_NAMES.join(", ")
+                ),
+            );
+        }
+    }
+    for (name, section) in os_sections {
+        check_known_keys(report, section, SETTING_KEYS, &format!(" in [os.{}]", name));
+        check_settings(report, section, env, &profile_names);
+    }
+}
+
+/// Check the settings of a file's top level or of one of its OS sections.
+fn check_settings(
+    report: &mut Report,
+    config: &dyn TableLike,
+    env: &Environment,
+    profile_names: &[String],
+) {
+    if let (Some(item), Some(themes)) = (config.get("theme"), env.themes) {
+        if let Some(name) = item
+            .as_str()
+            .filter(|name| !themes.iter().any(|t| t == name))
+        {
+            report.error(item.span(), format!("unknown theme '{}'", name));
+        }
+    }
+    check_fonts(report, config, env);
+    check_range(
+        report,
+        config.get("font-size"),
+        "font-size",
+        terminal::MIN_FONT_SIZE.into(),
+        terminal::MAX_FONT_SIZE.into(),
+    );
+    check_range(
+        report,
+        config.get("scrollback-lines"),
+        "scrollback-lines",
+        0.0,
+        scrollback::MAX_LINES as f64,
+    );
+    check_one_of(
+        report,
+        config.get("linux-decorations"),
+        "linux-decorations",
+        &["server", "client"],
+    );
+    check_one_of(
+        report,
+        config.get("windows-shell"),
+        "windows-shell",
+        &["powershell", "pwsh", "cmd"],
+    );
+
+    for keybinding in tables(config.get("keybindings")) {
+        check_keybinding(report, keybinding, env);
+    }
+
+    for profile in tables(config.get("profiles")) {
+        check_profile(report, profile, env);
+    }
+    if let Some(item) = config.get("default-profile") {
+        if let Some(name) = item
+            .as_str()
+            .filter(|name| !profile_names.iter().any(|profile| profile == name))
+        {
+            report.error(
+                item.span(),
+                format!("default-profile '{}' does not name a profile", name),
+            );
+        }
+    }
+}
+
+/// `include` is a file name or a list of them, each of which must exist.
+fn check_includes(report: &mut Report, item: &Item, dir: Option<&Path>) {
+    let entries: Vec<&Value> = match item.as_value() {
+        Some(Value::Array(entries)) => entries.iter().collect(),
+        Some(entry) => vec![entry],
+        None => Vec::new(),
+    };
+    for entry in entries {
+        let Some(name) = entry.as_str() else {
+            report.error(
+                entry.span(),
+                "include takes file names, such as \"team.toml\"".to_string(),
+            );
+            continue;
+        };
+        if let Some(dir) = dir.filter(|dir| !resolve_include(dir, name).is_file()) {
+            report.error(
+                entry.span(),
+                format!("included file '{}' not found in {}", name, dir.display()),
+            );
+        }
+    }
+}
+
+fn check_keybinding(report: &mut Report, keybinding: &dyn TableLike, env: &Environment) {
+    check_known_keys(report, keybinding, KEYBINDING_KEYS, " in a keybinding");
+
+    if let (Some(item), Some(check_chord)) = (keybinding.get("keys"), env.check_chord) {
+        if let Some(keys) = item.as_str() {
+            let problem = if keys.trim().is_empty() {
+                Some("no keys".to_string())
+            } else {
+                keys.split_whitespace()
+                    .find_map(|chord| check_chord(chord).err())
+            };
+            if let Some(problem) = problem {
+                report.error(item.span(), format!("invalid keys '{}': {}", keys, problem));
+            } else if let (Some(_), Some(check_sequence)) =
+                (keys.split_whitespace().nth(1), env.check_sequence)
+            {
+                let context = keybinding.get("context").and_then(Item::as_str);
+                if let Err(problem) = check_sequence(keys, context) {
+                    report.warning(item.span(), problem);
+                }
+            }
+        }
+    }
+    if let (Some(item), Some(check_action)) = (keybinding.get("action"), env.check_action) {
+        if let Some(Err(problem)) = item.as_str().map(check_action) {
+            report.error(item.span(), problem);
+        }
+    }
+    if let (Some(item), Some(check_context)) = (keybinding.get("context"), env.check_context) {
+        if let Some(Err(problem)) = item.as_str().map(check_context) {
+            report.error(item.span(), format!("invalid context: {}", problem));
+        }
+    }
+}
+
+fn check_profile(report: &mut Report, profile: &dyn TableLike, env: &Environment) {
+    check_known_keys(report, profile, PROFILE_KEYS, " in a profile");
+    check_fonts(report, profile, env);
+    check_range(
+        report,
+        profile.get("font-size"),
+        "font-size",
+        terminal::MIN_FONT_SIZE.into(),
+        terminal::MAX_FONT_SIZE.into(),
+    );
+
+    if let Some(auto_switch) = profile.get("auto-switch").and_then(Item::as_table_like) {
+        check_known_keys(report, auto_switch, AUTO_SWITCH_KEYS, " in auto-switch");
+    }
+    if let Some(serial) = profile.get("serial").and_then(Item::as_table_like) {
+        check_known_keys(report, serial, SERIAL_KEYS, " in serial");
+        check_range(
+            report,
+            serial.get("baud-rate"),
+            "baud-rate",
+            1.0,
+            u32::MAX.into(),
+        );
+        check_range(report, serial.get("data-bits"), "data-bits", 5.0, 8.0);
+        check_range(report, serial.get("stop-bits"), "stop-bits", 1.0, 2.0);
+    }
+    if let Some(telnet) = profile.get("telnet").and_then(Item::as_table_like) {
+        check_known_keys(report, telnet, TELNET_KEYS, " in telnet");
+        check_range(report, telnet.get("port"), "port", 1.0, u16::MAX.into());
+    }
+}
+
+/// Warn about keys that aren't settings, suggesting a close match.
+fn check_known_keys(report: &mut Report, table: &dyn TableLike, known: &[&str], place: &str) {
+    for (key, _) in table.iter() {
+        if known.contains(&key) {
+            continue;
+        }
+        let span = table.key(key).and_then(|key| key.span());
+        let suggestion = known
+            .iter()
+            .map(|candidate| (edit_distance(key, candidate), candidate))
+            .filter(|(distance, _)| *distance <= 2)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| format!("; did you mean '{}'?", candidate))
+            .unwrap_or_default();
+        report.warning(
+            span,
+            format!("unknown key '{}'{}{}", key, place, suggestion),
+        );
+    }
+}
+
+/// Warn about `font-family` and `font-fallbacks` entries that aren't installed.
+fn check_fonts(report: &mut Report, table: &dyn TableLike, env: &Environment) {
+    let Some(fonts) = env.fonts else {
+        return;
+    };
+    let mut check = |item: &dyn Spanned| {
+        if let Some(name) = item.as_str() {
+            if !fonts.iter().any(|font| font.eq_ignore_ascii_case(name)) {
+                report.warning(item.span(), format!("font '{}' is not installed", name));
+            }
+        }
+    };
+    if let Some(item) = table.get("font-family") {
+        check(item);
+    }
+    if let Some(array) = table.get("font-fallbacks").and_then(Item::as_array) {
+        for value in array.iter() {
+            check(value);
+        }
+    }
+}
+
+/// A TOML item or array element: both have a span and may be a string.
+trait Spanned {
+    fn as_str(&self) -> Option<&str>;
+    fn span(&self) -> Option<Range<usize>>;
+}
+
+impl Spanned for Item {
+    fn as_str(&self) -> Option<&str> {
+        Item::as_str(self)
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        Item::span(self)
+    }
+}
+
+impl Spanned for Value {
+    fn as_str(&self) -> Option<&str> {
+        Value::as_str(self)
+    }
+
+    fn span(&self) -> Option<Range<usize>> {
+        Value::span(self)
+    }
+}
+
+fn check_range(report: &mut Report, item: Option<&Item>, key: &str, min: f64, max: f64) {
+    let Some(item) = item else {
+        return;
+    };
+    let Some(value) = item
+        .as_float()
+        .or_else(|| item.as_integer().map(|i| i as f64))
+    else {
+        return;
+    };
+    if !(min..=max).contains(&value) {
+        report.error(
+            item.span(),
+            format!("{} must be between {} and {}, not {}", key, min, max, value),
+        );
+    }
+}
+
+fn check_one_of(report: &mut Report, item: Option<&Item>, key: &str, allowed: &[&str]) {
+    let Some(item) = item else {
+        return;
+    };
+    if let Some(value) = item.as_str().filter(|value| !allowed.contains(value)) {
+        report.error(
+            item.span(),
+            format!(
+                "{} must be one of {}, not '{}'",
+                key,
+                allowed
+                    .iter()
+                    .map(|a| format!("'{}'", a))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                value
+            ),
+        );
+    }
+}
+
+/// The tables of an array of tables (`[[profiles]]`) or of an array of
+/// inline tables (`profiles = [{ ... }]`).
+fn tables(item: Option<&Item>) -> Vec<&dyn TableLike> {
+    match item {
+        Some(Item::ArrayOfTables(array)) => {
+            array.iter().map(|table| table as &dyn TableLike).collect()
+        }
+        Some(Item::Value(Value::Array(array))) => array
+            .iter()
+            .filter_map(Value::as_inline_table)
+            .map(|table| table as &dyn TableLike)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// TOML parser messages span several lines; the banner has one.
+fn one_line(message: &str) -> String {
+    message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Levenshtein distance, for "did you mean" suggestions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AutoSwitchRule, KeybindingEntry, Profile, SerialConfig, TelnetConfig};
+    use serde::de::{self, Visitor};
+    use std::cell::Cell;
+    use std::collections::BTreeSet;
+
+    fn check(content: &str) -> Vec<String> {
+        let themes = ["Catppuccin Mocha".to_string(), "Nord".to_string()];
+        let fonts = ["Menlo".to_string(), "JetBrains Mono".to_string()];
+        let check_action = |action: &str| match action.split_whitespace().next() {
+            Some("new-tab" | "activate-tab") => Ok(()),
+            _ => Err(format!("unknown action '{}'", action)),
+        };
+        let check_chord = |chord: &str| match chord.split('-').next_back() {
+            Some("") | None => Err(format!("'{}' has no key", chord)),
+            Some(_) => Ok(()),
+        };
+        let check_sequence =
+            |keys: &str, context: Option<&str>| match (keys.split_whitespace().next(), context) {
+                (Some("ctrl-w"), None) => Err("'ctrl-w' is also bound to close-tab".to_string()),
+                _ => Ok(()),
+            };
+        let env = Environment {
+            themes: Some(&themes),
+            fonts: Some(&fonts),
+            check_action: Some(&check_action),
+            check_chord: Some(&check_chord),
+            check_context: None,
+            check_sequence: Some(&check_sequence),
+            profiles: None,
+        };
+        validate(content, &env)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn clean_config_has_no_diagnostics() {
+        let content = r#"
+theme = "Nord"
+font-family = "Menlo"
+font-size = 16
+font-fallbacks = ["JetBrains Mono"]
+default-profile = "work"
+
+[[keybindings]]
+keys = "ctrl-a v"
+action = "activate-tab 2"
+
+[[profiles]]
+name = "work"
+font-size = 12.5
+serial = { device = "/dev/ttyUSB0", data-bits = 7 }
+"#;
+        assert_eq!(check(content), Vec::<String>::new());
+    }
+
+    #[test]
+    fn default_config_has_no_diagnostics() {
+        let content = crate::file::DEFAULT_CONFIG.replace("FONT_PLACEHOLDER", "Menlo");
+        assert_eq!(validate(&content, &Environment::default()), Vec::new());
+    }
+
+    #[test]
+    fn syntax_error_has_position() {
+        let diagnostics = check("theme = \"Nord\"\nfont-size = = 3\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0].starts_with("2:13: error: "),
+            "{:?}",
+            diagnostics
+        );
+        assert!(diagnostics[0].ends_with("this file is ignored"));
+    }
+
+    #[test]
+    fn type_error_ignores_the_file() {
+        let diagnostics = check("font-size = \"big\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0].starts_with("1:13: error: "),
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn unknown_keys_suggest_a_close_match() {
+        assert_eq!(
+            check("font_size = 12\nbogus = 1\n\n[[profiles]]\nname = \"a\"\nhots = \"x\"\n"),
+            vec![
+                "1:1: warning: unknown key 'font_size'; did you mean 'font-size'?",
+                "2:1: warning: unknown key 'bogus'",
+                "6:1: warning: unknown key 'hots' in a profile; did you mean 'host'?",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_themes_fonts_and_profiles() {
+        assert_eq!(
+            check(
+                "theme = \"Nope\"\nfont-family = \"Comic Sans\"\nfont-fallbacks = [\"menlo\", \"Papyrus\"]\ndefault-profile = \"home\"\n"
+            ),
+            vec![
+                "1:9: error: unknown theme 'Nope'",
+                "2:15: warning: font 'Comic Sans' is not installed",
+                "3:28: warning: font 'Papyrus' is not installed",
+                "4:19: error: default-profile 'home' does not name a profile",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_bad_keybindings() {
+        assert_eq!(
+            check(
+                "keybindings = [\n  { keys = \"cmd-\", action = \"new-tab\" },\n  { keys = \"cmd-k\", action = \"explode\" },\n]\n"
+            ),
+            vec![
+                "2:12: error: invalid keys 'cmd-': 'cmd-' has no key",
+                "3:30: error: unknown action 'explode'",
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_unreachable_key_sequences() {
+        assert_eq!(
+            check(
+                "keybindings = [\n  { keys = \"ctrl-w v\", action = \"new-tab\" },\n  { keys = \"ctrl-w s\", action = \"new-tab\", context = \"terminal\" },\n]\n"
+            ),
+            vec!["2:12: warning: 'ctrl-w' is also bound to close-tab"]
+        );
+    }
+
+    #[test]
+    fn reports_out_of_range_values() {
+        assert_eq!(
+            check(
+                "font-size = 100\nscrollback-lines = 1000000\nlinux-decorations = \"fancy\"\n\n[[profiles]]\nname = \"a\"\nserial = { device = \"COM1\", stop-bits = 3 }\n"
+            ),
+            vec![
+                "1:13: error: font-size must be between 8 and 32, not 100",
+                "2:20: error: scrollback-lines must be between 0 and 100000, not 1000000",
+                "3:21: error: linux-decorations must be one of 'server', 'client', not 'fancy'",
+                "7:41: error: stop-bits must be between 1 and 2, not 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        let diagnostics = check("# ünïcödé\ntheme = \"Nöpe\"\n");
+        assert_eq!(diagnostics, vec!["2:9: error: unknown theme 'Nöpe'"]);
+    }
+
+    #[test]
+    fn missing_file_has_no_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert!(check_config_file(&path, &Environment::default()).is_empty());
+        std::fs::write(&path, "font-size = [").unwrap();
+        assert_eq!(check_config_file(&path, &Environment::default()).len(), 1);
+    }
+
+    #[test]
+    fn checks_os_sections() {
+        assert_eq!(
+            check(
+                "[os.linux]\nfont-size = 99\ninclude = []\n\n[os.beos]\ntheme = \"Nord\"\n\n[os.macos]\nfont-size = \"big\"\n"
+            ),
+            vec![
+                "2:13: error: font-size must be between 8 and 32, not 99",
+                "3:1: warning: unknown key 'include' in [os.linux]",
+                "5:5: warning: unknown OS 'beos' in os; expected one of macos, linux, windows",
+                "9:13: error: invalid type: string \"big\", expected f32; this file is ignored on that OS",
+            ]
+        );
+    }
+
+    #[test]
+    fn default_profile_may_name_a_profile_from_another_layer() {
+        let profiles = ["team".to_string()];
+        let env = Environment {
+            profiles: Some(&profiles),
+            ..Environment::default()
+        };
+        assert!(validate("default-profile = \"team\"\n", &env).is_empty());
+        assert_eq!(validate("default-profile = \"mine\"\n", &env).len(), 1);
+        // Without the other layers, a profile in an OS section counts
+        assert!(
+            check("default-profile = \"a\"\n\n[[os.linux.profiles]]\nname = \"a\"\n").is_empty()
+        );
+    }
+
+    #[test]
+    fn included_files_must_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("team.toml"), "").unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "include = [\"team.toml\", \"gone.toml\", 3]\n").unwrap();
+
+        let diagnostics: Vec<String> = check_config_file(&path, &Environment::default())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diagnostics,
+            vec![
+                format!(
+                    "1:25: error: included file 'gone.toml' not found in {}",
+                    dir.path().display()
+                ),
+                "1:38: error: include takes file names, such as \"team.toml\"".to_string(),
+            ]
+        );
+    }
+
+    /// A deserializer that only records the field names a struct asks for.
+    struct FieldNames<'a>(&'a Cell<&'static [&'static str]>);
+
+    impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0.set(fields);
+            Err(de::Error::custom("fields recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    /// The keys `T` reads from a table, as serde names them.
+    fn serde_keys<T: for<'de> Deserialize<'de>>() -> BTreeSet<&'static str> {
+        let fields = Cell::new(&[][..]);
+        let _ = T::deserialize(FieldNames(&fields));
+        fields.get().iter().copied().collect()
+    }
+
+    fn keys(list: &[&'static str]) -> BTreeSet<&'static str> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn known_keys_match_the_config_structs() {
+        assert_eq!(keys(SETTING_KEYS), serde_keys::<Config>());
+        assert_eq!(keys(KEYBINDING_KEYS), serde_keys::<KeybindingEntry>());
+        assert_eq!(keys(PROFILE_KEYS), serde_keys::<Profile>());
+        assert_eq!(keys(AUTO_SWITCH_KEYS), serde_keys::<AutoSwitchRule>());
+        assert_eq!(keys(SERIAL_KEYS), serde_keys::<SerialConfig>());
+        assert_eq!(keys(TELNET_KEYS), serde_keys::<TelnetConfig>());
+    }
+}
@@ -0,0 +1,530 @@
+//! In-band file transfers over the terminal stream (ZMODEM and trzsz).
+//!
+//! Remote `sz`/`rz` and `tsz`/`trz` announce themselves with a start sequence
+//! in their output. The VT thread spots it, stops feeding the terminal, and
+//! posts a [`TransferRequest`] for the UI to answer with files to send or a
+//! folder to save into. The protocol then runs on the VT thread against the
+//! raw PTY stream, and whatever follows the end of the transfer goes back to
+//! the terminal.
+
+use crate::types::ProgressState;
+use crate::{trzsz, zmodem};
+use anyhow::{anyhow, bail, Result};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Poll interval while waiting on the UI or the remote side.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// After an abort, output is discarded until the remote side goes quiet for this long.
+const QUIET_PERIOD: Duration = Duration::from_millis(300);
+
+/// Upper bound on discarding in-flight protocol data after an abort.
+const MAX_DRAIN: Duration = Duration::from_secs(3);
+
+/// ZMODEM `ZRQINIT` hex header: the remote `sz` wants to send.
+const ZMODEM_SEND_MARKER: &[u8] = b"**\x18B00";
+
+/// ZMODEM `ZRINIT` hex header: the remote `rz` is ready to receive.
+const ZMODEM_RECEIVE_MARKER: &[u8] = b"**\x18B01";
+
+/// Printed by `tsz`/`trz`, followed by the mode (`S`, `R` or `D`).
+const TRZSZ_MARKER: &[u8] = b"::TRZSZ:TRANSFER:";
+
+/// Writes bytes to the PTY, i.e. to the remote end of the transfer.
+pub type PtyWriter = Box<dyn FnMut(&[u8]) -> Result<()> + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferProtocol {
+    Zmodem,
+    Trzsz,
+}
+
+impl TransferProtocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Zmodem => "ZMODEM",
+            Self::Trzsz => "trzsz",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// The remote side receives (`rz`, `trz`): pick local files to send.
+    Upload,
+    /// The remote side sends (`sz`, `tsz`): pick a folder to save into.
+    Download,
+}
+
+/// A transfer the remote side started, waiting for the user's answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferRequest {
+    pub protocol: TransferProtocol,
+    pub direction: TransferDirection,
+    /// `trz -d`: directory uploads, which we decline without asking.
+    directories: bool,
+}
+
+/// The user's answer to a [`TransferRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferReply {
+    /// Files to send.
+    Upload(Vec<PathBuf>),
+    /// Folder to save received files into.
+    Download(PathBuf),
+    Decline,
+}
+
+/// Transfer state shared between the VT thread and the UI.
+#[derive(Default)]
+pub struct FileTransfers {
+    request: Mutex<Option<TransferRequest>>,
+    reply: Mutex<Option<(TransferReply, PtyWriter)>>,
+    active: AtomicBool,
+    cancel: AtomicBool,
+}
+
+impl FileTransfers {
+    /// Take the pending request, if any. Answer it with [`Self::reply`].
+    pub fn take_request(&self) -> Option<TransferRequest> {
+        self.request.lock().take()
+    }
+
+    /// Answer the pending request. `writer` carries protocol bytes to the PTY.
+    ///
+    /// Ignored if the transfer was cancelled in the meantime.
+    pub fn reply(&self, reply: TransferReply, writer: PtyWriter) {
+        if self.is_active() {
+            *self.reply.lock() = Some((reply, writer));
+        }
+    }
+
+    /// Whether a transfer currently owns the terminal stream.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Abort the running transfer and hand the stream back to the terminal.
+    pub fn cancel(&self) {
+        if self.is_active() {
+            self.cancel.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// The end of a read to search again with the next one, so a start sequence
+/// split across reads is still found. Long enough for all but the last byte of
+/// the trzsz marker and its mode.
+pub(crate) fn marker_tail(buffer: &[u8]) -> &[u8] {
+    &buffer[buffer.len().saturating_sub(TRZSZ_MARKER.len())..]
+}
+
+/// Find a transfer start sequence in `buffer`. Returns its offset and the request.
+pub(crate) fn detect(buffer: &[u8]) -> Option<(usize, TransferRequest)> {
+    let find = |marker: &[u8]| buffer.windows(marker.len()).position(|w| w == marker);
+
+    let zmodem = find(ZMODEM_SEND_MARKER)
+        .map(|offset| (offset, TransferDirection::Download))
+        .into_iter()
+        .chain(find(ZMODEM_RECEIVE_MARKER).map(|offset| (offset, TransferDirection::Upload)))
+        .min_by_key(|(offset, _)| *offset)
+        .map(|(offset, direction)| {
+            (
+                offset,
+                TransferRequest {
+                    protocol: TransferProtocol::Zmodem,
+                    direction,
+                    directories: false,
+                },
+            )
+        });
+
+    let trzsz = find(TRZSZ_MARKER).and_then(|offset| {
+        let (direction, directories) = match buffer.get(offset + TRZSZ_MARKER.len())? {
+            b'S' => (TransferDirection::Download, false),
+            b'R' => (TransferDirection::Upload, false),
+            b'D' => (TransferDirection::Upload, true),
+            _ => return None,
+        };
+        Some((
+            offset,
+            TransferRequest {
+                protocol: TransferProtocol::Trzsz,
+                direction,
+                directories,
+            },
+        ))
+    });
+
+    zmodem
+        .into_iter()
+        .chain(trzsz)
+        .min_by_key(|(offset, _)| *offset)
+}
+
+/// The end of a transfer session.
+pub(crate) struct Finished {
+    /// Output after the transfer, for the terminal.
+    pub(crate) leftover: Vec<u8>,
+    /// Status line to show in the terminal, if any.
+    pub(crate) message: Option<String>,
+}
+
+/// Run a transfer session on the VT thread, from the start sequence in `initial`.
+///
+/// Blocks until the UI has answered the request and the transfer has finished,
+/// failed or been cancelled.
+pub(crate) fn run(
+    request: TransferRequest,
+    initial: &[u8],
+    output_rx: &Receiver<Vec<u8>>,
+    transfers: &FileTransfers,
+    progress: &Mutex<ProgressState>,
+    shutdown: &AtomicBool,
+) -> Finished {
+    transfers.cancel.store(false, Ordering::Release);
+    *transfers.reply.lock() = None;
+    transfers.active.store(true, Ordering::Release);
+    *progress.lock() = ProgressState::Indeterminate;
+
+    let finished = run_session(request, initial, output_rx, transfers, progress, shutdown);
+
+    *transfers.request.lock() = None;
+    transfers.active.store(false, Ordering::Release);
+    *progress.lock() = ProgressState::Hidden;
+    finished
+}
+
+fn run_session(
+    request: TransferRequest,
+    initial: &[u8],
+    output_rx: &Receiver<Vec<u8>>,
+    transfers: &FileTransfers,
+    progress: &Mutex<ProgressState>,
+    shutdown: &AtomicBool,
+) -> Finished {
+    let mut pending: VecDeque<u8> = match request.protocol {
+        TransferProtocol::Zmodem => initial.iter().copied().collect(),
+        // Skip the marker; the mode and version that follow are read as a junk line
+        TransferProtocol::Trzsz => initial.iter().skip(TRZSZ_MARKER.len()).copied().collect(),
+    };
+
+    let answer = if request.directories {
+        None
+    } else {
+        // Wait for the UI, buffering whatever the remote side sends meanwhile
+        *transfers.request.lock() = Some(request);
+        loop {
+            if let Some(answer) = transfers.reply.lock().take() {
+                break Some(answer);
+            }
+            if shutdown.load(Ordering::Acquire) || transfers.cancel.load(Ordering::Acquire) {
+                break None;
+            }
+            match output_rx.recv_timeout(POLL_INTERVAL) {
+                Ok(data) => pending.extend(data),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break None,
+            }
+        }
+    };
+
+    let Some((reply, writer)) = answer else {
+        // Nobody to answer (or write to): leave the remote side to time out
+        return Finished {
+            leftover: Vec::new(),
+            message: Some(match request.directories {
+                true => "trzsz directory uploads are not supported".to_string(),
+                false => "File transfer cancelled".to_string(),
+            }),
+        };
+    };
+
+    let mut link = Link {
+        rx: output_rx,
+        pending,
+        writer,
+        cancel: &transfers.cancel,
+        shutdown,
+        progress,
+    };
+    let result = match (request.protocol, request.direction, reply) {
+        (protocol, _, TransferReply::Decline) => {
+            match protocol {
+                TransferProtocol::Zmodem => {
+                    let _ = link.write(zmodem::ABORT);
+                    link.discard_until_quiet();
+                }
+                TransferProtocol::Trzsz => trzsz::decline(&mut link),
+            }
+            return Finished {
+                leftover: Vec::new(),
+                message: Some("File transfer cancelled".to_string()),
+            };
+        }
+        (TransferProtocol::Zmodem, TransferDirection::Upload, TransferReply::Upload(files)) => {
+            zmodem::send(&mut link, &files).map(Some)
+        }
+        (
+            TransferProtocol::Zmodem,
+            TransferDirection::Download,
+            TransferReply::Download(directory),
+        ) => zmodem::receive(&mut link, &directory).map(Some),
+        // The remote side prints trzsz's own status message
+        (TransferProtocol::Trzsz, direction, reply) => {
+            trzsz::run(&mut link, direction, reply).map(|()| None)
+        }
+        _ => Err(anyhow!("Reply doesn't match the transfer direction")),
+    };
+
+    match result {
+        Ok(message) => Finished {
+            leftover: link.pending.into(),
+            message,
+        },
+        Err(error) => {
+            tracing::warn!("{} transfer failed: {:#}", request.protocol.name(), error);
+            match request.protocol {
+                TransferProtocol::Zmodem => {
+                    let _ = link.write(zmodem::ABORT);
+                }
+                TransferProtocol::Trzsz => trzsz::abort(&mut link, &format!("{:#}", error)),
+            }
+            link.discard_until_quiet();
+            Finished {
+                leftover: Vec::new(),
+                message: Some(format!(
+                    "{} transfer failed: {:#}",
+                    request.protocol.name(),
+                    error
+                )),
+            }
+        }
+    }
+}
+
+/// The raw PTY stream, as seen by a transfer protocol.
+pub(crate) struct Link<'a> {
+    rx: &'a Receiver<Vec<u8>>,
+    pending: VecDeque<u8>,
+    writer: PtyWriter,
+    cancel: &'a AtomicBool,
+    shutdown: &'a AtomicBool,
+    progress: &'a Mutex<ProgressState>,
+}
+
+impl Link<'_> {
+    /// Read one byte, waiting up to `timeout`.
+    pub(crate) fn read_byte(&mut self, timeout: Duration) -> Result<u8> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(byte) = self.pending.pop_front() {
+                return Ok(byte);
+            }
+            self.check_cancelled()?;
+            let wait = deadline
+                .saturating_duration_since(Instant::now())
+                .min(POLL_INTERVAL);
+            if wait.is_zero() {
+                bail!("Timed out waiting for the remote side");
+            }
+            match self.rx.recv_timeout(wait) {
+                Ok(data) => self.pending.extend(data),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => bail!("Session closed"),
+            }
+        }
+    }
+
+    /// Put a byte back to be read next.
+    pub(crate) fn unread(&mut self, byte: u8) {
+        self.pending.push_front(byte);
+    }
+
+    /// Collect whatever has arrived without blocking. Returns true if input is pending.
+    pub(crate) fn poll_input(&mut self) -> bool {
+        while let Ok(data) = self.rx.try_recv() {
+            self.pending.extend(data);
+        }
+        !self.pending.is_empty()
+    }
+
+    /// Whether already-received input contains `byte`.
+    pub(crate) fn pending_contains(&self, byte: u8) -> bool {
+        self.pending.contains(&byte)
+    }
+
+    pub(crate) fn write(&mut self, data: &[u8]) -> Result<()> {
+        (self.writer)(data)
+    }
+
+    /// Fail if the user cancelled or the terminal is shutting down.
+    pub(crate) fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.load(Ordering::Acquire) || self.shutdown.load(Ordering::Acquire) {
+            bail!("Cancelled");
+        }
+        Ok(())
+    }
+
+    /// Publish progress for the tab badge.
+    pub(crate) fn report(&self, done: u64, total: Option<u64>) {
+        *self.progress.lock() = match total {
+            Some(0) => ProgressState::Normal(100),
+            Some(total) => ProgressState::Normal((done.min(total) * 100 / total) as u8),
+            None => ProgressState::Indeterminate,
+        };
+    }
+
+    /// Throw away in-flight protocol data so it doesn't land on the screen.
+    fn discard_until_quiet(&mut self) {
+        self.pending.clear();
+        let deadline = Instant::now() + MAX_DRAIN;
+        while Instant::now() < deadline {
+            match self.rx.recv_timeout(QUIET_PERIOD) {
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// A path in `directory` for `name` that doesn't overwrite anything:
+/// `name`, then `name.1`, `name.2`, ...
+pub fn unique_path(directory: &Path, name: &str) -> PathBuf {
+    let path = directory.join(name);
+    if !path.exists() {
+        return path;
+    }
+    (1..)
+        .map(|n| directory.join(format!("{}.{}", name, n)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path)
+}
+
+/// A link over `rx` for protocol tests.
+#[cfg(test)]
+pub(crate) fn test_link<'a>(rx: &'a Receiver<Vec<u8>>, writer: PtyWriter) -> Link<'a> {
+    static NEVER: AtomicBool = AtomicBool::new(false);
+    let progress: &'a Mutex<ProgressState> = Box::leak(Box::default());
+    Link {
+        rx,
+        pending: VecDeque::new(),
+        writer,
+        cancel: &NEVER,
+        shutdown: &NEVER,
+        progress,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn detects_zmodem_after_text() {
+        let (offset, request) = detect(b"rz\r**\x18B00000000000000\r\x8a\x11").unwrap();
+        assert_eq!(offset, 3);
+        assert_eq!(request.protocol, TransferProtocol::Zmodem);
+        assert_eq!(request.direction, TransferDirection::Download);
+
+        let (_, request) = detect(b"rz waiting to receive.**\x18B0100000023be50").unwrap();
+        assert_eq!(request.direction, TransferDirection::Upload);
+    }
+
+    #[test]
+    fn detects_trzsz_modes() {
+        let (offset, request) =
+            detect(b"\x1b7\x07::TRZSZ:TRANSFER:S:1.1.5:0000000001\r\n").unwrap();
+        assert_eq!(offset, 3);
+        assert_eq!(request.protocol, TransferProtocol::Trzsz);
+        assert_eq!(request.direction, TransferDirection::Download);
+
+        let (_, request) = detect(b"::TRZSZ:TRANSFER:D:1.1.5").unwrap();
+        assert_eq!(request.direction, TransferDirection::Upload);
+        assert!(request.directories);
+
+        // Mode not received yet
+        assert!(detect(b"::TRZSZ:TRANSFER:").is_none());
+    }
+
+    #[test]
+    fn detects_markers_split_across_reads() {
+        for stream in [
+            &b"$ sz notes.txt\r\n**\x18B00000000000000\r\x8a\x11"[..],
+            &b"$ tsz notes.txt\r\n\x1b7\x07::TRZSZ:TRANSFER:S:1.1.5:0000000001\r\n"[..],
+        ] {
+            let (expected, _) = detect(stream).unwrap();
+            for split in 1..stream.len() {
+                let (first, second) = stream.split_at(split);
+                let carried = marker_tail(first);
+                let found = detect(first).map(|(offset, _)| offset).or_else(|| {
+                    let joined = [carried, second].concat();
+                    detect(&joined).map(|(offset, _)| first.len() - carried.len() + offset)
+                });
+                assert_eq!(found, Some(expected), "split at {}", split);
+            }
+        }
+    }
+
+    #[test]
+    fn plain_output_is_not_a_transfer() {
+        assert!(detect(b"ls -la\r\n**bold** text\r\n").is_none());
+    }
+
+    #[test]
+    fn decline_aborts_zmodem_and_drops_protocol_bytes() {
+        let (tx, rx) = sync_channel(8);
+        let (written_tx, written_rx) = sync_channel::<Vec<u8>>(8);
+        let transfers = FileTransfers::default();
+        let progress = Mutex::new(ProgressState::Hidden);
+        let shutdown = AtomicBool::new(false);
+        let initial = b"**\x18B00000000000000\r\x8a\x11";
+        let (_, request) = detect(initial).unwrap();
+
+        let finished = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while transfers.take_request().is_none() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                transfers.reply(
+                    TransferReply::Decline,
+                    Box::new(move |data| Ok(written_tx.send(data.to_vec())?)),
+                );
+            });
+            run(request, initial, &rx, &transfers, &progress, &shutdown)
+        });
+        drop(tx);
+        assert!(finished.leftover.is_empty());
+        assert_eq!(finished.message.as_deref(), Some("File transfer cancelled"));
+        assert_eq!(written_rx.try_recv().unwrap(), zmodem::ABORT);
+        assert!(!transfers.is_active());
+        assert_eq!(*progress.lock(), ProgressState::Hidden);
+    }
+
+    #[test]
+    fn late_reply_is_ignored() {
+        let transfers = FileTransfers::default();
+        transfers.reply(TransferReply::Decline, Box::new(|_| Ok(())));
+        assert!(transfers.reply.lock().is_none());
+    }
+
+    #[test]
+    fn unique_path_never_overwrites() {
+        let directory = tempfile::tempdir().unwrap();
+        assert_eq!(
+            unique_path(directory.path(), "a.txt"),
+            directory.path().join("a.txt")
+        );
+        std::fs::write(directory.path().join("a.txt"), b"").unwrap();
+        assert_eq!(
+            unique_path(directory.path(), "a.txt"),
+            directory.path().join("a.txt.1")
+        );
+    }
+}
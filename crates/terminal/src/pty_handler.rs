@@ -1,7 +1,7 @@
 //! PTY process management.
 
 use anyhow::{Context, Result};
-use portable_pty::{native_pty_system, Child, CommandBuilder, PtyPair, PtySize};
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, PtyPair, PtySize};
 use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -199,6 +199,32 @@ fn get_validated_shell() -> String {
     DEFAULT_SHELL_WINDOWS.to_string()
 }
 
+/// The shell tabs run, with the arguments that make it run `command` and
+/// exit: `-l -c` on Unix (`-c` alone for csh, which only takes `-l` on its
+/// own), `/C` for cmd.exe and `-Command` for PowerShell.
+pub fn shell_command(command: &str) -> (String, Vec<String>) {
+    let shell = get_validated_shell();
+
+    #[cfg(not(target_os = "windows"))]
+    let mut args = if shell.ends_with("csh") {
+        Vec::new()
+    } else {
+        vec!["-l".to_string()]
+    };
+    #[cfg(not(target_os = "windows"))]
+    args.push("-c".to_string());
+
+    #[cfg(target_os = "windows")]
+    let mut args = vec![if shell.to_ascii_lowercase().ends_with("cmd.exe") {
+        "/C".to_string()
+    } else {
+        "-Command".to_string()
+    }];
+
+    args.push(command.to_string());
+    (shell, args)
+}
+
 /// Maximum length of a cached process name. Process names longer than this
 /// are truncated. 64 bytes covers virtually all real process names without
 /// heap allocation.
@@ -261,6 +287,9 @@ struct ProcessCache {
 /// How often to refresh process cache (in milliseconds)
 const PROCESS_CACHE_TTL_MS: u64 = 500;
 
+/// How often the reaper thread checks whether the child has exited
+const REAPER_POLL_MS: u64 = 50;
+
 /// Handles PTY spawning and I/O for terminal sessions.
 ///
 /// Spawns a pseudo-terminal with the user's default shell and provides
@@ -271,13 +300,17 @@ pub struct PtyHandler {
     writer: Box<dyn Write + Send>,
     output_rx: Option<Receiver<Vec<u8>>>,
     exited: Arc<AtomicBool>,
-    child: Box<dyn Child + Send + Sync>,
+    /// Kills the child on drop; the child itself is owned by the reaper thread.
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    child_pid: Option<u32>,
     _reader_thread: thread::JoinHandle<()>,
     /// Cached process detection results (avoids blocking UI)
     process_cache: parking_lot::Mutex<ProcessCache>,
-    /// Cached exit code (0 = success, non-zero = failure).
-    /// Populated lazily on first query after process exits.
-    cached_exit_code: Option<i32>,
+    /// Exit code (0 = success, non-zero = failure), set by the reaper thread
+    /// before it marks the process as exited. The child is only reaped while
+    /// this is locked, so `Some` means its PID may already belong to another
+    /// process.
+    exit_code: Arc<parking_lot::Mutex<Option<i32>>>,
 }
 
 impl PtyHandler {
@@ -404,7 +437,9 @@ impl PtyHandler {
 
         // Flag to track if process exited
         let exited = Arc::new(AtomicBool::new(false));
-        let exited_clone = exited.clone();
+        let killer = child.clone_killer();
+        let child_pid = child.process_id();
+        let exit_code = spawn_reaper(child, exited.clone())?;
 
         // Spawn thread to read PTY output
         let reader_thread = thread::spawn(move || {
@@ -413,8 +448,7 @@ impl PtyHandler {
             loop {
                 match reader.read(&mut buf) {
                     Ok(0) => {
-                        // EOF - process exited cleanly
-                        exited_clone.store(true, Ordering::SeqCst);
+                        // EOF - process exited; the reaper marks it once reaped
                         break;
                     }
                     Ok(n) => {
@@ -435,7 +469,6 @@ impl PtyHandler {
                         // Read errors typically mean the PTY master fd was closed
                         // (e.g., child process exited). This is normal shutdown.
                         tracing::debug!(error = %e, "PTY read ended");
-                        exited_clone.store(true, Ordering::SeqCst);
                         break;
                     }
                 }
@@ -447,10 +480,11 @@ impl PtyHandler {
             writer,
             output_rx: Some(output_rx),
             exited,
-            child,
+            killer,
+            child_pid,
             _reader_thread: reader_thread,
             process_cache: parking_lot::Mutex::new(ProcessCache::default()),
-            cached_exit_code: None,
+            exit_code,
         })
     }
 
@@ -534,16 +568,15 @@ impl PtyHandler {
             mpsc::sync_channel(PTY_OUTPUT_QUEUE_SIZE);
 
         let exited = Arc::new(AtomicBool::new(false));
-        let exited_clone = exited.clone();
+        let killer = child.clone_killer();
+        let child_pid = child.process_id();
+        let exit_code = spawn_reaper(child, exited.clone())?;
 
         let reader_thread = thread::spawn(move || {
             let mut buf = [0u8; 32768];
             loop {
                 match reader.read(&mut buf) {
-                    Ok(0) => {
-                        exited_clone.store(true, Ordering::SeqCst);
-                        break;
-                    }
+                    Ok(0) => break,
                     Ok(n) => match output_tx.try_send(buf[..n].to_vec()) {
                         Ok(()) => {}
                         Err(TrySendError::Full(_)) => {
@@ -555,7 +588,6 @@ impl PtyHandler {
                     },
                     Err(e) => {
                         tracing::debug!(error = %e, "PTY read ended (command)");
-                        exited_clone.store(true, Ordering::SeqCst);
                         break;
                     }
                 }
@@ -567,10 +599,11 @@ impl PtyHandler {
             writer,
             output_rx: Some(output_rx),
             exited,
-            child,
+            killer,
+            child_pid,
             _reader_thread: reader_thread,
             process_cache: parking_lot::Mutex::new(ProcessCache::default()),
-            cached_exit_code: None,
+            exit_code,
         })
     }
 
@@ -600,33 +633,11 @@ impl PtyHandler {
 
     /// Get the exit code of the PTY process, if it has exited.
     ///
-    /// Returns `None` if the process is still running. Once the process exits,
-    /// the result is cached. `portable_pty::ExitStatus` only exposes `.success()`,
-    /// so we return 0 for success and 1 for failure.
-    pub fn exit_code(&mut self) -> Option<i32> {
-        if let Some(code) = self.cached_exit_code {
-            return Some(code);
-        }
-        if !self.has_exited() {
-            return None;
-        }
-        match self.child.try_wait() {
-            Ok(Some(status)) => {
-                let code = if status.success() { 0 } else { 1 };
-                self.cached_exit_code = Some(code);
-                Some(code)
-            }
-            Ok(None) => {
-                // Process marked as exited but status not yet available
-                self.cached_exit_code = Some(0);
-                Some(0)
-            }
-            Err(error) => {
-                tracing::warn!(%error, "Failed to get exit status, assuming success");
-                self.cached_exit_code = Some(0);
-                Some(0)
-            }
-        }
+    /// Returns `None` while the process is running, or if its status couldn't
+    /// be read. Processes killed by a signal report 1. Only reads the status
+    /// stored by the reaper thread, so it's cheap enough to call while rendering.
+    pub fn exit_code(&self) -> Option<i32> {
+        *self.exit_code.lock()
     }
 
     /// Resize the PTY
@@ -674,7 +685,7 @@ impl PtyHandler {
     /// - macOS: `lsof -p <pid>` to find the cwd file descriptor
     /// - Linux: reads `/proc/<pid>/cwd` symlink
     pub fn get_current_directory(&self) -> Option<std::path::PathBuf> {
-        let pid = self.child.process_id()?;
+        let pid = self.child_pid?;
         self.get_foreground_process_cwd(pid)
     }
 
@@ -836,7 +847,7 @@ impl PtyHandler {
     /// Actually detect child processes (the slow operation).
     /// Returns (has_children, process_name).
     fn detect_child_processes(&self) -> (bool, Option<String>) {
-        let Some(pid) = self.child.process_id() else {
+        let Some(pid) = self.child_pid else {
             return (false, None);
         };
 
@@ -949,20 +960,61 @@ impl PtyHandler {
     }
 }
 
+/// Reap `child` on a background thread so its exit code is known without
+/// blocking the UI. The code is stored before `exited` is set, so anyone who
+/// sees the exit can read it.
+///
+/// The child is polled rather than waited on so that reaping happens under
+/// the exit code lock: `Drop` takes the same lock and only kills a child that
+/// hasn't been reaped, never a process that reused its PID.
+fn spawn_reaper(
+    mut child: Box<dyn Child + Send + Sync>,
+    exited: Arc<AtomicBool>,
+) -> Result<Arc<parking_lot::Mutex<Option<i32>>>> {
+    let exit_code = Arc::new(parking_lot::Mutex::new(None));
+    let exit_code_clone = exit_code.clone();
+    thread::Builder::new()
+        .name("humanssh-pty-reaper".into())
+        .spawn(move || {
+            loop {
+                let mut exit_code = exit_code_clone.lock();
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        *exit_code = Some(if status.success() {
+                            0
+                        } else {
+                            i32::try_from(status.exit_code()).unwrap_or(1).max(1)
+                        });
+                        break;
+                    }
+                    Ok(None) => {}
+                    Err(error) => {
+                        tracing::warn!(%error, "Failed to get exit status");
+                        break;
+                    }
+                }
+                drop(exit_code);
+                thread::sleep(std::time::Duration::from_millis(REAPER_POLL_MS));
+            }
+            exited.store(true, Ordering::SeqCst);
+        })
+        .context("Failed to spawn PTY reaper thread")?;
+    Ok(exit_code)
+}
+
 impl Drop for PtyHandler {
     fn drop(&mut self) {
         // Signal reader thread to stop by marking as exited
         self.exited.store(true, Ordering::SeqCst);
 
-        // Kill the child process if still running
-        if let Err(e) = self.child.kill() {
-            // ESRCH (no such process) is expected if already exited
-            tracing::debug!("Kill child process: {}", e);
-        }
-
-        // Wait for child to reap it (avoid zombie)
-        if let Err(e) = self.child.wait() {
-            tracing::debug!("Wait for child process: {}", e);
+        // Kill the child process if it hasn't been reaped; the reaper thread
+        // reaps it. Holding the lock keeps the reaper from reaping in between.
+        let exit_code = self.exit_code.lock();
+        if exit_code.is_none() {
+            if let Err(e) = self.killer.kill() {
+                // ESRCH (no such process) is expected if it exited but isn't reaped yet
+                tracing::debug!("Kill child process: {}", e);
+            }
         }
 
         tracing::debug!("PTY handler dropped, child process cleaned up");
@@ -1036,6 +1088,14 @@ mod tests {
         );
     }
 
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_shell_command_runs_the_validated_shell() {
+        let (shell, args) = shell_command("echo hi");
+        assert!(shell.starts_with('/'));
+        assert_eq!(&args[args.len() - 2..], ["-c", "echo hi"]);
+    }
+
     #[cfg(not(target_os = "windows"))]
     #[test]
     fn test_get_validated_shell_returns_default_when_shell_unset() {
@@ -3239,7 +3299,7 @@ mod tests {
     #[test]
     fn test_exit_code_returns_none_before_exit() {
         // Use a real PtyHandler: spawn a shell, check exit_code before it exits
-        let mut handler = PtyHandler::spawn(24, 80).expect("Failed to spawn PTY");
+        let handler = PtyHandler::spawn(24, 80).expect("Failed to spawn PTY");
         assert_eq!(
             handler.exit_code(),
             None,
@@ -3266,6 +3326,20 @@ mod tests {
         assert_eq!(code, Some(0), "Clean exit should return code 0");
     }
 
+    #[test]
+    fn test_exit_code_reports_command_status() {
+        let handler = PtyHandler::spawn_command(24, 80, "sh", &["-c", "exit 255"], None)
+            .expect("Failed to spawn PTY");
+        for _ in 0..100 {
+            if handler.has_exited() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(handler.has_exited());
+        assert_eq!(handler.exit_code(), Some(255));
+    }
+
     #[test]
     fn test_exit_code_caches_result() {
         let mut handler = PtyHandler::spawn(24, 80).expect("Failed to spawn PTY");
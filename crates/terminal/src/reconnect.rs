@@ -0,0 +1,309 @@
+//! Automatic reconnect for remote sessions.
+//!
+//! A pane with a [`ReconnectTarget`] stays open when its connection drops and
+//! reopens the session after an exponential backoff. [`ReconnectState`] does
+//! the bookkeeping; the pane owns the timer and swaps the new session in, so
+//! the scrollback carries over.
+
+use crate::session::Session;
+use crate::telnet::{self, TelnetHandler};
+use crate::PtyHandler;
+use anyhow::Result;
+use settings::TelnetConfig;
+use std::time::{Duration, Instant};
+
+/// Delay before the first retry.
+pub const INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between retries.
+pub const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Automatic retries before giving up; the Reconnect action starts over.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// A session that stays up this long counts as recovered: the next drop
+/// starts the backoff over.
+pub const STABLE_AFTER: Duration = Duration::from_secs(30);
+
+/// ssh's own exit status for connection errors, as opposed to the remote
+/// command's status.
+const SSH_CONNECTION_ERROR: i32 = 255;
+
+/// How to reopen a remote session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReconnectTarget {
+    /// An `ssh` invocation. Only exit status 255 counts as a dropped
+    /// connection; any other status is the remote shell exiting.
+    Ssh { args: Vec<String> },
+    /// A Telnet or raw TCP connection. A reset counts as dropped; a clean
+    /// close from the server doesn't.
+    Telnet(TelnetConfig),
+}
+
+impl ReconnectTarget {
+    /// Whether a session that ended with `exit_code` lost its connection,
+    /// rather than being closed on purpose.
+    pub fn is_dropped(&self, exit_code: Option<i32>) -> bool {
+        match self {
+            Self::Ssh { .. } => exit_code == Some(SSH_CONNECTION_ERROR),
+            Self::Telnet(_) => exit_code.is_some_and(|code| code != 0),
+        }
+    }
+
+    /// Explain a dropped session for the status bar.
+    pub fn describe_drop(&self, exit_code: Option<i32>) -> String {
+        match (self, exit_code) {
+            (Self::Ssh { .. }, Some(code)) => format!("ssh exited with status {}", code),
+            (Self::Telnet(config), _) => format!("Connection to {} lost", config.display_name()),
+            (Self::Ssh { .. }, None) => "ssh exited".to_string(),
+        }
+    }
+
+    /// Host this target connects to.
+    pub fn host(&self) -> &str {
+        match self {
+            // The host follows `--` in the args built by `SshTarget`; bare
+            // invocations end with it
+            Self::Ssh { args } => args
+                .iter()
+                .position(|arg| arg == "--")
+                .and_then(|i| args.get(i + 1))
+                .or(args.last())
+                .map_or("", String::as_str),
+            Self::Telnet(config) => &config.host,
+        }
+    }
+
+    /// Open a fresh session. Telnet connects here, so call it off the main thread.
+    pub fn open(&self, rows: u16, cols: u16) -> Result<Session> {
+        match self {
+            Self::Ssh { args } => {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                Ok(PtyHandler::spawn_command(rows, cols, "ssh", &args, None)?.into())
+            }
+            Self::Telnet(config) => {
+                let stream = telnet::connect(config)?;
+                Ok(TelnetHandler::new(stream, config.mode, cols, rows)?.into())
+            }
+        }
+    }
+}
+
+/// Backoff before retry number `attempt` (1-based): 1s, 2s, 4s, ... up to [`MAX_DELAY`].
+pub fn backoff(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    INITIAL_DELAY.saturating_mul(1 << exponent).min(MAX_DELAY)
+}
+
+/// Retry bookkeeping for one pane.
+#[derive(Debug)]
+pub struct ReconnectState {
+    target: ReconnectTarget,
+    /// Retries since the session was last stable.
+    attempt: u32,
+    last_error: Option<String>,
+    /// Set from a drop until a new session is up.
+    disconnected: bool,
+    /// When the next retry is due; `None` while connected or connecting.
+    retry_at: Option<Instant>,
+    connected_at: Instant,
+}
+
+impl ReconnectState {
+    /// Start tracking a session that just connected.
+    pub fn new(target: ReconnectTarget, now: Instant) -> Self {
+        Self {
+            target,
+            attempt: 0,
+            last_error: None,
+            disconnected: false,
+            retry_at: None,
+            connected_at: now,
+        }
+    }
+
+    pub fn target(&self) -> &ReconnectTarget {
+        &self.target
+    }
+
+    /// Record a dropped or failed session and schedule the next retry.
+    /// Returns the delay until then, or `None` after [`MAX_ATTEMPTS`] retries.
+    pub fn schedule_retry(&mut self, error: String, now: Instant) -> Option<Duration> {
+        if self.is_stable(now) {
+            self.attempt = 0;
+        }
+        self.disconnected = true;
+        self.last_error = Some(error);
+        if self.attempt >= MAX_ATTEMPTS {
+            self.retry_at = None;
+            return None;
+        }
+        self.attempt += 1;
+        let delay = backoff(self.attempt);
+        self.retry_at = Some(now + delay);
+        Some(delay)
+    }
+
+    /// Record that a new session is up.
+    pub fn connected(&mut self, now: Instant) {
+        self.disconnected = false;
+        self.retry_at = None;
+        self.connected_at = now;
+    }
+
+    /// Drop the scheduled retry because an attempt is starting now.
+    pub fn cancel_retry(&mut self) {
+        self.retry_at = None;
+    }
+
+    /// Start the retry budget over, for a reconnect the user asked for.
+    pub fn reset_attempts(&mut self) {
+        self.attempt = 0;
+    }
+
+    fn is_stable(&self, now: Instant) -> bool {
+        !self.disconnected && now.duration_since(self.connected_at) >= STABLE_AFTER
+    }
+
+    /// Whether the connection is down (waiting, connecting or given up).
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Retries since the session was last stable.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Whether a retry is scheduled.
+    pub fn is_waiting(&self) -> bool {
+        self.retry_at.is_some()
+    }
+
+    /// Time left until the scheduled retry, if one is scheduled.
+    pub fn retry_in(&self, now: Instant) -> Option<Duration> {
+        self.retry_at.map(|at| at.saturating_duration_since(now))
+    }
+
+    /// Whether the scheduled retry is due.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.retry_at.is_some_and(|at| now >= at)
+    }
+
+    /// Retry count and last error while disconnected or recently recovered.
+    /// `None` once the session has been stable for [`STABLE_AFTER`].
+    pub fn status(&self, now: Instant) -> Option<(u32, Option<&str>)> {
+        (self.attempt > 0 && !self.is_stable(now))
+            .then_some((self.attempt, self.last_error.as_deref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ssh() -> ReconnectTarget {
+        ReconnectTarget::Ssh {
+            args: vec!["web1".to_string()],
+        }
+    }
+
+    #[test]
+    fn host_comes_from_the_target() {
+        assert_eq!(ssh().host(), "web1");
+        let args = ["-p", "2222", "-l", "deploy", "--", "db1"];
+        let target = ReconnectTarget::Ssh {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        };
+        assert_eq!(target.host(), "db1");
+        let telnet = ReconnectTarget::Telnet(TelnetConfig::parse("telnet://switch1").unwrap());
+        assert_eq!(telnet.host(), "switch1");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(5), Duration::from_secs(16));
+        assert_eq!(backoff(7), MAX_DELAY);
+        assert_eq!(backoff(u32::MAX), MAX_DELAY);
+    }
+
+    #[test]
+    fn only_connection_errors_count_as_dropped() {
+        assert!(ssh().is_dropped(Some(255)));
+        assert!(!ssh().is_dropped(Some(0)));
+        assert!(!ssh().is_dropped(Some(1)));
+        assert!(!ssh().is_dropped(None));
+
+        let telnet = ReconnectTarget::Telnet(TelnetConfig::parse("telnet://switch1").unwrap());
+        assert!(telnet.is_dropped(Some(1)));
+        assert!(!telnet.is_dropped(Some(0)));
+        assert_eq!(
+            telnet.describe_drop(Some(1)),
+            "Connection to telnet switch1 lost"
+        );
+    }
+
+    #[test]
+    fn retries_back_off_until_stable() {
+        let start = Instant::now();
+        let mut state = ReconnectState::new(ssh(), start);
+        assert_eq!(state.status(start), None);
+
+        let t = start + Duration::from_secs(60);
+        assert_eq!(
+            state.schedule_retry("dropped".to_string(), t),
+            Some(Duration::from_secs(1))
+        );
+        assert!(state.is_waiting());
+        assert!(!state.is_due(t));
+        assert_eq!(state.retry_in(t), Some(Duration::from_secs(1)));
+        assert_eq!(state.status(t), Some((1, Some("dropped"))));
+
+        // Connecting clears the timer but the session still counts as down
+        let t = t + Duration::from_secs(1);
+        assert!(state.is_due(t));
+        state.cancel_retry();
+        assert!(!state.is_waiting());
+        assert_eq!(state.status(t + STABLE_AFTER), Some((1, Some("dropped"))));
+
+        // The retry connects but drops straight away: backoff keeps growing
+        state.connected(t);
+        assert!(!state.is_waiting());
+        assert_eq!(
+            state.schedule_retry("again".to_string(), t),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(state.status(t), Some((2, Some("again"))));
+
+        // Once a session stays up, the status clears and backoff restarts
+        let t = t + Duration::from_secs(2);
+        state.connected(t);
+        let t = t + STABLE_AFTER;
+        assert_eq!(state.status(t), None);
+        assert_eq!(
+            state.schedule_retry("later".to_string(), t),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let t = Instant::now();
+        let mut state = ReconnectState::new(ssh(), t);
+        for _ in 0..MAX_ATTEMPTS {
+            assert!(state.schedule_retry("refused".to_string(), t).is_some());
+            state.cancel_retry();
+        }
+        assert_eq!(state.schedule_retry("refused".to_string(), t), None);
+        assert!(!state.is_waiting());
+        assert_eq!(state.status(t), Some((MAX_ATTEMPTS, Some("refused"))));
+
+        state.reset_attempts();
+        assert_eq!(
+            state.schedule_retry("refused".to_string(), t),
+            Some(INITIAL_DELAY)
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Serial console sessions.
+//!
+//! Opens a serial device with the configured line settings and exposes the
+//! same output channel and exit flag as [`crate::PtyHandler`], so the VT
+//! thread doesn't care where the bytes come from.
+
+use anyhow::{Context, Result};
+use serialport::{DataBits, SerialPort, StopBits};
+use settings::{FlowControl, Parity, SerialConfig};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Read timeout, so the reader thread notices shutdown while the line is idle.
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the line is held in the break condition.
+pub const BREAK_DURATION: Duration = Duration::from_millis(250);
+
+/// Bounded output queue, same size as the PTY's.
+const OUTPUT_QUEUE_SIZE: usize = 1024;
+
+/// An open serial port feeding a terminal pane.
+pub struct SerialHandler {
+    port: Box<dyn SerialPort>,
+    config: SerialConfig,
+    output_tx: SyncSender<Vec<u8>>,
+    output_rx: Option<Receiver<Vec<u8>>>,
+    exited: Arc<AtomicBool>,
+    local_echo: bool,
+    _reader_thread: thread::JoinHandle<()>,
+}
+
+impl SerialHandler {
+    /// Open the device in `config` and start reading from it.
+    pub fn open(config: &SerialConfig) -> Result<Self> {
+        let port = serialport::new(&config.device, config.baud_rate)
+            .data_bits(data_bits(config.data_bits)?)
+            .parity(match config.parity {
+                Parity::None => serialport::Parity::None,
+                Parity::Odd => serialport::Parity::Odd,
+                Parity::Even => serialport::Parity::Even,
+            })
+            .stop_bits(match config.stop_bits {
+                1 => StopBits::One,
+                2 => StopBits::Two,
+                bits => anyhow::bail!("Invalid stop bits: {} (expected 1 or 2)", bits),
+            })
+            .flow_control(match config.flow_control {
+                FlowControl::None => serialport::FlowControl::None,
+                FlowControl::Software => serialport::FlowControl::Software,
+                FlowControl::Hardware => serialport::FlowControl::Hardware,
+            })
+            .timeout(READ_TIMEOUT)
+            .open()
+            .with_context(|| format!("Failed to open {}", config.device))?;
+
+        let mut reader = port
+            .try_clone()
+            .context("Failed to get serial port reader")?;
+
+        let (output_tx, output_rx) = mpsc::sync_channel(OUTPUT_QUEUE_SIZE);
+        let exited = Arc::new(AtomicBool::new(false));
+
+        let reader_tx = output_tx.clone();
+        let exited_clone = exited.clone();
+        let reader_thread = thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while !exited_clone.load(Ordering::SeqCst) {
+                match reader.read(&mut buf) {
+                    Ok(0) => {}
+                    Ok(n) => match reader_tx.try_send(buf[..n].to_vec()) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => {
+                            tracing::trace!("Serial output queue full, dropping frame");
+                        }
+                        Err(TrySendError::Disconnected(_)) => break,
+                    },
+                    Err(e) if e.kind() == ErrorKind::TimedOut => {}
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => {
+                        tracing::debug!(error = %e, "Serial read ended");
+                        exited_clone.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            port,
+            config: config.clone(),
+            output_tx,
+            output_rx: Some(output_rx),
+            exited,
+            local_echo: config.local_echo,
+            _reader_thread: reader_thread,
+        })
+    }
+
+    /// Write input bytes to the device, echoing them locally if enabled.
+    pub fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.port
+            .write_all(data)
+            .context("Failed to write to serial port (device may be disconnected)")?;
+        if self.local_echo {
+            // Enter sends CR; echo it as a full line break
+            let mut echo = Vec::with_capacity(data.len());
+            for &byte in data {
+                echo.push(byte);
+                if byte == b'\r' {
+                    echo.push(b'\n');
+                }
+            }
+            let _ = self.output_tx.try_send(echo);
+        }
+        Ok(())
+    }
+
+    /// Take the output receiver for use by the VT processing thread.
+    /// Returns `None` if already taken.
+    pub fn take_output_receiver(&mut self) -> Option<Receiver<Vec<u8>>> {
+        self.output_rx.take()
+    }
+
+    /// Get a clone of the exit flag for use by the VT processing thread.
+    pub fn exited_flag(&self) -> Arc<AtomicBool> {
+        self.exited.clone()
+    }
+
+    /// Check if the device went away.
+    pub fn has_exited(&self) -> bool {
+        self.exited.load(Ordering::SeqCst)
+    }
+
+    /// Hold the line in the break condition for [`BREAK_DURATION`].
+    ///
+    /// Returns immediately; the break is released on a helper thread.
+    pub fn send_break(&self) -> Result<()> {
+        let port = self
+            .port
+            .try_clone()
+            .context("Failed to clone serial port")?;
+        port.set_break().context("Failed to send break")?;
+        thread::spawn(move || {
+            thread::sleep(BREAK_DURATION);
+            if let Err(error) = port.clear_break() {
+                tracing::warn!("Failed to clear break: {}", error);
+            }
+        });
+        Ok(())
+    }
+
+    /// Whether typed characters are echoed locally.
+    pub fn local_echo(&self) -> bool {
+        self.local_echo
+    }
+
+    pub fn set_local_echo(&mut self, enabled: bool) {
+        self.local_echo = enabled;
+    }
+
+    /// The settings the port was opened with.
+    pub fn config(&self) -> &SerialConfig {
+        &self.config
+    }
+}
+
+impl Drop for SerialHandler {
+    fn drop(&mut self) {
+        // Stops the reader thread within one read timeout
+        self.exited.store(true, Ordering::SeqCst);
+    }
+}
+
+fn data_bits(bits: u8) -> Result<DataBits> {
+    Ok(match bits {
+        5 => DataBits::Five,
+        6 => DataBits::Six,
+        7 => DataBits::Seven,
+        8 => DataBits::Eight,
+        _ => anyhow::bail!("Invalid data bits: {} (expected 5 to 8)", bits),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serialport::TTYPort;
+    use std::time::Instant;
+
+    /// A PTY pair: the handler opens the slave side as its "device".
+    fn open_pair(config: SerialConfig) -> (TTYPort, SerialHandler) {
+        let (master, slave) = TTYPort::pair().unwrap();
+        let config = SerialConfig {
+            device: slave.name().unwrap(),
+            ..config
+        };
+        let handler = SerialHandler::open(&config).unwrap();
+        drop(slave);
+        (master, handler)
+    }
+
+    fn recv_until(rx: &Receiver<Vec<u8>>, expected: &[u8]) -> Vec<u8> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut received = Vec::new();
+        while !received.ends_with(expected) {
+            assert!(Instant::now() < deadline, "got {:?}", received);
+            if let Ok(data) = rx.recv_timeout(Duration::from_millis(50)) {
+                received.extend(data);
+            }
+        }
+        received
+    }
+
+    #[test]
+    fn device_output_reaches_the_channel() {
+        let (mut master, mut handler) = open_pair(SerialConfig::default());
+        let rx = handler.take_output_receiver().unwrap();
+        assert!(handler.take_output_receiver().is_none());
+
+        master.write_all(b"U-Boot 2024.01\r\n").unwrap();
+        assert_eq!(recv_until(&rx, b"\r\n"), b"U-Boot 2024.01\r\n");
+        assert!(!handler.has_exited());
+    }
+
+    #[test]
+    fn input_is_written_to_the_device() {
+        let (mut master, mut handler) = open_pair(SerialConfig::default());
+        let rx = handler.take_output_receiver().unwrap();
+
+        handler.write(b"help\r").unwrap();
+        let mut buf = [0u8; 5];
+        master.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"help\r");
+
+        // No local echo by default
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn local_echo_copies_input_to_the_screen() {
+        let (_master, mut handler) = open_pair(SerialConfig {
+            local_echo: true,
+            ..SerialConfig::default()
+        });
+        let rx = handler.take_output_receiver().unwrap();
+        assert!(handler.local_echo());
+
+        handler.write(b"ls\r").unwrap();
+        assert_eq!(recv_until(&rx, b"\n"), b"ls\r\n");
+
+        handler.set_local_echo(false);
+        handler.write(b"x").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn opens_with_non_default_line_settings() {
+        // A PTY ignores framing, so this only checks the settings are accepted
+        let (_master, handler) = open_pair(SerialConfig {
+            baud_rate: 9600,
+            data_bits: 7,
+            parity: Parity::Even,
+            stop_bits: 2,
+            flow_control: FlowControl::Software,
+            ..SerialConfig::default()
+        });
+        assert_eq!(handler.config().baud_rate, 9600);
+        assert_eq!(handler.config().data_bits, 7);
+    }
+
+    #[test]
+    fn send_break_returns_immediately() {
+        let (_master, handler) = open_pair(SerialConfig::default());
+        let start = Instant::now();
+        handler.send_break().unwrap();
+        assert!(start.elapsed() < BREAK_DURATION);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let (_master, slave) = TTYPort::pair().unwrap();
+        let config = SerialConfig {
+            device: slave.name().unwrap(),
+            data_bits: 9,
+            ..SerialConfig::default()
+        };
+        assert!(SerialHandler::open(&config).is_err());
+
+        let missing = SerialConfig {
+            device: "/dev/does-not-exist".to_string(),
+            ..SerialConfig::default()
+        };
+        assert!(SerialHandler::open(&missing).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! What a terminal pane is connected to: a local PTY, a serial device or a
+//! Telnet/raw TCP socket.
+//!
+//! All feed the VT thread through the same bounded output channel and exit
+//! flag. PTY-only queries (child processes, working directory) have no
+//! equivalent elsewhere and return empty answers there.
+
+use crate::serial::SerialHandler;
+use crate::telnet::TelnetHandler;
+use crate::PtyHandler;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+pub enum Session {
+    Pty(PtyHandler),
+    Serial(SerialHandler),
+    Telnet(TelnetHandler),
+}
+
+impl Session {
+    pub fn write(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Self::Pty(pty) => pty.write(data),
+            Self::Serial(serial) => serial.write(data),
+            Self::Telnet(telnet) => telnet.write(data),
+        }
+    }
+
+    pub fn take_output_receiver(&mut self) -> Option<Receiver<Vec<u8>>> {
+        match self {
+            Self::Pty(pty) => pty.take_output_receiver(),
+            Self::Serial(serial) => serial.take_output_receiver(),
+            Self::Telnet(telnet) => telnet.take_output_receiver(),
+        }
+    }
+
+    pub fn exited_flag(&self) -> Arc<AtomicBool> {
+        match self {
+            Self::Pty(pty) => pty.exited_flag(),
+            Self::Serial(serial) => serial.exited_flag(),
+            Self::Telnet(telnet) => telnet.exited_flag(),
+        }
+    }
+
+    pub fn has_exited(&self) -> bool {
+        match self {
+            Self::Pty(pty) => pty.has_exited(),
+            Self::Serial(serial) => serial.has_exited(),
+            Self::Telnet(telnet) => telnet.has_exited(),
+        }
+    }
+
+    /// Exit code of the process. A serial device that went away counts as a failure.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::Pty(pty) => pty.exit_code(),
+            Self::Serial(serial) => serial.has_exited().then_some(1),
+            Self::Telnet(telnet) => telnet.exit_code(),
+        }
+    }
+
+    /// Resize the PTY or report the size over Telnet. Serial lines have no window size.
+    pub fn resize(&self, rows: u16, cols: u16, pixel_width: u16, pixel_height: u16) -> Result<()> {
+        match self {
+            Self::Pty(pty) => pty.resize(rows, cols, pixel_width, pixel_height),
+            Self::Serial(_) => Ok(()),
+            Self::Telnet(telnet) => telnet.resize(rows, cols),
+        }
+    }
+
+    pub fn has_running_processes(&self) -> bool {
+        match self {
+            Self::Pty(pty) => pty.has_running_processes(),
+            Self::Serial(_) => false,
+            Self::Telnet(_) => false,
+        }
+    }
+
+    pub fn get_running_process_name(&self) -> Option<String> {
+        match self {
+            Self::Pty(pty) => pty.get_running_process_name(),
+            Self::Serial(_) => None,
+            Self::Telnet(_) => None,
+        }
+    }
+
+    pub fn get_current_directory(&self) -> Option<PathBuf> {
+        match self {
+            Self::Pty(pty) => pty.get_current_directory(),
+            Self::Serial(_) => None,
+            Self::Telnet(_) => None,
+        }
+    }
+
+    /// Whether typed characters are echoed locally. `None` for PTYs, where
+    /// the line discipline handles echo.
+    pub fn local_echo(&self) -> Option<bool> {
+        match self {
+            Self::Pty(_) => None,
+            Self::Serial(serial) => Some(serial.local_echo()),
+            Self::Telnet(telnet) => Some(telnet.local_echo()),
+        }
+    }
+
+    /// Turn local echo on or off. No-op for PTYs.
+    pub fn set_local_echo(&mut self, enabled: bool) {
+        match self {
+            Self::Pty(_) => {}
+            Self::Serial(serial) => serial.set_local_echo(enabled),
+            Self::Telnet(telnet) => telnet.set_local_echo(enabled),
+        }
+    }
+
+    pub fn as_serial(&self) -> Option<&SerialHandler> {
+        match self {
+            Self::Serial(serial) => Some(serial),
+            _ => None,
+        }
+    }
+}
+
+impl From<PtyHandler> for Session {
+    fn from(pty: PtyHandler) -> Self {
+        Self::Pty(pty)
+    }
+}
+
+impl From<SerialHandler> for Session {
+    fn from(serial: SerialHandler) -> Self {
+        Self::Serial(serial)
+    }
+}
+
+impl From<TelnetHandler> for Session {
+    fn from(telnet: TelnetHandler) -> Self {
+        Self::Telnet(telnet)
+    }
+}
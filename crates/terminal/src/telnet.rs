@@ -0,0 +1,640 @@
+//! Telnet and raw TCP sessions.
+//!
+//! Telnet mode negotiates ECHO, SGA, NAWS and TTYPE and strips protocol
+//! bytes before the data reaches the VT thread. Raw mode passes the socket
+//! straight through, like netcat.
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use settings::{TelnetConfig, TelnetMode};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for each resolved address to accept the connection.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bounded output queue, same size as the PTY's.
+const OUTPUT_QUEUE_SIZE: usize = 1024;
+
+/// Terminal type reported through TTYPE, matching the PTY's `TERM`.
+const TERMINAL_TYPE: &[u8] = b"xterm-256color";
+
+/// Longest subnegotiation kept; anything longer is truncated.
+const MAX_SUBNEGOTIATION: usize = 512;
+
+// RFC 854 commands
+const SE: u8 = 240;
+const SB: u8 = 250;
+const WILL: u8 = 251;
+const WONT: u8 = 252;
+const DO: u8 = 253;
+const DONT: u8 = 254;
+const IAC: u8 = 255;
+
+// Options
+const ECHO: u8 = 1;
+const SGA: u8 = 3;
+const TTYPE: u8 = 24;
+const NAWS: u8 = 31;
+
+// TTYPE subnegotiation (RFC 1091)
+const TTYPE_IS: u8 = 0;
+const TTYPE_SEND: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseState {
+    Data,
+    /// Data byte after a CR, where a NUL is padding.
+    DataAfterCr,
+    Iac,
+    Command(u8),
+    Sub,
+    SubIac,
+}
+
+/// Client side of Telnet option negotiation.
+///
+/// Feed received bytes to [`Negotiator::receive`]; it splits them into
+/// terminal data and replies to send back. Only state changes are answered,
+/// so the two sides can't loop acknowledging each other (RFC 854).
+#[derive(Debug)]
+pub struct Negotiator {
+    state: ParseState,
+    subnegotiation: Vec<u8>,
+    remote_echo: bool,
+    remote_sga: bool,
+    local_sga: bool,
+    local_naws: bool,
+    local_ttype: bool,
+    cols: u16,
+    rows: u16,
+}
+
+impl Negotiator {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Self {
+            state: ParseState::Data,
+            subnegotiation: Vec::new(),
+            remote_echo: false,
+            remote_sga: false,
+            local_sga: false,
+            local_naws: false,
+            local_ttype: false,
+            cols,
+            rows,
+        }
+    }
+
+    /// Whether the server echoes our input (it sent WILL ECHO).
+    pub fn remote_echo(&self) -> bool {
+        self.remote_echo
+    }
+
+    /// Process received bytes: terminal data goes to `data`, negotiation
+    /// replies to `replies`.
+    pub fn receive(&mut self, input: &[u8], data: &mut Vec<u8>, replies: &mut Vec<u8>) {
+        for &byte in input {
+            self.state = match (self.state, byte) {
+                (ParseState::Data | ParseState::DataAfterCr, IAC) => ParseState::Iac,
+                (ParseState::DataAfterCr, 0) => ParseState::Data,
+                (ParseState::Data | ParseState::DataAfterCr, _) => {
+                    data.push(byte);
+                    if byte == b'\r' {
+                        ParseState::DataAfterCr
+                    } else {
+                        ParseState::Data
+                    }
+                }
+                (ParseState::Iac, IAC) => {
+                    data.push(IAC);
+                    ParseState::Data
+                }
+                (ParseState::Iac, WILL | WONT | DO | DONT) => ParseState::Command(byte),
+                (ParseState::Iac, SB) => {
+                    self.subnegotiation.clear();
+                    ParseState::Sub
+                }
+                // NOP, GA, AYT and friends carry nothing for the screen
+                (ParseState::Iac, _) => ParseState::Data,
+                (ParseState::Command(command), option) => {
+                    self.negotiate(command, option, replies);
+                    ParseState::Data
+                }
+                (ParseState::Sub, IAC) => ParseState::SubIac,
+                (ParseState::Sub, _) => {
+                    if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+                        self.subnegotiation.push(byte);
+                    }
+                    ParseState::Sub
+                }
+                (ParseState::SubIac, SE) => {
+                    self.subnegotiate(replies);
+                    ParseState::Data
+                }
+                (ParseState::SubIac, IAC) => {
+                    if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+                        self.subnegotiation.push(IAC);
+                    }
+                    ParseState::Sub
+                }
+                // Malformed: drop the subnegotiation
+                (ParseState::SubIac, _) => ParseState::Data,
+            };
+        }
+    }
+
+    fn negotiate(&mut self, command: u8, option: u8, replies: &mut Vec<u8>) {
+        match (command, option) {
+            (WILL, ECHO) => Self::enable(&mut self.remote_echo, DO, option, replies),
+            (WILL, SGA) => Self::enable(&mut self.remote_sga, DO, option, replies),
+            (WILL, _) => replies.extend([IAC, DONT, option]),
+            (WONT, ECHO) => Self::disable(&mut self.remote_echo, DONT, option, replies),
+            (WONT, SGA) => Self::disable(&mut self.remote_sga, DONT, option, replies),
+            (DO, SGA) => Self::enable(&mut self.local_sga, WILL, option, replies),
+            (DO, TTYPE) => Self::enable(&mut self.local_ttype, WILL, option, replies),
+            (DO, NAWS) => {
+                Self::enable(&mut self.local_naws, WILL, option, replies);
+                replies.extend(self.window_size_report());
+            }
+            (DO, _) => replies.extend([IAC, WONT, option]),
+            (DONT, SGA) => Self::disable(&mut self.local_sga, WONT, option, replies),
+            (DONT, TTYPE) => Self::disable(&mut self.local_ttype, WONT, option, replies),
+            (DONT, NAWS) => Self::disable(&mut self.local_naws, WONT, option, replies),
+            _ => {}
+        }
+    }
+
+    fn enable(flag: &mut bool, reply: u8, option: u8, replies: &mut Vec<u8>) {
+        if !*flag {
+            *flag = true;
+            replies.extend([IAC, reply, option]);
+        }
+    }
+
+    fn disable(flag: &mut bool, reply: u8, option: u8, replies: &mut Vec<u8>) {
+        if *flag {
+            *flag = false;
+            replies.extend([IAC, reply, option]);
+        }
+    }
+
+    fn subnegotiate(&mut self, replies: &mut Vec<u8>) {
+        if self.local_ttype && self.subnegotiation == [TTYPE, TTYPE_SEND] {
+            replies.extend([IAC, SB, TTYPE, TTYPE_IS]);
+            replies.extend(TERMINAL_TYPE);
+            replies.extend([IAC, SE]);
+        }
+    }
+
+    /// Record a new window size. Returns the NAWS report to send if the
+    /// server asked for window sizes and the size changed.
+    pub fn set_window_size(&mut self, cols: u16, rows: u16) -> Option<Vec<u8>> {
+        if (cols, rows) == (self.cols, self.rows) {
+            return None;
+        }
+        self.cols = cols;
+        self.rows = rows;
+        self.local_naws.then(|| self.window_size_report())
+    }
+
+    fn window_size_report(&self) -> Vec<u8> {
+        let mut report = vec![IAC, SB, NAWS];
+        for byte in self
+            .cols
+            .to_be_bytes()
+            .into_iter()
+            .chain(self.rows.to_be_bytes())
+        {
+            report.push(byte);
+            if byte == IAC {
+                report.push(IAC);
+            }
+        }
+        report.extend([IAC, SE]);
+        report
+    }
+}
+
+/// Encode typed input for the wire.
+///
+/// Telnet doubles IAC and sends Enter as CR LF; raw mode sends Enter as LF,
+/// like a cooked tty under netcat.
+fn encode_input(mode: TelnetMode, data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len());
+    let mut bytes = data.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        match (mode, byte) {
+            (TelnetMode::Telnet, IAC) => encoded.extend([IAC, IAC]),
+            (TelnetMode::Telnet, b'\r') => {
+                encoded.extend(b"\r\n");
+                bytes.next_if_eq(&b'\n');
+            }
+            (TelnetMode::Raw, b'\r') => {
+                encoded.push(b'\n');
+                bytes.next_if_eq(&b'\n');
+            }
+            _ => encoded.push(byte),
+        }
+    }
+    encoded
+}
+
+/// Resolve `config` and connect to the first address that answers.
+///
+/// Blocks for up to [`CONNECT_TIMEOUT`] per address; call it off the main thread.
+pub fn connect(config: &TelnetConfig) -> Result<TcpStream> {
+    let addresses = (config.host.as_str(), config.port)
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve {}", config.host))?;
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = Some(error),
+        }
+    }
+    match last_error {
+        Some(error) => Err(error)
+            .with_context(|| format!("Failed to connect to {}:{}", config.host, config.port)),
+        None => anyhow::bail!("{} has no addresses", config.host),
+    }
+}
+
+/// A connected Telnet or raw TCP session feeding a terminal pane.
+pub struct TelnetHandler {
+    writer: Arc<Mutex<TcpStream>>,
+    mode: TelnetMode,
+    negotiator: Arc<Mutex<Negotiator>>,
+    output_tx: SyncSender<Vec<u8>>,
+    output_rx: Option<Receiver<Vec<u8>>>,
+    exited: Arc<AtomicBool>,
+    failed: Arc<AtomicBool>,
+    /// Set by the user; otherwise raw mode echoes and Telnet echoes unless
+    /// the server does.
+    echo_override: Option<bool>,
+    _reader_thread: thread::JoinHandle<()>,
+}
+
+impl TelnetHandler {
+    /// Start a session on a connected socket.
+    pub fn new(stream: TcpStream, mode: TelnetMode, cols: u16, rows: u16) -> Result<Self> {
+        stream
+            .set_nodelay(true)
+            .context("Failed to configure socket")?;
+        let mut reader = stream.try_clone().context("Failed to get socket reader")?;
+        let writer = Arc::new(Mutex::new(stream));
+        let negotiator = Arc::new(Mutex::new(Negotiator::new(cols, rows)));
+
+        let (output_tx, output_rx) = mpsc::sync_channel(OUTPUT_QUEUE_SIZE);
+        let exited = Arc::new(AtomicBool::new(false));
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let reader_tx = output_tx.clone();
+        let reader_writer = writer.clone();
+        let reader_negotiator = negotiator.clone();
+        let exited_clone = exited.clone();
+        let failed_clone = failed.clone();
+        let reader_thread = thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        if !exited_clone.load(Ordering::SeqCst) {
+                            tracing::debug!(error = %e, "Telnet read ended");
+                            failed_clone.store(true, Ordering::SeqCst);
+                        }
+                        break;
+                    }
+                };
+
+                let data = match mode {
+                    TelnetMode::Raw => buf[..n].to_vec(),
+                    TelnetMode::Telnet => {
+                        let mut data = Vec::with_capacity(n);
+                        let mut replies = Vec::new();
+                        reader_negotiator
+                            .lock()
+                            .receive(&buf[..n], &mut data, &mut replies);
+                        if !replies.is_empty() {
+                            if let Err(e) = reader_writer.lock().write_all(&replies) {
+                                tracing::debug!(error = %e, "Telnet negotiation reply failed");
+                            }
+                        }
+                        data
+                    }
+                };
+                if data.is_empty() {
+                    continue;
+                }
+                match reader_tx.try_send(data) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        tracing::trace!("Telnet output queue full, dropping frame");
+                    }
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+            exited_clone.store(true, Ordering::SeqCst);
+        });
+
+        Ok(Self {
+            writer,
+            mode,
+            negotiator,
+            output_tx,
+            output_rx: Some(output_rx),
+            exited,
+            failed,
+            echo_override: None,
+            _reader_thread: reader_thread,
+        })
+    }
+
+    /// Write typed input to the socket, echoing it locally if enabled.
+    pub fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.writer
+            .lock()
+            .write_all(&encode_input(self.mode, data))
+            .context("Failed to write to socket (connection may be closed)")?;
+        if self.local_echo() {
+            // Enter sends CR; echo it as a full line break
+            let mut echo = Vec::with_capacity(data.len());
+            for &byte in data {
+                echo.push(byte);
+                if byte == b'\r' {
+                    echo.push(b'\n');
+                }
+            }
+            let _ = self.output_tx.try_send(echo);
+        }
+        Ok(())
+    }
+
+    /// Take the output receiver for use by the VT processing thread.
+    /// Returns `None` if already taken.
+    pub fn take_output_receiver(&mut self) -> Option<Receiver<Vec<u8>>> {
+        self.output_rx.take()
+    }
+
+    /// Get a clone of the exit flag for use by the VT processing thread.
+    pub fn exited_flag(&self) -> Arc<AtomicBool> {
+        self.exited.clone()
+    }
+
+    /// Check if the connection closed.
+    pub fn has_exited(&self) -> bool {
+        self.exited.load(Ordering::SeqCst)
+    }
+
+    /// 0 if the server closed the connection, 1 if it broke.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.has_exited()
+            .then(|| i32::from(self.failed.load(Ordering::SeqCst)))
+    }
+
+    /// Report a new window size to the server (NAWS).
+    pub fn resize(&self, rows: u16, cols: u16) -> Result<()> {
+        if self.mode == TelnetMode::Raw {
+            return Ok(());
+        }
+        let report = self.negotiator.lock().set_window_size(cols, rows);
+        if let Some(report) = report {
+            self.writer
+                .lock()
+                .write_all(&report)
+                .context("Failed to send window size")?;
+        }
+        Ok(())
+    }
+
+    /// Whether typed characters are echoed locally.
+    pub fn local_echo(&self) -> bool {
+        self.echo_override.unwrap_or_else(|| match self.mode {
+            TelnetMode::Raw => true,
+            TelnetMode::Telnet => !self.negotiator.lock().remote_echo(),
+        })
+    }
+
+    pub fn set_local_echo(&mut self, enabled: bool) {
+        self.echo_override = Some(enabled);
+    }
+
+    pub fn mode(&self) -> TelnetMode {
+        self.mode
+    }
+}
+
+impl Drop for TelnetHandler {
+    fn drop(&mut self) {
+        self.exited.store(true, Ordering::SeqCst);
+        // Unblocks the reader thread
+        let _ = self.writer.lock().shutdown(std::net::Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    fn receive(negotiator: &mut Negotiator, input: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut data = Vec::new();
+        let mut replies = Vec::new();
+        negotiator.receive(input, &mut data, &mut replies);
+        (data, replies)
+    }
+
+    #[test]
+    fn accepts_echo_and_sga_once() {
+        let mut negotiator = Negotiator::new(80, 24);
+        let (data, replies) = receive(
+            &mut negotiator,
+            &[IAC, WILL, ECHO, IAC, WILL, SGA, b'o', b'k'],
+        );
+        assert_eq!(data, b"ok");
+        assert_eq!(replies, [IAC, DO, ECHO, IAC, DO, SGA]);
+        assert!(negotiator.remote_echo());
+
+        // Repeats are not acknowledged again
+        let (_, replies) = receive(&mut negotiator, &[IAC, WILL, ECHO]);
+        assert!(replies.is_empty());
+
+        let (_, replies) = receive(&mut negotiator, &[IAC, WONT, ECHO]);
+        assert_eq!(replies, [IAC, DONT, ECHO]);
+        assert!(!negotiator.remote_echo());
+    }
+
+    #[test]
+    fn refuses_unknown_options() {
+        let mut negotiator = Negotiator::new(80, 24);
+        let (_, replies) = receive(&mut negotiator, &[IAC, DO, 39, IAC, WILL, 34]);
+        assert_eq!(replies, [IAC, WONT, 39, IAC, DONT, 34]);
+    }
+
+    #[test]
+    fn reports_window_size() {
+        let mut negotiator = Negotiator::new(80, 24);
+        assert_eq!(negotiator.set_window_size(100, 30), None);
+
+        let (_, replies) = receive(&mut negotiator, &[IAC, DO, NAWS]);
+        assert_eq!(
+            replies,
+            [IAC, WILL, NAWS, IAC, SB, NAWS, 0, 100, 0, 30, IAC, SE]
+        );
+
+        // 255 columns must be escaped
+        assert_eq!(
+            negotiator.set_window_size(255, 30),
+            Some(vec![IAC, SB, NAWS, 0, IAC, IAC, 0, 30, IAC, SE])
+        );
+        assert_eq!(negotiator.set_window_size(255, 30), None);
+    }
+
+    #[test]
+    fn answers_terminal_type_request() {
+        let mut negotiator = Negotiator::new(80, 24);
+        let (_, replies) = receive(&mut negotiator, &[IAC, DO, TTYPE]);
+        assert_eq!(replies, [IAC, WILL, TTYPE]);
+
+        let (_, replies) = receive(&mut negotiator, &[IAC, SB, TTYPE, TTYPE_SEND, IAC, SE]);
+        let mut expected = vec![IAC, SB, TTYPE, TTYPE_IS];
+        expected.extend(TERMINAL_TYPE);
+        expected.extend([IAC, SE]);
+        assert_eq!(replies, expected);
+    }
+
+    #[test]
+    fn strips_protocol_bytes_split_across_reads() {
+        let mut negotiator = Negotiator::new(80, 24);
+        let (data, _) = receive(&mut negotiator, &[b'a', IAC]);
+        assert_eq!(data, b"a");
+        let (data, replies) = receive(&mut negotiator, &[WILL]);
+        assert!(data.is_empty() && replies.is_empty());
+        let (data, replies) = receive(&mut negotiator, &[ECHO, IAC, IAC, b'\r', 0, b'b']);
+        assert_eq!(data, [IAC, b'\r', b'b']);
+        assert_eq!(replies, [IAC, DO, ECHO]);
+    }
+
+    #[test]
+    fn encodes_input_for_the_wire() {
+        assert_eq!(
+            encode_input(TelnetMode::Telnet, &[b'l', b's', b'\r', IAC]),
+            [b'l', b's', b'\r', b'\n', IAC, IAC]
+        );
+        assert_eq!(encode_input(TelnetMode::Telnet, b"a\r\nb"), b"a\r\nb");
+        assert_eq!(
+            encode_input(TelnetMode::Raw, &[b'x', b'\r', IAC]),
+            [b'x', b'\n', IAC]
+        );
+    }
+
+    fn recv_until(rx: &Receiver<Vec<u8>>, expected: &[u8]) -> Vec<u8> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut received = Vec::new();
+        while !received.ends_with(expected) {
+            assert!(Instant::now() < deadline, "got {:?}", received);
+            if let Ok(data) = rx.recv_timeout(Duration::from_millis(50)) {
+                received.extend(data);
+            }
+        }
+        received
+    }
+
+    fn connect_local(mode: TelnetMode) -> (TcpStream, TelnetHandler) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = TelnetConfig {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            mode,
+        };
+        let stream = connect(&config).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let handler = TelnetHandler::new(stream, mode, 80, 24).unwrap();
+        (server, handler)
+    }
+
+    #[test]
+    fn telnet_session_negotiates_with_server() {
+        let (mut server, mut handler) = connect_local(TelnetMode::Telnet);
+        let rx = handler.take_output_receiver().unwrap();
+        assert!(handler.local_echo());
+
+        server
+            .write_all(&[
+                IAC, WILL, ECHO, IAC, DO, NAWS, b'l', b'o', b'g', b'i', b'n', b':',
+            ])
+            .unwrap();
+        assert_eq!(recv_until(&rx, b"login:"), b"login:");
+
+        let expected = [
+            IAC, DO, ECHO, IAC, WILL, NAWS, IAC, SB, NAWS, 0, 80, 0, 24, IAC, SE,
+        ];
+        let mut replies = [0u8; 15];
+        server.read_exact(&mut replies).unwrap();
+        assert_eq!(replies, expected);
+        assert!(!handler.local_echo());
+
+        handler.resize(40, 120).unwrap();
+        let mut report = [0u8; 9];
+        server.read_exact(&mut report).unwrap();
+        assert_eq!(report, [IAC, SB, NAWS, 0, 120, 0, 40, IAC, SE]);
+
+        handler.write(b"root\r").unwrap();
+        let mut input = [0u8; 6];
+        server.read_exact(&mut input).unwrap();
+        assert_eq!(&input, b"root\r\n");
+
+        drop(server);
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !handler.has_exited() {
+            assert!(Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(handler.exit_code(), Some(0));
+    }
+
+    #[test]
+    fn raw_session_passes_bytes_through() {
+        let (mut server, mut handler) = connect_local(TelnetMode::Raw);
+        let rx = handler.take_output_receiver().unwrap();
+
+        server.write_all(&[IAC, DO, NAWS, b'\n']).unwrap();
+        assert_eq!(recv_until(&rx, b"\n"), [IAC, DO, NAWS, b'\n']);
+
+        handler.write(b"GET / HTTP/1.0\r").unwrap();
+        let mut request = [0u8; 15];
+        server.read_exact(&mut request).unwrap();
+        assert_eq!(&request, b"GET / HTTP/1.0\n");
+
+        // Local echo, since nothing else will show what was typed
+        assert!(recv_until(&rx, b"\r\n").ends_with(b"GET / HTTP/1.0\r\n"));
+        handler.set_local_echo(false);
+        handler.write(b"x").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn connect_reports_refused_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let config = TelnetConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            mode: TelnetMode::Raw,
+        };
+        assert!(connect(&config).is_err());
+    }
+}
@@ -1,14 +1,24 @@
 //! Terminal emulation core.
 //!
-//! PTY management and terminal data structures.
+//! PTY, serial and Telnet session management and terminal data structures.
 //! This crate contains no GPUI behavioral dependencies — it's the pure logic layer.
 //! (gpui types like Hsla and SharedString are used for data representation only.)
 
+pub mod file_transfer;
 mod pty_handler;
+pub mod reconnect;
 pub mod recording;
+pub mod serial;
+pub mod session;
+pub mod telnet;
+mod trzsz;
 pub mod types;
 pub mod vt_processor;
+mod zmodem;
 
-pub use pty_handler::PtyHandler;
+pub use pty_handler::{shell_command, PtyHandler};
+pub use serial::SerialHandler;
+pub use session::Session;
+pub use telnet::TelnetHandler;
 pub use types::*;
 pub use vt_processor::TerminalProcessor;
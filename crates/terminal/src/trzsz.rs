@@ -0,0 +1,342 @@
+//! trzsz (`trz`/`tsz`) over the terminal stream.
+//!
+//! trzsz is line-based: `#TYPE:value\n` messages, each acknowledged with
+//! `#SUCC:value`, file data base64-encoded in `#DATA` chunks and checked with
+//! an MD5 digest at the end. Binary mode (`trz -b`) and directory transfers
+//! aren't supported; the remote side is told so and exits cleanly.
+
+use crate::file_transfer::{unique_path, Link, TransferDirection, TransferReply};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Protocol version we claim in the handshake.
+const VERSION: &str = "1.1.5";
+/// Raw bytes per `#DATA` chunk when uploading.
+const CHUNK_SIZE: usize = 32 * 1024;
+/// Longest line we accept (a base64 chunk of the server's largest buffer).
+const MAX_LINE: usize = 16 * 1024 * 1024;
+/// How long to wait for the remote side before giving up.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tell the remote side the transfer failed.
+pub(crate) fn abort(link: &mut Link, message: &str) {
+    let _ = send_line(link, "FAIL", &STANDARD.encode(message));
+}
+
+/// Turn down the transfer before it starts.
+pub(crate) fn decline(link: &mut Link) {
+    let _ = send_action(link, false);
+}
+
+fn send_action(link: &mut Link, confirm: bool) -> Result<()> {
+    let action = serde_json::json!({
+        "lang": "rs",
+        "confirm": confirm,
+        "version": VERSION,
+        "support_dir": false,
+    });
+    send_line(link, "ACT", &STANDARD.encode(action.to_string()))
+}
+
+/// Run a trzsz session. The stream starts just after the `::TRZSZ:TRANSFER:` marker.
+pub(crate) fn run(
+    link: &mut Link,
+    direction: TransferDirection,
+    reply: TransferReply,
+) -> Result<()> {
+    send_action(link, true)?;
+
+    let config = recv_message(link, "CFG")?;
+    let config: serde_json::Value = serde_json::from_slice(&decode(&config)?)
+        .context("Invalid trzsz configuration from the remote side")?;
+    if config["binary"].as_bool() == Some(true) {
+        bail!("trzsz binary mode is not supported; run it without -b");
+    }
+    if config["directory"].as_bool() == Some(true) {
+        bail!("trzsz directory transfers are not supported");
+    }
+
+    let message = match (direction, reply) {
+        (TransferDirection::Upload, TransferReply::Upload(files)) => upload(link, &files)?,
+        (TransferDirection::Download, TransferReply::Download(directory)) => {
+            download(link, &directory)?
+        }
+        _ => bail!("Reply doesn't match the transfer direction"),
+    };
+    // The remote side prints this message and exits
+    send_line(link, "EXIT", &STANDARD.encode(message))
+}
+
+fn upload(link: &mut Link, files: &[PathBuf]) -> Result<String> {
+    let sizes = files
+        .iter()
+        .map(|path| {
+            std::fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let total: u64 = sizes.iter().sum();
+
+    exchange(link, "NUM", &files.len().to_string())?;
+    let mut remote_names = Vec::new();
+    let mut done = 0;
+    for (path, &size) in files.iter().zip(&sizes) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .context("File has no name")?;
+        let remote_name = exchange(link, "NAME", &STANDARD.encode(&name))?;
+        remote_names.push(String::from_utf8_lossy(&decode(&remote_name)?).into_owned());
+        exchange(link, "SIZE", &size.to_string())?;
+
+        let mut file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut digest = Md5::new();
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        loop {
+            link.check_cancelled()?;
+            let count = file.read(&mut chunk)?;
+            if count == 0 {
+                break;
+            }
+            digest.update(&chunk[..count]);
+            exchange(link, "DATA", &STANDARD.encode(&chunk[..count]))?;
+            done += count as u64;
+            link.report(done, Some(total));
+        }
+        exchange(link, "MD5", &STANDARD.encode(digest.finalize()))?;
+    }
+
+    Ok(format!("Saved {}", remote_names.join(", ")))
+}
+
+fn download(link: &mut Link, directory: &Path) -> Result<String> {
+    let count = recv_message(link, "NUM")?;
+    send_line(link, "SUCC", &count)?;
+    let count: usize = count.parse().context("Invalid file count")?;
+
+    let mut saved = Vec::new();
+    for _ in 0..count {
+        let name = recv_message(link, "NAME")?;
+        let decoded = String::from_utf8_lossy(&decode(&name)?).into_owned();
+        // Only the final component: never let the sender pick the directory
+        let file_name = Path::new(&decoded)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| anyhow!("Remote sent an invalid file name '{}'", decoded))?;
+        let path = unique_path(directory, &file_name);
+        let file =
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let local_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or(file_name);
+        if let Err(error) = receive_file(link, file, &local_name) {
+            // Don't leave a partial file behind
+            let _ = std::fs::remove_file(&path);
+            return Err(error);
+        }
+        saved.push(local_name);
+    }
+
+    Ok(format!(
+        "Saved {} to {}",
+        saved.join(", "),
+        directory.display()
+    ))
+}
+
+/// Receive one file's contents after its name, checking the digest at the end.
+fn receive_file(link: &mut Link, file: File, local_name: &str) -> Result<()> {
+    send_line(link, "SUCC", &STANDARD.encode(local_name))?;
+
+    let size = recv_message(link, "SIZE")?;
+    send_line(link, "SUCC", &size)?;
+    let size: u64 = size.parse().context("Invalid file size")?;
+
+    let mut file = BufWriter::new(file);
+    let mut digest = Md5::new();
+    let mut received = 0;
+    link.report(0, Some(size));
+    while received < size {
+        let data = decode(&recv_message(link, "DATA")?)?;
+        file.write_all(&data)?;
+        digest.update(&data);
+        received += data.len() as u64;
+        send_line(link, "SUCC", &data.len().to_string())?;
+        link.report(received, Some(size));
+    }
+    file.flush()?;
+
+    let expected = recv_message(link, "MD5")?;
+    if decode(&expected)? != digest.finalize().as_slice() {
+        bail!("{}: checksum mismatch", local_name);
+    }
+    send_line(link, "SUCC", &expected)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .context("Invalid base64 from the remote side")
+}
+
+fn send_line(link: &mut Link, kind: &str, value: &str) -> Result<()> {
+    link.write(format!("#{}:{}\n", kind, value).as_bytes())
+}
+
+/// Send a message and wait for its `#SUCC` acknowledgement. Returns the ack's value.
+fn exchange(link: &mut Link, kind: &str, value: &str) -> Result<String> {
+    send_line(link, kind, value)?;
+    recv_message(link, "SUCC")
+}
+
+/// Wait for a `#kind:value` message, skipping unrelated output.
+fn recv_message(link: &mut Link, kind: &str) -> Result<String> {
+    loop {
+        let line = read_line(link)?;
+        // Anything before `#` is leftover output (e.g. the rest of the marker line)
+        let Some(start) = line.find('#') else {
+            continue;
+        };
+        let Some((message_kind, value)) = line[start + 1..].split_once(':') else {
+            continue;
+        };
+        match message_kind {
+            kind_found if kind_found == kind => return Ok(value.to_string()),
+            "FAIL" | "fail" => {
+                let reason = decode(value)
+                    .map(|reason| String::from_utf8_lossy(&reason).into_owned())
+                    .unwrap_or_else(|_| value.to_string());
+                bail!("{}", reason.trim());
+            }
+            _ => bail!(
+                "Expected #{} from the remote side, got #{}",
+                kind,
+                message_kind
+            ),
+        }
+    }
+}
+
+fn read_line(link: &mut Link) -> Result<String> {
+    let mut line = Vec::new();
+    loop {
+        match link.read_byte(TIMEOUT)? {
+            b'\n' => break,
+            b'\r' => {}
+            // Ctrl+C in the remote terminal shows up as ETX
+            0x03 => bail!("Interrupted on the remote side"),
+            byte => {
+                if line.len() >= MAX_LINE {
+                    bail!("Line from the remote side is too long");
+                }
+                line.push(byte);
+            }
+        }
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_transfer::test_link;
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn recv_message_skips_marker_remainder() {
+        let (tx, rx) = sync_channel(8);
+        tx.send(b"R:1.1.5:0000000000123\r\n#CFG:e30=\n".to_vec())
+            .unwrap();
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        assert_eq!(recv_message(&mut link, "CFG").unwrap(), "e30=");
+    }
+
+    #[test]
+    fn recv_message_reports_remote_failure() {
+        let (tx, rx) = sync_channel(8);
+        let line = format!("#FAIL:{}\n", STANDARD.encode("disk full"));
+        tx.send(line.into_bytes()).unwrap();
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        let error = recv_message(&mut link, "SUCC").unwrap_err();
+        assert_eq!(error.to_string(), "disk full");
+    }
+
+    #[test]
+    fn download_saves_file_and_acknowledges() {
+        let directory = tempfile::tempdir().unwrap();
+        let contents = b"hello trzsz";
+
+        let (tx, rx) = sync_channel(16);
+        let (sent_tx, sent_rx) = sync_channel::<Vec<u8>>(64);
+        let server = format!(
+            ":1.1.5:0\r\n#CFG:{}\n#NUM:1\n#NAME:{}\n#SIZE:{}\n#DATA:{}\n#MD5:{}\n",
+            STANDARD.encode(r#"{"binary":false}"#),
+            STANDARD.encode("../notes.txt"),
+            contents.len(),
+            STANDARD.encode(contents),
+            STANDARD.encode(Md5::digest(contents)),
+        );
+        tx.send(server.into_bytes()).unwrap();
+
+        let mut link = test_link(&rx, Box::new(move |data| Ok(sent_tx.send(data.to_vec())?)));
+        let reply = TransferReply::Download(directory.path().to_path_buf());
+        run(&mut link, TransferDirection::Download, reply).unwrap();
+        drop(link);
+
+        assert_eq!(
+            std::fs::read(directory.path().join("notes.txt")).unwrap(),
+            contents
+        );
+        let sent: Vec<String> = sent_rx
+            .iter()
+            .map(|line| String::from_utf8(line).unwrap())
+            .collect();
+        assert!(sent[0].starts_with("#ACT:"));
+        assert_eq!(sent[1], "#SUCC:1\n");
+        assert_eq!(sent[2], format!("#SUCC:{}\n", STANDARD.encode("notes.txt")));
+        assert!(sent.last().unwrap().starts_with("#EXIT:"));
+    }
+
+    #[test]
+    fn failed_download_removes_partial_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let contents = b"hello trzsz";
+
+        let (tx, rx) = sync_channel(16);
+        let server = format!(
+            ":1.1.5:0\r\n#CFG:{}\n#NUM:1\n#NAME:{}\n#SIZE:{}\n#DATA:{}\n#MD5:{}\n",
+            STANDARD.encode(r#"{"binary":false}"#),
+            STANDARD.encode("notes.txt"),
+            contents.len(),
+            STANDARD.encode(contents),
+            STANDARD.encode(Md5::digest(b"something else")),
+        );
+        tx.send(server.into_bytes()).unwrap();
+
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        let reply = TransferReply::Download(directory.path().to_path_buf());
+        let error = run(&mut link, TransferDirection::Download, reply).unwrap_err();
+        assert_eq!(error.to_string(), "notes.txt: checksum mismatch");
+        assert!(!directory.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn binary_mode_is_refused() {
+        let (tx, rx) = sync_channel(8);
+        let line = format!("#CFG:{}\n", STANDARD.encode(r#"{"binary":true}"#));
+        tx.send(line.into_bytes()).unwrap();
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        let reply = TransferReply::Download(PathBuf::from("/tmp"));
+        assert!(run(&mut link, TransferDirection::Download, reply).is_err());
+    }
+}
@@ -6,7 +6,11 @@
 //!
 //! Signaling to the UI uses a simple `AtomicBool` render-needed flag that GPUI
 //! polls via a lightweight timer, avoiding async channel dependencies.
+//!
+//! ZMODEM and trzsz transfers also run here: once a start sequence shows up,
+//! the thread hands the raw stream to [`crate::file_transfer`] until it's done.
 
+use crate::file_transfer::{self, FileTransfers};
 use crate::recording::SessionRecorder;
 use crate::types::ProgressState;
 use alacritty_terminal::event::EventListener;
@@ -40,7 +44,9 @@ pub struct TerminalProcessor {
     render_needed: Arc<AtomicBool>,
     exited_flag: Arc<AtomicBool>,
     progress: Arc<Mutex<ProgressState>>,
+    cwd: Arc<Mutex<Option<String>>>,
     recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    file_transfers: Arc<FileTransfers>,
 }
 
 impl TerminalProcessor {
@@ -63,13 +69,17 @@ impl TerminalProcessor {
         let shutdown = Arc::new(AtomicBool::new(false));
         let render_needed = Arc::new(AtomicBool::new(false));
         let progress = Arc::new(Mutex::new(ProgressState::default()));
+        let cwd = Arc::new(Mutex::new(None));
         let recorder = Arc::new(Mutex::new(None));
+        let file_transfers = Arc::new(FileTransfers::default());
 
         let shutdown_clone = shutdown.clone();
         let render_needed_clone = render_needed.clone();
         let exited_clone = exited.clone();
         let progress_clone = progress.clone();
+        let cwd_clone = cwd.clone();
         let recorder_clone = recorder.clone();
+        let file_transfers_clone = file_transfers.clone();
 
         thread::Builder::new()
             .name("humanssh-vt-processor".into())
@@ -82,7 +92,9 @@ impl TerminalProcessor {
                     render_needed_clone,
                     shutdown_clone,
                     progress_clone,
+                    cwd_clone,
                     recorder_clone,
+                    file_transfers_clone,
                 );
             })
             .expect("failed to spawn VT processing thread");
@@ -92,7 +104,9 @@ impl TerminalProcessor {
             render_needed,
             exited_flag: exited,
             progress,
+            cwd,
             recorder,
+            file_transfers,
         }
     }
 
@@ -111,6 +125,13 @@ impl TerminalProcessor {
         *self.progress.lock()
     }
 
+    /// Get the working directory last reported by the shell (OSC 7).
+    ///
+    /// For SSH sessions this is a path on the remote host.
+    pub fn cwd(&self) -> Option<String> {
+        self.cwd.lock().clone()
+    }
+
     /// Get a shared reference to the recorder slot.
     ///
     /// The caller can set or clear the recorder; the VT thread will tee
@@ -118,6 +139,14 @@ impl TerminalProcessor {
     pub fn recorder(&self) -> &Arc<Mutex<Option<SessionRecorder>>> {
         &self.recorder
     }
+
+    /// Get the in-band file transfer state (ZMODEM / trzsz).
+    ///
+    /// The caller answers pending transfer requests and can cancel a
+    /// running transfer through it.
+    pub fn file_transfers(&self) -> &Arc<FileTransfers> {
+        &self.file_transfers
+    }
 }
 
 impl Drop for TerminalProcessor {
@@ -131,8 +160,12 @@ impl Drop for TerminalProcessor {
 /// Blocks on the PTY output channel, batches all available data, parses VT sequences
 /// under a brief term lock, then sets a render-needed flag (throttled to 60fps).
 ///
-/// Also intercepts OSC 9;4 (progress bar) sequences before alacritty processes them,
-/// since alacritty doesn't handle this ConEmu extension natively.
+/// Also intercepts OSC 9;4 (progress bar) and OSC 7 (working directory) sequences
+/// before alacritty processes them, since alacritty doesn't handle either natively.
+///
+/// When a ZMODEM or trzsz start sequence arrives, output before it goes to the
+/// terminal as usual and the rest of the stream belongs to the transfer until
+/// it finishes. Transfer data is never shown or recorded.
 fn vt_thread_loop<L: EventListener>(
     output_rx: Receiver<Vec<u8>>,
     term: Arc<Mutex<Term<L>>>,
@@ -141,11 +174,36 @@ fn vt_thread_loop<L: EventListener>(
     render_needed: Arc<AtomicBool>,
     shutdown: Arc<AtomicBool>,
     progress: Arc<Mutex<ProgressState>>,
+    cwd: Arc<Mutex<Option<String>>>,
     recorder: Arc<Mutex<Option<SessionRecorder>>>,
+    file_transfers: Arc<FileTransfers>,
 ) {
     // Start in the past so the first batch of data always triggers a signal
     let mut last_signal = Instant::now() - MIN_FRAME_INTERVAL;
     let mut batch_buffer = Vec::with_capacity(BATCH_BUFFER_CAPACITY);
+    let mut tail = Vec::new();
+
+    let feed = |bytes: &[u8]| {
+        // Tee output to session recorder if active
+        {
+            let mut recorder_guard = recorder.lock();
+            if let Some(ref mut rec) = *recorder_guard {
+                if let Err(error) = rec.record_output(bytes) {
+                    tracing::warn!("Recording error, stopping: {}", error);
+                    *recorder_guard = None;
+                }
+            }
+        }
+
+        // Intercept OSC 9;4 sequences before alacritty processes them
+        extract_osc9_4(bytes, &progress);
+        extract_osc7(bytes, &cwd);
+
+        // Parse VT sequences under brief lock
+        let mut term_guard = term.lock();
+        let mut proc_guard = processor.lock();
+        proc_guard.advance(&mut *term_guard, bytes);
+    };
 
     loop {
         if shutdown.load(Ordering::Acquire) {
@@ -155,32 +213,42 @@ fn vt_thread_loop<L: EventListener>(
         // Block until data arrives or timeout (keeps thread responsive to shutdown)
         match output_rx.recv_timeout(IDLE_RECV_TIMEOUT) {
             Ok(data) => {
-                // Batch: drain all pending data into a single buffer
+                // Batch: drain all pending data into a single buffer, after the
+                // end of the last batch (already fed) in case a transfer start
+                // sequence was split between them
                 batch_buffer.clear();
+                batch_buffer.extend_from_slice(&tail);
+                let carried = tail.len();
                 batch_buffer.extend_from_slice(&data);
                 while let Ok(more) = output_rx.try_recv() {
                     batch_buffer.extend_from_slice(&more);
                 }
 
-                // Tee output to session recorder if active
-                {
-                    let mut recorder_guard = recorder.lock();
-                    if let Some(ref mut rec) = *recorder_guard {
-                        if let Err(error) = rec.record_output(&batch_buffer) {
-                            tracing::warn!("Recording error, stopping: {}", error);
-                            *recorder_guard = None;
+                match file_transfer::detect(&batch_buffer) {
+                    Some((offset, request)) => {
+                        feed(&batch_buffer[carried.min(offset)..offset]);
+                        tail.clear();
+                        render_needed.store(true, Ordering::Release);
+
+                        // Blocks until the transfer is over
+                        let finished = file_transfer::run(
+                            request,
+                            &batch_buffer[offset..],
+                            &output_rx,
+                            &file_transfers,
+                            &progress,
+                            &shutdown,
+                        );
+                        if let Some(message) = finished.message {
+                            feed(format!("\r\n{}\r\n", message).as_bytes());
                         }
+                        feed(&finished.leftover);
+                    }
+                    None => {
+                        feed(&batch_buffer[carried..]);
+                        tail.clear();
+                        tail.extend_from_slice(file_transfer::marker_tail(&batch_buffer));
                     }
-                }
-
-                // Intercept OSC 9;4 sequences before alacritty processes them
-                extract_osc9_4(&batch_buffer, &progress);
-
-                // Parse VT sequences under brief lock
-                {
-                    let mut term_guard = term.lock();
-                    let mut proc_guard = processor.lock();
-                    proc_guard.advance(&mut *term_guard, &batch_buffer);
                 }
 
                 // Throttled render signal (60fps cap)
@@ -241,6 +309,55 @@ fn extract_osc9_4(buffer: &[u8], progress: &Arc<Mutex<ProgressState>>) {
     }
 }
 
+/// Scan a byte buffer for OSC 7 working directory reports and update the shared state.
+///
+/// OSC 7 format: `ESC ] 7 ; file://HOST/PATH BEL` (or ST-terminated), with the
+/// path percent-encoded. The host is ignored: the pane already knows whether
+/// it is local or an SSH session.
+fn extract_osc7(buffer: &[u8], cwd: &Arc<Mutex<Option<String>>>) {
+    const PREFIX: &[u8] = b"\x1b]7;";
+
+    let mut pos = 0;
+    while pos + PREFIX.len() < buffer.len() {
+        let Some(offset) = memchr_prefix(&buffer[pos..], PREFIX) else {
+            break;
+        };
+        let start = pos + offset + PREFIX.len();
+        let Some((end, payload)) = find_osc_terminator(&buffer[start..]) else {
+            break;
+        };
+        if let Some(path) = std::str::from_utf8(payload).ok().and_then(parse_osc7) {
+            *cwd.lock() = Some(path);
+        }
+        pos = start + end;
+    }
+}
+
+/// Extract the decoded path from an OSC 7 payload (`file://host/path`).
+fn parse_osc7(payload: &str) -> Option<String> {
+    let rest = payload.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    percent_decode(path)
+}
+
+/// Decode `%XX` escapes. Returns None for malformed escapes or non-UTF-8 results.
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
 /// Find the prefix in a byte slice (simple linear scan).
 fn memchr_prefix(haystack: &[u8], prefix: &[u8]) -> Option<usize> {
     haystack.windows(prefix.len()).position(|w| w == prefix)
@@ -369,6 +486,110 @@ mod tests {
         drop(vt);
     }
 
+    #[test]
+    fn vt_processor_detects_osc7_cwd() {
+        let (output_tx, output_rx) = std::sync::mpsc::sync_channel(64);
+        let size = TermSize::default();
+        let config = Config::default();
+        let term = Arc::new(Mutex::new(Term::new(config, &size, TestListener)));
+        let processor = Arc::new(Mutex::new(Processor::new()));
+        let exited = Arc::new(AtomicBool::new(false));
+
+        let vt = TerminalProcessor::start(output_rx, term, processor, exited);
+
+        output_tx
+            .send(b"\x1b]7;file://web1/srv/app\x07".to_vec())
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while vt.cwd().as_deref() != Some("/srv/app") {
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for cwd update"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        drop(vt);
+    }
+
+    #[test]
+    fn vt_processor_hands_zmodem_start_to_file_transfers() {
+        use crate::file_transfer::{TransferDirection, TransferReply};
+
+        let (output_tx, output_rx) = std::sync::mpsc::sync_channel(64);
+        let size = TermSize::default();
+        let config = Config::default();
+        let term = Arc::new(Mutex::new(Term::new(config, &size, TestListener)));
+        let processor = Arc::new(Mutex::new(Processor::new()));
+        let exited = Arc::new(AtomicBool::new(false));
+
+        let vt = TerminalProcessor::start(output_rx, term.clone(), processor, exited);
+
+        output_tx
+            .send(b"ok**\x18B00000000000000\r\x8a\x11".to_vec())
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let request = loop {
+            if let Some(request) = vt.file_transfers().take_request() {
+                break request;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for transfer request"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(request.direction, TransferDirection::Download);
+        assert!(vt.file_transfers().is_active());
+
+        vt.file_transfers()
+            .reply(TransferReply::Decline, Box::new(|_| Ok(())));
+        while vt.file_transfers().is_active() {
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for transfer to end"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // Text before the start sequence reaches the terminal; the header doesn't
+        let term_guard = term.lock();
+        let line = &term_guard.grid()[alacritty_terminal::index::Line(0)];
+        assert_eq!(line[alacritty_terminal::index::Column(0)].c, 'o');
+        assert_eq!(line[alacritty_terminal::index::Column(1)].c, 'k');
+        assert_eq!(line[alacritty_terminal::index::Column(2)].c, ' ');
+        drop(term_guard);
+
+        drop(vt);
+    }
+
+    // ==================== OSC 7 Parsing Tests ====================
+
+    #[test]
+    fn parse_osc7_decodes_path() {
+        assert_eq!(
+            parse_osc7("file://host/home/me/My%20Docs").as_deref(),
+            Some("/home/me/My Docs")
+        );
+        assert_eq!(parse_osc7("file:///tmp").as_deref(), Some("/tmp"));
+    }
+
+    #[test]
+    fn parse_osc7_rejects_invalid() {
+        assert_eq!(parse_osc7("http://host/path"), None);
+        assert_eq!(parse_osc7("file://host"), None);
+        assert_eq!(parse_osc7("file://host/bad%zz"), None);
+    }
+
+    #[test]
+    fn extract_osc7_st_terminated_in_output() {
+        let cwd = Arc::new(Mutex::new(None));
+        extract_osc7(b"prompt\x1b]7;file://h/var/log\x1b\\$ ", &cwd);
+        assert_eq!(cwd.lock().as_deref(), Some("/var/log"));
+    }
+
     // ==================== OSC 9;4 Parsing Tests ====================
 
     #[test]
@@ -0,0 +1,878 @@
+//! ZMODEM (`rz`/`sz`) over the terminal stream.
+//!
+//! Covers what lrzsz and compatible peers use: hex and binary headers,
+//! streaming data subpackets and `ZRPOS` error recovery. We send CRC-16
+//! frames and don't advertise CRC-32, but still decode CRC-32 frames from
+//! senders that use them anyway.
+
+use crate::file_transfer::{unique_path, Link};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const ZPAD: u8 = b'*';
+const ZDLE: u8 = 0x18;
+const ZBIN: u8 = b'A';
+const ZHEX: u8 = b'B';
+const ZBIN32: u8 = b'C';
+
+// Frame types
+const ZRQINIT: u8 = 0;
+const ZRINIT: u8 = 1;
+const ZSINIT: u8 = 2;
+const ZACK: u8 = 3;
+const ZFILE: u8 = 4;
+const ZSKIP: u8 = 5;
+const ZNAK: u8 = 6;
+const ZABORT: u8 = 7;
+const ZFIN: u8 = 8;
+const ZRPOS: u8 = 9;
+const ZDATA: u8 = 10;
+const ZEOF: u8 = 11;
+const ZFERR: u8 = 12;
+const ZCRC: u8 = 13;
+const ZCHALLENGE: u8 = 14;
+const ZCAN: u8 = 16;
+const ZCOMMAND: u8 = 18;
+
+// Data subpacket terminators
+const ZCRCE: u8 = b'h';
+const ZCRCG: u8 = b'i';
+const ZCRCQ: u8 = b'j';
+const ZCRCW: u8 = b'k';
+const ZRUB0: u8 = b'l';
+const ZRUB1: u8 = b'm';
+
+/// `ZRINIT` capabilities: full duplex, can overlap disk I/O (i.e. streaming).
+const RECEIVER_FLAGS: u8 = 0x01 | 0x02;
+/// `ZFILE` conversion option: binary transfer.
+const ZCBIN: u8 = 1;
+
+/// Payload size of data subpackets we send.
+const SUBPACKET_SIZE: usize = 1024;
+/// Largest subpacket we accept (lrzsz sends up to 8K).
+const MAX_SUBPACKET: usize = 16 * 1024;
+/// How long to wait for the remote side before giving up.
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Consecutive CANs that mean the remote side aborted.
+const CANCEL_RUN: usize = 5;
+
+/// Abort sequence: CANs stop the peer, backspaces erase them from a tty.
+pub(crate) const ABORT: &[u8] = &[
+    0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08,
+];
+
+/// A corrupted header or subpacket — recoverable by asking for a resend.
+#[derive(Debug)]
+struct BadFrame;
+
+impl fmt::Display for BadFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("corrupted ZMODEM frame")
+    }
+}
+
+impl std::error::Error for BadFrame {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Header {
+    kind: u8,
+    /// ZP0..ZP3 (a little-endian position), or ZF3..ZF0 flags
+    data: [u8; 4],
+}
+
+impl Header {
+    fn new(kind: u8) -> Self {
+        Self { kind, data: [0; 4] }
+    }
+
+    fn with_position(kind: u8, position: u32) -> Self {
+        Self {
+            kind,
+            data: position.to_le_bytes(),
+        }
+    }
+
+    /// Header with flag byte ZF0 set.
+    fn with_flags(kind: u8, zf0: u8) -> Self {
+        Self {
+            kind,
+            data: [0, 0, 0, zf0],
+        }
+    }
+
+    fn position(&self) -> u32 {
+        u32::from_le_bytes(self.data)
+    }
+
+    fn bytes(&self) -> [u8; 5] {
+        [
+            self.kind,
+            self.data[0],
+            self.data[1],
+            self.data[2],
+            self.data[3],
+        ]
+    }
+}
+
+/// CRC-16/XMODEM (poly 0x1021, init 0).
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-32 (IEEE, as used by zip and ZMODEM's 32-bit frames).
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0xFFFF_FFFF, data) ^ 0xFFFF_FFFF
+}
+
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Bytes that must not appear raw: ZDLE, flow control, and CR (telnet/rlogin escapes).
+fn needs_escape(byte: u8) -> bool {
+    matches!(
+        byte,
+        ZDLE | 0x10 | 0x90 | 0x11 | 0x91 | 0x13 | 0x93 | 0x0d | 0x8d
+    )
+}
+
+fn escape_into(out: &mut Vec<u8>, bytes: &[u8]) {
+    for &byte in bytes {
+        if needs_escape(byte) {
+            out.push(ZDLE);
+            out.push(byte ^ 0x40);
+        } else {
+            out.push(byte);
+        }
+    }
+}
+
+fn encode_hex_header(header: Header) -> Vec<u8> {
+    let bytes = header.bytes();
+    let crc = crc16(&bytes);
+    let mut out = vec![ZPAD, ZPAD, ZDLE, ZHEX];
+    for byte in bytes.iter().chain(crc.to_be_bytes().iter()) {
+        out.extend_from_slice(format!("{:02x}", byte).as_bytes());
+    }
+    out.extend_from_slice(b"\r\x8a");
+    if header.kind != ZFIN && header.kind != ZACK {
+        out.push(0x11);
+    }
+    out
+}
+
+fn encode_binary_header(header: Header) -> Vec<u8> {
+    let bytes = header.bytes();
+    let mut out = vec![ZPAD, ZDLE, ZBIN];
+    escape_into(&mut out, &bytes);
+    escape_into(&mut out, &crc16(&bytes).to_be_bytes());
+    out
+}
+
+fn encode_subpacket(data: &[u8], end: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 8 + 8);
+    escape_into(&mut out, data);
+    out.push(ZDLE);
+    out.push(end);
+    let mut crc_input = Vec::with_capacity(data.len() + 1);
+    crc_input.extend_from_slice(data);
+    crc_input.push(end);
+    escape_into(&mut out, &crc16(&crc_input).to_be_bytes());
+    out
+}
+
+/// A decoded byte from a ZDLE-escaped stream.
+enum Escaped {
+    Byte(u8),
+    /// ZDLE followed by a subpacket terminator
+    End(u8),
+}
+
+fn read_escaped(link: &mut Link) -> Result<Escaped> {
+    loop {
+        match link.read_byte(TIMEOUT)? {
+            // Raw flow control characters are noise (they're always escaped)
+            0x11 | 0x13 | 0x91 | 0x93 => continue,
+            ZDLE => break,
+            byte => return Ok(Escaped::Byte(byte)),
+        }
+    }
+
+    let mut cancels = 1;
+    loop {
+        let byte = link.read_byte(TIMEOUT)?;
+        return Ok(match byte {
+            0x11 | 0x13 | 0x91 | 0x93 => continue,
+            ZDLE => {
+                cancels += 1;
+                if cancels >= CANCEL_RUN {
+                    bail!("Cancelled by the remote side");
+                }
+                continue;
+            }
+            ZCRCE | ZCRCG | ZCRCQ | ZCRCW => Escaped::End(byte),
+            ZRUB0 => Escaped::Byte(0x7f),
+            ZRUB1 => Escaped::Byte(0xff),
+            byte if byte & 0x60 == 0x40 => Escaped::Byte(byte ^ 0x40),
+            _ => return Err(BadFrame.into()),
+        });
+    }
+}
+
+fn read_escaped_byte(link: &mut Link) -> Result<u8> {
+    match read_escaped(link)? {
+        Escaped::Byte(byte) => Ok(byte),
+        Escaped::End(_) => Err(BadFrame.into()),
+    }
+}
+
+fn hex_value(byte: u8) -> Result<u8> {
+    match byte & 0x7f {
+        digit @ b'0'..=b'9' => Ok(digit - b'0'),
+        letter @ b'a'..=b'f' => Ok(letter - b'a' + 10),
+        letter @ b'A'..=b'F' => Ok(letter - b'A' + 10),
+        _ => Err(BadFrame.into()),
+    }
+}
+
+/// Read the next header, skipping line noise. Returns the header and whether
+/// a following data subpacket uses CRC-32.
+fn read_header(link: &mut Link) -> Result<(Header, bool)> {
+    loop {
+        match read_header_once(link) {
+            Err(error) if error.is::<BadFrame>() => continue,
+            result => return result,
+        }
+    }
+}
+
+fn read_header_once(link: &mut Link) -> Result<(Header, bool)> {
+    // Find ZPAD, watching for a run of CANs
+    let mut cancels = 0;
+    loop {
+        match link.read_byte(TIMEOUT)? {
+            ZPAD => break,
+            ZDLE => {
+                cancels += 1;
+                if cancels >= CANCEL_RUN {
+                    bail!("Cancelled by the remote side");
+                }
+            }
+            _ => cancels = 0,
+        }
+    }
+
+    let mut byte = link.read_byte(TIMEOUT)?;
+    while byte == ZPAD {
+        byte = link.read_byte(TIMEOUT)?;
+    }
+    if byte != ZDLE {
+        return Err(BadFrame.into());
+    }
+
+    match link.read_byte(TIMEOUT)? {
+        ZHEX => {
+            let mut raw = [0u8; 7];
+            for value in raw.iter_mut() {
+                let high = hex_value(link.read_byte(TIMEOUT)?)?;
+                let low = hex_value(link.read_byte(TIMEOUT)?)?;
+                *value = (high << 4) | low;
+            }
+            if crc16(&raw[..5]) != u16::from_be_bytes([raw[5], raw[6]]) {
+                return Err(BadFrame.into());
+            }
+            // Trailing CR LF (XON, if any, is skipped as noise later)
+            let byte = link.read_byte(TIMEOUT)?;
+            if byte & 0x7f == b'\r' {
+                let byte = link.read_byte(TIMEOUT)?;
+                if byte & 0x7f != b'\n' {
+                    link.unread(byte);
+                }
+            } else if byte & 0x7f != b'\n' {
+                link.unread(byte);
+            }
+            Ok((header_from(&raw), false))
+        }
+        ZBIN => {
+            let mut raw = [0u8; 7];
+            for value in raw.iter_mut() {
+                *value = read_escaped_byte(link)?;
+            }
+            if crc16(&raw[..5]) != u16::from_be_bytes([raw[5], raw[6]]) {
+                return Err(BadFrame.into());
+            }
+            Ok((header_from(&raw), false))
+        }
+        ZBIN32 => {
+            let mut raw = [0u8; 9];
+            for value in raw.iter_mut() {
+                *value = read_escaped_byte(link)?;
+            }
+            if crc32(&raw[..5]) != u32::from_le_bytes([raw[5], raw[6], raw[7], raw[8]]) {
+                return Err(BadFrame.into());
+            }
+            Ok((header_from(&raw), true))
+        }
+        _ => Err(BadFrame.into()),
+    }
+}
+
+fn header_from(raw: &[u8]) -> Header {
+    Header {
+        kind: raw[0],
+        data: [raw[1], raw[2], raw[3], raw[4]],
+    }
+}
+
+/// Read a data subpacket. Returns the payload and its terminator.
+fn read_subpacket(link: &mut Link, crc32_frames: bool) -> Result<(Vec<u8>, u8)> {
+    let mut data = Vec::new();
+    let end = loop {
+        match read_escaped(link)? {
+            Escaped::Byte(byte) => {
+                if data.len() >= MAX_SUBPACKET {
+                    return Err(BadFrame.into());
+                }
+                data.push(byte);
+            }
+            Escaped::End(end) => break end,
+        }
+    };
+
+    let valid = if crc32_frames {
+        let mut received = [0u8; 4];
+        for value in received.iter_mut() {
+            *value = read_escaped_byte(link)?;
+        }
+        let crc = crc32_update(crc32_update(0xFFFF_FFFF, &data), &[end]) ^ 0xFFFF_FFFF;
+        crc == u32::from_le_bytes(received)
+    } else {
+        let high = read_escaped_byte(link)?;
+        let low = read_escaped_byte(link)?;
+        let mut crc_input = data.clone();
+        crc_input.push(end);
+        crc16(&crc_input) == u16::from_be_bytes([high, low])
+    };
+    if !valid {
+        return Err(BadFrame.into());
+    }
+    Ok((data, end))
+}
+
+/// File name and size from a `ZFILE` subpacket (`name\0size mtime mode ...\0`).
+fn parse_file_info(info: &[u8]) -> Result<(String, Option<u64>)> {
+    let mut fields = info.split(|&byte| byte == 0);
+    let name = String::from_utf8_lossy(fields.next().unwrap_or_default()).into_owned();
+    let size = fields
+        .next()
+        .and_then(|rest| std::str::from_utf8(rest).ok())
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|size| size.parse().ok());
+    // Only the final component: never let the sender pick the directory
+    let name = Path::new(&name)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !name.is_empty())
+        .context("Remote sent a file without a valid name")?;
+    Ok((name, size))
+}
+
+fn file_info(name: &str, size: u64, mtime: u64, files_left: usize, bytes_left: u64) -> Vec<u8> {
+    format!(
+        "{}\0{} {:o} 100644 0 {} {}\0",
+        name, size, mtime, files_left, bytes_left
+    )
+    .into_bytes()
+}
+
+/// Receive files from a remote `sz` into `directory`.
+pub(crate) fn receive(link: &mut Link, directory: &Path) -> Result<String> {
+    let zrinit = encode_hex_header(Header::with_flags(ZRINIT, RECEIVER_FLAGS));
+    link.write(&zrinit)?;
+
+    let mut received = Vec::new();
+    loop {
+        let (header, crc32_frames) = read_header(link)?;
+        match header.kind {
+            ZRQINIT => link.write(&zrinit)?,
+            ZSINIT => match read_subpacket(link, crc32_frames) {
+                Ok(_) => link.write(&encode_hex_header(Header::new(ZACK)))?,
+                Err(error) if error.is::<BadFrame>() => {
+                    link.write(&encode_hex_header(Header::new(ZNAK)))?
+                }
+                Err(error) => return Err(error),
+            },
+            ZFILE => {
+                let info = match read_subpacket(link, crc32_frames) {
+                    Ok((info, _)) => info,
+                    Err(error) if error.is::<BadFrame>() => {
+                        link.write(&encode_hex_header(Header::new(ZNAK)))?;
+                        continue;
+                    }
+                    Err(error) => return Err(error),
+                };
+                let (name, size) = parse_file_info(&info)?;
+                let path = unique_path(directory, &name);
+                receive_file(link, &path, size)?;
+                link.write(&zrinit)?;
+                received.push(path);
+            }
+            ZFIN => {
+                link.write(&encode_hex_header(Header::new(ZFIN)))?;
+                read_over_and_out(link);
+                break;
+            }
+            ZCOMMAND => bail!("Remote requested command execution; refused"),
+            ZCAN | ZABORT => bail!("Aborted by the remote side"),
+            _ => {}
+        }
+    }
+
+    Ok(summary("Received", &received, Some(directory)))
+}
+
+fn receive_file(link: &mut Link, path: &Path, size: Option<u64>) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let result = receive_into(link, BufWriter::new(file), size);
+    if result.is_err() {
+        // Don't leave a partial file behind
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+fn receive_into(link: &mut Link, mut file: BufWriter<File>, size: Option<u64>) -> Result<()> {
+    let mut position: u64 = 0;
+    link.write(&encode_hex_header(Header::with_position(ZRPOS, 0)))?;
+    link.report(0, size);
+
+    loop {
+        let (header, crc32_frames) = read_header(link)?;
+        match header.kind {
+            ZDATA => {
+                if header.position() != position as u32 {
+                    link.write(&encode_hex_header(Header::with_position(
+                        ZRPOS,
+                        position as u32,
+                    )))?;
+                    continue;
+                }
+                loop {
+                    let (data, end) = match read_subpacket(link, crc32_frames) {
+                        Ok(subpacket) => subpacket,
+                        Err(error) if error.is::<BadFrame>() => {
+                            link.write(&encode_hex_header(Header::with_position(
+                                ZRPOS,
+                                position as u32,
+                            )))?;
+                            break;
+                        }
+                        Err(error) => return Err(error),
+                    };
+                    file.write_all(&data)?;
+                    position += data.len() as u64;
+                    link.report(position, size);
+                    match end {
+                        ZCRCW => {
+                            link.write(&encode_hex_header(Header::with_position(
+                                ZACK,
+                                position as u32,
+                            )))?;
+                            break;
+                        }
+                        ZCRCQ => link.write(&encode_hex_header(Header::with_position(
+                            ZACK,
+                            position as u32,
+                        )))?,
+                        ZCRCG => {}
+                        _ => break,
+                    }
+                }
+            }
+            ZEOF if header.position() == position as u32 => {
+                file.flush()?;
+                return Ok(());
+            }
+            ZFILE => {
+                // Our ZRPOS got lost and the sender repeated the offer
+                let _ = read_subpacket(link, crc32_frames);
+                link.write(&encode_hex_header(Header::with_position(
+                    ZRPOS,
+                    position as u32,
+                )))?;
+            }
+            ZCAN | ZABORT | ZFIN => bail!("Aborted by the remote side"),
+            _ => {}
+        }
+    }
+}
+
+/// Consume the sender's closing "OO", leaving anything after it for the terminal.
+fn read_over_and_out(link: &mut Link) {
+    for _ in 0..2 {
+        match link.read_byte(Duration::from_millis(500)) {
+            Ok(b'O') => {}
+            Ok(byte) => {
+                link.unread(byte);
+                return;
+            }
+            Err(_) => return,
+        }
+    }
+}
+
+/// Send `files` to a remote `rz`.
+pub(crate) fn send(link: &mut Link, files: &[PathBuf]) -> Result<String> {
+    // The remote's ZRINIT is what started the session
+    loop {
+        let (header, _) = read_header(link)?;
+        match header.kind {
+            ZRINIT => break,
+            ZCHALLENGE => link.write(&encode_hex_header(Header {
+                kind: ZACK,
+                data: header.data,
+            }))?,
+            ZCAN | ZABORT => bail!("Aborted by the remote side"),
+            _ => {}
+        }
+    }
+
+    let sizes = files
+        .iter()
+        .map(|path| {
+            std::fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let total: u64 = sizes.iter().sum();
+
+    let mut sent = Vec::new();
+    let mut done = 0;
+    for (index, (path, &size)) in files.iter().zip(&sizes).enumerate() {
+        let bytes_left = total - done;
+        if send_file(
+            link,
+            path,
+            size,
+            files.len() - index,
+            bytes_left,
+            done,
+            total,
+        )? {
+            sent.push(path.clone());
+        }
+        done += size;
+    }
+
+    // Session end: ZFIN both ways, then "over and out"
+    let fin = encode_hex_header(Header::new(ZFIN));
+    link.write(&fin)?;
+    for _ in 0..3 {
+        match read_header(link) {
+            Ok((header, _)) if header.kind == ZFIN => {
+                link.write(b"OO")?;
+                break;
+            }
+            Ok(_) => link.write(&fin)?,
+            Err(_) => break,
+        }
+    }
+
+    Ok(summary("Sent", &sent, None))
+}
+
+/// Send one file. Returns false if the receiver skipped it.
+fn send_file(
+    link: &mut Link,
+    path: &Path,
+    size: u64,
+    files_left: usize,
+    bytes_left: u64,
+    done_before: u64,
+    total: u64,
+) -> Result<bool> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .context("File has no name")?;
+    let mtime = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |age| age.as_secs());
+
+    let mut offer = encode_binary_header(Header::with_flags(ZFILE, ZCBIN));
+    offer.extend(encode_subpacket(
+        &file_info(&name, size, mtime, files_left, bytes_left),
+        ZCRCW,
+    ));
+    link.write(&offer)?;
+
+    let mut position = loop {
+        let (header, _) = read_header(link)?;
+        match header.kind {
+            ZRPOS => break header.position() as u64,
+            ZSKIP => return Ok(false),
+            ZRINIT | ZNAK => link.write(&offer)?,
+            ZCRC => {
+                let contents = std::fs::read(path)?;
+                link.write(&encode_hex_header(Header {
+                    kind: ZCRC,
+                    data: crc32(&contents).to_le_bytes(),
+                }))?;
+            }
+            ZCAN | ZABORT | ZFERR | ZFIN => bail!("Aborted by the remote side"),
+            _ => {}
+        }
+    };
+
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut chunk = vec![0u8; SUBPACKET_SIZE];
+    'frame: loop {
+        file.seek(SeekFrom::Start(position))?;
+        link.write(&encode_binary_header(Header::with_position(
+            ZDATA,
+            position as u32,
+        )))?;
+
+        loop {
+            link.check_cancelled()?;
+            let count = read_full(&mut file, &mut chunk)?;
+            let end = if count < SUBPACKET_SIZE { ZCRCE } else { ZCRCG };
+            link.write(&encode_subpacket(&chunk[..count], end))?;
+            position += count as u64;
+            link.report(done_before + position, Some(total));
+            if end == ZCRCE {
+                break;
+            }
+
+            // The receiver only talks mid-stream to report errors
+            if link.poll_input() && link.pending_contains(ZPAD) {
+                let (header, _) = read_header(link)?;
+                match header.kind {
+                    ZRPOS => {
+                        link.write(&encode_subpacket(&[], ZCRCE))?;
+                        position = header.position() as u64;
+                        continue 'frame;
+                    }
+                    ZSKIP => {
+                        link.write(&encode_subpacket(&[], ZCRCE))?;
+                        return Ok(false);
+                    }
+                    ZCAN | ZABORT | ZFERR => bail!("Aborted by the remote side"),
+                    _ => {}
+                }
+            }
+        }
+
+        link.write(&encode_binary_header(Header::with_position(
+            ZEOF,
+            position as u32,
+        )))?;
+        loop {
+            let (header, _) = read_header(link)?;
+            match header.kind {
+                ZRINIT => return Ok(true),
+                ZRPOS => {
+                    position = header.position() as u64;
+                    continue 'frame;
+                }
+                ZSKIP => return Ok(false),
+                ZCAN | ZABORT | ZFERR => bail!("Aborted by the remote side"),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Fill `buffer` as far as the file allows. Returns the number of bytes read.
+fn read_full(file: &mut File, buffer: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            count => filled += count,
+        }
+    }
+    Ok(filled)
+}
+
+fn summary(verb: &str, paths: &[PathBuf], directory: Option<&Path>) -> String {
+    let names = paths
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy())
+        .collect::<Vec<_>>();
+    match (names.as_slice(), directory) {
+        ([], _) => format!("{}: no files transferred", verb),
+        (names, Some(directory)) => {
+            format!("{} {} to {}", verb, names.join(", "), directory.display())
+        }
+        (names, None) => format!("{} {}", verb, names.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_transfer::test_link;
+
+    #[test]
+    fn crc16_matches_standard_headers() {
+        // The canonical lrzsz ZRINIT and ZRQINIT hex headers
+        assert_eq!(crc16(&[ZRINIT, 0, 0, 0, 0x23]), 0xbe50);
+        assert_eq!(crc16(&[ZRQINIT, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn hex_header_encoding() {
+        assert_eq!(
+            encode_hex_header(Header::with_flags(ZRINIT, 0x23)),
+            b"**\x18B0100000023be50\r\x8a\x11".to_vec()
+        );
+        // ZFIN and ZACK omit the trailing XON
+        assert!(!encode_hex_header(Header::new(ZFIN)).ends_with(&[0x11]));
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let (tx, rx) = std::sync::mpsc::sync_channel(8);
+        let mut bytes = b"rz waiting\r\n".to_vec();
+        bytes.extend(encode_hex_header(Header::with_position(ZRPOS, 70_000)));
+        bytes.extend(encode_binary_header(Header::with_position(ZDATA, 0x1811)));
+        bytes.extend(encode_subpacket(&[0x18, 0x11, 0x7f, b'a'], ZCRCW));
+        tx.send(bytes).unwrap();
+
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        let (header, _) = read_header(&mut link).unwrap();
+        assert_eq!(header, Header::with_position(ZRPOS, 70_000));
+        let (header, crc32_frames) = read_header(&mut link).unwrap();
+        assert_eq!(header, Header::with_position(ZDATA, 0x1811));
+        assert!(!crc32_frames);
+        let (data, end) = read_subpacket(&mut link, false).unwrap();
+        assert_eq!(data, vec![0x18, 0x11, 0x7f, b'a']);
+        assert_eq!(end, ZCRCW);
+    }
+
+    #[test]
+    fn corrupted_subpacket_is_bad_frame() {
+        let (tx, rx) = std::sync::mpsc::sync_channel(8);
+        let mut bytes = encode_subpacket(b"hello", ZCRCE);
+        bytes[0] = b'j';
+        tx.send(bytes).unwrap();
+
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        let error = read_subpacket(&mut link, false).unwrap_err();
+        assert!(error.is::<BadFrame>());
+    }
+
+    #[test]
+    fn cancel_run_aborts() {
+        let (tx, rx) = std::sync::mpsc::sync_channel(8);
+        tx.send(ABORT.to_vec()).unwrap();
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        assert!(read_header(&mut link).is_err());
+    }
+
+    #[test]
+    fn file_info_keeps_only_the_file_name() {
+        let (name, size) = parse_file_info(b"../../etc/passwd\x00123 14371234 100644\x00").unwrap();
+        assert_eq!(name, "passwd");
+        assert_eq!(size, Some(123));
+        assert!(parse_file_info(b"\x00").is_err());
+    }
+
+    #[test]
+    fn failed_download_removes_partial_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let (tx, rx) = std::sync::mpsc::sync_channel(8);
+        let mut bytes = encode_hex_header(Header::new(ZFILE));
+        bytes.extend(encode_subpacket(
+            &file_info("notes.txt", 10, 0, 1, 10),
+            ZCRCW,
+        ));
+        bytes.extend(encode_hex_header(Header::with_position(ZDATA, 0)));
+        bytes.extend(encode_subpacket(b"hello", ZCRCG));
+        tx.send(bytes).unwrap();
+        // The session ends halfway through the file
+        drop(tx);
+
+        let mut link = test_link(&rx, Box::new(|_| Ok(())));
+        let error = receive(&mut link, directory.path()).unwrap_err();
+        assert_eq!(error.to_string(), "Session closed");
+        assert!(!directory.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn send_and_receive_loopback() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let big: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 256) as u8).collect();
+        std::fs::write(source.path().join("big.bin"), &big).unwrap();
+        std::fs::write(source.path().join("empty.txt"), b"").unwrap();
+        let files = vec![
+            source.path().join("big.bin"),
+            source.path().join("empty.txt"),
+        ];
+
+        // Two links wired back to back, like sz on one end and rz on the other
+        let (to_receiver, receiver_rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(1024);
+        let (to_sender, sender_rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(1024);
+        let target_path = target.path().to_path_buf();
+        let receiver = std::thread::spawn(move || {
+            let mut link = test_link(
+                &receiver_rx,
+                Box::new(move |data| Ok(to_sender.send(data.to_vec())?)),
+            );
+            receive(&mut link, &target_path)
+        });
+
+        let mut link = test_link(
+            &sender_rx,
+            Box::new(move |data| Ok(to_receiver.send(data.to_vec())?)),
+        );
+        let sent = send(&mut link, &files).unwrap();
+        assert_eq!(sent, "Sent big.bin, empty.txt");
+
+        let received = receiver.join().unwrap().unwrap();
+        assert!(received.starts_with("Received big.bin, empty.txt"));
+        assert_eq!(std::fs::read(target.path().join("big.bin")).unwrap(), big);
+        assert!(std::fs::read(target.path().join("empty.txt"))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,5 +1,22 @@
 use std::cmp;
 
+/// Keys copy mode handles and what they do, for the keybinding cheat sheet.
+/// The terminal pane's copy mode key handler is the source of truth.
+pub const COPY_MODE_KEYS: &[(&str, &str)] = &[
+    ("h j k l", "Move the cursor"),
+    ("0", "Start of line"),
+    ("$", "End of line"),
+    ("g", "Top of screen"),
+    ("shift-g", "Bottom of screen"),
+    ("ctrl-u", "Half page up"),
+    ("ctrl-d", "Half page down"),
+    ("v", "Select characters"),
+    ("shift-v", "Select lines"),
+    ("ctrl-v", "Select a block"),
+    ("y", "Copy the selection and exit"),
+    ("escape", "Exit copy mode"),
+];
+
 /// Selection type in copy mode.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CopyModeSelection {
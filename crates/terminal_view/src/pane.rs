@@ -16,11 +16,13 @@ use alacritty_terminal::vte::ansi::{CursorShape, Processor, Rgb};
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::ActiveTheme;
+use terminal::file_transfer::{TransferDirection, TransferReply, TransferRequest};
+use terminal::reconnect::{ReconnectState, ReconnectTarget, MAX_ATTEMPTS};
 use terminal::types::{
     BgRegion, CursorInfo, DisplayState, MouseEscBuf, ProgressState, RenderCell, RenderData,
     TermSize,
 };
-use terminal::PtyHandler;
+use terminal::{PtyHandler, SerialHandler, Session, TelnetHandler};
 use termwiz::input::{KeyCode, KeyCodeEncodeModes, KeyboardEncoding, Modifiers as TermwizMods};
 use theme::{terminal_colors, TerminalColors};
 
@@ -91,13 +93,15 @@ impl ReplayState {
 
 use crate::copy_mode::CopyModeState;
 use actions::{
-    EnterCopyMode, ExitCopyMode, SearchNext, SearchPrev, SearchToggle, SearchToggleRegex,
-    SendShiftTab, SendTab, StartRecording, StopRecording, OPTION_AS_ALT,
+    EnterCopyMode, ExitCopyMode, Reconnect, SearchNext, SearchPrev, SearchToggle,
+    SearchToggleRegex, SendBreak, SendText, StartRecording, StopRecording, ToggleLocalEcho,
+    OPTION_AS_ALT,
 };
 use parking_lot::{Mutex, RwLock};
 use std::fmt::Write as FmtWrite;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Instant;
 
 // Import centralized configuration
 // FONT_FAMILY used in tests via super::*
@@ -194,12 +198,12 @@ struct Listener {
     /// Line number of the most recent prompt start (OSC 133;A).
     /// Used for prompt-to-prompt navigation and command output selection.
     last_prompt_line: Arc<Mutex<Option<i32>>>,
-    /// PTY handle for writing terminal query responses back (CSI 6n, OSC 11, etc.)
-    pty: Arc<Mutex<Option<PtyHandler>>>,
+    /// Session handle for writing terminal query responses back (CSI 6n, OSC 11, etc.)
+    pty: Arc<Mutex<Option<Session>>>,
 }
 
 impl Listener {
-    fn new(pty: Arc<Mutex<Option<PtyHandler>>>) -> Self {
+    fn new(pty: Arc<Mutex<Option<Session>>>) -> Self {
         Self {
             title: Arc::new(Mutex::new(None)),
             cwd: Arc::new(Mutex::new(None)),
@@ -249,6 +253,16 @@ impl EventListener for Listener {
 #[derive(Clone, Debug)]
 pub struct TerminalExitEvent;
 
+/// Input typed or pasted into a broadcasting pane.
+/// Workspace mirrors it into the other panes of the broadcast group.
+#[derive(Clone, Debug)]
+pub enum TerminalInputEvent {
+    /// Keystrokes, already encoded for the PTY
+    Keys(String),
+    /// Pasted text; each receiving pane applies its own bracketed paste mode
+    Paste(String),
+}
+
 struct SearchState {
     active: bool,
     query: String,
@@ -291,8 +305,8 @@ impl SearchState {
 /// - `term`/`processor`: Terminal emulation state
 /// - `display`: Read-heavy display state (size, dims, bounds, font) uses RwLock
 pub struct TerminalPane {
-    /// PTY process handler for shell communication
-    pty: Arc<Mutex<Option<PtyHandler>>>,
+    /// PTY process or serial device the pane talks to
+    pty: Arc<Mutex<Option<Session>>>,
     /// Terminal emulator state (screen buffer, cursor, etc.)
     term: Arc<Mutex<Term<Listener>>>,
     /// Event listener for terminal events (title changes, etc.)
@@ -322,9 +336,22 @@ pub struct TerminalPane {
     progress: ProgressState,
     /// Replay state (Some when this pane is playing back a .cast recording).
     replay: Option<ReplayState>,
+    /// In-band file transfer (ZMODEM / trzsz) the remote side started, while it runs.
+    file_transfer: Option<TransferRequest>,
+    /// Retry state for remote sessions that reconnect when the connection drops.
+    reconnect: Option<ReconnectState>,
+    /// Whether a reconnect attempt is opening a new session.
+    reconnecting: bool,
+    /// Bumped for each reconnected session, so stale poll tasks stop.
+    session_generation: u32,
+    /// Whether typed input is mirrored to a broadcast group.
+    broadcasting: bool,
+    /// Keep the pane open after the process exits, showing its exit code.
+    hold_on_exit: bool,
 }
 
 impl EventEmitter<TerminalExitEvent> for TerminalPane {}
+impl EventEmitter<TerminalInputEvent> for TerminalPane {}
 
 impl TerminalPane {
     /// Create a new terminal pane with the user's default shell.
@@ -351,7 +378,7 @@ impl TerminalPane {
         // Spawn PTY first so Listener can hold a write-back reference
         let (pty, spawn_error) =
             match PtyHandler::spawn_in_dir(size.rows, size.cols, working_dir.as_deref()) {
-                Ok(pty) => (Some(pty), None),
+                Ok(pty) => (Some(pty.into()), None),
                 Err(e) => {
                     tracing::error!("Failed to spawn PTY: {}", e);
                     (None, Some(e.to_string()))
@@ -393,7 +420,8 @@ impl TerminalPane {
             proc_guard.advance(&mut *term_guard, error_msg.as_bytes());
         }
 
-        let vt_processor = Self::start_vt_processor(&pty_arc, term.clone(), processor.clone(), cx);
+        let vt_processor =
+            Self::start_vt_processor(&pty_arc, term.clone(), processor.clone(), 0, cx);
 
         Self {
             pty: pty_arc,
@@ -411,6 +439,12 @@ impl TerminalPane {
             _vt_processor: vt_processor,
             progress: ProgressState::default(),
             replay: None,
+            file_transfer: None,
+            reconnect: None,
+            reconnecting: false,
+            session_generation: 0,
+            broadcasting: false,
+            hold_on_exit: false,
         }
     }
 
@@ -423,17 +457,39 @@ impl TerminalPane {
     ///
     /// This is used for opening system utilities from the status bar.
     pub fn new_with_command(cx: &mut Context<Self>, command: &str, args: &[&str]) -> Self {
+        Self::new_command_in_dir(cx, command, args, None)
+    }
+
+    /// Create a new terminal pane running a specific command in a specific
+    /// directory.
+    ///
+    /// # Arguments
+    /// * `cx` - GPUI context
+    /// * `command` - The command to run
+    /// * `args` - Arguments to pass to the command
+    /// * `working_dir` - Optional working directory for the command
+    pub fn new_command_in_dir(
+        cx: &mut Context<Self>,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<std::path::PathBuf>,
+    ) -> Self {
         let display_state = DisplayState::default();
         let size = display_state.size;
 
-        let (pty, spawn_error) =
-            match PtyHandler::spawn_command(size.rows, size.cols, command, args, None) {
-                Ok(pty) => (Some(pty), None),
-                Err(e) => {
-                    tracing::error!("Failed to spawn command {}: {}", command, e);
-                    (None, Some(e.to_string()))
-                }
-            };
+        let (pty, spawn_error) = match PtyHandler::spawn_command(
+            size.rows,
+            size.cols,
+            command,
+            args,
+            working_dir.as_deref(),
+        ) {
+            Ok(pty) => (Some(pty.into()), None),
+            Err(e) => {
+                tracing::error!("Failed to spawn command {}: {}", command, e);
+                (None, Some(e.to_string()))
+            }
+        };
         let pty_arc = Arc::new(Mutex::new(pty));
 
         let listener = Listener::new(pty_arc.clone());
@@ -465,7 +521,122 @@ impl TerminalPane {
             proc_guard.advance(&mut *term_guard, error_msg.as_bytes());
         }
 
-        let vt_processor = Self::start_vt_processor(&pty_arc, term.clone(), processor.clone(), cx);
+        let vt_processor =
+            Self::start_vt_processor(&pty_arc, term.clone(), processor.clone(), 0, cx);
+
+        Self {
+            pty: pty_arc,
+            term,
+            listener,
+            display: Arc::new(RwLock::new(display_state)),
+            dragging: false,
+            focus_handle,
+            exit_emitted: false,
+            search: SearchState::new(),
+            hovered_url: None,
+            font_fallbacks,
+            scroll_reverse: user_config.scroll_reverse,
+            copy_mode: CopyModeState::new(size.rows as usize, size.cols as usize),
+            _vt_processor: vt_processor,
+            progress: ProgressState::default(),
+            replay: None,
+            file_transfer: None,
+            reconnect: None,
+            reconnecting: false,
+            session_generation: 0,
+            broadcasting: false,
+            hold_on_exit: false,
+        }
+    }
+
+    /// Create a terminal pane attached to a serial device.
+    ///
+    /// If the device can't be opened, the pane shows the error instead.
+    pub fn new_serial(cx: &mut Context<Self>, serial: &settings::SerialConfig) -> Self {
+        let (session, status) = match SerialHandler::open(serial) {
+            Ok(handler) => (
+                Some(handler.into()),
+                format!("\x1b[2mConnected to {}\x1b[0m\r\n", serial.display_name()),
+            ),
+            Err(e) => {
+                tracing::error!("Failed to open serial device {}: {:#}", serial.device, e);
+                (
+                    None,
+                    format!(
+                        "\x1b[31m\x1b[1mError: Failed to open {}\x1b[0m\r\n\r\n{:#}\r\n",
+                        serial.device, e
+                    ),
+                )
+            }
+        };
+        Self::new_with_session(cx, session, &status)
+    }
+
+    /// Create a terminal pane on an established Telnet or raw TCP connection.
+    ///
+    /// `connection` is the result of [`terminal::telnet::connect`]; a failed
+    /// connection shows the error in the pane.
+    pub fn new_telnet(
+        cx: &mut Context<Self>,
+        telnet: &settings::TelnetConfig,
+        connection: anyhow::Result<std::net::TcpStream>,
+    ) -> Self {
+        let size = DisplayState::default().size;
+        let session = connection
+            .and_then(|stream| TelnetHandler::new(stream, telnet.mode, size.cols, size.rows));
+        let (session, status) = match session {
+            Ok(handler) => (
+                Some(handler.into()),
+                format!("\x1b[2mConnected to {}\x1b[0m\r\n", telnet.display_name()),
+            ),
+            Err(e) => {
+                tracing::error!("Failed to connect to {}: {:#}", telnet.display_name(), e);
+                (
+                    None,
+                    format!(
+                        "\x1b[31m\x1b[1mError: Failed to connect to {}\x1b[0m\r\n\r\n{:#}\r\n",
+                        telnet.display_name(),
+                        e
+                    ),
+                )
+            }
+        };
+        Self::new_with_session(cx, session, &status)
+    }
+
+    /// Shared setup for panes whose session isn't a local PTY: prints
+    /// `status` and starts the VT thread if there is a session.
+    fn new_with_session(cx: &mut Context<Self>, session: Option<Session>, status: &str) -> Self {
+        let display_state = DisplayState::default();
+        let size = display_state.size;
+        let pty_arc = Arc::new(Mutex::new(session));
+
+        let listener = Listener::new(pty_arc.clone());
+        let config = Config::default();
+        let term = Term::new(config, &size, listener.clone());
+        let term = Arc::new(Mutex::new(term));
+        let processor = Arc::new(Mutex::new(Processor::new()));
+
+        let focus_handle = cx.focus_handle().tab_stop(false);
+
+        let user_config = settings::load_config();
+        let font_fallbacks = if user_config.font_fallbacks.is_empty() {
+            None
+        } else {
+            Some(FontFallbacks::from_fonts(
+                user_config.font_fallbacks.clone(),
+            ))
+        };
+
+        // Inject status line before starting VT thread
+        {
+            let mut term_guard = term.lock();
+            let mut proc_guard = processor.lock();
+            proc_guard.advance(&mut *term_guard, status.as_bytes());
+        }
+
+        let vt_processor =
+            Self::start_vt_processor(&pty_arc, term.clone(), processor.clone(), 0, cx);
 
         Self {
             pty: pty_arc,
@@ -483,17 +654,27 @@ impl TerminalPane {
             _vt_processor: vt_processor,
             progress: ProgressState::default(),
             replay: None,
+            file_transfer: None,
+            reconnect: None,
+            reconnecting: false,
+            session_generation: 0,
+            broadcasting: false,
+            hold_on_exit: false,
         }
     }
 
     /// Start the dedicated VT processing thread and spawn a GPUI timer task
     /// that polls the render-needed flag.
     ///
+    /// `generation` identifies the session; the poll task stops once the pane
+    /// has moved on to a newer one.
+    ///
     /// Returns `None` if the PTY has no output receiver (already taken or no PTY).
     fn start_vt_processor(
-        pty_arc: &Arc<Mutex<Option<PtyHandler>>>,
+        pty_arc: &Arc<Mutex<Option<Session>>>,
         term: Arc<Mutex<Term<Listener>>>,
         processor: Arc<Mutex<Processor>>,
+        generation: u32,
         cx: &mut Context<Self>,
     ) -> Option<terminal::TerminalProcessor> {
         let (output_rx, exited) = {
@@ -532,8 +713,8 @@ impl TerminalPane {
                 let should_break = this
                     .update(cx, |pane, cx| {
                         let vt = match pane._vt_processor.as_ref() {
-                            Some(vt) => vt,
-                            None => return (true, false),
+                            Some(vt) if pane.session_generation == generation => vt,
+                            _ => return (true, false),
                         };
                         let needs_render = vt.take_render_needed();
                         let is_exited = vt.has_exited();
@@ -545,12 +726,28 @@ impl TerminalPane {
                             cx.notify();
                         }
 
+                        let transfer_request = vt.file_transfers().take_request();
+                        if pane.file_transfer.is_some() && !vt.file_transfers().is_active() {
+                            pane.file_transfer = None;
+                            cx.notify();
+                        }
+                        if let Some(request) = transfer_request {
+                            pane.prompt_file_transfer(request, cx);
+                        }
+
                         if needs_render {
                             cx.notify();
                         }
                         if is_exited && !pane.exit_emitted {
-                            pane.exit_emitted = true;
-                            cx.emit(TerminalExitEvent);
+                            if !pane.on_connection_dropped(cx) {
+                                pane.exit_emitted = true;
+                                if pane.hold_on_exit {
+                                    pane.print_exit_code();
+                                    cx.notify();
+                                } else {
+                                    cx.emit(TerminalExitEvent);
+                                }
+                            }
                             return (true, needs_render);
                         }
                         (false, needs_render)
@@ -575,6 +772,283 @@ impl TerminalPane {
         Some(vt_processor)
     }
 
+    /// Ask the user what to send or where to save for a transfer the remote side started.
+    ///
+    /// Dismissing the picker declines the transfer.
+    fn prompt_file_transfer(&mut self, request: TransferRequest, cx: &mut Context<Self>) {
+        let Some(transfers) = self
+            ._vt_processor
+            .as_ref()
+            .map(|vt| vt.file_transfers().clone())
+        else {
+            return;
+        };
+        self.file_transfer = Some(request);
+        cx.notify();
+
+        let direction = request.direction;
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: direction == TransferDirection::Upload,
+            directories: direction == TransferDirection::Download,
+            multiple: direction == TransferDirection::Upload,
+            prompt: Some(match direction {
+                TransferDirection::Upload => "Send".into(),
+                TransferDirection::Download => "Save Here".into(),
+            }),
+        });
+        let pty = self.pty.clone();
+
+        cx.spawn(async move |_, _| {
+            let reply = match paths.await {
+                Ok(Ok(Some(mut paths))) if !paths.is_empty() => match direction {
+                    TransferDirection::Upload => TransferReply::Upload(paths),
+                    TransferDirection::Download => TransferReply::Download(paths.swap_remove(0)),
+                },
+                Ok(Err(error)) => {
+                    tracing::warn!("File picker failed: {}", error);
+                    TransferReply::Decline
+                }
+                _ => TransferReply::Decline,
+            };
+            transfers.reply(
+                reply,
+                Box::new(move |data| match pty.lock().as_mut() {
+                    Some(pty) => pty.write(data),
+                    None => Err(anyhow::anyhow!("Session closed")),
+                }),
+            );
+        })
+        .detach();
+    }
+
+    /// A pane for `target`'s remote session that isn't connected yet; call
+    /// [`TerminalPane::reconnect`] to open it. Used to reopen a closed remote
+    /// pane.
+    pub fn new_unconnected(cx: &mut Context<Self>, target: ReconnectTarget) -> Self {
+        let mut pane = Self::new_with_session(cx, None, "");
+        pane.reconnect = Some(ReconnectState::new(target, Instant::now()));
+        pane
+    }
+
+    /// Keep the pane open and reconnect to `target` when the connection drops.
+    ///
+    /// If the first connection already failed, retries start right away.
+    pub fn set_reconnect_target(&mut self, target: ReconnectTarget, cx: &mut Context<Self>) {
+        self.reconnect = Some(ReconnectState::new(target, Instant::now()));
+        if self.pty.lock().is_none() {
+            self.schedule_reconnect("Connection failed".to_string(), cx);
+        }
+    }
+
+    /// Host of a remote session (SSH or Telnet), if this pane is one.
+    pub fn remote_host(&self) -> Option<String> {
+        let host = self.reconnect.as_ref()?.target().host();
+        (!host.is_empty()).then(|| host.to_string())
+    }
+
+    /// How this pane's remote session is reopened, if it is one.
+    pub fn reconnect_target(&self) -> Option<&ReconnectTarget> {
+        self.reconnect.as_ref().map(ReconnectState::target)
+    }
+
+    /// Retry count and last error while the session is reconnecting or has
+    /// recently recovered.
+    pub fn reconnect_status(&self) -> Option<(u32, Option<String>)> {
+        let (attempt, error) = self.reconnect.as_ref()?.status(Instant::now())?;
+        Some((attempt, error.map(str::to_string)))
+    }
+
+    /// Handle the end of the session. Returns `true` if the connection
+    /// dropped and a reconnect was scheduled, so the pane stays open.
+    fn on_connection_dropped(&mut self, cx: &mut Context<Self>) -> bool {
+        let exit_code = self.pty.lock().as_ref().and_then(Session::exit_code);
+        let Some(state) = &self.reconnect else {
+            return false;
+        };
+        if !state.target().is_dropped(exit_code) {
+            return false;
+        }
+        let error = state.target().describe_drop(exit_code);
+        self.schedule_reconnect(error, cx);
+        true
+    }
+
+    /// Show the error and start counting down to the next retry.
+    fn schedule_reconnect(&mut self, error: String, cx: &mut Context<Self>) {
+        let Some(state) = self.reconnect.as_mut() else {
+            return;
+        };
+        let Some(delay) = state.schedule_retry(error.clone(), Instant::now()) else {
+            tracing::warn!("{}; giving up after {} attempts", error, MAX_ATTEMPTS);
+            self.print_status(&format!(
+                "\x1b[31m{}. Gave up after {} attempts; use Reconnect to try again.\x1b[0m",
+                error, MAX_ATTEMPTS
+            ));
+            cx.notify();
+            return;
+        };
+        tracing::info!("{}; reconnecting in {:?}", error, delay);
+        self.print_status(&format!("\x1b[33m{}\x1b[0m", error));
+
+        cx.spawn(async move |this, cx| loop {
+            cx.background_executor()
+                .timer(std::time::Duration::from_millis(250))
+                .await;
+            let waiting = this
+                .update(cx, |pane, cx| {
+                    let Some(state) = &pane.reconnect else {
+                        return false;
+                    };
+                    if !state.is_waiting() {
+                        return false;
+                    }
+                    if state.is_due(Instant::now()) {
+                        pane.start_reconnect(cx);
+                        return false;
+                    }
+                    // Countdown in the banner
+                    cx.notify();
+                    true
+                })
+                .unwrap_or(false);
+            if !waiting {
+                break;
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    /// Reopen the session now, replacing the current one if it is still up.
+    /// No-op for panes without a reconnect target.
+    pub fn reconnect(&mut self, cx: &mut Context<Self>) {
+        if let Some(state) = self.reconnect.as_mut() {
+            state.reset_attempts();
+        }
+        self.start_reconnect(cx);
+    }
+
+    fn start_reconnect(&mut self, cx: &mut Context<Self>) {
+        if self.reconnecting || self.exit_emitted {
+            return;
+        }
+        let Some(state) = self.reconnect.as_mut() else {
+            return;
+        };
+        state.cancel_retry();
+        self.reconnecting = true;
+        let target = state.target().clone();
+
+        // Stop the old VT thread first so its exit isn't mistaken for a new drop
+        let old_vt = self._vt_processor.take();
+        let recorder = old_vt.as_ref().and_then(|vt| vt.recorder().lock().take());
+        drop(old_vt);
+        self.pty.lock().take();
+        self.print_status("\x1b[2mReconnecting...\x1b[0m");
+
+        let size = self.display.read().size;
+        let session = cx.background_spawn(async move { target.open(size.rows, size.cols) });
+        cx.spawn(async move |this, cx| {
+            let session = session.await;
+            this.update(cx, |pane, cx| pane.finish_reconnect(session, recorder, cx))
+                .ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn finish_reconnect(
+        &mut self,
+        session: anyhow::Result<Session>,
+        recorder: Option<terminal::recording::SessionRecorder>,
+        cx: &mut Context<Self>,
+    ) {
+        self.reconnecting = false;
+        match session {
+            Ok(session) => {
+                *self.pty.lock() = Some(session);
+                let processor = Arc::new(Mutex::new(Processor::new()));
+                self.session_generation += 1;
+                self._vt_processor = Self::start_vt_processor(
+                    &self.pty,
+                    self.term.clone(),
+                    processor,
+                    self.session_generation,
+                    cx,
+                );
+                if let Some(vt) = &self._vt_processor {
+                    *vt.recorder().lock() = recorder;
+                }
+                if let Some(state) = self.reconnect.as_mut() {
+                    state.connected(Instant::now());
+                }
+                cx.notify();
+            }
+            Err(error) => self.schedule_reconnect(format!("{:#}", error), cx),
+        }
+    }
+
+    /// Banner text and button label while the session is down.
+    fn reconnect_banner(&self) -> Option<(String, Option<&'static str>)> {
+        if self.reconnecting {
+            return Some(("Reconnecting...".to_string(), None));
+        }
+        let state = self
+            .reconnect
+            .as_ref()
+            .filter(|state| state.is_disconnected())?;
+        Some(match state.retry_in(Instant::now()) {
+            Some(delay) => (
+                format!(
+                    "Disconnected \u{2014} reconnecting in {}s (attempt {}/{})",
+                    delay.as_secs_f32().ceil() as u64,
+                    state.attempt(),
+                    MAX_ATTEMPTS
+                ),
+                Some("Reconnect now"),
+            ),
+            None => (
+                format!(
+                    "Disconnected \u{2014} gave up after {} attempts",
+                    MAX_ATTEMPTS
+                ),
+                Some("Reconnect"),
+            ),
+        })
+    }
+
+    /// Print a status line into the terminal, below the existing output.
+    fn print_status(&self, line: &str) {
+        let mut term_guard = self.term.lock();
+        let mut processor: Processor = Processor::new();
+        processor.advance(&mut *term_guard, format!("\r\n{}\r\n", line).as_bytes());
+    }
+
+    /// Print how the process ended, for panes held open after exit.
+    fn print_exit_code(&self) {
+        let code = self.pty.lock().as_ref().and_then(Session::exit_code);
+        let line = match code {
+            Some(0) => "\x1b[32m[Process exited with code 0]\x1b[0m".to_string(),
+            Some(code) => format!("\x1b[31m[Process exited with code {}]\x1b[0m", code),
+            None => "\x1b[33m[Process exited with an unknown status]\x1b[0m".to_string(),
+        };
+        self.print_status(&line);
+    }
+
+    /// Whether a file transfer currently owns the terminal stream.
+    fn is_file_transfer_active(&self) -> bool {
+        self._vt_processor
+            .as_ref()
+            .is_some_and(|vt| vt.file_transfers().is_active())
+    }
+
+    /// Abort the running file transfer, if any.
+    fn cancel_file_transfer(&self) {
+        if let Some(vt) = self._vt_processor.as_ref() {
+            vt.file_transfers().cancel();
+        }
+    }
+
     /// Create a replay pane that plays back a .cast recording file.
     pub fn new_replay(
         cx: &mut Context<Self>,
@@ -584,7 +1058,7 @@ impl TerminalPane {
 
         let display_state = DisplayState::default();
 
-        let pty_arc: Arc<Mutex<Option<PtyHandler>>> = Arc::new(Mutex::new(None));
+        let pty_arc: Arc<Mutex<Option<Session>>> = Arc::new(Mutex::new(None));
         let listener = Listener::new(pty_arc.clone());
         let config = Config::default();
         let size = TermSize {
@@ -675,6 +1149,12 @@ impl TerminalPane {
             _vt_processor: None,
             progress: ProgressState::default(),
             replay: Some(replay),
+            file_transfer: None,
+            reconnect: None,
+            reconnecting: false,
+            session_generation: 0,
+            broadcasting: false,
+            hold_on_exit: false,
         })
     }
 
@@ -741,6 +1221,10 @@ impl TerminalPane {
     /// If the write fails (e.g., broken pipe because the process exited),
     /// the PTY handler is dropped so subsequent operations treat it as exited.
     pub fn send_input(&mut self, input: &str) {
+        // The transfer protocol owns the stream; stray input would corrupt it
+        if self.is_file_transfer_active() {
+            return;
+        }
         let mut pty_guard = self.pty.lock();
         if let Some(ref mut pty) = *pty_guard {
             if let Err(e) = pty.write(input.as_bytes()) {
@@ -754,11 +1238,60 @@ impl TerminalPane {
         }
     }
 
+    /// Send input the user typed, mirroring it to the broadcast group.
+    fn send_keys(&mut self, input: &str, cx: &mut Context<Self>) {
+        self.send_input(input);
+        self.mirror_input(TerminalInputEvent::Keys(input.to_string()), cx);
+    }
+
+    /// Hand typed or pasted input to the workspace while broadcasting.
+    fn mirror_input(&self, event: TerminalInputEvent, cx: &mut Context<Self>) {
+        if self.broadcasting && !self.is_file_transfer_active() {
+            cx.emit(event);
+        }
+    }
+
+    /// Mark the pane as part of a broadcast group: its input is mirrored
+    /// to the other members and it gets a tinted border.
+    pub fn set_broadcasting(&mut self, broadcasting: bool, cx: &mut Context<Self>) {
+        if self.broadcasting != broadcasting {
+            self.broadcasting = broadcasting;
+            cx.notify();
+        }
+    }
+
+    /// Whether the pane is in a broadcast group.
+    pub fn is_broadcasting(&self) -> bool {
+        self.broadcasting
+    }
+
+    /// Keep the pane open after its process exits, printing the exit code,
+    /// instead of closing it.
+    pub fn set_hold_on_exit(&mut self, hold: bool) {
+        self.hold_on_exit = hold;
+    }
+
+    /// Hold the pane open after its process has already exited, printing
+    /// the exit code it would have shown.
+    pub fn hold_after_exit(&mut self, cx: &mut Context<Self>) {
+        if self.hold_on_exit {
+            return;
+        }
+        self.hold_on_exit = true;
+        self.print_exit_code();
+        cx.notify();
+    }
+
     /// Check if the shell has exited
     pub fn has_exited(&self) -> bool {
-        if self.replay.is_some() {
+        if self.replay.is_some() || self.hold_on_exit {
+            // Held panes stay open until closed on purpose
             return false;
         }
+        if self.reconnect.is_some() {
+            // Between sessions while reconnecting; exits only when closed on purpose
+            return self.exit_emitted;
+        }
         let pty_guard = self.pty.lock();
         match &*pty_guard {
             None => true,
@@ -766,6 +1299,33 @@ impl TerminalPane {
         }
     }
 
+    /// Whether this pane is a serial console.
+    pub fn is_serial(&self) -> bool {
+        self.pty
+            .lock()
+            .as_ref()
+            .is_some_and(|session| session.as_serial().is_some())
+    }
+
+    /// Send a break on the serial line. No-op for other sessions.
+    pub fn send_break(&self) {
+        if let Some(serial) = self.pty.lock().as_ref().and_then(Session::as_serial) {
+            if let Err(error) = serial.send_break() {
+                tracing::warn!("Serial break failed: {:#}", error);
+            }
+        }
+    }
+
+    /// Toggle local echo on a serial or Telnet session. Returns the new state,
+    /// or `None` for local shells.
+    pub fn toggle_local_echo(&self) -> Option<bool> {
+        let mut pty_guard = self.pty.lock();
+        let session = pty_guard.as_mut()?;
+        let enabled = !session.local_echo()?;
+        session.set_local_echo(enabled);
+        Some(enabled)
+    }
+
     /// Check if the terminal has running child processes
     pub fn has_running_processes(&self) -> bool {
         let pty_guard = self.pty.lock();
@@ -865,17 +1425,26 @@ impl TerminalPane {
     /// Get the current working directory reported by the shell via OSC 7.
     ///
     /// Falls back to the OS-level `PtyHandler::get_current_directory()` when
-    /// OSC 7 data is not available (which is the current state, since
-    /// alacritty_terminal/vte do not parse OSC 7).
+    /// the shell doesn't emit OSC 7.
     pub fn current_working_directory(&self) -> Option<std::path::PathBuf> {
-        // Prefer OSC 7 value if available (will be populated once we add a pre-parser).
-        if let Some(cwd) = self.listener.cwd.lock().as_ref() {
+        if let Some(cwd) = self.reported_directory() {
             return Some(std::path::PathBuf::from(cwd));
         }
         // Fallback: query the OS for the foreground process CWD.
         self.get_current_directory()
     }
 
+    /// Get the directory last reported via OSC 7, with no OS-level fallback.
+    ///
+    /// In an SSH session this is the remote shell's directory, which the
+    /// local process table knows nothing about.
+    pub fn reported_directory(&self) -> Option<String> {
+        self._vt_processor
+            .as_ref()
+            .and_then(|vt| vt.cwd())
+            .or_else(|| self.listener.cwd.lock().clone())
+    }
+
     /// Get the line number of the most recent shell prompt (OSC 133;A).
     ///
     /// Returns `None` until a custom pre-parser is added to intercept OSC 133
@@ -884,6 +1453,44 @@ impl TerminalPane {
         *self.listener.last_prompt_line.lock()
     }
 
+    /// Text of the last `max_lines` lines of the buffer, scrollback
+    /// included, without trailing blank lines.
+    pub fn buffer_text(&self, max_lines: usize) -> String {
+        let term = self.term.lock();
+        let grid = term.grid();
+        let screen_lines = grid.screen_lines() as i32;
+        let start_line = screen_lines - grid.total_lines().min(max_lines) as i32;
+        let cols = grid.columns();
+
+        let mut lines: Vec<String> = (start_line..screen_lines)
+            .map(|line| {
+                let row = &grid[Line(line)];
+                let text: String = (0..cols).map(|c| row[Column(c)].c).collect();
+                text.trim_end().to_string()
+            })
+            .collect();
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// Print `history`, the text of a closed pane, dimmed and followed by a
+    /// separator. Call it right after creating the pane so the history sits
+    /// above the new session's output. It is only text: nothing in it runs.
+    pub fn show_history(&self, history: &str) {
+        let mut text = String::from("\x1b[2m");
+        for line in history.lines() {
+            text.extend(line.chars().filter(|c| !c.is_control()));
+            text.push_str("\r\n");
+        }
+        text.push_str("\u{2500}\u{2500}\u{2500} reopened \u{2500}\u{2500}\u{2500}\x1b[0m\r\n");
+
+        let mut term_guard = self.term.lock();
+        let mut processor: Processor = Processor::new();
+        processor.advance(&mut *term_guard, text.as_bytes());
+    }
+
     /// Convert pixel position (window coords) to terminal cell coordinates
     fn pixel_to_cell(&self, position: Point<Pixels>) -> Option<(usize, usize)> {
         // Get display state (single lock for all display-related fields)
@@ -1504,6 +2111,7 @@ impl TerminalPane {
         cx.notify();
     }
 
+    /// Handle a key in copy mode. Keep `copy_mode::COPY_MODE_KEYS` in step.
     fn handle_copy_mode_key(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
         let key = event.keystroke.key.as_str();
         let mods = &event.keystroke.modifiers;
@@ -1659,19 +2267,19 @@ impl TerminalPane {
                         return;
                     }
                     "k" => {
-                        self.send_input("\x0c");
+                        self.send_keys("\x0c", cx);
                         return;
                     }
                     "backspace" => {
-                        self.send_input("\x15");
+                        self.send_keys("\x15", cx);
                         return;
                     }
                     "left" => {
-                        self.send_input("\x01");
+                        self.send_keys("\x01", cx);
                         return;
                     }
                     "right" => {
-                        self.send_input("\x05");
+                        self.send_keys("\x05", cx);
                         return;
                     }
                     "up" => {
@@ -1751,11 +2359,11 @@ impl TerminalPane {
             if mods.alt && !mods.control && !mods.shift {
                 match key {
                     "left" => {
-                        self.send_input("\x1bb"); // backward-word
+                        self.send_keys("\x1bb", cx); // backward-word
                         return;
                     }
                     "right" => {
-                        self.send_input("\x1bf"); // forward-word
+                        self.send_keys("\x1bf", cx); // forward-word
                         return;
                     }
                     _ => {}
@@ -1766,11 +2374,11 @@ impl TerminalPane {
             if mods.shift && !mods.control && !mods.alt {
                 match key {
                     "home" => {
-                        self.send_input("\x1b[1;2H"); // Shift+Home
+                        self.send_keys("\x1b[1;2H", cx); // Shift+Home
                         return;
                     }
                     "end" => {
-                        self.send_input("\x1b[1;2F"); // Shift+End
+                        self.send_keys("\x1b[1;2F", cx); // Shift+End
                         return;
                     }
                     _ => {}
@@ -1780,7 +2388,7 @@ impl TerminalPane {
             // Use termwiz to encode the key
             if let Ok(seq) = keycode.encode(termwiz_mods, encode_modes, true) {
                 if !seq.is_empty() {
-                    self.send_input(&seq);
+                    self.send_keys(&seq, cx);
                 }
             }
         }
@@ -2051,17 +2659,9 @@ impl TerminalPane {
         }
 
         if !output.is_empty() {
-            // Use bracketed paste mode if enabled
-            let bracketed_paste = self.term.lock().mode().contains(TermMode::BRACKETED_PASTE);
             self.term.lock().selection = None;
-
-            if bracketed_paste {
-                self.send_input("\x1b[200~");
-                self.send_input(&output);
-                self.send_input("\x1b[201~");
-            } else {
-                self.send_input(&output);
-            }
+            self.paste_text(&output);
+            self.mirror_input(TerminalInputEvent::Paste(output), cx);
             cx.notify();
         }
     }
@@ -2073,26 +2673,29 @@ impl TerminalPane {
         if let Some(item) = cx.read_from_clipboard() {
             if let Some(text) = item.text() {
                 // Clear selection
-                let term_guard = self.term.lock();
-                let bracketed_paste = term_guard.mode().contains(TermMode::BRACKETED_PASTE);
-                drop(term_guard);
-
                 self.term.lock().selection = None;
-
-                // Wrap with bracketed paste escape sequences if mode is enabled
-                if bracketed_paste {
-                    // Start bracketed paste: ESC[200~
-                    self.send_input("\x1b[200~");
-                    self.send_input(&text);
-                    // End bracketed paste: ESC[201~
-                    self.send_input("\x1b[201~");
-                } else {
-                    self.send_input(&text);
-                }
+                self.paste_text(&text);
+                self.mirror_input(TerminalInputEvent::Paste(text), cx);
                 cx.notify();
             }
         }
     }
+
+    /// Send pasted text to the PTY.
+    /// Wraps it with escape sequences if the terminal has bracketed paste
+    /// mode enabled, preventing paste injection attacks.
+    pub fn paste_text(&mut self, text: &str) {
+        let bracketed_paste = self.term.lock().mode().contains(TermMode::BRACKETED_PASTE);
+        if bracketed_paste {
+            // Start bracketed paste: ESC[200~
+            self.send_input("\x1b[200~");
+            self.send_input(text);
+            // End bracketed paste: ESC[201~
+            self.send_input("\x1b[201~");
+        } else {
+            self.send_input(text);
+        }
+    }
 }
 
 /// Build render data from terminal state - collects individual cells for precise positioning
@@ -2311,12 +2914,6 @@ impl Render for TerminalPane {
             .key_context("terminal")
             .track_focus(&focus_handle)
             .when(show_pointer, |d| d.cursor_pointer())
-            .on_action(cx.listener(|this, _: &SendTab, _window, _cx| {
-                this.send_input("\t");
-            }))
-            .on_action(cx.listener(|this, _: &SendShiftTab, _window, _cx| {
-                this.send_input("\x1b[Z");
-            }))
             .on_action(cx.listener(|this, _: &SearchToggle, _window, cx| {
                 this.toggle_search(cx);
             }))
@@ -2345,6 +2942,20 @@ impl Render for TerminalPane {
                 this.stop_recording();
                 cx.notify();
             }))
+            .on_action(cx.listener(|this, _: &Reconnect, _window, cx| {
+                this.reconnect(cx);
+            }))
+            .on_action(cx.listener(|this, action: &SendText, _window, cx| {
+                this.send_keys(&action.text, cx);
+            }))
+            .on_action(cx.listener(|this, _: &SendBreak, _window, _cx| {
+                this.send_break();
+            }))
+            .on_action(cx.listener(|this, _: &ToggleLocalEcho, _window, _cx| {
+                if let Some(enabled) = this.toggle_local_echo() {
+                    tracing::info!("Local echo {}", if enabled { "on" } else { "off" });
+                }
+            }))
             .on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
                 // When in replay mode, handle replay-specific keys
                 if this.replay.is_some() {
@@ -2375,6 +2986,15 @@ impl Render for TerminalPane {
                     }
                 }
 
+                // While a file transfer runs, keys only cancel it
+                if this.is_file_transfer_active() {
+                    let key = event.keystroke.key.as_str();
+                    if key == "escape" || (key == "c" && event.keystroke.modifiers.control) {
+                        this.cancel_file_transfer();
+                    }
+                    return;
+                }
+
                 // When copy mode is active, route keys to copy mode handler
                 if this.copy_mode.active {
                     this.handle_copy_mode_key(event, cx);
@@ -2438,23 +3058,23 @@ impl Render for TerminalPane {
                     // Tab/Shift+Tab - GPUI uses for focus navigation
                     "tab" => {
                         if mods.shift {
-                            this.send_input("\x1b[Z"); // Shift-Tab (backtab)
+                            this.send_keys("\x1b[Z", cx); // Shift-Tab (backtab)
                         } else {
-                            this.send_input("\t");
+                            this.send_keys("\t", cx);
                         }
                         return;
                     }
                     // Escape - GPUI might use for closing dialogs
                     "escape" => {
-                        this.send_input("\x1b");
+                        this.send_keys("\x1b", cx);
                         return;
                     }
                     // Enter - GPUI might use for form submission
                     "enter" => {
                         if mods.shift {
-                            this.send_input("\x1b[13;2u"); // CSI u: Shift+Enter
+                            this.send_keys("\x1b[13;2u", cx); // CSI u: Shift+Enter
                         } else if !mods.control && !mods.alt {
-                            this.send_input("\r");
+                            this.send_keys("\r", cx);
                         } else {
                             // Ctrl+Enter, Alt+Enter etc. -> let handle_key encode
                             this.handle_key(event, cx);
@@ -2463,16 +3083,16 @@ impl Render for TerminalPane {
                     }
                     // Backspace - handle Shift+Backspace as DEL
                     "backspace" if mods.shift && !mods.control && !mods.alt && !mods.platform => {
-                        this.send_input("\x7f"); // DEL
+                        this.send_keys("\x7f", cx); // DEL
                         return;
                     }
                     // Space - GPUI might use for button activation
                     "space" if mods.shift && !mods.control && !mods.alt && !mods.platform => {
-                        this.send_input("\x1b[32;2u"); // CSI u: Shift+Space
+                        this.send_keys("\x1b[32;2u", cx); // CSI u: Shift+Space
                         return;
                     }
                     "space" if !mods.control && !mods.alt && !mods.platform => {
-                        this.send_input(" ");
+                        this.send_keys(" ", cx);
                         return;
                     }
                     _ => {}
@@ -2626,6 +3246,103 @@ impl Render for TerminalPane {
                         ),
                 )
             })
+            // Tinted border while input is broadcast to other panes
+            .when(self.broadcasting, |d| {
+                d.child(
+                    div()
+                        .absolute()
+                        .inset_0()
+                        .border_2()
+                        .border_color(hsla(0.08, 0.9, 0.55, 0.8))
+                        .bg(hsla(0.08, 0.9, 0.55, 0.04)),
+                )
+            })
+            // Reconnect banner for dropped remote sessions
+            .when_some(self.reconnect_banner(), |d, (label, action)| {
+                d.child(
+                    div()
+                        .id("reconnect-bar")
+                        .absolute()
+                        .top(px(0.0))
+                        .left(px(0.0))
+                        .h(px(28.0))
+                        .bg(hsla(0.0, 0.0, 0.15, 0.95))
+                        .border_1()
+                        .border_color(hsla(0.0, 0.0, 0.3, 1.0))
+                        .rounded_br(px(6.0))
+                        .flex()
+                        .items_center()
+                        .px(px(8.0))
+                        .gap(px(8.0))
+                        .child(
+                            div()
+                                .text_size(px(12.0))
+                                .text_color(hsla(0.12, 0.8, 0.65, 1.0))
+                                .child(label),
+                        )
+                        .when_some(action, |d, action| {
+                            d.child(
+                                div()
+                                    .id("reconnect-now")
+                                    .text_size(px(11.0))
+                                    .text_color(hsla(0.0, 0.0, 0.5, 1.0))
+                                    .cursor_pointer()
+                                    .hover(|s| s.text_color(hsla(0.0, 0.0, 0.85, 1.0)))
+                                    .on_click(cx.listener(|this, _: &ClickEvent, _window, cx| {
+                                        this.reconnect(cx);
+                                    }))
+                                    .child(action),
+                            )
+                        }),
+                )
+            })
+            // File transfer bar overlay (progress is also shown on the tab)
+            .when_some(self.file_transfer, |d, request| {
+                let verb = match request.direction {
+                    TransferDirection::Upload => "upload",
+                    TransferDirection::Download => "download",
+                };
+                let label = match self.progress {
+                    ProgressState::Normal(percent) => {
+                        format!("{} {}: {}%", request.protocol.name(), verb, percent)
+                    }
+                    _ => format!("{} {}", request.protocol.name(), verb),
+                };
+                d.child(
+                    div()
+                        .id("file-transfer-bar")
+                        .absolute()
+                        .top(px(0.0))
+                        .left(px(0.0))
+                        .h(px(28.0))
+                        .bg(hsla(0.0, 0.0, 0.15, 0.95))
+                        .border_1()
+                        .border_color(hsla(0.0, 0.0, 0.3, 1.0))
+                        .rounded_br(px(6.0))
+                        .flex()
+                        .items_center()
+                        .px(px(8.0))
+                        .gap(px(8.0))
+                        .child(
+                            div()
+                                .text_size(px(12.0))
+                                .text_color(hsla(0.0, 0.0, 0.85, 1.0))
+                                .child(label),
+                        )
+                        .child(
+                            div()
+                                .id("file-transfer-cancel")
+                                .text_size(px(11.0))
+                                .text_color(hsla(0.0, 0.0, 0.5, 1.0))
+                                .cursor_pointer()
+                                .hover(|s| s.text_color(hsla(0.0, 0.0, 0.85, 1.0)))
+                                .on_click(cx.listener(|this, _: &ClickEvent, _window, _cx| {
+                                    this.cancel_file_transfer();
+                                }))
+                                .child("Cancel (Esc)"),
+                        ),
+                )
+            })
             // Replay control bar overlay (rendered at bottom when in replay mode)
             .when(self.replay.is_some(), |d| {
                 let replay = self.replay.as_ref().expect("checked above");
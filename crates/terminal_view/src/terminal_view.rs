@@ -8,4 +8,4 @@ pub mod copy_mode;
 pub mod kitty_keyboard;
 mod pane;
 
-pub use pane::{TabBadge, TerminalExitEvent, TerminalPane};
+pub use pane::{TabBadge, TerminalExitEvent, TerminalInputEvent, TerminalPane};
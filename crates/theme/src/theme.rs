@@ -16,7 +16,7 @@ mod theme_actions;
 pub use colors::{terminal_colors, TerminalColors};
 pub use persistence::WindowsShell;
 
-use gpui::App;
+use gpui::{App, SharedString};
 use gpui_component::theme::{Theme, ThemeRegistry};
 use parking_lot::Mutex;
 use std::path::PathBuf;
@@ -104,7 +104,7 @@ pub fn init(cx: &mut App) {
     .detach();
 
     // Start config file watcher (live reload)
-    if let Some(debouncer) = settings::watch_config(cx, on_config_apply) {
+    if let Some(debouncer) = settings::watch_config(cx, on_config_apply, |_| {}) {
         Box::leak(Box::new(debouncer));
     }
 
@@ -112,6 +112,40 @@ pub fn init(cx: &mut App) {
     theme_actions::register_actions(cx);
 }
 
+/// Names of the loaded themes, sorted.
+pub fn theme_names(cx: &App) -> Vec<SharedString> {
+    let mut names: Vec<SharedString> = ThemeRegistry::global(cx).themes().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Names of the themes in the themes directory, read from disk, for when
+/// the registry hasn't loaded them yet (`humanssh --check-config`).
+pub fn theme_names_on_disk() -> Vec<String> {
+    #[derive(serde::Deserialize)]
+    struct ThemeFile {
+        themes: Vec<ThemeEntry>,
+    }
+    #[derive(serde::Deserialize)]
+    struct ThemeEntry {
+        name: String,
+    }
+
+    let Some(entries) = find_themes_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| std::fs::read(path).ok())
+        .filter_map(|bytes| serde_json::from_slice::<ThemeFile>(&bytes).ok())
+        .flat_map(|file| file.themes.into_iter().map(|theme| theme.name))
+        .collect();
+    names.sort();
+    names
+}
+
 /// Callback for config file changes — applies cross-crate side effects.
 fn on_config_apply(config: &settings::Config, _cx: &mut App) {
     // Update font preference
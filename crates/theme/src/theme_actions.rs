@@ -1,8 +1,9 @@
 //! Theme action handlers.
 //!
-//! Registers GPUI actions for macOS-native features.
+//! Registers GPUI actions for theme selection and macOS-native features.
 
 use gpui::App;
+use gpui_component::theme::{Theme, ThemeRegistry};
 
 /// Register theme-related actions
 pub fn register_actions(cx: &mut App) {
@@ -16,6 +17,21 @@ pub fn register_actions(cx: &mut App) {
         cx.refresh_windows();
     });
 
+    cx.on_action(|action: &actions::SelectTheme, cx| {
+        let Some(theme) = ThemeRegistry::global(cx)
+            .themes()
+            .get(action.name.as_str())
+            .cloned()
+        else {
+            tracing::warn!("Theme '{}' not found", action.name);
+            return;
+        };
+        Theme::global_mut(cx).apply_config(&theme);
+        settings::file::save_theme(&action.name);
+        tracing::info!("Selected theme: {}", action.name);
+        cx.refresh_windows();
+    });
+
     cx.on_action(|_: &actions::ToggleOptionAsAlt, cx| {
         use std::sync::atomic::Ordering;
         let current = actions::OPTION_AS_ALT.load(Ordering::Relaxed);
@@ -0,0 +1,111 @@
+//! Broadcast input ("synchronize panes").
+//!
+//! Keystrokes and pastes sent to a pane in the group are mirrored to every
+//! other pane in it. A whole tab can join, or single panes ad hoc; panes of a
+//! broadcasting tab can be excluded one at a time.
+
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Which panes receive broadcast input.
+#[derive(Debug, Default)]
+pub(crate) struct BroadcastGroup {
+    /// Tabs whose panes all broadcast
+    tabs: HashSet<Uuid>,
+    /// Panes added on their own
+    panes: HashSet<Uuid>,
+    /// Panes left out of a broadcasting tab
+    excluded: HashSet<Uuid>,
+}
+
+impl BroadcastGroup {
+    /// Whether `pane` in `tab` is in the group.
+    pub(crate) fn includes(&self, tab: Uuid, pane: Uuid) -> bool {
+        (self.tabs.contains(&tab) || self.panes.contains(&pane)) && !self.excluded.contains(&pane)
+    }
+
+    /// Add or remove every pane of `tab`. Returns whether the tab now broadcasts.
+    pub(crate) fn toggle_tab(&mut self, tab: Uuid, panes: &[Uuid]) -> bool {
+        let on = self.tabs.insert(tab);
+        if !on {
+            self.tabs.remove(&tab);
+        }
+        // Start from a clean slate either way: all panes in, or all out
+        for pane in panes {
+            self.panes.remove(pane);
+            self.excluded.remove(pane);
+        }
+        on
+    }
+
+    /// Add or remove a single pane. Returns whether it's now in the group.
+    pub(crate) fn toggle_pane(&mut self, tab: Uuid, pane: Uuid) -> bool {
+        if self.includes(tab, pane) {
+            self.panes.remove(&pane);
+            if self.tabs.contains(&tab) {
+                self.excluded.insert(pane);
+            }
+            false
+        } else {
+            self.excluded.remove(&pane);
+            if !self.tabs.contains(&tab) {
+                self.panes.insert(pane);
+            }
+            true
+        }
+    }
+
+    /// Stop broadcasting everywhere.
+    pub(crate) fn clear(&mut self) {
+        self.tabs.clear();
+        self.panes.clear();
+        self.excluded.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_broadcast_includes_all_panes_but_excluded() {
+        let (tab, a, b) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut group = BroadcastGroup::default();
+        assert!(!group.includes(tab, a));
+
+        assert!(group.toggle_tab(tab, &[a, b]));
+        assert!(group.includes(tab, a) && group.includes(tab, b));
+
+        // Quick exclude, then bring the pane back
+        assert!(!group.toggle_pane(tab, b));
+        assert!(group.includes(tab, a) && !group.includes(tab, b));
+        assert!(group.toggle_pane(tab, b));
+        assert!(group.includes(tab, b));
+
+        assert!(!group.toggle_pane(tab, b));
+        assert!(!group.toggle_tab(tab, &[a, b]));
+        assert!(!group.includes(tab, a) && !group.includes(tab, b));
+
+        // Turning the tab back on clears old exclusions
+        assert!(group.toggle_tab(tab, &[a, b]));
+        assert!(group.includes(tab, b));
+    }
+
+    #[test]
+    fn ad_hoc_panes_span_tabs() {
+        let (tab1, tab2) = (Uuid::new_v4(), Uuid::new_v4());
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut group = BroadcastGroup::default();
+
+        assert!(group.toggle_pane(tab1, a));
+        assert!(group.toggle_pane(tab2, c));
+        assert!(group.includes(tab1, a) && group.includes(tab2, c));
+        assert!(!group.includes(tab1, b));
+
+        assert!(!group.toggle_pane(tab1, a));
+        assert!(!group.includes(tab1, a));
+
+        group.clear();
+        assert!(!group.includes(tab2, c));
+    }
+}
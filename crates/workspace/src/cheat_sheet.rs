@@ -0,0 +1,628 @@
+//! Keybinding cheat sheet: every effective binding grouped by key context,
+//! built-in and from `[[keybindings]]`, with conflicts, overridden defaults
+//! and bindings that never fire marked. Searchable by keys or action.
+
+use crate::command_palette::fuzzy_score;
+use crate::picker::{self, PickerKey};
+use actions::{find_action, parse_action, ArgumentKind, DefaultBinding, DEFAULT_KEYBINDINGS};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, px, Action, App, Context, ElementId, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, KeyDownEvent, Keystroke, ParentElement, Render, SharedString,
+    StatefulInteractiveElement, Styled, Window,
+};
+use settings::KeybindingEntry;
+use terminal_view::copy_mode::COPY_MODE_KEYS;
+
+/// The key context a binding applies in, in display order.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Group {
+    Global,
+    Terminal,
+    /// Any other context a user binding names
+    Other(String),
+    /// Keys handled by copy mode itself, not by keybindings
+    CopyMode,
+}
+
+impl Group {
+    fn for_context(context: Option<&str>) -> Self {
+        match context {
+            None => Group::Global,
+            Some("terminal") => Group::Terminal,
+            Some(other) => Group::Other(other.to_string()),
+        }
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            Group::Global => "Global",
+            Group::Terminal => "Terminal",
+            Group::Other(context) => context,
+            Group::CopyMode => "Copy Mode",
+        }
+    }
+}
+
+/// Why a binding stands out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Note {
+    /// A user binding that replaces the default action for its keys.
+    OverridesDefault(String),
+    /// A default whose keys the user bound to another action.
+    ShadowedDefault(String),
+    /// Several user bindings on the same keys; only the last one applies.
+    Conflict { with: String, wins: bool },
+    /// A key sequence takes these keys first.
+    ShadowedBySequence(String),
+    /// Another context bound the keys to something else and takes them there.
+    ShadowedIn { context: String, action: String },
+    /// The binding is ignored because it doesn't parse.
+    Invalid(String),
+}
+
+impl Note {
+    /// Whether the binding fails to do what it says in some or all places.
+    fn is_problem(&self) -> bool {
+        !matches!(self, Note::OverridesDefault(_))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Note::OverridesDefault(action) => format!("Overrides default `{}`", action),
+            Note::ShadowedDefault(action) => format!("Overridden by your `{}`", action),
+            Note::Conflict { with, wins: true } => {
+                format!("Conflicts with `{}`; this one wins", with)
+            }
+            Note::Conflict { with, wins: false } => {
+                format!("Conflicts with `{}`, which wins", with)
+            }
+            Note::ShadowedBySequence(keys) => {
+                format!("Never fires: key sequence `{}` takes these keys", keys)
+            }
+            Note::ShadowedIn { context, action } => {
+                format!("Bound to `{}` in {}", action, context)
+            }
+            Note::Invalid(error) => format!("Ignored: {}", error),
+        }
+    }
+}
+
+/// A line of the cheat sheet.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Row {
+    pub(crate) group: Group,
+    pub(crate) keys: String,
+    /// The action as written in the config; empty for copy mode keys
+    pub(crate) action: String,
+    pub(crate) label: String,
+    /// Whether it comes from the user's config
+    pub(crate) custom: bool,
+    pub(crate) note: Option<Note>,
+}
+
+impl Row {
+    /// Everything the query is matched against.
+    fn search_text(&self) -> String {
+        let note = self.note.as_ref().map(Note::describe).unwrap_or_default();
+        format!(
+            "{} {} {} {} {}",
+            self.keys,
+            self.label,
+            self.action,
+            self.group.title(),
+            note
+        )
+    }
+}
+
+/// A binding in the order GPUI sees it.
+struct Binding {
+    group: Group,
+    keys: String,
+    /// Parsed chords; `None` if the keys don't parse
+    chords: Option<Vec<Keystroke>>,
+    action: String,
+    parsed: Option<Box<dyn Action>>,
+    /// Why the binding is ignored, if it is
+    error: Option<String>,
+    custom: bool,
+}
+
+impl Binding {
+    fn new(keys: &str, action: &str, context: Option<&str>, custom: bool) -> Self {
+        let chords = keys
+            .split_whitespace()
+            .map(|chord| Keystroke::parse(chord).ok())
+            .collect::<Option<Vec<_>>>()
+            .filter(|chords| !chords.is_empty());
+        let is_sequence = chords.as_ref().is_some_and(|chords| chords.len() > 1);
+        let (parsed, error) = match parse_action(action) {
+            Ok(parsed) => (Some(parsed), None),
+            Err(error) => (None, Some(error.to_string())),
+        };
+        let error = match chords {
+            Some(_) => error,
+            None => Some(format!("invalid keys `{}`", keys)),
+        };
+        Self {
+            // Key sequences are matched by the workspace, whatever the context
+            group: if is_sequence {
+                Group::Global
+            } else {
+                Group::for_context(context)
+            },
+            keys: keys.to_string(),
+            chords,
+            action: action.to_string(),
+            parsed,
+            error,
+            custom,
+        }
+    }
+
+    fn is_sequence(&self) -> bool {
+        self.chords.as_ref().is_some_and(|chords| chords.len() > 1)
+    }
+
+    /// Whether both are bound to the same keys in the same context.
+    fn same_keys(&self, other: &Binding) -> bool {
+        self.group == other.group
+            && match (&self.chords, &other.chords) {
+                (Some(a), Some(b)) => chords_equal(a, b),
+                _ => false,
+            }
+    }
+
+    /// Whether both run the same action.
+    fn same_action(&self, other: &Binding) -> bool {
+        match (&self.parsed, &other.parsed) {
+            (Some(a), Some(b)) => a.partial_eq(b.as_ref()),
+            _ => self.action == other.action,
+        }
+    }
+}
+
+fn chords_equal(a: &[Keystroke], b: &[Keystroke]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.modifiers == b.modifiers && a.key == b.key)
+}
+
+/// What an action spec does, from its label in the action registry.
+fn action_label(spec: &str) -> String {
+    let (name, argument) = spec
+        .split_once(char::is_whitespace)
+        .map_or((spec, ""), |(name, rest)| (name, rest.trim()));
+    let found = find_action(name)
+        .map(|found| (found, argument))
+        .or_else(|| {
+            // `activate-tab-3`
+            let (prefix, number) = name.rsplit_once('-')?;
+            Some((find_action(prefix)?, number))
+        });
+    match found {
+        Some((found, "")) => found.label.to_string(),
+        Some((found, argument)) if found.argument() == ArgumentKind::Number => {
+            format!("{} {}", found.label, argument)
+        }
+        Some((found, argument)) => format!("{}: {}", found.label, argument),
+        None => name.to_string(),
+    }
+}
+
+/// The note for `bindings[index]`, looking at every other binding.
+fn note_for(bindings: &[Binding], index: usize) -> Option<Note> {
+    let binding = &bindings[index];
+    if let Some(error) = &binding.error {
+        return Some(Note::Invalid(error.clone()));
+    }
+    let valid = |other: &&Binding| other.error.is_none();
+    let rebinds = |other: &&Binding| binding.same_keys(other) && !binding.same_action(other);
+
+    // GPUI uses the last binding for the same keys
+    if let Some(winner) = bindings[index + 1..].iter().filter(valid).rfind(rebinds) {
+        return Some(if binding.custom {
+            Note::Conflict {
+                with: winner.action.clone(),
+                wins: false,
+            }
+        } else {
+            Note::ShadowedDefault(winner.action.clone())
+        });
+    }
+
+    // The workspace matches key sequences before any binding
+    let chords = binding.chords.as_deref().unwrap_or_default();
+    let sequence = bindings.iter().filter(valid).find(|other| {
+        let Some(other_chords) = other.chords.as_deref().filter(|_| other.is_sequence()) else {
+            return false;
+        };
+        if chords.len() == 1 {
+            // A chord that starts a sequence
+            chords_equal(&other_chords[..1], chords)
+        } else {
+            // A longer sequence that a shorter one completes first
+            other_chords.len() < chords.len()
+                && chords_equal(other_chords, &chords[..other_chords.len()])
+        }
+    });
+    if let Some(sequence) = sequence {
+        return Some(Note::ShadowedBySequence(sequence.keys.clone()));
+    }
+
+    let mut earlier = bindings[..index].iter().filter(valid).filter(rebinds);
+    if let Some(other) = earlier.clone().rfind(|other| other.custom) {
+        return Some(Note::Conflict {
+            with: other.action.clone(),
+            wins: true,
+        });
+    }
+    if let Some(default) = earlier.next_back() {
+        return Some(Note::OverridesDefault(default.action.clone()));
+    }
+
+    // A binding in a narrower context takes the keys while it's focused
+    if binding.group != Group::Global {
+        return None;
+    }
+    let narrower = bindings.iter().enumerate().rfind(|&(other_index, other)| {
+        other.group != Group::Global
+            && other.error.is_none()
+            && !other.is_sequence()
+            && other
+                .chords
+                .as_deref()
+                .is_some_and(|other| chords_equal(other, chords))
+            && !binding.same_action(other)
+            && !bindings[other_index + 1..]
+                .iter()
+                .any(|later| later.error.is_none() && other.same_keys(later))
+    });
+    narrower.map(|(_, other)| Note::ShadowedIn {
+        context: other.group.title().to_string(),
+        action: other.action.clone(),
+    })
+}
+
+/// Every binding: the defaults, then the user's, then the copy mode keys.
+pub(crate) fn build_rows(defaults: &[DefaultBinding], custom: &[KeybindingEntry]) -> Vec<Row> {
+    let bindings: Vec<Binding> = defaults
+        .iter()
+        .map(|b| Binding::new(b.keys, b.action, b.context, false))
+        .chain(
+            custom
+                .iter()
+                .map(|b| Binding::new(&b.keys, &b.action, b.context.as_deref(), true)),
+        )
+        .collect();
+
+    let mut rows: Vec<Row> = bindings
+        .iter()
+        .enumerate()
+        .map(|(index, binding)| Row {
+            group: binding.group.clone(),
+            keys: binding.keys.clone(),
+            action: binding.action.clone(),
+            label: action_label(&binding.action),
+            custom: binding.custom,
+            note: note_for(&bindings, index),
+        })
+        .collect();
+    rows.extend(COPY_MODE_KEYS.iter().map(|(keys, label)| Row {
+        group: Group::CopyMode,
+        keys: keys.to_string(),
+        action: String::new(),
+        label: label.to_string(),
+        custom: false,
+        note: None,
+    }));
+    // Stable, so each group keeps the binding order
+    rows.sort_by(|a, b| a.group.cmp(&b.group));
+    rows
+}
+
+/// Indices of the rows matching `query`, in order.
+fn filter_rows(rows: &[Row], query: &str) -> Vec<usize> {
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| fuzzy_score(query, &row.search_text()).is_some())
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Event emitted when the cheat sheet closes.
+pub struct CheatSheetDismiss;
+
+impl gpui::EventEmitter<CheatSheetDismiss> for CheatSheet {}
+
+/// The keybinding cheat sheet overlay view.
+pub struct CheatSheet {
+    query: String,
+    rows: Vec<Row>,
+    filtered_indices: Vec<usize>,
+    pub(crate) focus_handle: FocusHandle,
+}
+
+impl CheatSheet {
+    /// Open with the default bindings and the user's `[[keybindings]]`.
+    pub(crate) fn new(custom: &[KeybindingEntry], cx: &mut Context<Self>) -> Self {
+        let rows = build_rows(DEFAULT_KEYBINDINGS, custom);
+        let filtered_indices = (0..rows.len()).collect();
+        Self {
+            query: String::new(),
+            rows,
+            filtered_indices,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn update_filter(&mut self, cx: &mut Context<Self>) {
+        self.filtered_indices = filter_rows(&self.rows, &self.query);
+        cx.notify();
+    }
+
+    fn dismiss(&mut self, cx: &mut Context<Self>) {
+        cx.emit(CheatSheetDismiss);
+    }
+}
+
+impl Focusable for CheatSheet {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for CheatSheet {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = theme::terminal_colors(cx);
+        let foreground = colors.foreground;
+        let muted = colors.muted;
+        let accent = colors.accent;
+        let warning = colors.yellow;
+        let problems = self
+            .rows
+            .iter()
+            .filter(|row| row.note.as_ref().is_some_and(Note::is_problem))
+            .count();
+
+        let mut list = Vec::new();
+        let mut current_group: Option<&Group> = None;
+        for &row_idx in &self.filtered_indices {
+            let row = &self.rows[row_idx];
+            if current_group != Some(&row.group) {
+                current_group = Some(&row.group);
+                list.push(
+                    div()
+                        .id(ElementId::Name(format!("keys-group-{}", row_idx).into()))
+                        .pt(px(10.0))
+                        .pb(px(4.0))
+                        .px(px(12.0))
+                        .text_size(px(11.0))
+                        .text_color(muted)
+                        .child(SharedString::from(row.group.title().to_uppercase())),
+                );
+            }
+            let problem = row.note.as_ref().is_some_and(Note::is_problem);
+            let note: Option<SharedString> = row.note.as_ref().map(|n| n.describe().into());
+            let keys: SharedString = row.keys.clone().into();
+            let label: SharedString = row.label.clone().into();
+            let action: SharedString = row.action.clone().into();
+
+            list.push(
+                div()
+                    .id(ElementId::Name(format!("keys-{}", row_idx).into()))
+                    .w_full()
+                    .px(px(12.0))
+                    .py(px(4.0))
+                    .flex()
+                    .flex_col()
+                    .when(problem, |d| d.bg(warning.opacity(0.08)))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap(px(12.0))
+                            .child(
+                                div()
+                                    .w(px(170.0))
+                                    .flex_shrink_0()
+                                    .text_sm()
+                                    .text_color(if row.custom { accent } else { foreground })
+                                    .child(keys),
+                            )
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .text_sm()
+                                    .text_color(foreground)
+                                    .overflow_hidden()
+                                    .whitespace_nowrap()
+                                    .child(label),
+                            )
+                            .child(
+                                div()
+                                    .flex_shrink_0()
+                                    .text_size(px(11.0))
+                                    .text_color(muted)
+                                    .child(action),
+                            ),
+                    )
+                    .when_some(note, |d, note| {
+                        d.child(
+                            div()
+                                .pl(px(182.0))
+                                .text_size(px(11.0))
+                                .text_color(if problem { warning } else { accent })
+                                .child(note),
+                        )
+                    }),
+            );
+        }
+
+        let header = picker::query_line(&colors)
+            .justify_between()
+            .child(picker::query_text(
+                &self.query,
+                "Search keybindings...",
+                &colors,
+            ))
+            .when(problems > 0, |d| {
+                d.child(
+                    div()
+                        .flex_shrink_0()
+                        .text_size(px(11.0))
+                        .text_color(warning)
+                        .child(format!(
+                            "{} binding{} with problems",
+                            problems,
+                            if problems == 1 { "" } else { "s" }
+                        )),
+                )
+            });
+        let panel = picker::panel(680.0, &colors)
+            .child(header)
+            .child(
+                div()
+                    .id("keys-results")
+                    .max_h(px(480.0))
+                    .pb(px(8.0))
+                    .overflow_y_scroll()
+                    .scrollbar_width(px(4.0))
+                    .children(list),
+            )
+            .when(self.filtered_indices.is_empty(), |d| {
+                d.child(picker::empty_message("No matching keybindings", &colors))
+            });
+
+        picker::overlay("keys-backdrop", panel, Self::dismiss, cx)
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
+                match picker::picker_key(&mut this.query, &event.keystroke) {
+                    PickerKey::Dismiss => this.dismiss(cx),
+                    PickerKey::Edited => this.update_filter(cx),
+                    _ => {}
+                }
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(keys: &str, action: &str, context: Option<&str>) -> KeybindingEntry {
+        KeybindingEntry {
+            keys: keys.to_string(),
+            action: action.to_string(),
+            context: context.map(str::to_string),
+        }
+    }
+
+    fn note<'a>(rows: &'a [Row], keys: &str, action: &str) -> Option<&'a Note> {
+        rows.iter()
+            .find(|row| row.keys == keys && row.action == action)
+            .unwrap_or_else(|| panic!("no row {} -> {}", keys, action))
+            .note
+            .as_ref()
+    }
+
+    #[test]
+    fn defaults_are_grouped_without_notes() {
+        let rows = build_rows(DEFAULT_KEYBINDINGS, &[]);
+        assert!(rows.iter().all(|row| row.note.is_none()));
+        assert_eq!(rows[0].group, Group::Global);
+        assert_eq!(rows.last().unwrap().group, Group::CopyMode);
+        let search = rows.iter().find(|row| row.keys == "cmd-f").unwrap();
+        assert_eq!(search.group, Group::Terminal);
+        assert_eq!(search.label, "Search");
+        let tab = rows.iter().find(|row| row.keys == "cmd-3").unwrap();
+        assert_eq!(tab.label, "Go to Tab 3");
+    }
+
+    #[test]
+    fn user_bindings_override_defaults() {
+        let rows = build_rows(DEFAULT_KEYBINDINGS, &[custom("cmd-d", "new-tab", None)]);
+        assert_eq!(
+            note(&rows, "cmd-d", "split-vertical"),
+            Some(&Note::ShadowedDefault("new-tab".into()))
+        );
+        assert_eq!(
+            note(&rows, "cmd-d", "new-tab"),
+            Some(&Note::OverridesDefault("split-vertical".into()))
+        );
+        // Rebinding to the same action (by alias) is no conflict
+        let rows = build_rows(DEFAULT_KEYBINDINGS, &[custom("cmd-1", "go-to-tab 1", None)]);
+        assert_eq!(note(&rows, "cmd-1", "activate-tab 1"), None);
+    }
+
+    #[test]
+    fn conflicting_user_bindings_are_flagged() {
+        let rows = build_rows(
+            &[],
+            &[
+                custom("ctrl-k", "new-tab", None),
+                custom("ctrl-k", "close-tab", None),
+            ],
+        );
+        assert_eq!(
+            note(&rows, "ctrl-k", "new-tab"),
+            Some(&Note::Conflict {
+                with: "close-tab".into(),
+                wins: false
+            })
+        );
+        assert_eq!(
+            note(&rows, "ctrl-k", "close-tab"),
+            Some(&Note::Conflict {
+                with: "new-tab".into(),
+                wins: true
+            })
+        );
+    }
+
+    #[test]
+    fn sequences_and_contexts_shadow_bindings() {
+        let rows = build_rows(
+            &[],
+            &[
+                custom("ctrl-a", "new-tab", None),
+                custom("ctrl-a v", "split-vertical", None),
+                custom("ctrl-l", "close-tab", None),
+                custom("ctrl-l", r#"send-text "\x0c""#, Some("terminal")),
+                custom("ctrl-x", "no-such-action", None),
+            ],
+        );
+        assert_eq!(
+            note(&rows, "ctrl-a", "new-tab"),
+            Some(&Note::ShadowedBySequence("ctrl-a v".into()))
+        );
+        assert_eq!(note(&rows, "ctrl-a v", "split-vertical"), None);
+        assert_eq!(
+            note(&rows, "ctrl-l", "close-tab"),
+            Some(&Note::ShadowedIn {
+                context: "Terminal".into(),
+                action: r#"send-text "\x0c""#.into()
+            })
+        );
+        assert!(matches!(
+            note(&rows, "ctrl-x", "no-such-action"),
+            Some(Note::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn filter_matches_keys_and_actions() {
+        let rows = build_rows(DEFAULT_KEYBINDINGS, &[]);
+        let keys = |query| -> Vec<&str> {
+            filter_rows(&rows, query)
+                .into_iter()
+                .map(|idx| rows[idx].keys.as_str())
+                .collect()
+        };
+        assert!(keys("zoom").contains(&"cmd-shift-enter"));
+        assert!(keys("cmd-shift-p").contains(&"cmd-shift-p"));
+        assert!(keys("copy mode").contains(&"shift-v"));
+    }
+}
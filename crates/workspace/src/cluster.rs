@@ -0,0 +1,345 @@
+//! Cluster open — one tab with a grid of sessions and broadcast input on.
+//!
+//! A cluster is a profile group (profiles sharing `group = "..."`) or a
+//! comma-separated list of profiles, SSH targets, `telnet://` / `tcp://` URLs
+//! and `!commands`, e.g. `web1, deploy@web2:2222, !htop`.
+
+use crate::workspace_view::Workspace;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, hsla, px, Context, ElementId, Entity, InteractiveElement, IntoElement, KeyDownEvent,
+    ParentElement, SharedString, Styled, Subscription,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::Sizable;
+use remote::SshTarget;
+use settings::{Config, Profile, TelnetConfig};
+use theme::terminal_colors;
+
+/// Most panes a cluster opens (a 6x6 grid).
+pub(crate) const MAX_CLUSTER_SIZE: usize = 36;
+
+/// One session in a cluster.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ClusterMember {
+    Ssh(SshTarget),
+    Telnet(TelnetConfig),
+    /// A local program, run directly (no shell)
+    Command {
+        program: String,
+        args: Vec<String>,
+    },
+}
+
+impl ClusterMember {
+    /// Short name for logs and the tab title.
+    pub(crate) fn display_name(&self) -> String {
+        match self {
+            Self::Ssh(target) => target.display_name(),
+            Self::Telnet(config) => config.display_name(),
+            Self::Command { program, .. } => program.clone(),
+        }
+    }
+}
+
+/// State for the Open Cluster panel.
+pub(crate) struct ClusterPanelState {
+    /// Input for a group name or member list; cleared once a cluster opens.
+    pub(crate) input: Entity<InputState>,
+    /// Hiding keeps the typed spec, so a rejected one can be fixed.
+    pub(crate) visible: bool,
+    /// Why the last cluster didn't open.
+    pub(crate) error: Option<SharedString>,
+    /// Profile groups with their sizes, re-read from the config each time the panel opens.
+    pub(crate) groups: Vec<(String, usize)>,
+    /// Opens the typed cluster on Enter.
+    pub(crate) _subscriptions: Vec<Subscription>,
+}
+
+/// Profile groups in config order, with how many profiles each has.
+pub(crate) fn cluster_groups(config: &Config) -> Vec<(String, usize)> {
+    let mut groups: Vec<(String, usize)> = Vec::new();
+    for group in config.profiles.iter().filter_map(|p| p.group.as_deref()) {
+        match groups.iter_mut().find(|(name, _)| name == group) {
+            Some((_, count)) => *count += 1,
+            None => groups.push((group.to_string(), 1)),
+        }
+    }
+    groups
+}
+
+/// Resolve a profile group or member list into a tab title and its members.
+pub(crate) fn resolve_cluster(
+    spec: &str,
+    config: &Config,
+) -> Result<(String, Vec<ClusterMember>), String> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Err("Enter a profile group or a comma-separated list of hosts".to_string());
+    }
+
+    let grouped: Vec<&Profile> = config
+        .profiles
+        .iter()
+        .filter(|p| p.group.as_deref() == Some(spec))
+        .collect();
+    let (title, members) = if grouped.is_empty() {
+        let members = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| parse_member(item, config))
+            .collect::<Result<Vec<_>, _>>()?;
+        let title = format!("Cluster ({})", members.len());
+        (title, members)
+    } else {
+        let members = grouped
+            .into_iter()
+            .filter_map(|profile| {
+                let member = profile_member(profile);
+                if member.is_none() {
+                    tracing::warn!(
+                        "Profile '{}' in group '{}' has no host or telnet settings",
+                        profile.name,
+                        spec
+                    );
+                }
+                member
+            })
+            .collect::<Vec<_>>();
+        (spec.to_string(), members)
+    };
+
+    match members.len() {
+        0 => Err(format!("'{}' has no SSH or Telnet hosts", spec)),
+        n if n > MAX_CLUSTER_SIZE => Err(format!(
+            "A cluster opens at most {} panes, got {}",
+            MAX_CLUSTER_SIZE, n
+        )),
+        _ => Ok((title, members)),
+    }
+}
+
+/// Parse one item of a member list.
+fn parse_member(item: &str, config: &Config) -> Result<ClusterMember, String> {
+    if let Some(command) = item.strip_prefix('!') {
+        let mut words = command.split_whitespace().map(str::to_string);
+        let program = words
+            .next()
+            .ok_or_else(|| "'!' needs a command, e.g. !htop".to_string())?;
+        return Ok(ClusterMember::Command {
+            program,
+            args: words.collect(),
+        });
+    }
+    if let Some(profile) = config.profiles.iter().find(|p| p.name == item) {
+        return profile_member(profile)
+            .ok_or_else(|| format!("Profile '{}' has no host or telnet settings", item));
+    }
+    if let Some(telnet) = TelnetConfig::parse(item) {
+        return Ok(ClusterMember::Telnet(telnet));
+    }
+    SshTarget::parse(item)
+        .map(ClusterMember::Ssh)
+        .ok_or_else(|| {
+            format!(
+                "'{}' is not a profile, telnet:// or tcp:// URL, SSH target, or !command",
+                item
+            )
+        })
+}
+
+/// The session a profile opens, if it's remote.
+fn profile_member(profile: &Profile) -> Option<ClusterMember> {
+    if let Some(telnet) = &profile.telnet {
+        return Some(ClusterMember::Telnet(telnet.clone()));
+    }
+    let host = profile.host.as_deref()?;
+    SshTarget::parse(host).map(ClusterMember::Ssh)
+}
+
+impl ClusterPanelState {
+    /// Toggle visibility. Returns the new visible state.
+    pub(crate) fn toggle(&mut self) -> bool {
+        self.visible = !self.visible;
+        self.visible
+    }
+}
+
+/// Render the Open Cluster panel overlay.
+pub(crate) fn render_cluster_panel(
+    state: &ClusterPanelState,
+    cx: &mut Context<Workspace>,
+) -> impl IntoElement {
+    let colors = terminal_colors(cx);
+    let foreground = colors.foreground;
+    let muted = colors.muted;
+    let red = colors.red;
+
+    let group_rows = state
+        .groups
+        .iter()
+        .map(|(group, count)| {
+            let name = group.clone();
+            div()
+                .flex()
+                .items_center()
+                .gap_3()
+                .py_1()
+                .child(
+                    div()
+                        .flex_1()
+                        .text_sm()
+                        .text_color(foreground)
+                        .child(group.clone()),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(muted)
+                        .child(format!("{} host(s)", count)),
+                )
+                .child(
+                    Button::new(ElementId::Name(format!("open-cluster-{}", group).into()))
+                        .xsmall()
+                        .ghost()
+                        .label("Open")
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.open_cluster_from_panel(&name, window, cx);
+                        })),
+                )
+        })
+        .collect::<Vec<_>>();
+
+    div()
+        .id("cluster-panel")
+        .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+            if event.keystroke.key == "escape" {
+                this.hide_cluster_panel(window, cx);
+            }
+        }))
+        .absolute()
+        .top(px(60.0))
+        .left(px(120.0))
+        .right(px(120.0))
+        .max_h(px(480.0))
+        .bg(hsla(0.0, 0.0, 0.10, 1.0))
+        .border_1()
+        .border_color(hsla(0.0, 0.0, 0.25, 1.0))
+        .rounded(px(8.0))
+        .shadow_lg()
+        .overflow_hidden()
+        .p(px(12.0))
+        .flex()
+        .flex_col()
+        .gap_2()
+        .child(
+            div()
+                .text_base()
+                .text_color(foreground)
+                .child("Open Cluster"),
+        )
+        .child(Input::new(&state.input))
+        .when_some(state.error.clone(), |d, error| {
+            d.child(div().text_xs().text_color(red).child(error))
+        })
+        .child(
+            div()
+                .text_xs()
+                .text_color(muted)
+                .child("Opens a grid in a new tab with broadcast input on"),
+        )
+        .when(!group_rows.is_empty(), |d| {
+            d.child(div().mt_2().text_xs().text_color(muted).child("Groups"))
+                .children(group_rows)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            profiles: vec![
+                Profile {
+                    name: "web1".to_string(),
+                    host: Some("deploy@web1".to_string()),
+                    group: Some("web".to_string()),
+                    ..Profile::default()
+                },
+                Profile {
+                    name: "local".to_string(),
+                    group: Some("web".to_string()),
+                    ..Profile::default()
+                },
+                Profile {
+                    name: "switch1".to_string(),
+                    telnet: TelnetConfig::parse("telnet://switch1"),
+                    group: Some("web".to_string()),
+                    ..Profile::default()
+                },
+                Profile {
+                    name: "db".to_string(),
+                    host: Some("db.internal".to_string()),
+                    group: Some("data".to_string()),
+                    ..Profile::default()
+                },
+            ],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn group_opens_its_remote_profiles() {
+        let (title, members) = resolve_cluster("web", &config()).unwrap();
+        assert_eq!(title, "web");
+        assert_eq!(
+            members,
+            vec![
+                ClusterMember::Ssh(SshTarget::parse("deploy@web1").unwrap()),
+                ClusterMember::Telnet(TelnetConfig::parse("telnet://switch1").unwrap()),
+            ]
+        );
+        assert_eq!(
+            cluster_groups(&config()),
+            vec![("web".to_string(), 3), ("data".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn list_mixes_profiles_hosts_and_commands() {
+        let (title, members) = resolve_cluster(
+            "db, ops@web3:2222, tcp://10.0.0.5:9000, !tail -f log",
+            &config(),
+        )
+        .unwrap();
+        assert_eq!(title, "Cluster (4)");
+        assert_eq!(
+            members[0],
+            ClusterMember::Ssh(SshTarget::parse("db.internal").unwrap())
+        );
+        assert_eq!(
+            members[1],
+            ClusterMember::Ssh(SshTarget::parse("ops@web3:2222").unwrap())
+        );
+        assert!(matches!(members[2], ClusterMember::Telnet(_)));
+        assert_eq!(
+            members[3],
+            ClusterMember::Command {
+                program: "tail".to_string(),
+                args: vec!["-f".to_string(), "log".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_unknown_members() {
+        assert!(resolve_cluster("  ", &config()).is_err());
+        assert!(resolve_cluster("local", &config()).is_err());
+        assert!(resolve_cluster("web1, !", &config()).is_err());
+        let too_many = vec!["web1"; MAX_CLUSTER_SIZE + 1].join(",");
+        assert!(resolve_cluster(&too_many, &config()).is_err());
+    }
+}
@@ -1,20 +1,45 @@
 //! Command palette overlay with fuzzy search for HumanSSH actions.
 
-use actions::*;
+use std::path::{Path, PathBuf};
+
+use crate::key_sequence::KeySequences;
+use crate::picker::{self, PickerKey};
+use actions::{AddPortForward, RunCommand, RunTarget, ACTIONS};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    div, hsla, px, Action, App, Context, ElementId, FocusHandle, Focusable, InteractiveElement,
+    div, px, Action, App, Context, ElementId, FocusHandle, Focusable, InteractiveElement,
     IntoElement, KeyDownEvent, ParentElement, Render, SharedString, StatefulInteractiveElement,
     Styled, Window,
 };
 
+/// History of confirmed palette commands, by label.
+const HISTORY: &str = "commands";
+
+/// History of command lines run from the palette.
+const RUN_HISTORY: &str = "run-command";
+
+/// A query starting with this runs a shell command line instead.
+const RUN_PREFIX: char = '>';
+
 /// A single entry in the command palette.
-struct CommandEntry {
-    label: &'static str,
-    shortcut: &'static str,
+pub(crate) struct CommandEntry {
+    label: SharedString,
+    /// Keys bound to the action, filled in when the palette opens.
+    shortcut: SharedString,
     action: Box<dyn Action>,
 }
 
+impl CommandEntry {
+    /// An entry for a command that depends on the workspace's state.
+    pub(crate) fn new(label: impl Into<SharedString>, action: impl Action) -> Self {
+        Self {
+            label: label.into(),
+            shortcut: SharedString::default(),
+            action: Box::new(action),
+        }
+    }
+}
+
 /// Event emitted when the palette should close, optionally executing an action.
 pub struct CommandPaletteDismiss {
     pub action: Option<Box<dyn Action>>,
@@ -22,124 +47,130 @@ pub struct CommandPaletteDismiss {
 
 impl gpui::EventEmitter<CommandPaletteDismiss> for CommandPalette {}
 
+/// Every registry action that needs no argument.
 fn build_command_entries() -> Vec<CommandEntry> {
-    vec![
-        CommandEntry {
-            label: "New Tab",
-            shortcut: "Cmd+T",
-            action: Box::new(NewTab),
-        },
-        CommandEntry {
-            label: "Close Tab",
-            shortcut: "Cmd+W",
-            action: Box::new(CloseTab),
-        },
-        CommandEntry {
-            label: "Next Tab",
-            shortcut: "Cmd+Shift+]",
-            action: Box::new(NextTab),
-        },
-        CommandEntry {
-            label: "Previous Tab",
-            shortcut: "Cmd+Shift+[",
-            action: Box::new(PrevTab),
-        },
-        CommandEntry {
-            label: "Split Vertical",
-            shortcut: "Cmd+D",
-            action: Box::new(SplitVertical),
-        },
-        CommandEntry {
-            label: "Split Horizontal",
-            shortcut: "Cmd+Shift+D",
-            action: Box::new(SplitHorizontal),
-        },
-        CommandEntry {
-            label: "Close Pane",
-            shortcut: "",
-            action: Box::new(ClosePane),
-        },
-        CommandEntry {
-            label: "Focus Next Pane",
-            shortcut: "Cmd+Alt+Right",
-            action: Box::new(FocusNextPane),
-        },
-        CommandEntry {
-            label: "Focus Previous Pane",
-            shortcut: "Cmd+Alt+Left",
-            action: Box::new(FocusPrevPane),
-        },
-        CommandEntry {
-            label: "Search",
-            shortcut: "Cmd+F",
-            action: Box::new(SearchToggle),
-        },
-        CommandEntry {
-            label: "Search Next",
-            shortcut: "Cmd+G",
-            action: Box::new(SearchNext),
-        },
-        CommandEntry {
-            label: "Search Previous",
-            shortcut: "Cmd+Shift+G",
-            action: Box::new(SearchPrev),
-        },
-        CommandEntry {
-            label: "Toggle Regex Search",
-            shortcut: "Cmd+Alt+R",
-            action: Box::new(SearchToggleRegex),
-        },
-        CommandEntry {
-            label: "Enter Copy Mode",
-            shortcut: "Cmd+Shift+C",
-            action: Box::new(EnterCopyMode),
-        },
-        CommandEntry {
-            label: "Exit Copy Mode",
-            shortcut: "",
-            action: Box::new(ExitCopyMode),
-        },
-        CommandEntry {
-            label: "Open Settings",
-            shortcut: "Cmd+,",
-            action: Box::new(OpenSettings),
-        },
-        CommandEntry {
-            label: "Toggle Secure Input",
-            shortcut: "Cmd+Shift+S",
-            action: Box::new(ToggleSecureInput),
-        },
-        CommandEntry {
-            label: "Toggle Option as Alt",
-            shortcut: "",
-            action: Box::new(ToggleOptionAsAlt),
-        },
-        CommandEntry {
-            label: "Toggle Scratchpad",
-            shortcut: "Ctrl+`",
-            action: Box::new(ToggleScratchpad),
-        },
-        CommandEntry {
-            label: "Start Recording",
-            shortcut: "",
-            action: Box::new(StartRecording),
-        },
-        CommandEntry {
-            label: "Stop Recording",
-            shortcut: "",
-            action: Box::new(StopRecording),
-        },
-        CommandEntry {
-            label: "Open Replay",
-            shortcut: "",
-            action: Box::new(OpenReplay),
-        },
-        CommandEntry {
-            label: "Quit",
-            shortcut: "Cmd+Q",
-            action: Box::new(Quit),
-        },
-    ]
+    ACTIONS
+        .iter()
+        .filter_map(|spec| {
+            Some(CommandEntry {
+                label: spec.label.into(),
+                shortcut: SharedString::default(),
+                action: spec.action()?,
+            })
+        })
+        .collect()
+}
+
+/// Entries running `command` in each place, followed by past command lines
+/// containing it, most recent first.
+fn run_entries(command: &str, history: &[String]) -> Vec<CommandEntry> {
+    let run = |command: &str, target| RunCommand {
+        command: command.to_string(),
+        target,
+    };
+    let mut entries = Vec::new();
+    if !command.is_empty() {
+        for (target, label) in [
+            (RunTarget::Tab, "Run in New Tab"),
+            (RunTarget::Split, "Run in Split"),
+            (RunTarget::Floating, "Run in Floating Pane"),
+        ] {
+            entries.push(CommandEntry::new(
+                format!("{}: {}", label, command),
+                run(command, target),
+            ));
+        }
+    }
+    entries.extend(
+        history
+            .iter()
+            .filter(|past| past.as_str() != command && past.contains(command))
+            .map(|past| CommandEntry {
+                label: past.clone().into(),
+                shortcut: "recent".into(),
+                action: Box::new(run(past, RunTarget::Tab)),
+            }),
+    );
+    entries
+}
+
+/// An entry starting the port forward the query spells, such as
+/// `L 8080:localhost:80` or `D 1080 me@bastion`.
+fn forward_entry(query: &str) -> Option<CommandEntry> {
+    let tokens: Vec<&str> = query.split_whitespace().collect();
+    let spec = match tokens.as_slice() {
+        [kind, fields] | [kind, fields, _] => format!("{} {}", kind, fields),
+        _ => return None,
+    };
+    remote::ForwardSpec::parse(&spec).ok()?;
+    let forward = tokens.join(" ");
+    Some(CommandEntry::new(
+        format!("Add Port Forward: {}", forward),
+        AddPortForward { forward },
+    ))
+}
+
+/// Complete the path being typed at the end of `command_line`, relative to
+/// `working_dir`: to the single matching name (with a `/` after a
+/// directory), or as far as all matches agree. `None` if nothing matches or
+/// the matches add nothing.
+fn complete_path(command_line: &str, working_dir: Option<&Path>) -> Option<String> {
+    let word_start = command_line
+        .rfind(char::is_whitespace)
+        .map_or(0, |index| index + 1);
+    let word = &command_line[word_start..];
+    let (dir, prefix) = match word.rfind('/') {
+        Some(index) => (&word[..=index], &word[index + 1..]),
+        None => ("", word),
+    };
+
+    let search_dir = match dir.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()?.join(rest),
+        None if Path::new(dir).is_absolute() => PathBuf::from(dir),
+        None => working_dir
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok())?
+            .join(dir),
+    };
+    let mut matches: Vec<(String, bool)> = std::fs::read_dir(search_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // Hidden files only when asked for
+            let wanted =
+                name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.'));
+            wanted.then(|| (name, entry.path().is_dir()))
+        })
+        .collect();
+    matches.sort();
+
+    let completion = match matches.as_slice() {
+        [] => return None,
+        [(name, true)] => format!("{}/", name),
+        [(name, false)] => name.clone(),
+        [(first, _), rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, (name, _)| {
+                let len = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map_or(0, |((index, a), _)| index + a.len_utf8());
+                &common[..len]
+            });
+            common.to_string()
+        }
+    };
+    if completion == prefix {
+        return None;
+    }
+    Some(format!(
+        "{}{}{}",
+        &command_line[..word_start],
+        dir,
+        completion
+    ))
 }
 
 /// Fuzzy match: check if all query characters appear in order in the target (case-insensitive).
@@ -168,7 +199,7 @@ fn fuzzy_match(query: &str, target: &str) -> bool {
 
 /// Score a fuzzy match -- lower is better. Returns None if no match.
 /// Prefers matches at word boundaries and consecutive characters.
-fn fuzzy_score(query: &str, target: &str) -> Option<u32> {
+pub(crate) fn fuzzy_score(query: &str, target: &str) -> Option<u32> {
     if query.is_empty() {
         return Some(0);
     }
@@ -206,68 +237,179 @@ fn fuzzy_score(query: &str, target: &str) -> Option<u32> {
     }
 }
 
+/// Indices of the entries matching `query`, best match first. Recently used
+/// commands (`recent`, most recent first) go ahead of others that match as
+/// well.
+fn filter_entries(entries: &[CommandEntry], query: &str, recent: &[String]) -> Vec<usize> {
+    let recency = |entry: &CommandEntry| {
+        recent
+            .iter()
+            .position(|label| label.as_str() == entry.label.as_ref())
+            .unwrap_or(recent.len())
+    };
+    let mut scored: Vec<(usize, u32, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, entry)| {
+            fuzzy_score(query, &entry.label).map(|score| (idx, score, recency(entry)))
+        })
+        .collect();
+
+    scored.sort_by_key(|&(_, score, recency)| (score, recency));
+    scored.into_iter().map(|(idx, _, _)| idx).collect()
+}
+
+/// The keys bound to `action` in the focused context, as the platform
+/// writes them; a key sequence if no single chord is bound.
+fn shortcut_for(action: &dyn Action, window: &Window, cx: &App) -> SharedString {
+    if let Some(binding) = window.bindings_for_action(action).last() {
+        let keys: Vec<String> = binding
+            .keystrokes()
+            .iter()
+            .map(|keystroke| keystroke.to_string())
+            .collect();
+        return keys.join(" ").into();
+    }
+    cx.try_global::<KeySequences>()
+        .and_then(|sequences| sequences.chords_for(action))
+        .unwrap_or_default()
+}
+
 /// The command palette overlay view.
 pub struct CommandPalette {
     query: String,
     selected_index: usize,
     entries: Vec<CommandEntry>,
+    /// Length of `entries` without the port forward entry built from the query
+    fixed_entries: usize,
     filtered_indices: Vec<usize>,
+    /// Labels of recently confirmed commands, most recent first
+    recent: Vec<String>,
+    /// Entries for the command line typed after [`RUN_PREFIX`]
+    run_entries: Vec<CommandEntry>,
+    /// Command lines run from the palette, most recent first
+    run_history: Vec<String>,
+    /// Directory commands run in, for path completion
+    working_dir: Option<PathBuf>,
     pub(crate) focus_handle: FocusHandle,
 }
 
 impl CommandPalette {
-    pub fn new(cx: &mut Context<Self>) -> Self {
-        let entries = build_command_entries();
-        let filtered_indices: Vec<usize> = (0..entries.len()).collect();
+    /// Open with the built-in commands followed by `extra`. Command lines
+    /// run from the palette start in `working_dir`.
+    pub(crate) fn new(
+        extra: Vec<CommandEntry>,
+        working_dir: Option<PathBuf>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut entries = build_command_entries();
+        entries.extend(extra);
+        for entry in &mut entries {
+            entry.shortcut = shortcut_for(entry.action.as_ref(), window, cx);
+        }
+        let recent = settings::history::load_history(HISTORY);
+        let filtered_indices = filter_entries(&entries, "", &recent);
         let focus_handle = cx.focus_handle();
 
         Self {
             query: String::new(),
             selected_index: 0,
+            fixed_entries: entries.len(),
             entries,
             filtered_indices,
+            recent,
+            run_entries: Vec::new(),
+            run_history: settings::history::load_history(RUN_HISTORY),
+            working_dir,
             focus_handle,
         }
     }
 
-    fn update_filter(&mut self) {
-        let mut scored: Vec<(usize, u32)> = self
-            .entries
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, entry)| fuzzy_score(&self.query, entry.label).map(|s| (idx, s)))
-            .collect();
+    /// The command line typed after [`RUN_PREFIX`], in run mode.
+    fn command_line(&self) -> Option<&str> {
+        self.query.strip_prefix(RUN_PREFIX).map(str::trim_start)
+    }
 
-        scored.sort_by_key(|&(_, score)| score);
-        self.filtered_indices = scored.into_iter().map(|(idx, _)| idx).collect();
+    /// The entries `filtered_indices` refers to.
+    fn shown_entries(&self) -> &[CommandEntry] {
+        if self.command_line().is_some() {
+            &self.run_entries
+        } else {
+            &self.entries
+        }
+    }
 
+    fn update_filter(&mut self) {
+        match self.command_line() {
+            Some(command) => {
+                self.run_entries = run_entries(command.trim_end(), &self.run_history);
+                self.filtered_indices = (0..self.run_entries.len()).collect();
+            }
+            None => {
+                self.run_entries.clear();
+                self.entries.truncate(self.fixed_entries);
+                self.filtered_indices = filter_entries(&self.entries, &self.query, &self.recent);
+                if let Some(entry) = forward_entry(&self.query) {
+                    self.filtered_indices.insert(0, self.entries.len());
+                    self.entries.push(entry);
+                }
+            }
+        }
         self.selected_index = 0;
     }
 
-    fn move_up(&mut self, cx: &mut Context<Self>) {
-        if !self.filtered_indices.is_empty() {
-            if self.selected_index == 0 {
-                self.selected_index = self.filtered_indices.len() - 1;
-            } else {
-                self.selected_index -= 1;
-            }
+    /// In run mode, complete the path at the end of the command line, or
+    /// take the selected past command line to edit.
+    fn complete(&mut self, cx: &mut Context<Self>) {
+        let Some(command) = self.command_line() else {
+            return;
+        };
+        let completed = complete_path(command, self.working_dir.as_deref()).or_else(|| {
+            let entry = self
+                .run_entries
+                .get(*self.filtered_indices.get(self.selected_index)?)?;
+            let past = entry.action.as_any().downcast_ref::<RunCommand>()?;
+            (past.command != command).then(|| past.command.clone())
+        });
+        if let Some(completed) = completed {
+            self.query = format!("{}{}", RUN_PREFIX, completed);
+            self.update_filter();
             cx.notify();
         }
     }
 
+    fn move_up(&mut self, cx: &mut Context<Self>) {
+        self.selected_index = picker::select_prev(self.selected_index, self.filtered_indices.len());
+        cx.notify();
+    }
+
     fn move_down(&mut self, cx: &mut Context<Self>) {
-        if !self.filtered_indices.is_empty() {
-            self.selected_index = (self.selected_index + 1) % self.filtered_indices.len();
-            cx.notify();
-        }
+        self.selected_index = picker::select_next(self.selected_index, self.filtered_indices.len());
+        cx.notify();
     }
 
     fn confirm(&mut self, cx: &mut Context<Self>) {
-        let action = self
+        let entry = self
             .filtered_indices
             .get(self.selected_index)
-            .and_then(|&idx| self.entries.get(idx))
-            .map(|entry| entry.action.boxed_clone());
+            .and_then(|&idx| self.shown_entries().get(idx));
+        let action = entry.map(|entry| entry.action.boxed_clone());
+        let run = action
+            .as_ref()
+            .and_then(|action| action.as_any().downcast_ref::<RunCommand>());
+        match (run, entry) {
+            (Some(run), _) if self.command_line().is_some() => {
+                settings::history::push_history(&mut self.run_history, &run.command);
+                settings::history::save_history(RUN_HISTORY, &self.run_history);
+            }
+            (_, Some(entry)) => {
+                let label = entry.label.to_string();
+                settings::history::push_history(&mut self.recent, &label);
+                settings::history::save_history(HISTORY, &self.recent);
+            }
+            _ => {}
+        }
 
         cx.emit(CommandPaletteDismiss { action });
     }
@@ -288,171 +430,88 @@ impl Render for CommandPalette {
         let colors = theme::terminal_colors(cx);
         let foreground = colors.foreground;
         let muted = colors.muted;
-        let border_color = colors.border;
 
-        let query_display: SharedString = if self.query.is_empty() {
-            "Type a command...".into()
+        let max_visible = 12;
+
+        let header = picker::query_line(&colors)
+            .child(div().text_sm().text_color(muted).mr(px(8.0)).child(">"))
+            .child(picker::query_text(
+                &self.query,
+                "Type a command, or > to run a command line...",
+                &colors,
+            ));
+        let rows: Vec<_> = self
+            .filtered_indices
+            .iter()
+            .enumerate()
+            .map(|(visible_idx, &entry_idx)| {
+                let is_selected = visible_idx == self.selected_index;
+                let entry = &self.shown_entries()[entry_idx];
+                let label = entry.label.clone();
+                let shortcut = entry.shortcut.clone();
+                let has_shortcut = !shortcut.is_empty();
+
+                let row = div()
+                    .id(ElementId::Name(format!("cmd-{}", entry_idx).into()))
+                    .h(px(32.0))
+                    .w_full()
+                    .px(px(12.0))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .cursor_pointer();
+                picker::highlight_row(row, is_selected, &colors)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.selected_index = visible_idx;
+                        this.confirm(cx);
+                    }))
+                    .child(div().flex_1().text_sm().text_color(foreground).child(label))
+                    .when(has_shortcut, |d| {
+                        d.child(
+                            div()
+                                .flex_shrink_0()
+                                .text_size(px(11.0))
+                                .text_color(muted)
+                                .child(shortcut),
+                        )
+                    })
+            })
+            .collect();
+        let empty_message = if self.command_line().is_some() {
+            "Type a command line to run"
         } else {
-            self.query.clone().into()
+            "No matching commands"
         };
-        let query_is_empty = self.query.is_empty();
-
-        let max_visible = 12;
+        let panel = picker::panel(520.0, &colors)
+            .child(header)
+            .child(
+                div()
+                    .id("palette-results")
+                    .max_h(px(max_visible as f32 * 32.0))
+                    .overflow_y_scroll()
+                    .scrollbar_width(px(4.0))
+                    .children(rows),
+            )
+            .when(self.filtered_indices.is_empty(), |d| {
+                d.child(picker::empty_message(empty_message, &colors))
+            });
 
-        div()
+        picker::overlay("palette-backdrop", panel, Self::dismiss, cx)
             .track_focus(&self.focus_handle)
             .on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
-                let key = event.keystroke.key.as_str();
-                let mods = &event.keystroke.modifiers;
-
-                match key {
-                    "escape" => this.dismiss(cx),
-                    "up" => this.move_up(cx),
-                    "down" => this.move_down(cx),
-                    "enter" => this.confirm(cx),
-                    "backspace" => {
-                        this.query.pop();
-                        this.update_filter();
-                        cx.notify();
-                    }
-                    "space" => {
-                        this.query.push(' ');
+                match picker::picker_key(&mut this.query, &event.keystroke) {
+                    PickerKey::Dismiss => this.dismiss(cx),
+                    PickerKey::Up => this.move_up(cx),
+                    PickerKey::Down => this.move_down(cx),
+                    PickerKey::Confirm => this.confirm(cx),
+                    PickerKey::Tab { .. } => this.complete(cx),
+                    PickerKey::Edited => {
                         this.update_filter();
                         cx.notify();
                     }
-                    "tab" => {}
-                    _ => {
-                        if key.len() == 1 && !mods.control && !mods.alt && !mods.platform {
-                            let ch = if mods.shift {
-                                key.to_uppercase()
-                            } else {
-                                key.to_string()
-                            };
-                            this.query.push_str(&ch);
-                            this.update_filter();
-                            cx.notify();
-                        }
-                    }
+                    PickerKey::Ignored => {}
                 }
             }))
-            .absolute()
-            .inset_0()
-            .flex()
-            .flex_col()
-            .items_center()
-            .child(
-                div()
-                    .id("palette-backdrop")
-                    .absolute()
-                    .inset_0()
-                    .bg(hsla(0.0, 0.0, 0.0, 0.4))
-                    .on_click(cx.listener(|this, _, _, cx| {
-                        this.dismiss(cx);
-                    })),
-            )
-            .child(
-                div()
-                    .mt(px(60.0))
-                    .w(px(520.0))
-                    .bg(hsla(0.0, 0.0, 0.10, 1.0))
-                    .border_1()
-                    .border_color(hsla(0.0, 0.0, 0.25, 1.0))
-                    .rounded(px(8.0))
-                    .shadow_lg()
-                    .overflow_hidden()
-                    .flex()
-                    .flex_col()
-                    .child(
-                        div()
-                            .h(px(40.0))
-                            .w_full()
-                            .px(px(12.0))
-                            .flex()
-                            .items_center()
-                            .border_b_1()
-                            .border_color(border_color)
-                            .child(
-                                div()
-                                    .text_sm()
-                                    .text_color(hsla(0.0, 0.0, 0.4, 1.0))
-                                    .mr(px(8.0))
-                                    .child(">"),
-                            )
-                            .child(
-                                div()
-                                    .flex_1()
-                                    .text_sm()
-                                    .when(query_is_empty, |d| {
-                                        d.text_color(hsla(0.0, 0.0, 0.4, 1.0))
-                                    })
-                                    .when(!query_is_empty, |d| d.text_color(foreground))
-                                    .child(query_display),
-                            ),
-                    )
-                    .child(
-                        div()
-                            .id("palette-results")
-                            .max_h(px(max_visible as f32 * 32.0))
-                            .overflow_y_scroll()
-                            .scrollbar_width(px(4.0))
-                            .children(self.filtered_indices.iter().enumerate().map(
-                                |(visible_idx, &entry_idx)| {
-                                    let is_selected = visible_idx == self.selected_index;
-                                    let entry = &self.entries[entry_idx];
-                                    let label: SharedString = entry.label.into();
-                                    let shortcut: SharedString = entry.shortcut.into();
-                                    let has_shortcut = !entry.shortcut.is_empty();
-
-                                    div()
-                                        .id(ElementId::Name(format!("cmd-{}", entry_idx).into()))
-                                        .h(px(32.0))
-                                        .w_full()
-                                        .px(px(12.0))
-                                        .flex()
-                                        .items_center()
-                                        .justify_between()
-                                        .cursor_pointer()
-                                        .when(is_selected, |d| d.bg(hsla(0.0, 0.0, 0.18, 1.0)))
-                                        .when(!is_selected, |d| {
-                                            d.hover(|d| d.bg(hsla(0.0, 0.0, 0.14, 1.0)))
-                                        })
-                                        .on_click(cx.listener(move |this, _, _, cx| {
-                                            this.selected_index = visible_idx;
-                                            this.confirm(cx);
-                                        }))
-                                        .child(
-                                            div()
-                                                .flex_1()
-                                                .text_sm()
-                                                .text_color(foreground)
-                                                .child(label),
-                                        )
-                                        .when(has_shortcut, |d| {
-                                            d.child(
-                                                div()
-                                                    .flex_shrink_0()
-                                                    .text_size(px(11.0))
-                                                    .text_color(muted)
-                                                    .child(shortcut),
-                                            )
-                                        })
-                                },
-                            )),
-                    )
-                    .when(self.filtered_indices.is_empty(), |d| {
-                        d.child(
-                            div()
-                                .h(px(40.0))
-                                .w_full()
-                                .flex()
-                                .items_center()
-                                .justify_center()
-                                .text_sm()
-                                .text_color(muted)
-                                .child("No matching commands"),
-                        )
-                    }),
-            )
     }
 }
 
@@ -523,10 +582,92 @@ mod tests {
         assert_eq!(fuzzy_score("", "New Tab"), Some(0));
     }
 
+    #[test]
+    fn test_recent_commands_rank_first() {
+        let entries = build_command_entries();
+        let label = |idx: usize| entries[idx].label.to_string();
+        let recent = vec!["Quit".to_string(), "Split Vertical".to_string()];
+
+        let all = filter_entries(&entries, "", &recent);
+        assert_eq!(all.len(), entries.len());
+        assert_eq!(label(all[0]), "Quit");
+        assert_eq!(label(all[1]), "Split Vertical");
+
+        // Among equally good matches the recent one wins
+        let tabs = filter_entries(&entries, "tab", &["Close Tab".to_string()]);
+        assert_eq!(label(tabs[0]), "Close Tab");
+    }
+
+    #[test]
+    fn test_run_entries() {
+        let history = vec![
+            "cargo test".to_string(),
+            "ls -la".to_string(),
+            "cargo build".to_string(),
+        ];
+        let entries = run_entries("cargo", &history);
+        let labels: Vec<&str> = entries.iter().map(|e| e.label.as_ref()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Run in New Tab: cargo",
+                "Run in Split: cargo",
+                "Run in Floating Pane: cargo",
+                "cargo test",
+                "cargo build",
+            ]
+        );
+        assert!(entries[1].action.partial_eq(&RunCommand {
+            command: "cargo".to_string(),
+            target: RunTarget::Split,
+        }));
+
+        // Nothing typed yet: just the history
+        assert_eq!(run_entries("", &history).len(), 3);
+    }
+
+    #[test]
+    fn test_forward_entry() {
+        let entry = forward_entry("L 8080:localhost:80").expect("a local forward");
+        assert_eq!(
+            entry.label.as_ref(),
+            "Add Port Forward: L 8080:localhost:80"
+        );
+        assert!(entry.action.partial_eq(&AddPortForward {
+            forward: "L 8080:localhost:80".to_string(),
+        }));
+        assert!(forward_entry("-D 1080  me@bastion").is_some());
+        assert!(forward_entry("L 8080").is_none());
+        assert!(forward_entry("New Tab").is_none());
+        assert!(forward_entry("").is_none());
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("logs")).unwrap();
+        std::fs::write(dir.path().join("notes-a.txt"), "").unwrap();
+        std::fs::write(dir.path().join("notes-b.txt"), "").unwrap();
+        std::fs::write(dir.path().join(".hidden"), "").unwrap();
+        let cwd = Some(dir.path());
+
+        assert_eq!(complete_path("cd lo", cwd).as_deref(), Some("cd logs/"));
+        assert_eq!(complete_path("cat no", cwd).as_deref(), Some("cat notes-"));
+        assert_eq!(complete_path("cat notes-", cwd), None);
+        assert_eq!(complete_path("cat x", cwd), None);
+        assert_eq!(complete_path("cat .h", cwd).as_deref(), Some("cat .hidden"));
+
+        let absolute = format!("ls {}/lo", dir.path().display());
+        assert_eq!(
+            complete_path(&absolute, None),
+            Some(format!("ls {}/logs/", dir.path().display()))
+        );
+    }
+
     #[test]
     fn test_command_entries_complete() {
         let entries = build_command_entries();
-        let labels: Vec<&str> = entries.iter().map(|e| e.label).collect();
+        let labels: Vec<&str> = entries.iter().map(|e| e.label.as_ref()).collect();
 
         assert!(labels.contains(&"New Tab"));
         assert!(labels.contains(&"Close Tab"));
@@ -0,0 +1,213 @@
+//! Config file problems: the check behind `humanssh --check-config`, and the
+//! banner that lists them after a live reload.
+
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, px, App, FontWeight, Global, IntoElement, KeyBindingContextPredicate, Keystroke,
+    ParentElement, Styled,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::Sizable;
+use settings::validate::{check_config_file, Diagnostic, Environment, Severity};
+use std::path::PathBuf;
+use theme::terminal_colors;
+
+/// Lines of the banner before the rest are summarised.
+const BANNER_LINES: usize = 4;
+
+/// Problems from the last config check, with the file each is in, until the
+/// banner is dismissed.
+#[derive(Default)]
+pub struct ConfigDiagnostics {
+    pub diagnostics: Vec<(PathBuf, Diagnostic)>,
+}
+
+impl Global for ConfigDiagnostics {}
+
+/// Check every config file against the loaded themes, installed fonts and
+/// the keymap: everything `load_config` would quietly ignore or fall back on.
+pub fn check_config(cx: &App) -> Vec<(PathBuf, Diagnostic)> {
+    let mut themes: Vec<String> = theme::theme_names(cx)
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+    if themes.is_empty() {
+        themes = theme::theme_names_on_disk();
+    }
+    check_config_with(&themes, &cx.text_system().all_font_names())
+}
+
+/// Like [`check_config`], before any app starts (`humanssh --check-config`).
+/// Themes are read from disk, and fonts from the system's font directories.
+pub fn check_config_without_app() -> Vec<(PathBuf, Diagnostic)> {
+    check_config_with(&theme::theme_names_on_disk(), &installed_font_families())
+}
+
+/// Families of the fonts installed on this system, found in the same font
+/// directories and fontconfig setup the app's text system reads.
+fn installed_font_families() -> Vec<String> {
+    let mut database = fontdb::Database::new();
+    database.load_system_fonts();
+    let mut families: Vec<String> = database
+        .faces()
+        .flat_map(|face| face.families.iter().map(|(family, _)| family.clone()))
+        .collect();
+    families.sort_unstable();
+    families.dedup();
+    families
+}
+
+/// Check every config file against `themes` and `fonts`; an empty list
+/// skips that check.
+fn check_config_with(themes: &[String], fonts: &[String]) -> Vec<(PathBuf, Diagnostic)> {
+    let layers = settings::layers::load_layers();
+    let config = layers.config().ok();
+    let profiles: Option<Vec<String>> = config
+        .as_ref()
+        .map(|config| config.profiles.iter().map(|p| p.name.clone()).collect());
+
+    let check_action = |action: &str| {
+        actions::parse_action(action)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    };
+    let check_chord = |chord: &str| {
+        Keystroke::parse(chord)
+            .map(|_| ())
+            .map_err(|_| format!("'{}' is not a key chord", chord))
+    };
+    let check_context = |context: &str| {
+        KeyBindingContextPredicate::parse(context)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    };
+
+    let config = config.unwrap_or_default();
+    let check_sequence = |keys: &str, context: Option<&str>| {
+        let shadowed_by = crate::key_sequence::shadowing_bindings(keys, context, &config);
+        if shadowed_by.is_empty() {
+            return Ok(());
+        }
+        Err(format!(
+            "sequence '{}' can't start: its first chord is bound to {}",
+            keys,
+            shadowed_by.join(", ")
+        ))
+    };
+
+    let env = Environment {
+        themes: (!themes.is_empty()).then_some(themes),
+        fonts: (!fonts.is_empty()).then_some(fonts),
+        check_action: Some(&check_action),
+        check_chord: Some(&check_chord),
+        check_context: Some(&check_context),
+        check_sequence: Some(&check_sequence),
+        profiles: profiles.as_deref(),
+    };
+    layers
+        .files
+        .iter()
+        .flat_map(|path| {
+            check_config_file(path, &env)
+                .into_iter()
+                .map(|diagnostic| (path.clone(), diagnostic))
+        })
+        .collect()
+}
+
+/// Re-check the config file and show what's wrong with it in every window.
+pub fn refresh_config_diagnostics(cx: &mut App) {
+    let diagnostics = check_config(cx);
+    for (path, diagnostic) in &diagnostics {
+        tracing::warn!("{}:{}", path.display(), diagnostic);
+    }
+    cx.set_global(ConfigDiagnostics { diagnostics });
+    cx.refresh_windows();
+}
+
+fn dismiss_config_diagnostics(cx: &mut App) {
+    cx.set_global(ConfigDiagnostics::default());
+    cx.refresh_windows();
+}
+
+/// The banner listing config problems, if there are any to show.
+pub(crate) fn render_config_banner(cx: &App) -> Option<impl IntoElement> {
+    let diagnostics = &cx.try_global::<ConfigDiagnostics>()?.diagnostics;
+    if diagnostics.is_empty() {
+        return None;
+    }
+
+    let colors = terminal_colors(cx);
+    let errors = diagnostics
+        .iter()
+        .filter(|(_, diagnostic)| diagnostic.severity == Severity::Error)
+        .count();
+    let accent = if errors > 0 {
+        colors.red
+    } else {
+        colors.yellow
+    };
+    let title = match diagnostics.len() {
+        1 => "The config has a problem".to_string(),
+        count => format!("The config has {} problems", count),
+    };
+    let more = diagnostics.len().saturating_sub(BANNER_LINES);
+
+    Some(
+        div()
+            .w_full()
+            .flex()
+            .items_start()
+            .gap_3()
+            .px(px(12.0))
+            .py(px(8.0))
+            .bg(colors.title_bar)
+            .border_b_1()
+            .border_color(accent)
+            .text_sm()
+            .child(
+                div()
+                    .flex_1()
+                    .flex()
+                    .flex_col()
+                    .gap(px(2.0))
+                    .child(
+                        div()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(accent)
+                            .child(title),
+                    )
+                    .children(
+                        diagnostics
+                            .iter()
+                            .take(BANNER_LINES)
+                            .map(|(path, diagnostic)| {
+                                let file = path.file_name().unwrap_or_default().to_string_lossy();
+                                div()
+                                    .text_color(colors.foreground)
+                                    .child(format!("{}:{}", file, diagnostic))
+                            }),
+                    )
+                    .when(more > 0, |d| {
+                        d.child(div().text_color(colors.muted).child(format!(
+                            "and {} more; run `humanssh --check-config` for the full list",
+                            more
+                        )))
+                    }),
+            )
+            .child(
+                Button::new("config-banner-open")
+                    .xsmall()
+                    .ghost()
+                    .label("Open Config")
+                    .on_click(|_, _window, _cx| crate::settings_opener::open_config_file()),
+            )
+            .child(
+                Button::new("config-banner-dismiss")
+                    .xsmall()
+                    .ghost()
+                    .label("Dismiss")
+                    .on_click(|_, _window, cx| dismiss_config_diagnostics(cx)),
+            ),
+    )
+}
@@ -0,0 +1,853 @@
+//! File browser pane — lists a local or SFTP directory next to a terminal.
+//!
+//! The browser follows the working directory of the terminal it was opened
+//! from: OSC 7 reports for SSH sessions (the local process table knows nothing
+//! about the remote shell), or the OS-level cwd for local shells. Remote
+//! operations and transfers go through `sftp`; transfer progress feeds the
+//! tab's `ProgressState` badge like OSC 9;4 does for terminals.
+
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, hsla, px, App, AppContext, ClickEvent, Context, ElementId, Entity, ExternalPaths,
+    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyDownEvent, ParentElement, Render,
+    SharedString, StatefulInteractiveElement, Styled, Subscription, Task, WeakEntity, Window,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::Sizable;
+use remote::sftp::{self, FileEntry};
+use remote::{SftpClient, Transfer, TransferDirection, TransferStatus};
+use std::path::{Path, PathBuf};
+use terminal::file_transfer::unique_path;
+use terminal::ProgressState;
+use terminal_view::TerminalPane;
+use theme::terminal_colors;
+
+/// How often the browser checks the linked terminal's directory and transfer progress.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Where the browsed files live.
+#[derive(Clone)]
+pub enum BrowserSource {
+    Local,
+    Remote(SftpClient),
+}
+
+/// An in-progress rename or new-folder prompt.
+struct EditPrompt {
+    kind: EditKind,
+    input: Entity<InputState>,
+    _subscription: Subscription,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum EditKind {
+    /// Rename the entry with this name
+    Rename(String),
+    Mkdir,
+}
+
+/// A file browser pane.
+pub struct FileBrowserPane {
+    focus_handle: FocusHandle,
+    source: BrowserSource,
+    /// Terminal whose working directory this browser follows
+    linked: Option<WeakEntity<TerminalPane>>,
+    /// Whether to follow the linked terminal's directory
+    follow: bool,
+    /// Last directory seen from the linked terminal (change detection)
+    last_linked_dir: Option<String>,
+    /// Directory being shown (absolute path)
+    path: String,
+    entries: Vec<FileEntry>,
+    selected: Option<usize>,
+    loading: bool,
+    error: Option<SharedString>,
+    edit: Option<EditPrompt>,
+    /// Entry name awaiting delete confirmation
+    confirm_delete: Option<String>,
+    transfers: Vec<Transfer>,
+    _poll: Task<()>,
+}
+
+impl FileBrowserPane {
+    /// Create a browser, following `linked` if given.
+    pub fn new(
+        source: BrowserSource,
+        linked: Option<&Entity<TerminalPane>>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let poll = cx.spawn(async move |this, cx| loop {
+            cx.background_executor().timer(POLL_INTERVAL).await;
+            if this.update(cx, |this, cx| this.poll(cx)).is_err() {
+                break;
+            }
+        });
+
+        let mut browser = Self {
+            focus_handle: cx.focus_handle(),
+            source,
+            linked: linked.map(|terminal| terminal.downgrade()),
+            follow: linked.is_some(),
+            last_linked_dir: None,
+            path: String::new(),
+            entries: Vec::new(),
+            selected: None,
+            loading: false,
+            error: None,
+            edit: None,
+            confirm_delete: None,
+            transfers: Vec::new(),
+            _poll: poll,
+        };
+
+        match browser.linked_directory(cx) {
+            Some(dir) => {
+                browser.last_linked_dir = Some(dir.clone());
+                browser.navigate(dir, cx);
+            }
+            None => browser.navigate_home(cx),
+        }
+        browser
+    }
+
+    /// Directory currently shown.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Directory shown, when browsing the local filesystem.
+    pub fn local_directory(&self) -> Option<PathBuf> {
+        match self.source {
+            BrowserSource::Local if !self.path.is_empty() => Some(PathBuf::from(&self.path)),
+            _ => None,
+        }
+    }
+
+    /// Host browsed over SFTP, if remote.
+    pub fn remote_host(&self) -> Option<String> {
+        match &self.source {
+            BrowserSource::Local => None,
+            BrowserSource::Remote(client) => Some(client.target().host.clone()),
+        }
+    }
+
+    /// Display title for the tab bar.
+    pub fn title(&self) -> SharedString {
+        match &self.source {
+            BrowserSource::Local => format!("Files: {}", self.path).into(),
+            BrowserSource::Remote(client) => {
+                format!("Files: {}:{}", client.target().display_name(), self.path).into()
+            }
+        }
+    }
+
+    /// Whether any transfer is still running.
+    pub fn has_active_transfers(&self) -> bool {
+        self.transfers
+            .iter()
+            .any(|t| matches!(t.status(), TransferStatus::Running(_)))
+    }
+
+    /// Transfer progress for the tab badge: the first running transfer, else
+    /// an error if the most recent transfer failed.
+    pub fn progress(&self) -> ProgressState {
+        for transfer in &self.transfers {
+            match transfer.status() {
+                TransferStatus::Running(Some(percent)) => return ProgressState::Normal(percent),
+                TransferStatus::Running(None) => return ProgressState::Indeterminate,
+                _ => {}
+            }
+        }
+        match self.transfers.last().map(Transfer::status) {
+            Some(TransferStatus::Failed(_)) => ProgressState::Error(100),
+            _ => ProgressState::Hidden,
+        }
+    }
+
+    /// Directory reported by the linked terminal, if it's still open.
+    fn linked_directory(&self, cx: &App) -> Option<String> {
+        let terminal = self.linked.as_ref()?.upgrade()?;
+        let terminal = terminal.read(cx);
+        match self.source {
+            BrowserSource::Remote(_) => terminal.reported_directory(),
+            BrowserSource::Local => terminal
+                .current_working_directory()
+                .map(|p| p.display().to_string()),
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Context<Self>) {
+        if self.follow {
+            let dir = self.linked_directory(cx);
+            if dir.is_some() && dir != self.last_linked_dir {
+                self.last_linked_dir = dir.clone();
+                if let Some(dir) = dir.filter(|dir| *dir != self.path) {
+                    self.navigate(dir, cx);
+                }
+            }
+        }
+        if !self.transfers.is_empty() {
+            cx.notify();
+        }
+    }
+
+    fn navigate_home(&mut self, cx: &mut Context<Self>) {
+        match &self.source {
+            BrowserSource::Local => {
+                let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+                self.navigate(home.display().to_string(), cx);
+            }
+            BrowserSource::Remote(client) => {
+                let client = client.clone();
+                self.loading = true;
+                let home = cx.background_spawn(async move { client.home_dir() });
+                cx.spawn(async move |this, cx| {
+                    let home = home.await;
+                    this.update(cx, |this, cx| match home {
+                        Ok(home) => this.navigate(home, cx),
+                        Err(error) => {
+                            this.navigate("/".to_string(), cx);
+                            this.with_error(error);
+                        }
+                    })
+                    .ok();
+                })
+                .detach();
+            }
+        }
+    }
+
+    /// Show `path`, listing it in the background.
+    pub fn navigate(&mut self, path: String, cx: &mut Context<Self>) {
+        self.loading = true;
+        self.error = None;
+        self.confirm_delete = None;
+        let source = self.source.clone();
+        let list_path = path.clone();
+        let listing = cx.background_spawn(async move {
+            match source {
+                BrowserSource::Local => sftp::list_local(Path::new(&list_path)),
+                BrowserSource::Remote(client) => client.list(&list_path),
+            }
+        });
+
+        cx.spawn(async move |this, cx| {
+            let listing = listing.await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match listing {
+                    Ok(entries) => {
+                        this.path = path;
+                        this.entries = entries;
+                        this.selected = None;
+                    }
+                    Err(error) => this.error = Some(format!("{:#}", error).into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn with_error(&mut self, error: anyhow::Error) {
+        self.error = Some(format!("{:#}", error).into());
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.navigate(self.path.clone(), cx);
+    }
+
+    fn go_up(&mut self, cx: &mut Context<Self>) {
+        self.follow = false;
+        let parent = match self.source {
+            BrowserSource::Local => Path::new(&self.path)
+                .parent()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| self.path.clone()),
+            BrowserSource::Remote(_) => sftp::parent_remote(&self.path),
+        };
+        self.navigate(parent, cx);
+    }
+
+    fn entry_path(&self, name: &str) -> String {
+        match self.source {
+            BrowserSource::Local => Path::new(&self.path).join(name).display().to_string(),
+            BrowserSource::Remote(_) => sftp::join_remote(&self.path, name),
+        }
+    }
+
+    fn open_entry(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(index) else {
+            return;
+        };
+        // Symlinks may point at directories; let the listing decide
+        if entry.is_dir || entry.is_symlink {
+            self.follow = false;
+            let path = self.entry_path(&entry.name);
+            self.navigate(path, cx);
+        }
+    }
+
+    /// Run a file operation in the background, then refresh the listing.
+    fn run_operation(
+        &mut self,
+        operation: impl FnOnce(&BrowserSource) -> anyhow::Result<()> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let source = self.source.clone();
+        let task = cx.background_spawn(async move { operation(&source) });
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |this, cx| {
+                this.refresh(cx);
+                if let Err(error) = result {
+                    this.with_error(error);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn start_edit(&mut self, kind: EditKind, window: &mut Window, cx: &mut Context<Self>) {
+        let initial = match &kind {
+            EditKind::Rename(name) => name.clone(),
+            EditKind::Mkdir => String::new(),
+        };
+        let input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Name — Enter to apply, Escape to cancel")
+                .default_value(initial)
+        });
+        let subscription =
+            cx.subscribe_in(&input, window, |this, _, event: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    this.apply_edit(window, cx);
+                }
+            });
+        input.read(cx).focus_handle(cx).focus(window);
+        self.edit = Some(EditPrompt {
+            kind,
+            input,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn cancel_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.edit = None;
+        self.focus_handle.focus(window);
+        cx.notify();
+    }
+
+    fn apply_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(edit) = self.edit.take() else {
+            return;
+        };
+        self.focus_handle.focus(window);
+        let name = edit.input.read(cx).value().trim().to_string();
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            self.error = Some(format!("Invalid name '{}'", name).into());
+            cx.notify();
+            return;
+        }
+
+        let target = self.entry_path(&name);
+        match edit.kind {
+            EditKind::Rename(old) if old == name => cx.notify(),
+            EditKind::Rename(old) => {
+                let from = self.entry_path(&old);
+                self.run_operation(
+                    move |source| match source {
+                        BrowserSource::Local => Ok(std::fs::rename(&from, &target)?),
+                        BrowserSource::Remote(client) => client.rename(&from, &target),
+                    },
+                    cx,
+                );
+            }
+            EditKind::Mkdir => {
+                self.run_operation(
+                    move |source| match source {
+                        BrowserSource::Local => Ok(std::fs::create_dir(&target)?),
+                        BrowserSource::Remote(client) => client.mkdir(&target),
+                    },
+                    cx,
+                );
+            }
+        }
+    }
+
+    fn delete(&mut self, name: String, cx: &mut Context<Self>) {
+        self.confirm_delete = None;
+        let Some(entry) = self.entries.iter().find(|e| e.name == name) else {
+            return;
+        };
+        let is_dir = entry.is_dir;
+        let path = self.entry_path(&name);
+        self.run_operation(
+            move |source| match source {
+                BrowserSource::Local if is_dir => Ok(std::fs::remove_dir(&path)?),
+                BrowserSource::Local => Ok(std::fs::remove_file(&path)?),
+                BrowserSource::Remote(client) => client.remove(&path, is_dir),
+            },
+            cx,
+        );
+    }
+
+    /// Download a remote entry to the user's downloads folder, next to
+    /// anything already there with the same name.
+    fn download(&mut self, name: String, cx: &mut Context<Self>) {
+        let BrowserSource::Remote(client) = &self.source else {
+            return;
+        };
+        let Some(entry) = self.entries.iter().find(|e| e.name == name) else {
+            return;
+        };
+        let Some(downloads) = dirs::download_dir().or_else(dirs::home_dir) else {
+            self.error = Some("Could not determine the downloads folder".into());
+            cx.notify();
+            return;
+        };
+        let result = Transfer::start(
+            client.target(),
+            TransferDirection::Download,
+            unique_path(&downloads, &name),
+            self.entry_path(&name),
+            entry.is_dir,
+        );
+        self.add_transfer(result, cx);
+    }
+
+    /// Upload dropped files and folders into the current directory (or copy
+    /// them, when local).
+    fn upload(&mut self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        match &self.source {
+            BrowserSource::Remote(client) => {
+                let target = client.target().clone();
+                for local in paths {
+                    let Some(name) = local.file_name().map(|n| n.to_string_lossy().into_owned())
+                    else {
+                        continue;
+                    };
+                    let recursive = local.is_dir();
+                    let result = Transfer::start(
+                        &target,
+                        TransferDirection::Upload,
+                        local,
+                        self.entry_path(&name),
+                        recursive,
+                    );
+                    self.add_transfer(result, cx);
+                }
+            }
+            BrowserSource::Local => {
+                let dir = PathBuf::from(&self.path);
+                self.run_operation(
+                    move |_| {
+                        for local in &paths {
+                            let Some(name) = local.file_name() else {
+                                continue;
+                            };
+                            if dir.starts_with(local) {
+                                anyhow::bail!("Can't copy {} into itself", local.display());
+                            }
+                            copy_recursive(local, &unique_path(&dir, &name.to_string_lossy()))?;
+                        }
+                        Ok(())
+                    },
+                    cx,
+                );
+            }
+        }
+    }
+
+    fn add_transfer(&mut self, result: anyhow::Result<Transfer>, cx: &mut Context<Self>) {
+        // Finished transfers make way for new ones
+        self.transfers
+            .retain(|t| matches!(t.status(), TransferStatus::Running(_)));
+        match result {
+            Ok(transfer) => self.transfers.push(transfer),
+            Err(error) => self.with_error(error),
+        }
+        cx.notify();
+    }
+
+    fn dismiss_transfer(&mut self, index: usize, cx: &mut Context<Self>) {
+        if let Some(transfer) = self.transfers.get(index) {
+            transfer.cancel();
+            let finished_upload = transfer.direction() == TransferDirection::Upload;
+            self.transfers.remove(index);
+            if finished_upload {
+                self.refresh(cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn handle_key(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if self.edit.is_some() {
+            if event.keystroke.key == "escape" {
+                self.cancel_edit(window, cx);
+            }
+            return;
+        }
+        let count = self.entries.len();
+        match event.keystroke.key.as_str() {
+            "down" if count > 0 => {
+                self.selected = Some(self.selected.map_or(0, |i| (i + 1).min(count - 1)));
+            }
+            "up" if count > 0 => {
+                self.selected = Some(self.selected.map_or(0, |i| i.saturating_sub(1)));
+            }
+            "enter" => {
+                if let Some(index) = self.selected {
+                    self.open_entry(index, cx);
+                }
+            }
+            "backspace" => self.go_up(cx),
+            "f2" => {
+                if let Some(entry) = self.selected.and_then(|i| self.entries.get(i)) {
+                    let kind = EditKind::Rename(entry.name.clone());
+                    self.start_edit(kind, window, cx);
+                }
+            }
+            "delete" => {
+                self.confirm_delete = self
+                    .selected
+                    .and_then(|i| self.entries.get(i))
+                    .map(|e| e.name.clone());
+            }
+            _ => return,
+        }
+        cx.notify();
+    }
+}
+
+impl Focusable for FileBrowserPane {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        // While a name prompt is open it owns focus, so the workspace's
+        // focus-the-active-pane pass doesn't steal it back every frame
+        match &self.edit {
+            Some(edit) => edit.input.read(cx).focus_handle(cx),
+            None => self.focus_handle.clone(),
+        }
+    }
+}
+
+/// Copy a file, or a folder and everything in it, to `to`.
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(from)?.is_dir() {
+        std::fs::create_dir(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Human-readable file size.
+fn format_size(entry: &FileEntry) -> String {
+    if entry.is_dir {
+        String::new()
+    } else {
+        crate::status_bar::format_bytes(entry.size)
+    }
+}
+
+impl Render for FileBrowserPane {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = terminal_colors(cx);
+        let foreground = colors.foreground;
+        let muted = colors.muted;
+        let border = colors.border;
+        let red = colors.red;
+        let green = colors.green;
+        let selection = colors.selection;
+        let is_remote = matches!(self.source, BrowserSource::Remote(_));
+
+        let header = div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(border)
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .text_sm()
+                    .text_color(foreground)
+                    .child(self.title()),
+            )
+            .when(self.loading, |d| {
+                d.child(div().text_xs().text_color(muted).child("Loading…"))
+            })
+            .when(self.linked.is_some(), |d| {
+                d.child(
+                    Button::new("browser-follow")
+                        .xsmall()
+                        .ghost()
+                        .label(if self.follow { "Following" } else { "Follow" })
+                        .tooltip("Follow the terminal's working directory")
+                        .on_click(cx.listener(|this, _, _window, cx| {
+                            this.follow = !this.follow;
+                            this.last_linked_dir = None;
+                            this.poll(cx);
+                            cx.notify();
+                        })),
+                )
+            })
+            .child(
+                Button::new("browser-up")
+                    .xsmall()
+                    .ghost()
+                    .label("Up")
+                    .on_click(cx.listener(|this, _, _window, cx| this.go_up(cx))),
+            )
+            .child(
+                Button::new("browser-mkdir")
+                    .xsmall()
+                    .ghost()
+                    .label("New Folder")
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.start_edit(EditKind::Mkdir, window, cx);
+                    })),
+            )
+            .child(
+                Button::new("browser-refresh")
+                    .xsmall()
+                    .ghost()
+                    .label("Refresh")
+                    .on_click(cx.listener(|this, _, _window, cx| this.refresh(cx))),
+            );
+
+        let rows = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let is_selected = self.selected == Some(index);
+                let name = entry.name.clone();
+                let suffix = if entry.is_dir {
+                    "/"
+                } else if entry.is_symlink {
+                    "@"
+                } else {
+                    ""
+                };
+                div()
+                    .id(ElementId::Name(format!("browser-entry-{}", index).into()))
+                    .flex()
+                    .items_center()
+                    .gap_3()
+                    .px_2()
+                    .py(px(2.0))
+                    .text_sm()
+                    .cursor_pointer()
+                    .when(is_selected, |d| d.bg(selection))
+                    .hover(|d| d.bg(hsla(0.0, 0.0, 1.0, 0.05)))
+                    .on_click(cx.listener(move |this, event: &ClickEvent, _window, cx| {
+                        this.selected = Some(index);
+                        this.confirm_delete = None;
+                        if event.click_count() >= 2 {
+                            this.open_entry(index, cx);
+                        }
+                        cx.notify();
+                    }))
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_color(foreground)
+                            .child(format!("{}{}", entry.name, suffix)),
+                    )
+                    .child(div().text_xs().text_color(muted).child(format_size(entry)))
+                    .when(!entry.modified.is_empty(), |d| {
+                        d.child(
+                            div()
+                                .w(px(96.0))
+                                .text_xs()
+                                .text_color(muted)
+                                .child(entry.modified.clone()),
+                        )
+                    })
+                    .when(is_selected, |d| {
+                        let rename_name = name.clone();
+                        let delete_name = name.clone();
+                        let download_name = name.clone();
+                        d.when(is_remote, |d| {
+                            d.child(
+                                Button::new("browser-download")
+                                    .xsmall()
+                                    .ghost()
+                                    .label("Download")
+                                    .on_click(cx.listener(move |this, _, _window, cx| {
+                                        this.download(download_name.clone(), cx);
+                                    })),
+                            )
+                        })
+                        .child(
+                            Button::new("browser-rename")
+                                .xsmall()
+                                .ghost()
+                                .label("Rename")
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    let kind = EditKind::Rename(rename_name.clone());
+                                    this.start_edit(kind, window, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("browser-delete")
+                                .xsmall()
+                                .ghost()
+                                .label("Delete")
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.confirm_delete = Some(delete_name.clone());
+                                    cx.notify();
+                                })),
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let transfers = self
+            .transfers
+            .iter()
+            .enumerate()
+            .map(|(index, transfer)| {
+                let arrow = match transfer.direction() {
+                    TransferDirection::Upload => "\u{2191}",
+                    TransferDirection::Download => "\u{2193}",
+                };
+                let (status, color, running) = match transfer.status() {
+                    TransferStatus::Running(Some(percent)) => {
+                        (format!("{}%", percent), foreground, true)
+                    }
+                    TransferStatus::Running(None) => ("starting…".to_string(), muted, true),
+                    TransferStatus::Done => ("done".to_string(), green, false),
+                    TransferStatus::Failed(reason) => (reason, red, false),
+                };
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .text_xs()
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_color(foreground)
+                            .child(format!("{} {}", arrow, transfer.name())),
+                    )
+                    .child(div().text_color(color).child(status))
+                    .child(
+                        Button::new(ElementId::Name(format!("transfer-{}", index).into()))
+                            .xsmall()
+                            .ghost()
+                            .label(if running { "Cancel" } else { "\u{00D7}" })
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.dismiss_transfer(index, cx);
+                            })),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("file-browser")
+            .track_focus(&self.focus_handle)
+            .key_context("FileBrowser")
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(colors.background)
+            .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                this.handle_key(event, window, cx);
+            }))
+            .drag_over::<ExternalPaths>(|style, _, _, _| style.bg(hsla(0.6, 0.5, 0.3, 0.15)))
+            .on_drop(cx.listener(|this, paths: &ExternalPaths, _window, cx| {
+                this.upload(paths.paths().to_vec(), cx);
+            }))
+            .child(header)
+            .when_some(self.error.clone(), |d, error| {
+                d.child(div().px_2().py_1().text_xs().text_color(red).child(error))
+            })
+            .when_some(self.edit.as_ref(), |d, edit| {
+                d.child(div().px_2().py_1().child(Input::new(&edit.input)))
+            })
+            .when_some(self.confirm_delete.clone(), |d, name| {
+                d.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .px_2()
+                        .py_1()
+                        .text_sm()
+                        .text_color(red)
+                        .child(div().flex_1().child(format!("Delete '{}'?", name)))
+                        .child(
+                            Button::new("browser-delete-confirm")
+                                .xsmall()
+                                .danger()
+                                .label("Delete")
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.delete(name.clone(), cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("browser-delete-cancel")
+                                .xsmall()
+                                .ghost()
+                                .label("Cancel")
+                                .on_click(cx.listener(|this, _, _window, cx| {
+                                    this.confirm_delete = None;
+                                    cx.notify();
+                                })),
+                        ),
+                )
+            })
+            .child(
+                div()
+                    .id("file-browser-entries")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+            .when(!transfers.is_empty(), |d| {
+                d.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .px_2()
+                        .py_1()
+                        .border_t_1()
+                        .border_color(border)
+                        .children(transfers),
+                )
+            })
+            .when(is_remote && self.transfers.is_empty(), |d| {
+                d.child(
+                    div()
+                        .px_2()
+                        .py_1()
+                        .border_t_1()
+                        .border_color(border)
+                        .text_xs()
+                        .text_color(muted)
+                        .child("Drop files here to upload"),
+                )
+            })
+    }
+}
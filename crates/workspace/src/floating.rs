@@ -0,0 +1,242 @@
+//! Floating terminal — a shell hovering over the tab's split layout.
+//!
+//! Each tab can have one, created on first toggle. Hiding it keeps the shell
+//! running; its position and size are shares of the pane area, so it keeps
+//! its place when the window resizes.
+
+use crate::pane_group::PaneRect;
+use crate::pane_group_view::EmptyDragGhost;
+use crate::workspace_view::Workspace;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, hsla, px, relative, AppContext, Bounds, Context, Entity, InteractiveElement, IntoElement,
+    MouseButton, MouseDownEvent, ParentElement, Pixels, Point, StatefulInteractiveElement, Styled,
+};
+use terminal_view::TerminalPane;
+use theme::terminal_colors;
+
+/// Where a new floating terminal opens.
+const DEFAULT_RECT: PaneRect = PaneRect {
+    x: 0.15,
+    y: 0.1,
+    width: 0.7,
+    height: 0.75,
+};
+
+/// Smallest share of the pane area either side can be resized to.
+const MIN_SIZE: f32 = 0.15;
+
+/// Height of the title bar used to move the pane.
+const TITLE_BAR_HEIGHT: f32 = 22.0;
+
+/// Size of the resize handle in the bottom-right corner.
+const RESIZE_HANDLE_SIZE: f32 = 12.0;
+
+/// A tab's floating terminal.
+pub(crate) struct FloatingPane {
+    pub(crate) terminal: Entity<TerminalPane>,
+    /// Whether it's shown; the shell keeps running while hidden
+    pub(crate) visible: bool,
+    /// Whether it has focus rather than the tab's active pane
+    pub(crate) focused: bool,
+    /// Position and size as shares of the pane area
+    pub(crate) rect: PaneRect,
+    /// Mouse position and rect when the current move or resize began
+    drag_start: Option<(Point<Pixels>, PaneRect)>,
+}
+
+/// Payload for dragging the title bar (move) or corner handle (resize).
+#[derive(Clone)]
+pub(crate) struct FloatingDrag {
+    pub(crate) resize: bool,
+}
+
+impl FloatingPane {
+    /// Wrap a new terminal, shown and focused at the default spot.
+    pub(crate) fn new(terminal: Entity<TerminalPane>) -> Self {
+        Self {
+            terminal,
+            visible: true,
+            focused: true,
+            rect: DEFAULT_RECT,
+            drag_start: None,
+        }
+    }
+
+    /// Remember where a move or resize starts.
+    pub(crate) fn begin_drag(&mut self, position: Point<Pixels>) {
+        self.drag_start = Some((position, self.rect));
+    }
+
+    /// Follow the mouse during a drag over the pane area `bounds`.
+    pub(crate) fn drag_to(
+        &mut self,
+        position: Point<Pixels>,
+        bounds: Bounds<Pixels>,
+        resize: bool,
+    ) {
+        let Some((start, rect)) = self.drag_start else {
+            return;
+        };
+        let width = f32::from(bounds.size.width);
+        let height = f32::from(bounds.size.height);
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        let dx = f32::from(position.x - start.x) / width;
+        let dy = f32::from(position.y - start.y) / height;
+        self.rect = dragged(rect, dx, dy, resize);
+    }
+}
+
+/// `rect` moved (or resized from its bottom-right corner) by `dx`, `dy`,
+/// kept inside the pane area and no smaller than [`MIN_SIZE`]. Minimum size
+/// wins when the pane sits too close to the edge for both.
+fn dragged(rect: PaneRect, dx: f32, dy: f32, resize: bool) -> PaneRect {
+    if resize {
+        PaneRect {
+            width: (rect.width + dx).clamp(MIN_SIZE, (1.0 - rect.x).max(MIN_SIZE)),
+            height: (rect.height + dy).clamp(MIN_SIZE, (1.0 - rect.y).max(MIN_SIZE)),
+            ..rect
+        }
+    } else {
+        PaneRect {
+            x: (rect.x + dx).clamp(0.0, (1.0 - rect.width).max(0.0)),
+            y: (rect.y + dy).clamp(0.0, (1.0 - rect.height).max(0.0)),
+            ..rect
+        }
+    }
+}
+
+/// Render a tab's floating terminal, positioned over the pane area.
+pub(crate) fn render_floating_pane(
+    floating: &FloatingPane,
+    cx: &mut Context<Workspace>,
+) -> impl IntoElement {
+    let colors = terminal_colors(cx);
+    let title = floating
+        .terminal
+        .read(cx)
+        .title()
+        .unwrap_or_else(|| "Floating terminal".into());
+    let border = if floating.focused {
+        colors.accent
+    } else {
+        colors.border
+    };
+    let rect = floating.rect;
+
+    div()
+        .id("floating-pane")
+        .absolute()
+        .left(relative(rect.x))
+        .top(relative(rect.y))
+        .w(relative(rect.width))
+        .h(relative(rect.height))
+        .flex()
+        .flex_col()
+        .bg(colors.background)
+        .border_1()
+        .border_color(border)
+        .rounded(px(6.0))
+        .shadow_lg()
+        .overflow_hidden()
+        // Keep clicks from reaching the panes underneath
+        .occlude()
+        .on_mouse_down(
+            MouseButton::Left,
+            cx.listener(|this, _: &MouseDownEvent, window, cx| {
+                this.focus_floating_pane(window, cx);
+            }),
+        )
+        .child(
+            div()
+                .id("floating-pane-title")
+                .flex_none()
+                .h(px(TITLE_BAR_HEIGHT))
+                .px_2()
+                .flex()
+                .items_center()
+                .bg(colors.title_bar)
+                .text_xs()
+                .text_color(colors.muted)
+                .cursor_move()
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(|this, event: &MouseDownEvent, _window, _cx| {
+                        this.begin_floating_drag(event.position);
+                    }),
+                )
+                .on_drag(FloatingDrag { resize: false }, |_, _, _, cx| {
+                    cx.new(|_| EmptyDragGhost)
+                })
+                .child(title),
+        )
+        .child(
+            div()
+                .flex_1()
+                .relative()
+                .child(floating.terminal.clone())
+                .when(!floating.focused, |d| {
+                    d.child(div().absolute().inset_0().bg(hsla(0.0, 0.0, 0.0, 0.35)))
+                }),
+        )
+        .child(
+            div()
+                .id("floating-pane-resize")
+                .absolute()
+                .right_0()
+                .bottom_0()
+                .size(px(RESIZE_HANDLE_SIZE))
+                .cursor_nwse_resize()
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(|this, event: &MouseDownEvent, _window, _cx| {
+                        this.begin_floating_drag(event.position);
+                    }),
+                )
+                .on_drag(FloatingDrag { resize: true }, |_, _, _, cx| {
+                    cx.new(|_| EmptyDragGhost)
+                }),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drags_stay_inside_the_pane_area() {
+        let moved = dragged(DEFAULT_RECT, 0.1, -0.5, false);
+        assert!((moved.x - 0.25).abs() < 1e-6);
+        assert_eq!(moved.y, 0.0);
+        assert_eq!(moved.width, DEFAULT_RECT.width);
+
+        // Pushed past the right edge, it stops flush against it
+        let moved = dragged(DEFAULT_RECT, 1.0, 0.0, false);
+        assert!((moved.x + moved.width - 1.0).abs() < 1e-6);
+
+        let resized = dragged(DEFAULT_RECT, -1.0, 1.0, true);
+        assert_eq!(resized.width, MIN_SIZE);
+        assert!((resized.y + resized.height - 1.0).abs() < 1e-6);
+        assert_eq!((resized.x, resized.y), (DEFAULT_RECT.x, DEFAULT_RECT.y));
+    }
+
+    #[test]
+    fn resizing_near_the_edge_keeps_the_minimum_size() {
+        let rect = PaneRect {
+            x: 0.9,
+            y: 0.95,
+            width: MIN_SIZE,
+            height: MIN_SIZE,
+        };
+        let resized = dragged(rect, 0.2, -0.2, true);
+        assert_eq!((resized.width, resized.height), (MIN_SIZE, MIN_SIZE));
+
+        let oversized = PaneRect {
+            width: 1.2,
+            ..DEFAULT_RECT
+        };
+        assert_eq!(dragged(oversized, 0.1, 0.0, false).x, 0.0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Multi-stroke key sequences such as `ctrl-a v`: a leader chord, then
+//! further keys, tmux style.
+//!
+//! Sequences are matched by the workspace before keys reach the terminal,
+//! in the key context of the focused element, like single-chord bindings.
+//! While one is pending the status bar shows the keys pressed so far and a
+//! hint lists what can follow. A key that continues no sequence ends it and
+//! goes to the terminal as usual.
+//!
+//! A single-chord binding on a sequence's first chord is matched first, so
+//! the sequence can never start; [`shadowing_bindings`] finds those.
+
+use gpui::{Action, App, Global, KeyBindingContextPredicate, KeyContext, Keystroke, SharedString};
+
+/// A key sequence bound to an action.
+pub struct KeySequence {
+    keys: Vec<Keystroke>,
+    /// The chords as written in the config, for display.
+    chords: Vec<String>,
+    action: Box<dyn Action>,
+    /// What the sequence does, shown in the hint.
+    label: SharedString,
+    /// Key context the sequence applies in; `None` for everywhere.
+    context: Option<KeyBindingContextPredicate>,
+}
+
+impl KeySequence {
+    /// Parse space-separated chords (`ctrl-a v`) bound in `context`. `None`
+    /// if a chord or the context is invalid, or there is only one chord.
+    pub fn parse(
+        keys: &str,
+        action: Box<dyn Action>,
+        label: impl Into<SharedString>,
+        context: Option<&str>,
+    ) -> Option<Self> {
+        let chords: Vec<String> = keys.split_whitespace().map(str::to_string).collect();
+        if chords.len() < 2 {
+            return None;
+        }
+        let keys = chords
+            .iter()
+            .map(|chord| Keystroke::parse(chord).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let context = context
+            .map(KeyBindingContextPredicate::parse)
+            .transpose()
+            .ok()?;
+        Some(Self {
+            keys,
+            chords,
+            action,
+            label: label.into(),
+            context,
+        })
+    }
+
+    /// Whether the keys pressed so far start this sequence, with the focused
+    /// element in `contexts`.
+    fn continues(&self, pressed: &[Keystroke], contexts: &[KeyContext]) -> bool {
+        self.context
+            .as_ref()
+            .is_none_or(|predicate| predicate.depth_of(contexts).is_some())
+            && pressed.len() < self.keys.len()
+            && self
+                .keys
+                .iter()
+                .zip(pressed)
+                .all(|(bound, pressed)| chord_matches(bound, pressed))
+    }
+}
+
+/// Key sequences from the config, shared by every window.
+#[derive(Default)]
+pub struct KeySequences {
+    sequences: Vec<KeySequence>,
+    /// How long a pending sequence waits for its next key, in
+    /// milliseconds; 0 waits until a key is pressed.
+    pub(crate) timeout_ms: u64,
+}
+
+impl Global for KeySequences {}
+
+/// Replace the key sequences and their timeout, on startup and when the
+/// config is reloaded.
+pub fn set_key_sequences(sequences: Vec<KeySequence>, timeout_ms: u64, cx: &mut App) {
+    cx.set_global(KeySequences {
+        sequences,
+        timeout_ms,
+    });
+}
+
+/// What a key press does to the pending sequence.
+pub(crate) enum SequenceStep {
+    /// The keys so far start a longer sequence; wait for more.
+    Pending,
+    /// The keys complete a sequence.
+    Complete(Box<dyn Action>),
+    /// No sequence starts with these keys.
+    Unbound,
+}
+
+impl KeySequences {
+    /// Advance the sequence `pending` with the key just pressed, in the
+    /// focused element's `contexts`.
+    pub(crate) fn step(
+        &self,
+        pending: &[Keystroke],
+        key: &Keystroke,
+        contexts: &[KeyContext],
+    ) -> SequenceStep {
+        let mut step = SequenceStep::Unbound;
+        for sequence in self
+            .sequences
+            .iter()
+            .filter(|s| s.continues(pending, contexts))
+        {
+            if !chord_matches(&sequence.keys[pending.len()], key) {
+                continue;
+            }
+            if sequence.keys.len() == pending.len() + 1 {
+                return SequenceStep::Complete(sequence.action.boxed_clone());
+            }
+            step = SequenceStep::Pending;
+        }
+        step
+    }
+
+    /// The next chord and label of every sequence that continues `pending`.
+    pub(crate) fn hints(
+        &self,
+        pending: &[Keystroke],
+        contexts: &[KeyContext],
+    ) -> Vec<(SharedString, SharedString)> {
+        self.sequences
+            .iter()
+            .filter(|sequence| sequence.continues(pending, contexts))
+            .map(|sequence| {
+                let rest = sequence.chords[pending.len()..].join(" ");
+                (rest.into(), sequence.label.clone())
+            })
+            .collect()
+    }
+
+    /// The chords of the first sequence bound to `action`.
+    pub(crate) fn chords_for(&self, action: &dyn Action) -> Option<SharedString> {
+        let sequence = self
+            .sequences
+            .iter()
+            .find(|s| s.action.partial_eq(action))?;
+        Some(sequence.chords.join(" ").into())
+    }
+
+    /// The chords of `pending` as written in the config, for the status bar.
+    pub(crate) fn describe(
+        &self,
+        pending: &[Keystroke],
+        contexts: &[KeyContext],
+    ) -> Option<SharedString> {
+        let sequence = self
+            .sequences
+            .iter()
+            .find(|s| s.continues(pending, contexts))?;
+        Some(sequence.chords[..pending.len()].join(" ").into())
+    }
+}
+
+/// The single-chord bindings, built in or in `config`, that take the first
+/// chord of the sequence `keys` in a context it also applies in, written as
+/// `action (chord)`. The sequence can't start while any remain.
+pub fn shadowing_bindings(
+    keys: &str,
+    context: Option<&str>,
+    config: &settings::Config,
+) -> Vec<String> {
+    let Some(leader) = keys
+        .split_whitespace()
+        .next()
+        .and_then(|chord| Keystroke::parse(chord).ok())
+    else {
+        return Vec::new();
+    };
+    let defaults = actions::DEFAULT_KEYBINDINGS
+        .iter()
+        .map(|binding| (binding.keys, binding.action, binding.context));
+    let configured = config
+        .keybindings
+        .iter()
+        .filter(|entry| entry.keys.split_whitespace().nth(1).is_none())
+        .map(|entry| {
+            (
+                entry.keys.as_str(),
+                entry.action.as_str(),
+                entry.context.as_deref(),
+            )
+        });
+    defaults
+        .chain(configured)
+        .filter(|(chord, _, binding_context)| {
+            Keystroke::parse(chord)
+                .is_ok_and(|chord| chord.modifiers == leader.modifiers && chord.key == leader.key)
+                && contexts_overlap(context, *binding_context)
+        })
+        .map(|(chord, action, _)| format!("{} ({})", action, chord))
+        .collect()
+}
+
+/// Whether bindings in these contexts can both apply to the same key press.
+/// Different contexts are assumed not to, as the built-in ones never nest.
+fn contexts_overlap(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.trim() == b.trim(),
+        _ => true,
+    }
+}
+
+/// Whether the chord pressed is the chord bound.
+fn chord_matches(bound: &Keystroke, pressed: &Keystroke) -> bool {
+    if bound.modifiers == pressed.modifiers && bound.key == pressed.key {
+        return true;
+    }
+    // Shifted symbols (`%`, `"`) are bound by the character they type
+    !bound.modifiers.modified()
+        && !pressed.modifiers.control
+        && !pressed.modifiers.alt
+        && !pressed.modifiers.platform
+        && pressed.key_char.as_deref() == Some(bound.key.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actions::{NewTab, SplitHorizontal, SplitVertical};
+    use settings::KeybindingEntry;
+
+    fn key(chord: &str) -> Keystroke {
+        Keystroke::parse(chord).unwrap()
+    }
+
+    fn sequences() -> KeySequences {
+        KeySequences {
+            sequences: vec![
+                KeySequence::parse("ctrl-a v", Box::new(SplitVertical), "split-vertical", None)
+                    .unwrap(),
+                KeySequence::parse(
+                    "ctrl-a s",
+                    Box::new(SplitHorizontal),
+                    "split-horizontal",
+                    None,
+                )
+                .unwrap(),
+                KeySequence::parse("ctrl-a w c", Box::new(NewTab), "new-tab", None).unwrap(),
+                KeySequence::parse("ctrl-b n", Box::new(NewTab), "new-tab", Some("terminal"))
+                    .unwrap(),
+            ],
+            timeout_ms: 2000,
+        }
+    }
+
+    #[test]
+    fn single_chords_are_not_sequences() {
+        assert!(KeySequence::parse("ctrl-a", Box::new(NewTab), "new-tab", None).is_none());
+        assert!(
+            KeySequence::parse("ctrl-a n", Box::new(NewTab), "new-tab", Some("a &&")).is_none()
+        );
+    }
+
+    #[test]
+    fn leader_then_key_completes() {
+        let sequences = sequences();
+        assert!(matches!(
+            sequences.step(&[], &key("ctrl-a"), &[]),
+            SequenceStep::Pending
+        ));
+        match sequences.step(&[key("ctrl-a")], &key("v"), &[]) {
+            SequenceStep::Complete(action) => assert!(action.partial_eq(&SplitVertical)),
+            _ => panic!("ctrl-a v should complete"),
+        }
+        assert!(matches!(
+            sequences.step(&[key("ctrl-a")], &key("w"), &[]),
+            SequenceStep::Pending
+        ));
+        assert!(matches!(
+            sequences.step(&[key("ctrl-a"), key("w")], &key("c"), &[]),
+            SequenceStep::Complete(_)
+        ));
+    }
+
+    #[test]
+    fn unbound_keys_end_the_sequence() {
+        let sequences = sequences();
+        assert!(matches!(
+            sequences.step(&[], &key("v"), &[]),
+            SequenceStep::Unbound
+        ));
+        assert!(matches!(
+            sequences.step(&[key("ctrl-a")], &key("x"), &[]),
+            SequenceStep::Unbound
+        ));
+    }
+
+    #[test]
+    fn hints_list_what_can_follow() {
+        let sequences = sequences();
+        let hints = sequences.hints(&[key("ctrl-a")], &[]);
+        let keys: Vec<&str> = hints.iter().map(|(keys, _)| keys.as_ref()).collect();
+        assert_eq!(keys, vec!["v", "s", "w c"]);
+        assert_eq!(
+            sequences.describe(&[key("ctrl-a")], &[]),
+            Some("ctrl-a".into())
+        );
+    }
+
+    #[test]
+    fn sequences_apply_in_their_context() {
+        let sequences = sequences();
+        assert!(matches!(
+            sequences.step(&[], &key("ctrl-b"), &[]),
+            SequenceStep::Unbound
+        ));
+        let contexts = [
+            KeyContext::parse("Workspace").unwrap(),
+            KeyContext::parse("terminal").unwrap(),
+        ];
+        assert!(matches!(
+            sequences.step(&[], &key("ctrl-b"), &contexts),
+            SequenceStep::Pending
+        ));
+        assert_eq!(
+            sequences.hints(&[key("ctrl-b")], &contexts).len(),
+            1,
+            "Only the sequence for the focused context is hinted"
+        );
+    }
+
+    #[test]
+    fn single_chord_bindings_shadow_sequences() {
+        let mut config = settings::Config::default();
+        config.keybindings.push(KeybindingEntry {
+            keys: "ctrl-b".to_string(),
+            action: "new-tab".to_string(),
+            context: Some("FileBrowser".to_string()),
+        });
+        assert_eq!(
+            shadowing_bindings("ctrl-w v", None, &config),
+            vec!["close-tab (ctrl-w)".to_string()]
+        );
+        assert!(shadowing_bindings("ctrl-a v", None, &config).is_empty());
+        assert_eq!(
+            shadowing_bindings("ctrl-b n", None, &config),
+            vec!["new-tab (ctrl-b)".to_string()]
+        );
+        assert!(shadowing_bindings("ctrl-b n", Some("terminal"), &config).is_empty());
+    }
+}
@@ -11,8 +11,8 @@
 //! │  PaneKind (enum dispatch - type-safe, extensible)           │
 //! ├─────────────────────────────────────────────────────────────┤
 //! │  Terminal(Entity<TerminalPane>)                             │
+//! │  FileBrowser(Entity<FileBrowserPane>)                       │
 //! │  // Future: Ssh(Entity<SshPane>)                            │
-//! │  // Future: FileBrowser(Entity<FileBrowserPane>)            │
 //! └─────────────────────────────────────────────────────────────┘
 //! ```
 //!
@@ -24,7 +24,8 @@
 //! 3. Adding new pane types is explicit and compile-time checked
 //! 4. Pattern matching enables exhaustive handling of all pane types
 
-use gpui::{AnyElement, App, Entity, FocusHandle, IntoElement, SharedString, Window};
+use crate::file_browser::FileBrowserPane;
+use gpui::{AnyElement, App, Entity, FocusHandle, Focusable, IntoElement, SharedString, Window};
 use terminal::ProgressState;
 use terminal_view::{TabBadge, TerminalPane};
 
@@ -38,9 +39,10 @@ use terminal_view::{TabBadge, TerminalPane};
 pub enum PaneKind {
     /// A local terminal pane (PTY session)
     Terminal(Entity<TerminalPane>),
+    /// A local or SFTP file browser
+    FileBrowser(Entity<FileBrowserPane>),
     // Future pane types:
     // Ssh(Entity<SshPane>),
-    // FileBrowser(Entity<FileBrowserPane>),
     // Documentation(Entity<DocsPane>),
 }
 
@@ -49,6 +51,7 @@ impl PaneKind {
     pub fn has_running_processes(&self, cx: &App) -> bool {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).has_running_processes(),
+            PaneKind::FileBrowser(browser) => browser.read(cx).has_active_transfers(),
         }
     }
 
@@ -56,6 +59,10 @@ impl PaneKind {
     pub fn get_running_process_name(&self, cx: &App) -> Option<String> {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).get_running_process_name(),
+            PaneKind::FileBrowser(browser) => browser
+                .read(cx)
+                .has_active_transfers()
+                .then(|| "sftp".to_string()),
         }
     }
 
@@ -63,6 +70,30 @@ impl PaneKind {
     pub fn get_current_directory(&self, cx: &App) -> Option<std::path::PathBuf> {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).get_current_directory(),
+            PaneKind::FileBrowser(browser) => browser.read(cx).local_directory(),
+        }
+    }
+
+    /// Directory shown to the user: the shell's reported directory (remote
+    /// for SSH sessions) or the file browser's path.
+    pub fn working_directory(&self, cx: &App) -> Option<String> {
+        match self {
+            PaneKind::Terminal(terminal) => terminal
+                .read(cx)
+                .current_working_directory()
+                .map(|path| path.display().to_string()),
+            PaneKind::FileBrowser(browser) => {
+                let path = browser.read(cx).path();
+                (!path.is_empty()).then(|| path.to_string())
+            }
+        }
+    }
+
+    /// Host of a remote session or SFTP browser, if the pane has one.
+    pub fn remote_host(&self, cx: &App) -> Option<String> {
+        match self {
+            PaneKind::Terminal(terminal) => terminal.read(cx).remote_host(),
+            PaneKind::FileBrowser(browser) => browser.read(cx).remote_host(),
         }
     }
 
@@ -71,6 +102,7 @@ impl PaneKind {
     pub fn has_exited(&self, cx: &App) -> bool {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).has_exited(),
+            PaneKind::FileBrowser(_) => false,
         }
     }
 
@@ -78,6 +110,7 @@ impl PaneKind {
     pub fn title(&self, cx: &App) -> Option<SharedString> {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).title(),
+            PaneKind::FileBrowser(browser) => Some(browser.read(cx).title()),
         }
     }
 
@@ -85,6 +118,7 @@ impl PaneKind {
     pub fn progress(&self, cx: &App) -> ProgressState {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).progress(),
+            PaneKind::FileBrowser(browser) => browser.read(cx).progress(),
         }
     }
 
@@ -92,6 +126,18 @@ impl PaneKind {
     pub fn badge(&self, cx: &App) -> TabBadge {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).badge(),
+            PaneKind::FileBrowser(browser) => match browser.read(cx).progress() {
+                ProgressState::Error(_) => TabBadge::Failed(1),
+                _ => TabBadge::Running,
+            },
+        }
+    }
+
+    /// Retry count and last error while a remote session is reconnecting.
+    pub fn reconnect_status(&self, cx: &App) -> Option<(u32, Option<String>)> {
+        match self {
+            PaneKind::Terminal(terminal) => terminal.read(cx).reconnect_status(),
+            PaneKind::FileBrowser(_) => None,
         }
     }
 
@@ -99,6 +145,7 @@ impl PaneKind {
     pub fn is_recording(&self, cx: &App) -> bool {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).is_recording(),
+            PaneKind::FileBrowser(_) => false,
         }
     }
 
@@ -106,6 +153,7 @@ impl PaneKind {
     pub fn focus_handle(&self, cx: &App) -> FocusHandle {
         match self {
             PaneKind::Terminal(terminal) => terminal.read(cx).focus_handle.clone(),
+            PaneKind::FileBrowser(browser) => browser.read(cx).focus_handle(cx),
         }
     }
 
@@ -115,6 +163,7 @@ impl PaneKind {
     pub fn render(&self, _window: &mut Window) -> AnyElement {
         match self {
             PaneKind::Terminal(terminal) => terminal.clone().into_any_element(),
+            PaneKind::FileBrowser(browser) => browser.clone().into_any_element(),
         }
     }
 
@@ -133,6 +182,27 @@ impl PaneKind {
                     .unwrap_or_else(|| shell.clone());
                 (shell, cwd, process)
             }
+            PaneKind::FileBrowser(browser) => {
+                let browser = browser.read(cx);
+                let process = if browser.has_active_transfers() {
+                    "sftp"
+                } else {
+                    "files"
+                };
+                (
+                    "files".to_string(),
+                    browser.path().to_string(),
+                    process.to_string(),
+                )
+            }
+        }
+    }
+
+    /// The terminal entity, if this is a terminal pane.
+    pub fn as_terminal(&self) -> Option<&Entity<TerminalPane>> {
+        match self {
+            PaneKind::Terminal(terminal) => Some(terminal),
+            PaneKind::FileBrowser(_) => None,
         }
     }
 }
@@ -142,3 +212,9 @@ impl From<Entity<TerminalPane>> for PaneKind {
         PaneKind::Terminal(terminal)
     }
 }
+
+impl From<Entity<FileBrowserPane>> for PaneKind {
+    fn from(browser: Entity<FileBrowserPane>) -> Self {
+        PaneKind::FileBrowser(browser)
+    }
+}
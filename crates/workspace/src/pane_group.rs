@@ -44,6 +44,47 @@ pub enum SplitDirection {
     Vertical,
 }
 
+/// Smallest share of a split either side can be resized to.
+pub const MIN_RATIO: f32 = 0.1;
+
+/// Where a pane sits on screen, as a share of the tab's area (0.0 to 1.0).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaneRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PaneRect {
+    /// The whole tab.
+    pub const FULL: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    /// Start and end of the rect along `direction`'s axis.
+    fn along(&self, direction: SplitDirection) -> (f32, f32) {
+        match direction {
+            SplitDirection::Horizontal => (self.x, self.x + self.width),
+            SplitDirection::Vertical => (self.y, self.y + self.height),
+        }
+    }
+
+    /// Start and end of the rect across `direction`'s axis.
+    fn across(&self, direction: SplitDirection) -> (f32, f32) {
+        match direction {
+            SplitDirection::Horizontal => self.along(SplitDirection::Vertical),
+            SplitDirection::Vertical => self.along(SplitDirection::Horizontal),
+        }
+    }
+}
+
+/// Slack for comparing pane edges, which are sums of float ratios.
+const EDGE_EPSILON: f32 = 1e-4;
+
 /// A pane group node - either a leaf (pane content) or a split (two children)
 #[derive(Clone)]
 pub enum PaneNode {
@@ -113,6 +154,309 @@ impl PaneNode {
         }
     }
 
+    /// Inserts `node` beside pane `target_id`, splitting it with `node` in
+    /// the second slot. Unlike [`PaneNode::split`] the inserted subtree keeps
+    /// its pane IDs.
+    ///
+    /// Returns `false` (dropping `node`) if the target wasn't found.
+    pub fn insert(&mut self, target_id: Uuid, direction: SplitDirection, node: PaneNode) -> bool {
+        if self.find_pane(target_id).is_none() {
+            return false;
+        }
+        self.insert_at(target_id, direction, node);
+        true
+    }
+
+    fn insert_at(&mut self, target_id: Uuid, direction: SplitDirection, node: PaneNode) {
+        match self {
+            PaneNode::Leaf { id, pane } => {
+                let old = PaneNode::Leaf {
+                    id: *id,
+                    pane: pane.clone(),
+                };
+                *self = PaneNode::Split {
+                    direction,
+                    first: Box::new(old),
+                    second: Box::new(node),
+                    ratio: 0.5,
+                };
+            }
+            PaneNode::Split { first, second, .. } => {
+                if first.find_pane(target_id).is_some() {
+                    first.insert_at(target_id, direction, node);
+                } else {
+                    second.insert_at(target_id, direction, node);
+                }
+            }
+        }
+    }
+
+    /// Lays `panes` out in a balanced grid, filling rows left to right.
+    ///
+    /// Built with [`PaneNode::split`]: one vertical split per row, then one
+    /// horizontal split per column within each row, equalized so every pane
+    /// in a row gets the same width and every row the same height.
+    ///
+    /// Returns `None` if `panes` is empty.
+    pub fn grid(panes: Vec<PaneKind>) -> Option<Self> {
+        let columns = grid_columns(panes.len());
+        let mut rows = panes.chunks(columns);
+        let mut node = PaneNode::new_leaf(rows.next()?[0].clone());
+
+        let mut row_starts = vec![node.first_leaf_id()];
+        for row in rows {
+            let last = *row_starts.last()?;
+            row_starts.push(node.split(last, SplitDirection::Vertical, row[0].clone())?);
+        }
+        for (start, row) in row_starts.into_iter().zip(panes.chunks(columns)) {
+            let mut last = start;
+            for pane in &row[1..] {
+                last = node.split(last, SplitDirection::Horizontal, pane.clone())?;
+            }
+        }
+
+        node.equalize();
+        Some(node)
+    }
+
+    /// Resets split ratios so panes side by side share the space equally.
+    ///
+    /// A chain of same-direction splits counts as one row (or column): each
+    /// split's ratio is its first child's share of the panes along that axis.
+    pub fn equalize(&mut self) {
+        if let PaneNode::Split {
+            direction,
+            first,
+            second,
+            ratio,
+        } = self
+        {
+            let before = first.span(*direction) as f32;
+            let after = second.span(*direction) as f32;
+            *ratio = before / (before + after);
+            first.equalize();
+            second.equalize();
+        }
+    }
+
+    /// Sets the ratio of the split whose second child starts with pane
+    /// `second_id` (each divider sits just before exactly one such pane).
+    ///
+    /// The ratio is clamped to `MIN_RATIO..=1.0 - MIN_RATIO`. Returns whether
+    /// the split was found.
+    pub fn set_ratio(&mut self, second_id: Uuid, new_ratio: f32) -> bool {
+        match self {
+            PaneNode::Leaf { .. } => false,
+            PaneNode::Split {
+                first,
+                second,
+                ratio,
+                ..
+            } => {
+                if second.first_leaf_id() == second_id {
+                    *ratio = clamp_ratio(new_ratio);
+                    true
+                } else {
+                    first.set_ratio(second_id, new_ratio) || second.set_ratio(second_id, new_ratio)
+                }
+            }
+        }
+    }
+
+    /// Moves the divider nearest to pane `target_id` across `direction` by
+    /// `delta` (a share of the split; positive moves right or down).
+    ///
+    /// Returns whether a split was resized: `false` if the pane isn't in a
+    /// split of that direction.
+    pub fn resize(&mut self, target_id: Uuid, direction: SplitDirection, delta: f32) -> bool {
+        match self {
+            PaneNode::Leaf { .. } => false,
+            PaneNode::Split {
+                direction: split_direction,
+                first,
+                second,
+                ratio,
+            } => {
+                let child = if first.find_pane(target_id).is_some() {
+                    first
+                } else if second.find_pane(target_id).is_some() {
+                    second
+                } else {
+                    return false;
+                };
+                // The innermost matching split wins
+                if child.resize(target_id, direction, delta) {
+                    return true;
+                }
+                if *split_direction == direction {
+                    *ratio = clamp_ratio(*ratio + delta);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Where every pane sits on screen, in tree order.
+    pub fn layout(&self) -> Vec<(Uuid, PaneRect)> {
+        let mut out = Vec::new();
+        self.layout_into(PaneRect::FULL, &mut out);
+        out
+    }
+
+    fn layout_into(&self, rect: PaneRect, out: &mut Vec<(Uuid, PaneRect)>) {
+        match self {
+            PaneNode::Leaf { id, .. } => out.push((*id, rect)),
+            PaneNode::Split {
+                direction,
+                first,
+                second,
+                ratio,
+            } => {
+                let (mut head, mut tail) = (rect, rect);
+                match direction {
+                    SplitDirection::Horizontal => {
+                        head.width = rect.width * ratio;
+                        tail.x = rect.x + head.width;
+                        tail.width = rect.width - head.width;
+                    }
+                    SplitDirection::Vertical => {
+                        head.height = rect.height * ratio;
+                        tail.y = rect.y + head.height;
+                        tail.height = rect.height - head.height;
+                    }
+                }
+                first.layout_into(head, out);
+                second.layout_into(tail, out);
+            }
+        }
+    }
+
+    /// The pane on screen next to `from` along `direction`'s axis: after it
+    /// (right or below) if `forward`, else before it.
+    pub fn neighbor(&self, from: Uuid, direction: SplitDirection, forward: bool) -> Option<Uuid> {
+        let layout = self.layout();
+        let (_, rect) = layout.iter().find(|(id, _)| *id == from)?;
+        nearest(*rect, &layout, direction, forward)
+    }
+
+    /// Swaps panes `a` and `b`. Each pane keeps its ID, so whatever tracks
+    /// a pane (focus, broadcast membership) follows it to the new slot.
+    ///
+    /// Returns whether both panes were found.
+    pub fn swap(&mut self, a: Uuid, b: Uuid) -> bool {
+        let mut leaves = Vec::new();
+        self.leaves_mut(&mut leaves);
+        let (Some(i), Some(j)) = (
+            leaves.iter().position(|(id, _)| **id == a),
+            leaves.iter().position(|(id, _)| **id == b),
+        ) else {
+            return false;
+        };
+        if i == j {
+            return true;
+        }
+        let (low, high) = leaves.split_at_mut(i.max(j));
+        let (x, y) = (&mut low[i.min(j)], &mut high[0]);
+        std::mem::swap(x.0, y.0);
+        std::mem::swap(x.1, y.1);
+        true
+    }
+
+    fn leaves_mut<'a>(&'a mut self, out: &mut Vec<(&'a mut Uuid, &'a mut PaneKind)>) {
+        match self {
+            PaneNode::Leaf { id, pane } => out.push((id, pane)),
+            PaneNode::Split { first, second, .. } => {
+                first.leaves_mut(out);
+                second.leaves_mut(out);
+            }
+        }
+    }
+
+    /// Rotates the row or column holding pane `target_id`: every member
+    /// moves one slot right (or down) and the last wraps around to the
+    /// first. Members are whole subtrees; the slots keep their sizes.
+    ///
+    /// Returns whether the pane is in a split.
+    pub fn rotate(&mut self, target_id: Uuid) -> bool {
+        if self.in_run(target_id) {
+            let PaneNode::Split { direction, .. } = self else {
+                return false;
+            };
+            let direction = *direction;
+            let mut members = Vec::new();
+            self.run_members(direction, &mut members);
+            for i in (1..members.len()).rev() {
+                let (head, tail) = members.split_at_mut(i);
+                std::mem::swap(&mut *head[i - 1], &mut *tail[0]);
+            }
+            return true;
+        }
+        match self {
+            PaneNode::Leaf { .. } => false,
+            PaneNode::Split { first, second, .. } => {
+                if first.find_pane(target_id).is_some() {
+                    first.rotate(target_id)
+                } else {
+                    second.rotate(target_id)
+                }
+            }
+        }
+    }
+
+    /// Whether this split belongs to the run of same-direction splits that
+    /// directly holds pane `target_id`.
+    fn in_run(&self, target_id: Uuid) -> bool {
+        let PaneNode::Split {
+            direction,
+            first,
+            second,
+            ..
+        } = self
+        else {
+            return false;
+        };
+        let child = if first.find_pane(target_id).is_some() {
+            first
+        } else if second.find_pane(target_id).is_some() {
+            second
+        } else {
+            return false;
+        };
+        match child.as_ref() {
+            PaneNode::Leaf { .. } => true,
+            PaneNode::Split { direction: d, .. } => d == direction && child.in_run(target_id),
+        }
+    }
+
+    /// Slots of the run rooted here: children of nested same-direction
+    /// splits, in order.
+    fn run_members<'a>(&'a mut self, direction: SplitDirection, out: &mut Vec<&'a mut PaneNode>) {
+        if !matches!(self, PaneNode::Split { direction: d, .. } if *d == direction) {
+            out.push(self);
+            return;
+        }
+        if let PaneNode::Split { first, second, .. } = self {
+            first.run_members(direction, out);
+            second.run_members(direction, out);
+        }
+    }
+
+    /// Number of panes across `direction`, counting nested splits in the
+    /// same direction as one run.
+    fn span(&self, direction: SplitDirection) -> usize {
+        match self {
+            PaneNode::Split {
+                direction: d,
+                first,
+                second,
+                ..
+            } if *d == direction => first.span(direction) + second.span(direction),
+            _ => 1,
+        }
+    }
+
     /// Returns the UUID of the first (leftmost/topmost) leaf in the tree.
     ///
     /// Useful for setting initial focus when a tab is created or switched to.
@@ -213,3 +557,118 @@ impl PaneNode {
         }
     }
 }
+
+fn clamp_ratio(ratio: f32) -> f32 {
+    ratio.clamp(MIN_RATIO, 1.0 - MIN_RATIO)
+}
+
+/// Of the panes in `layout`, the one closest to `from` along `direction`'s
+/// axis (after it if `forward`) that overlaps it across the axis. Ties go to
+/// the pane sharing the longest edge, then to the first in tree order.
+fn nearest(
+    from: PaneRect,
+    layout: &[(Uuid, PaneRect)],
+    direction: SplitDirection,
+    forward: bool,
+) -> Option<Uuid> {
+    let (start, end) = from.along(direction);
+    let (low, high) = from.across(direction);
+    let candidates: Vec<(Uuid, f32, f32)> = layout
+        .iter()
+        .filter_map(|(id, rect)| {
+            let (other_start, other_end) = rect.along(direction);
+            let gap = if forward {
+                other_start - end
+            } else {
+                start - other_end
+            };
+            let (other_low, other_high) = rect.across(direction);
+            let overlap = high.min(other_high) - low.max(other_low);
+            (gap > -EDGE_EPSILON && overlap > EDGE_EPSILON).then_some((*id, gap, overlap))
+        })
+        .collect();
+    let closest = candidates
+        .iter()
+        .map(|(_, gap, _)| *gap)
+        .fold(f32::INFINITY, f32::min);
+    candidates
+        .into_iter()
+        .filter(|(_, gap, _)| *gap < closest + EDGE_EPSILON)
+        .fold(
+            None,
+            |best: Option<(Uuid, f32)>, (id, _, overlap)| match best {
+                Some((_, longest)) if overlap <= longest + EDGE_EPSILON => best,
+                _ => Some((id, overlap)),
+            },
+        )
+        .map(|(id, _)| id)
+}
+
+/// Columns for a grid of `count` panes: the smallest square that fits them,
+/// dropping empty rows (5 panes → 3 columns, 2 rows).
+pub fn grid_columns(count: usize) -> usize {
+    (1..=count.max(1))
+        .find(|columns| columns * columns >= count)
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> PaneRect {
+        PaneRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn nearest_follows_screen_geometry() {
+        // +---+---+
+        // | a | b |
+        // |   +---+
+        // |   | c |
+        // +---+---+
+        // |   d   |
+        // +-------+
+        let (a, b, c, d) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let layout = [
+            (a, rect(0.0, 0.0, 0.5, 0.6)),
+            (b, rect(0.5, 0.0, 0.5, 0.2)),
+            (c, rect(0.5, 0.2, 0.5, 0.4)),
+            (d, rect(0.0, 0.6, 1.0, 0.4)),
+        ];
+        let find =
+            |from: usize, direction, forward| nearest(layout[from].1, &layout, direction, forward);
+
+        // Longest shared edge wins: c borders a more than b does
+        assert_eq!(find(0, SplitDirection::Horizontal, true), Some(c));
+        assert_eq!(find(1, SplitDirection::Horizontal, false), Some(a));
+        assert_eq!(find(1, SplitDirection::Vertical, true), Some(c));
+        assert_eq!(find(2, SplitDirection::Vertical, true), Some(d));
+        assert_eq!(find(0, SplitDirection::Vertical, true), Some(d));
+        // d spans both columns; a comes first in tree order
+        assert_eq!(find(3, SplitDirection::Vertical, false), Some(a));
+        assert_eq!(find(0, SplitDirection::Horizontal, false), None);
+        assert_eq!(find(3, SplitDirection::Vertical, true), None);
+    }
+
+    #[test]
+    fn grid_columns_are_square_ish() {
+        assert_eq!(grid_columns(0), 1);
+        assert_eq!(grid_columns(1), 1);
+        assert_eq!(grid_columns(2), 2);
+        assert_eq!(grid_columns(4), 2);
+        assert_eq!(grid_columns(5), 3);
+        assert_eq!(grid_columns(9), 3);
+        assert_eq!(grid_columns(10), 4);
+    }
+}
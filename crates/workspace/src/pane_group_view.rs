@@ -9,6 +9,26 @@ use gpui::*;
 use theme::terminal_colors;
 use uuid::Uuid;
 
+/// Width of the grab area around a split divider.
+const DIVIDER_HIT_SIZE: f32 = 6.0;
+
+/// Payload for dragging a split divider. The split is identified by the first
+/// pane of its second child, which sits right after the divider.
+#[derive(Clone)]
+pub(crate) struct DividerDrag {
+    second_id: Uuid,
+}
+
+/// Drag ghost for drags that show none because the layout itself follows the
+/// mouse: split dividers and the floating terminal.
+pub(crate) struct EmptyDragGhost;
+
+impl Render for EmptyDragGhost {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+    }
+}
+
 /// Render a pane tree as a GPUI element.
 ///
 /// Recursively builds nested flex containers for splits and terminal views for leaves.
@@ -84,25 +104,75 @@ pub fn render_pane_tree(
             ratio,
         } => {
             let ratio = *ratio;
+            let direction = *direction;
+            let second_id = second.first_leaf_id();
 
             let first_elem = render_pane_tree(first, active_pane, _window, cx);
             let second_elem = render_pane_tree(second, active_pane, _window, cx);
 
+            // Draggable divider: a wider transparent grab area around the visible line
+            let divider = div()
+                .id(ElementId::Name(format!("divider-{}", second_id).into()))
+                .flex_none()
+                .flex()
+                .justify_center()
+                .items_center()
+                .hover(|style| style.bg(hsla(0.0, 0.0, 1.0, 0.05)))
+                .on_drag(DividerDrag { second_id }, |_, _, _, cx| {
+                    cx.new(|_| EmptyDragGhost)
+                });
+            let divider = match direction {
+                SplitDirection::Horizontal => divider
+                    .h_full()
+                    .w(px(DIVIDER_HIT_SIZE))
+                    .cursor_col_resize()
+                    .child(div().h_full().w(px(2.0)).bg(border)),
+                SplitDirection::Vertical => divider
+                    .w_full()
+                    .h(px(DIVIDER_HIT_SIZE))
+                    .cursor_row_resize()
+                    .child(div().w_full().h(px(2.0)).bg(border)),
+            };
+
+            // The container's bounds turn the mouse position into a ratio
+            let on_divider_drag = cx.listener(
+                move |this, event: &DragMoveEvent<DividerDrag>, _window, cx| {
+                    if event.drag(cx).second_id != second_id {
+                        return;
+                    }
+                    let bounds = event.bounds;
+                    let position = event.event.position;
+                    let ratio = match direction {
+                        SplitDirection::Horizontal => {
+                            f32::from(position.x - bounds.origin.x) / f32::from(bounds.size.width)
+                        }
+                        SplitDirection::Vertical => {
+                            f32::from(position.y - bounds.origin.y) / f32::from(bounds.size.height)
+                        }
+                    };
+                    this.set_split_ratio(second_id, ratio, cx);
+                },
+            );
+
             match direction {
                 SplitDirection::Horizontal => div()
+                    .id(ElementId::Name(format!("split-{}", second_id).into()))
                     .size_full()
                     .flex()
                     .flex_row()
+                    .on_drag_move(on_divider_drag)
                     .child(div().h_full().w(relative(ratio)).child(first_elem))
-                    .child(div().h_full().w(px(2.0)).bg(border))
+                    .child(divider)
                     .child(div().h_full().w(relative(1.0 - ratio)).child(second_elem))
                     .into_any_element(),
                 SplitDirection::Vertical => div()
+                    .id(ElementId::Name(format!("split-{}", second_id).into()))
                     .size_full()
                     .flex()
                     .flex_col()
+                    .on_drag_move(on_divider_drag)
                     .child(div().w_full().h(relative(ratio)).child(first_elem))
-                    .child(div().w_full().h(px(2.0)).bg(border))
+                    .child(divider)
                     .child(div().w_full().h(relative(1.0 - ratio)).child(second_elem))
                     .into_any_element(),
             }
@@ -0,0 +1,201 @@
+//! Shared pieces of the searchable overlays (command palette, tab switcher,
+//! keybinding cheat sheet): query editing, selection and the panel they
+//! draw in, colored by the theme.
+
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    black, div, px, ClickEvent, Context, Div, InteractiveElement, Keystroke, ParentElement,
+    SharedString, Stateful, StatefulInteractiveElement, Styled,
+};
+use theme::TerminalColors;
+
+/// What a key press asks of a picker.
+#[derive(Debug, PartialEq)]
+pub(crate) enum PickerKey {
+    Dismiss,
+    Up,
+    Down,
+    Confirm,
+    /// Tab, or shift-tab.
+    Tab {
+        shift: bool,
+    },
+    /// The key edited the query.
+    Edited,
+    /// Not a picker key.
+    Ignored,
+}
+
+/// Apply `keystroke` to `query` if it types or deletes, and say what it asks
+/// of the picker.
+pub(crate) fn picker_key(query: &mut String, keystroke: &Keystroke) -> PickerKey {
+    let mods = &keystroke.modifiers;
+    match keystroke.key.as_str() {
+        "escape" => PickerKey::Dismiss,
+        "up" => PickerKey::Up,
+        "down" => PickerKey::Down,
+        "enter" => PickerKey::Confirm,
+        "tab" => PickerKey::Tab { shift: mods.shift },
+        "backspace" => {
+            query.pop();
+            PickerKey::Edited
+        }
+        "space" => {
+            query.push(' ');
+            PickerKey::Edited
+        }
+        key if key.len() == 1 && !mods.control && !mods.alt && !mods.platform => {
+            if mods.shift {
+                query.push_str(&key.to_uppercase());
+            } else {
+                query.push_str(key);
+            }
+            PickerKey::Edited
+        }
+        _ => PickerKey::Ignored,
+    }
+}
+
+/// The row above `selected` among `len` rows, wrapping to the last.
+pub(crate) fn select_prev(selected: usize, len: usize) -> usize {
+    match selected {
+        0 => len.saturating_sub(1),
+        selected => selected - 1,
+    }
+}
+
+/// The row below `selected` among `len` rows, wrapping to the first.
+pub(crate) fn select_next(selected: usize, len: usize) -> usize {
+    if len == 0 {
+        0
+    } else {
+        (selected + 1) % len
+    }
+}
+
+/// The layer a picker sits in: a dimmed backdrop over the window that calls
+/// `dismiss` when clicked, with `panel` near the top. The picker adds its
+/// focus and key handling.
+pub(crate) fn overlay<V: 'static>(
+    id: &'static str,
+    panel: Div,
+    dismiss: fn(&mut V, &mut Context<V>),
+    cx: &mut Context<V>,
+) -> Div {
+    div()
+        .absolute()
+        .inset_0()
+        .flex()
+        .flex_col()
+        .items_center()
+        .child(
+            div()
+                .id(id)
+                .absolute()
+                .inset_0()
+                .bg(black().opacity(0.4))
+                .on_click(cx.listener(move |this, _: &ClickEvent, _, cx| dismiss(this, cx))),
+        )
+        .child(panel)
+}
+
+/// The picker's box, `width` wide, holding its query line and results.
+pub(crate) fn panel(width: f32, colors: &TerminalColors) -> Div {
+    div()
+        .mt(px(60.0))
+        .w(px(width))
+        .bg(colors.title_bar)
+        .border_1()
+        .border_color(colors.border)
+        .rounded(px(8.0))
+        .shadow_lg()
+        .overflow_hidden()
+        .flex()
+        .flex_col()
+}
+
+/// The line at the top of the panel, for the query.
+pub(crate) fn query_line(colors: &TerminalColors) -> Div {
+    div()
+        .h(px(40.0))
+        .w_full()
+        .px(px(12.0))
+        .flex()
+        .items_center()
+        .border_b_1()
+        .border_color(colors.border)
+}
+
+/// The query as typed, or `placeholder` while it's empty.
+pub(crate) fn query_text(query: &str, placeholder: &'static str, colors: &TerminalColors) -> Div {
+    let (text, color) = if query.is_empty() {
+        (SharedString::from(placeholder), colors.muted)
+    } else {
+        (SharedString::from(query.to_string()), colors.foreground)
+    };
+    div().flex_1().text_sm().text_color(color).child(text)
+}
+
+/// Highlight a result row if it's selected, or while the mouse is over it.
+pub(crate) fn highlight_row(
+    row: Stateful<Div>,
+    selected: bool,
+    colors: &TerminalColors,
+) -> Stateful<Div> {
+    let selection = colors.selection;
+    row.when(selected, |d| d.bg(selection))
+        .when(!selected, |d| d.hover(|d| d.bg(selection.opacity(0.5))))
+}
+
+/// Shown in place of the results when nothing matches.
+pub(crate) fn empty_message(message: &'static str, colors: &TerminalColors) -> Div {
+    div()
+        .h(px(40.0))
+        .w_full()
+        .flex()
+        .items_center()
+        .justify_center()
+        .text_sm()
+        .text_color(colors.muted)
+        .child(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(query: &mut String, key: &str) -> PickerKey {
+        picker_key(query, &Keystroke::parse(key).unwrap())
+    }
+
+    #[test]
+    fn keys_edit_the_query() {
+        let mut query = String::new();
+        assert_eq!(press(&mut query, "a"), PickerKey::Edited);
+        assert_eq!(press(&mut query, "shift-b"), PickerKey::Edited);
+        assert_eq!(press(&mut query, "space"), PickerKey::Edited);
+        assert_eq!(query, "aB ");
+        assert_eq!(press(&mut query, "backspace"), PickerKey::Edited);
+        assert_eq!(query, "aB");
+
+        // Shortcuts and navigation leave it alone
+        assert_eq!(press(&mut query, "ctrl-c"), PickerKey::Ignored);
+        assert_eq!(press(&mut query, "escape"), PickerKey::Dismiss);
+        assert_eq!(press(&mut query, "enter"), PickerKey::Confirm);
+        assert_eq!(
+            press(&mut query, "shift-tab"),
+            PickerKey::Tab { shift: true }
+        );
+        assert_eq!(query, "aB");
+    }
+
+    #[test]
+    fn selection_wraps_around() {
+        assert_eq!(select_prev(0, 3), 2);
+        assert_eq!(select_prev(2, 3), 1);
+        assert_eq!(select_next(2, 3), 0);
+        assert_eq!(select_next(0, 3), 1);
+        assert_eq!(select_prev(0, 0), 0);
+        assert_eq!(select_next(0, 0), 0);
+    }
+}
@@ -0,0 +1,337 @@
+//! Port forwarding panel — lists SSH tunnels with live byte counters.
+//!
+//! Forwards come from SSH profiles (`forwards = [...]`) or are typed into the
+//! panel at runtime as `L 8080:db:5432 [user@host]`. They run independently of
+//! terminal tabs: closing a tab leaves its tunnels up until stopped here.
+
+use crate::status_bar::format_bytes;
+use crate::workspace_view::Workspace;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, hsla, px, Context, ElementId, Entity, InteractiveElement, IntoElement, KeyDownEvent,
+    ParentElement, SharedString, Styled, Subscription, Task,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::Sizable;
+use remote::{ForwardSpec, ForwardState, PortForward, SshTarget};
+use theme::terminal_colors;
+
+/// How often the panel re-reads byte counters while visible.
+pub(crate) const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// An SSH profile from the config, with its forwards parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ForwardProfile {
+    pub(crate) name: String,
+    pub(crate) target: SshTarget,
+    pub(crate) forwards: Vec<ForwardSpec>,
+}
+
+/// State for the port forwarding panel.
+pub(crate) struct PortForwardsState {
+    /// Input for adding a forward at runtime.
+    pub(crate) input: Entity<InputState>,
+    /// Whether the panel is currently visible.
+    pub(crate) visible: bool,
+    /// Last error from adding or starting a forward.
+    pub(crate) error: Option<SharedString>,
+    /// SSH profiles, re-read from the config each time the panel opens.
+    pub(crate) profiles: Vec<ForwardProfile>,
+    /// Subscriptions kept alive by this state.
+    pub(crate) _subscriptions: Vec<Subscription>,
+    /// Counter refresh loop, dropped (cancelled) when the panel hides.
+    pub(crate) _refresh: Option<Task<()>>,
+}
+
+/// Collect profiles that have an SSH `host`. Unparseable forwards are logged and skipped.
+pub(crate) fn ssh_profiles(config: &settings::Config) -> Vec<ForwardProfile> {
+    config
+        .profiles
+        .iter()
+        .filter_map(|profile| {
+            let host = profile.host.as_deref()?;
+            let Some(target) = SshTarget::parse(host) else {
+                tracing::warn!("Profile '{}' has an invalid host '{}'", profile.name, host);
+                return None;
+            };
+            let forwards = profile
+                .forwards
+                .iter()
+                .filter_map(|spec| match ForwardSpec::parse(spec) {
+                    Ok(spec) => Some(spec),
+                    Err(error) => {
+                        tracing::warn!("Profile '{}': {:#}", profile.name, error);
+                        None
+                    }
+                })
+                .collect();
+            Some(ForwardProfile {
+                name: profile.name.clone(),
+                target,
+                forwards,
+            })
+        })
+        .collect()
+}
+
+/// Parse a runtime forward request: `<spec> [user@host[:port]]`.
+///
+/// Without a host, the forward goes through `default_target` (the active
+/// tab's SSH session, if any).
+pub(crate) fn parse_forward_request(
+    input: &str,
+    default_target: Option<&SshTarget>,
+) -> Result<(SshTarget, ForwardSpec), String> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let (spec, target) = match tokens.as_slice() {
+        [] => return Err("Enter a forward, e.g. L 8080:localhost:80".to_string()),
+        [kind, fields] => (format!("{} {}", kind, fields), None),
+        [kind, fields, host] => (format!("{} {}", kind, fields), Some(*host)),
+        _ => return Err(format!("Invalid forward '{}'", input.trim())),
+    };
+
+    let target = match target {
+        Some(host) => {
+            SshTarget::parse(host).ok_or_else(|| format!("Invalid SSH host '{}'", host))?
+        }
+        None => default_target.cloned().ok_or_else(|| {
+            "No SSH session in this tab; add a host, e.g. L 8080:db:5432 user@bastion".to_string()
+        })?,
+    };
+
+    let spec = ForwardSpec::parse(&spec).map_err(|error| format!("{:#}", error))?;
+    Ok((target, spec))
+}
+
+impl PortForwardsState {
+    /// Toggle visibility. Returns the new visible state.
+    pub(crate) fn toggle(&mut self) -> bool {
+        self.visible = !self.visible;
+        self.visible
+    }
+}
+
+/// Render the port forwarding panel overlay.
+pub(crate) fn render_port_forwards_panel(
+    state: &PortForwardsState,
+    forwards: &[PortForward],
+    cx: &mut Context<Workspace>,
+) -> impl IntoElement {
+    let colors = terminal_colors(cx);
+    let foreground = colors.foreground;
+    let muted = colors.muted;
+    let red = colors.red;
+    let green = colors.green;
+    let border = colors.border;
+
+    let rows = forwards
+        .iter()
+        .map(|forward| {
+            let id = forward.id();
+            let stats = forward.stats();
+            let (state_label, state_color) = match forward.state() {
+                ForwardState::Active => (SharedString::from("active"), green),
+                ForwardState::Failed(reason) => (SharedString::from(reason), red),
+                ForwardState::Stopped => (SharedString::from("stopped"), muted),
+            };
+
+            div()
+                .id(ElementId::Name(format!("forward-{}", id).into()))
+                .flex()
+                .items_center()
+                .gap_3()
+                .py_1()
+                .border_b_1()
+                .border_color(border)
+                .child(
+                    div()
+                        .flex_1()
+                        .flex()
+                        .flex_col()
+                        .overflow_hidden()
+                        .child(div().text_sm().text_color(foreground).child(format!(
+                            "{} via {}",
+                            forward.spec(),
+                            forward.target().display_name()
+                        )))
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(state_color)
+                                .whitespace_nowrap()
+                                .overflow_hidden()
+                                .child(state_label),
+                        ),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(muted)
+                        .whitespace_nowrap()
+                        .child(format!(
+                            "\u{2191} {}  \u{2193} {}  {} conn",
+                            format_bytes(stats.bytes_sent),
+                            format_bytes(stats.bytes_received),
+                            stats.active_connections
+                        )),
+                )
+                .child(
+                    Button::new(ElementId::Name(format!("stop-forward-{}", id).into()))
+                        .xsmall()
+                        .ghost()
+                        .label("Stop")
+                        .on_click(cx.listener(move |this, _, _window, cx| {
+                            this.stop_forward(id, cx);
+                        })),
+                )
+        })
+        .collect::<Vec<_>>();
+
+    let profile_rows = state
+        .profiles
+        .iter()
+        .filter(|profile| !profile.forwards.is_empty())
+        .map(|profile| {
+            let name = profile.name.clone();
+            div()
+                .flex()
+                .items_center()
+                .gap_3()
+                .py_1()
+                .child(
+                    div()
+                        .flex_1()
+                        .text_sm()
+                        .text_color(foreground)
+                        .child(format!(
+                            "{} ({})",
+                            profile.name,
+                            profile.target.display_name()
+                        )),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(muted)
+                        .child(format!("{} forward(s)", profile.forwards.len())),
+                )
+                .child(
+                    Button::new(ElementId::Name(
+                        format!("start-profile-{}", profile.name).into(),
+                    ))
+                    .xsmall()
+                    .ghost()
+                    .label("Start")
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.start_profile_forwards(&name, cx);
+                    })),
+                )
+        })
+        .collect::<Vec<_>>();
+
+    div()
+        .id("port-forwards-panel")
+        .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+            if event.keystroke.key == "escape" {
+                this.hide_port_forwards(window, cx);
+            }
+        }))
+        .absolute()
+        .top(px(60.0))
+        .left(px(120.0))
+        .right(px(120.0))
+        .max_h(px(480.0))
+        .bg(hsla(0.0, 0.0, 0.10, 1.0))
+        .border_1()
+        .border_color(hsla(0.0, 0.0, 0.25, 1.0))
+        .rounded(px(8.0))
+        .shadow_lg()
+        .overflow_hidden()
+        .p(px(12.0))
+        .flex()
+        .flex_col()
+        .gap_2()
+        .child(
+            div()
+                .text_base()
+                .text_color(foreground)
+                .child("Port Forwards"),
+        )
+        .child(div().text_xs().text_color(muted).child(
+            "Forwards open their own SSH connection and can't ask for a password: \
+             the host needs key or agent authentication.",
+        ))
+        .child(Input::new(&state.input))
+        .when_some(state.error.clone(), |d, error| {
+            d.child(div().text_xs().text_color(red).child(error))
+        })
+        .when(forwards.is_empty(), |d| {
+            d.child(
+                div()
+                    .text_sm()
+                    .text_color(muted)
+                    .child("No active forwards"),
+            )
+        })
+        .children(rows)
+        .when(!profile_rows.is_empty(), |d| {
+            d.child(div().mt_2().text_xs().text_color(muted).child("Profiles"))
+                .children(profile_rows)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::Profile;
+
+    #[test]
+    fn request_uses_default_target() {
+        let default = SshTarget::parse("me@bastion").unwrap();
+        let (target, spec) = parse_forward_request("L 8080:db:5432", Some(&default)).unwrap();
+        assert_eq!(target, default);
+        assert_eq!(spec, ForwardSpec::parse("L 8080:db:5432").unwrap());
+    }
+
+    #[test]
+    fn request_with_explicit_host() {
+        let (target, spec) = parse_forward_request("-D 1080 ops@jump:2222", None).unwrap();
+        assert_eq!(target, SshTarget::parse("ops@jump:2222").unwrap());
+        assert_eq!(spec, ForwardSpec::parse("D 1080").unwrap());
+    }
+
+    #[test]
+    fn request_without_any_target_fails() {
+        assert!(parse_forward_request("R 9000:localhost:3000", None).is_err());
+        assert!(parse_forward_request("", None).is_err());
+        assert!(parse_forward_request("L 1:a:2 host extra", None).is_err());
+    }
+
+    #[test]
+    fn ssh_profiles_skip_local_profiles_and_bad_forwards() {
+        let config = settings::Config {
+            profiles: vec![
+                Profile {
+                    name: "local".to_string(),
+                    ..Profile::default()
+                },
+                Profile {
+                    name: "bastion".to_string(),
+                    host: Some("deploy@bastion".to_string()),
+                    forwards: vec!["L 5432:db:5432".to_string(), "X nonsense".to_string()],
+                    ..Profile::default()
+                },
+            ],
+            ..settings::Config::default()
+        };
+        let profiles = ssh_profiles(&config);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "bastion");
+        assert_eq!(
+            profiles[0].target,
+            SshTarget::parse("deploy@bastion").unwrap()
+        );
+        assert_eq!(profiles[0].forwards.len(), 1);
+    }
+}
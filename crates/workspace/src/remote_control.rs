@@ -0,0 +1,234 @@
+//! Remote control: run actions in the app from scripts.
+//!
+//! The app listens on a Unix socket and exports its path to every shell it
+//! starts as `HUMANSSH_CONTROL`. Clients write one action spec per line, as
+//! in `[[keybindings]]` (see [`actions::parse_action`]), and get back `ok` or
+//! `error: <message>` for each. `humanssh --remote <action>` is such a client.
+//!
+//! Actions run in the focused window, or the most recently opened one when
+//! the app isn't focused.
+
+use anyhow::{Context as _, Result};
+use gpui::App;
+use std::path::Path;
+#[cfg(unix)]
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Sender},
+};
+
+/// Environment variable holding the control socket's path.
+pub const CONTROL_ENV: &str = "HUMANSSH_CONTROL";
+
+/// An action spec read from a client, with where to send its result.
+#[cfg(unix)]
+struct Request {
+    spec: String,
+    reply: Sender<Result<(), String>>,
+}
+
+/// Listen for remote-control clients and export the socket's path to shells
+/// started from now on. Does nothing on Windows, which has no Unix sockets.
+pub fn start_remote_control(cx: &mut App) {
+    #[cfg(unix)]
+    {
+        let socket = create_socket_dir().map(|dir| dir.join("control"));
+        let socket = match socket.and_then(|socket| listen_on(&socket, cx).map(|()| socket)) {
+            Ok(socket) => socket,
+            Err(error) => {
+                tracing::warn!("Remote control unavailable: {:#}", error);
+                return;
+            }
+        };
+        tracing::info!("Remote control listening on {}", socket.display());
+        std::env::set_var(CONTROL_ENV, &socket);
+        cx.on_app_quit(move |_| {
+            if let Some(dir) = socket.parent() {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+            async {}
+        })
+        .detach();
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = cx;
+        tracing::info!("Remote control needs Unix sockets, not available on this platform");
+    }
+}
+
+/// Bind `socket`, serve clients on background threads and run their actions
+/// on the main thread.
+#[cfg(unix)]
+fn listen_on(socket: &Path, cx: &mut App) -> Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Cannot listen on {}", socket.display()))?;
+    let (requests, rx) = mpsc::channel::<Request>();
+
+    std::thread::Builder::new()
+        .name("remote-control".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let requests = requests.clone();
+                let _ = std::thread::Builder::new()
+                    .name("remote-control-client".into())
+                    .spawn(move || {
+                        let Ok(reader) = stream.try_clone() else {
+                            return;
+                        };
+                        if let Err(error) =
+                            serve(std::io::BufReader::new(reader), stream, &requests)
+                        {
+                            tracing::debug!("Remote-control client gone: {}", error);
+                        }
+                    });
+            }
+        })
+        .context("Cannot start remote-control thread")?;
+
+    // Poll for requests on a timer to run them on the main thread
+    cx.spawn(async move |cx: &mut gpui::AsyncApp| loop {
+        cx.background_executor()
+            .timer(std::time::Duration::from_millis(50))
+            .await;
+        while let Ok(request) = rx.try_recv() {
+            let result = cx
+                .update(|cx| run_action(&request.spec, cx))
+                .unwrap_or_else(|_| Err("the app is shutting down".into()));
+            let _ = request.reply.send(result);
+        }
+    })
+    .detach();
+    Ok(())
+}
+
+/// Make a directory only this user can enter for the control socket, so no
+/// one else can drive the app.
+#[cfg(unix)]
+fn create_socket_dir() -> Result<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+    let dir = std::env::temp_dir().join(format!("humanssh-{}-control", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .with_context(|| format!("Cannot create {}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Read action specs from a client one line at a time and write back each
+/// one's result.
+#[cfg(unix)]
+fn serve(reader: impl BufRead, mut writer: impl Write, requests: &Sender<Request>) -> Result<()> {
+    for line in reader.lines() {
+        let spec = line?;
+        if spec.trim().is_empty() {
+            continue;
+        }
+        let (reply, result) = mpsc::channel();
+        if requests.send(Request { spec, reply }).is_err() {
+            break;
+        }
+        let Ok(result) = result.recv() else { break };
+        match result {
+            Ok(()) => writeln!(writer, "ok")?,
+            Err(error) => writeln!(writer, "error: {}", error)?,
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Parse `spec` and dispatch it to the window it should run in.
+#[cfg(unix)]
+fn run_action(spec: &str, cx: &mut App) -> Result<(), String> {
+    let action = actions::parse_action(spec).map_err(|error| error.to_string())?;
+    let window = cx
+        .active_window()
+        .or_else(|| crate::windows::last_workspace_window(cx));
+    match window {
+        Some(window) => window
+            .update(cx, |_, window, cx| window.dispatch_action(action, cx))
+            .map_err(|_| "the window was closed".to_string()),
+        // Without a window only app-wide actions like new-window can run
+        None => {
+            cx.dispatch_action(action.as_ref());
+            Ok(())
+        }
+    }
+}
+
+/// Send `spec` to the app whose socket `HUMANSSH_CONTROL` names and return
+/// its reply.
+pub fn send_remote_action(spec: &str) -> Result<String> {
+    let socket = std::env::var_os(CONTROL_ENV).with_context(|| {
+        format!(
+            "{} isn't set: run this from a HumanSSH tab or set it to the app's control socket",
+            CONTROL_ENV
+        )
+    })?;
+    send_to(Path::new(&socket), spec)
+}
+
+#[cfg(unix)]
+fn send_to(socket: &Path, spec: &str) -> Result<String> {
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("Cannot connect to {}", socket.display()))?;
+    writeln!(stream, "{}", spec.replace('\n', " "))?;
+    let mut reply = String::new();
+    std::io::BufReader::new(stream).read_line(&mut reply)?;
+    if reply.is_empty() {
+        anyhow::bail!("HumanSSH closed the connection without replying");
+    }
+    Ok(reply.trim_end().to_string())
+}
+
+#[cfg(not(unix))]
+fn send_to(_socket: &Path, _spec: &str) -> Result<String> {
+    anyhow::bail!("Remote control needs Unix sockets, not available on this platform")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Serve `input` with requests answered by parsing them, as the app does
+    /// before dispatching.
+    fn serve_parsed(input: &str) -> String {
+        let (requests, rx) = mpsc::channel::<Request>();
+        let app = std::thread::spawn(move || {
+            for request in rx {
+                let result = actions::parse_action(&request.spec)
+                    .map(|_| ())
+                    .map_err(|error| error.to_string());
+                let _ = request.reply.send(result);
+            }
+        });
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output, &requests).unwrap();
+        drop(requests);
+        app.join().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_replies_once_per_action() {
+        let output = serve_parsed("new-tab\n\nactivate-tab 3\nsend-text \"ls\\n\"\n");
+        assert_eq!(output, "ok\nok\nok\n");
+    }
+
+    #[test]
+    fn test_reports_bad_actions() {
+        let output = serve_parsed("no-such-action\nactivate-tab zero\nnew-tab\n");
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "error: unknown action 'no-such-action'");
+        assert!(lines[1].starts_with("error: "));
+        assert_eq!(lines[2], "ok");
+    }
+}
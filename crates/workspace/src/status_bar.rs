@@ -3,7 +3,8 @@
 //! Displays CPU, memory, network stats and terminal info in a footer bar,
 //! similar to MobaXTerm's remote monitoring bar.
 
-use gpui::{div, px, App, IntoElement, ParentElement, SharedString, Styled};
+use gpui::prelude::FluentBuilder;
+use gpui::{div, px, App, FontWeight, IntoElement, ParentElement, SharedString, Styled};
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::Sizable;
 use parking_lot::RwLock;
@@ -287,7 +288,7 @@ pub fn stats_collector() -> Arc<RwLock<SystemStatsCollector>> {
 }
 
 /// Format bytes into human-readable string (KB, MB, GB).
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -333,7 +334,15 @@ fn format_mem_percent(used: u64, total: u64) -> String {
 }
 
 /// Render the status bar.
-pub fn render_status_bar(stats: &SystemStats, cx: &App) -> impl IntoElement {
+/// `reconnect` is the active pane's retry count and last error while its
+/// remote session is reconnecting; `pending_keys` the chords of a key
+/// sequence waiting for its next key.
+pub fn render_status_bar(
+    stats: &SystemStats,
+    reconnect: Option<(u32, Option<String>)>,
+    pending_keys: Option<SharedString>,
+    cx: &App,
+) -> impl IntoElement {
     let colors = terminal_colors(cx);
     let bg = colors.title_bar;
     let fg = colors.foreground;
@@ -539,6 +548,33 @@ pub fn render_status_bar(stats: &SystemStats, cx: &App) -> impl IntoElement {
         )
         // Spacer
         .child(div().flex_1())
+        // Leader pressed, waiting for the rest of a key sequence
+        .when_some(pending_keys, |d, keys| {
+            d.child(
+                div()
+                    .text_color(colors.accent)
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .child(format!("{} \u{2026}", keys)),
+            )
+            .child(div().text_color(muted).child("│"))
+        })
+        // Reconnect attempts for a dropped remote session
+        .when_some(reconnect, |d, (attempt, error)| {
+            let error = error.unwrap_or_else(|| "Connection lost".to_string());
+            d.child(
+                Button::new("status-reconnect")
+                    .xsmall()
+                    .ghost()
+                    .label(format!(
+                        "RETRY {} \u{b7} {}",
+                        attempt,
+                        truncate_end(&error, 40)
+                    ))
+                    .text_color(colors.yellow)
+                    .tooltip(error),
+            )
+            .child(div().text_color(muted).child("│"))
+        })
         // Process
         .child(
             div()
@@ -568,6 +604,16 @@ pub fn render_status_bar(stats: &SystemStats, cx: &App) -> impl IntoElement {
         )
 }
 
+/// Truncate text on the right, keeping the leftmost chars.
+fn truncate_end(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(max_chars - 1).collect();
+        format!("{}…", kept)
+    }
+}
+
 /// Truncate a path from the left, keeping the rightmost chars.
 fn truncate_path(path: &str, max_len: usize) -> String {
     if path.len() <= max_len {
@@ -591,6 +637,12 @@ mod tests {
         assert_eq!(format_bytes(1536 * 1024 * 1024), "1.5G");
     }
 
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(truncate_end("ssh exited", 40), "ssh exited");
+        assert_eq!(truncate_end("Connection refused", 10), "Connectio…");
+    }
+
     #[test]
     fn test_format_throughput() {
         assert_eq!(format_throughput(0), "0B/s");
@@ -0,0 +1,256 @@
+//! Tab switcher overlay: every tab and pane, most recently used first, with
+//! fuzzy search over title, foreground process, directory and host.
+
+use crate::command_palette::fuzzy_score;
+use crate::picker::{self, PickerKey};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    div, px, App, Context, ElementId, FocusHandle, Focusable, InteractiveElement, IntoElement,
+    KeyDownEvent, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window,
+};
+use uuid::Uuid;
+
+/// A pane listed in the switcher.
+pub(crate) struct SwitcherEntry {
+    pub(crate) tab: Uuid,
+    pub(crate) pane: Uuid,
+    /// Tab title, followed by the pane's own title in split tabs
+    pub(crate) title: String,
+    pub(crate) process: Option<String>,
+    pub(crate) cwd: Option<String>,
+    pub(crate) host: Option<String>,
+}
+
+impl SwitcherEntry {
+    /// Process, directory and host, as shown under the title.
+    fn detail(&self) -> String {
+        [&self.process, &self.cwd, &self.host]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("  \u{00B7}  ")
+    }
+
+    /// Everything the query is matched against.
+    fn search_text(&self) -> String {
+        format!("{} {}", self.title, self.detail())
+    }
+}
+
+/// Event emitted when the switcher closes, with the chosen tab and pane.
+pub struct TabSwitcherDismiss {
+    pub target: Option<(Uuid, Uuid)>,
+}
+
+impl gpui::EventEmitter<TabSwitcherDismiss> for TabSwitcher {}
+
+/// Indices of `entries` matching `query`, best match first. Ties keep the
+/// entries' (most recently used) order.
+fn filter_entries(entries: &[SwitcherEntry], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(usize, u32)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, entry)| fuzzy_score(query, &entry.search_text()).map(|s| (idx, s)))
+        .collect();
+
+    scored.sort_by_key(|&(_, score)| score);
+    scored.into_iter().map(|(idx, _)| idx).collect()
+}
+
+/// The tab switcher overlay view.
+pub struct TabSwitcher {
+    query: String,
+    selected_index: usize,
+    entries: Vec<SwitcherEntry>,
+    filtered_indices: Vec<usize>,
+    pub(crate) focus_handle: FocusHandle,
+}
+
+impl TabSwitcher {
+    /// Open over `entries`, given most recently used first.
+    pub(crate) fn new(entries: Vec<SwitcherEntry>, cx: &mut Context<Self>) -> Self {
+        let filtered_indices: Vec<usize> = (0..entries.len()).collect();
+        let mut switcher = Self {
+            query: String::new(),
+            selected_index: 0,
+            entries,
+            filtered_indices,
+            focus_handle: cx.focus_handle(),
+        };
+        switcher.select_first();
+        switcher
+    }
+
+    /// Select the top match; with no query that's the current pane, so start
+    /// on the one used before it.
+    fn select_first(&mut self) {
+        self.selected_index = if self.query.is_empty() && self.filtered_indices.len() > 1 {
+            1
+        } else {
+            0
+        };
+    }
+
+    fn update_filter(&mut self) {
+        self.filtered_indices = filter_entries(&self.entries, &self.query);
+        self.select_first();
+    }
+
+    fn move_up(&mut self, cx: &mut Context<Self>) {
+        self.selected_index = picker::select_prev(self.selected_index, self.filtered_indices.len());
+        cx.notify();
+    }
+
+    fn move_down(&mut self, cx: &mut Context<Self>) {
+        self.selected_index = picker::select_next(self.selected_index, self.filtered_indices.len());
+        cx.notify();
+    }
+
+    fn confirm(&mut self, cx: &mut Context<Self>) {
+        let target = self
+            .filtered_indices
+            .get(self.selected_index)
+            .and_then(|&idx| self.entries.get(idx))
+            .map(|entry| (entry.tab, entry.pane));
+
+        cx.emit(TabSwitcherDismiss { target });
+    }
+
+    fn dismiss(&mut self, cx: &mut Context<Self>) {
+        cx.emit(TabSwitcherDismiss { target: None });
+    }
+}
+
+impl Focusable for TabSwitcher {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for TabSwitcher {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = theme::terminal_colors(cx);
+        let foreground = colors.foreground;
+        let muted = colors.muted;
+
+        let max_visible = 10;
+
+        let rows: Vec<_> = self
+            .filtered_indices
+            .iter()
+            .enumerate()
+            .map(|(visible_idx, &entry_idx)| {
+                let is_selected = visible_idx == self.selected_index;
+                let entry = &self.entries[entry_idx];
+                let title: SharedString = entry.title.clone().into();
+                let detail: SharedString = entry.detail().into();
+
+                let row = div()
+                    .id(ElementId::Name(format!("switcher-{}", entry_idx).into()))
+                    .h(px(44.0))
+                    .w_full()
+                    .px(px(12.0))
+                    .flex()
+                    .flex_col()
+                    .justify_center()
+                    .cursor_pointer();
+                picker::highlight_row(row, is_selected, &colors)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.selected_index = visible_idx;
+                        this.confirm(cx);
+                    }))
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(foreground)
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .child(title),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(11.0))
+                            .text_color(muted)
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .child(detail),
+                    )
+            })
+            .collect();
+        let panel = picker::panel(600.0, &colors)
+            .child(picker::query_line(&colors).child(picker::query_text(
+                &self.query,
+                "Switch to tab or pane...",
+                &colors,
+            )))
+            .child(
+                div()
+                    .id("switcher-results")
+                    .max_h(px(max_visible as f32 * 44.0))
+                    .overflow_y_scroll()
+                    .scrollbar_width(px(4.0))
+                    .children(rows),
+            )
+            .when(self.filtered_indices.is_empty(), |d| {
+                d.child(picker::empty_message("No matching tabs", &colors))
+            });
+
+        picker::overlay("switcher-backdrop", panel, Self::dismiss, cx)
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
+                match picker::picker_key(&mut this.query, &event.keystroke) {
+                    PickerKey::Dismiss => this.dismiss(cx),
+                    PickerKey::Up | PickerKey::Tab { shift: true } => this.move_up(cx),
+                    PickerKey::Down | PickerKey::Tab { shift: false } => this.move_down(cx),
+                    PickerKey::Confirm => this.confirm(cx),
+                    PickerKey::Edited => {
+                        this.update_filter();
+                        cx.notify();
+                    }
+                    PickerKey::Ignored => {}
+                }
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, process: Option<&str>, host: Option<&str>) -> SwitcherEntry {
+        SwitcherEntry {
+            tab: Uuid::new_v4(),
+            pane: Uuid::new_v4(),
+            title: title.to_string(),
+            process: process.map(str::to_string),
+            cwd: Some("/home/me".to_string()),
+            host: host.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn filter_matches_process_and_host_and_keeps_recent_order() {
+        let entries = vec![
+            entry("Terminal 3", Some("vim"), None),
+            entry("prod", Some("htop"), Some("web1")),
+            entry("Terminal 1", None, None),
+        ];
+
+        assert_eq!(filter_entries(&entries, ""), vec![0, 1, 2]);
+        assert_eq!(filter_entries(&entries, "htop"), vec![1]);
+        assert_eq!(filter_entries(&entries, "web1"), vec![1]);
+        // Equal scores keep the most recently used first
+        assert_eq!(filter_entries(&entries, "Terminal"), vec![0, 2]);
+        assert!(filter_entries(&entries, "zzz").is_empty());
+    }
+
+    #[test]
+    fn detail_skips_missing_fields() {
+        assert_eq!(
+            entry("prod", Some("htop"), Some("web1")).detail(),
+            "htop  \u{00B7}  /home/me  \u{00B7}  web1"
+        );
+        assert_eq!(entry("local", None, None).detail(), "/home/me");
+    }
+}
@@ -0,0 +1,153 @@
+//! Workspace windows: opening more of them and handing tabs between them.
+//!
+//! Every window holds its own [`Workspace`]. Windows opened through
+//! [`open_workspace_window`] are remembered so a tab dragged out of one can
+//! be dropped into another, and saved for session restore.
+
+use crate::workspace_view::Workspace;
+use anyhow::{Context as _, Result};
+use gpui::{
+    AnyWindowHandle, App, AppContext, Entity, Global, Pixels, Point, WeakEntity, Window,
+    WindowBounds, WindowHandle, WindowOptions,
+};
+use gpui_component::Root;
+use settings::session::SessionSnapshot;
+
+/// Builds the options for a new workspace window. The app sets this at
+/// startup so every window gets the same size and titlebar.
+pub struct WindowOptionsBuilder(pub fn(&mut App) -> WindowOptions);
+
+impl Global for WindowOptionsBuilder {}
+
+/// Open workspace windows.
+#[derive(Default)]
+struct WorkspaceWindows(Vec<(AnyWindowHandle, WeakEntity<Workspace>)>);
+
+impl Global for WorkspaceWindows {}
+
+/// Open a window around the workspace returned by `build`, with its top-left
+/// corner at `origin` (screen coordinates) if given.
+pub fn open_workspace_window(
+    cx: &mut App,
+    origin: Option<Point<Pixels>>,
+    build: impl FnOnce(&mut Window, &mut App) -> Entity<Workspace>,
+) -> Result<WindowHandle<Root>> {
+    let builder = cx.try_global::<WindowOptionsBuilder>().map(|b| b.0);
+    let mut options = match builder {
+        Some(build_options) => build_options(cx),
+        None => WindowOptions::default(),
+    };
+    if let (Some(origin), Some(WindowBounds::Windowed(bounds))) =
+        (origin, options.window_bounds.as_mut())
+    {
+        bounds.origin = origin;
+    }
+
+    let mut workspace = None;
+    let handle = cx
+        .open_window(options, |window, cx| {
+            let view = build(window, cx);
+            workspace = Some(view.downgrade());
+            cx.new(|cx| Root::new(view, window, cx))
+        })
+        .context("Failed to open window")?;
+
+    if let Some(workspace) = workspace {
+        let windows = &mut cx.default_global::<WorkspaceWindows>().0;
+        windows.retain(|(_, workspace)| workspace.upgrade().is_some());
+        windows.push((handle.into(), workspace));
+    }
+    Ok(handle)
+}
+
+/// Reopen the windows saved when the app last quit, and save them again on
+/// quit, if `restore-session` is on. Returns whether any window opened.
+pub fn restore_session(cx: &mut App) -> bool {
+    if !settings::load_config().restore_session {
+        return false;
+    }
+    cx.on_app_quit(|cx| {
+        save_session(cx);
+        async {}
+    })
+    .detach();
+
+    let Some(session) = settings::session::load_session() else {
+        return false;
+    };
+    let mut opened = false;
+    for snapshot in &session.windows {
+        let window = open_workspace_window(cx, None, |_window, cx| {
+            cx.new(|cx| Workspace::restore(snapshot, cx))
+        });
+        match window {
+            Ok(_) => opened = true,
+            Err(error) => tracing::error!("Cannot restore window: {}", error),
+        }
+    }
+    opened
+}
+
+/// Save every open window's tab groups and layout.
+fn save_session(cx: &mut App) {
+    let Some(windows) = cx.try_global::<WorkspaceWindows>().map(|w| w.0.clone()) else {
+        return;
+    };
+    let windows = windows
+        .iter()
+        .filter_map(|(_, workspace)| workspace.upgrade())
+        .map(|workspace| workspace.read(cx).snapshot(cx))
+        .collect();
+    settings::session::save_session(&SessionSnapshot { windows });
+}
+
+/// The workspace of a window other than `except` under `position` (screen
+/// coordinates).
+pub(crate) fn workspace_at(
+    position: Point<Pixels>,
+    except: AnyWindowHandle,
+    cx: &mut App,
+) -> Option<(AnyWindowHandle, Entity<Workspace>)> {
+    let windows = cx.try_global::<WorkspaceWindows>()?.0.clone();
+    windows
+        .into_iter()
+        .filter(|(handle, _)| *handle != except)
+        .find_map(|(handle, workspace)| {
+            let workspace = workspace.upgrade()?;
+            let bounds = handle.update(cx, |_, window, _| window.bounds()).ok()?;
+            bounds.contains(&position).then_some((handle, workspace))
+        })
+}
+
+/// The most recently opened workspace window that's still open.
+pub(crate) fn last_workspace_window(cx: &App) -> Option<AnyWindowHandle> {
+    let windows = &cx.try_global::<WorkspaceWindows>()?.0;
+    windows
+        .iter()
+        .rev()
+        .find(|(_, workspace)| workspace.upgrade().is_some())
+        .map(|(handle, _)| *handle)
+}
+
+/// Close the window holding `workspace` once its last tab is gone, or quit
+/// if it is the last window.
+pub(crate) fn close_window(workspace: &Entity<Workspace>, cx: &mut App) {
+    let handle = cx.try_global::<WorkspaceWindows>().and_then(|windows| {
+        windows
+            .0
+            .iter()
+            .find(|(_, ws)| ws.entity_id() == workspace.entity_id())
+            .map(|(handle, _)| *handle)
+    });
+    match handle {
+        Some(handle) if cx.windows().len() > 1 => {
+            // Removing the window while it's mid-update has to wait
+            cx.defer(move |cx| {
+                handle
+                    .update(cx, |_, window, _| window.remove_window())
+                    .ok();
+            });
+        }
+        _ => cx.quit(),
+    }
+}
@@ -2,15 +2,33 @@
 //!
 //! Tabs, split panes, and status bar.
 
+mod broadcast;
+mod cheat_sheet;
+mod cluster;
 mod command_palette;
+mod config_check;
+mod file_browser;
+mod floating;
+mod key_sequence;
 mod pane;
 mod pane_group;
 mod pane_group_view;
+mod picker;
+mod port_forwards;
+mod remote_control;
 mod scratchpad;
 mod settings_opener;
 mod status_bar;
+mod tab_switcher;
+mod windows;
 mod workspace_view;
 
+pub use config_check::{
+    check_config, check_config_without_app, refresh_config_diagnostics, ConfigDiagnostics,
+};
+pub use key_sequence::{set_key_sequences, shadowing_bindings, KeySequence};
+pub use remote_control::{send_remote_action, start_remote_control, CONTROL_ENV};
 pub use settings_opener::open_config_file;
 pub use status_bar::{render_status_bar, stats_collector, SystemStats};
-pub use workspace_view::Workspace;
+pub use windows::{open_workspace_window, restore_session, WindowOptionsBuilder};
+pub use workspace_view::{set_reopen_with_scrollback, Workspace};
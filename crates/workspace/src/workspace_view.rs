@@ -1,26 +1,49 @@
 //! Main workspace - container for tabs and split panes.
 
-use crate::command_palette::{CommandPalette, CommandPaletteDismiss};
+use crate::broadcast::BroadcastGroup;
+use crate::cheat_sheet::{CheatSheet, CheatSheetDismiss};
+use crate::cluster::{ClusterMember, ClusterPanelState};
+use crate::command_palette::{CommandEntry, CommandPalette, CommandPaletteDismiss};
+use crate::file_browser::{BrowserSource, FileBrowserPane};
+use crate::floating::{FloatingDrag, FloatingPane};
+use crate::key_sequence::{KeySequences, SequenceStep};
 use crate::pane::PaneKind;
-use crate::pane_group::{PaneNode, SplitDirection};
+use crate::pane_group::{PaneNode, SplitDirection, MIN_RATIO};
+use crate::port_forwards::{PortForwardsState, REFRESH_INTERVAL};
 use crate::scratchpad::ScratchpadState;
 use crate::status_bar::{render_status_bar, stats_collector, SystemStats};
+use crate::tab_switcher::{SwitcherEntry, TabSwitcher, TabSwitcherDismiss};
 use actions::{
-    ClosePane, CloseTab, FocusNextPane, FocusPrevPane, NewTab, NextTab, OpenReplay, OpenSettings,
-    PrevTab, Quit, SplitHorizontal, SplitVertical, ToggleCommandPalette, ToggleScratchpad,
+    AddPortForward, ClosePane, CloseTab, EqualizePanes, FocusNextPane, FocusPane, FocusPrevPane,
+    GoToLastTab, GoToTab, GoToTabGroup, MovePaneToNewTab, MovePaneToTab, MoveTabLeft, MoveTabRight,
+    NewTab, NewTabGroup, NewTabWithProfile, NextTab, NextTabGroup, OpenCluster, OpenFileBrowser,
+    OpenReplay, OpenSettings, PaneDirection, PrevTab, PrevTabGroup, Quit, RenameTab,
+    RenameTabGroup, ReopenClosedTab, ReplayRecording, ResizePane, RotatePanes, RunCommand,
+    RunTarget, SelectTheme, SetTabColor, ShowEffectiveConfig, SplitHorizontal, SplitVertical,
+    StopBroadcast, SwapPane, TabColor, ToggleBroadcastPane, ToggleBroadcastTab,
+    ToggleCommandPalette, ToggleFloatingPane, ToggleKeybindings, TogglePaneZoom, TogglePinTab,
+    TogglePortForwards, ToggleScratchpad, ToggleTabSwitcher,
 };
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    div, hsla, px, App, AppContext, ClickEvent, Context, ElementId, Entity, Focusable, FontWeight,
-    InteractiveElement, IntoElement, KeyDownEvent, ParentElement, Render, SharedString,
-    StatefulInteractiveElement, Styled, Subscription, Window,
+    div, hsla, px, App, AppContext, ClickEvent, Context, DragMoveEvent, ElementId, Entity,
+    Focusable, FontWeight, Global, InteractiveElement, IntoElement, KeyDownEvent, Keystroke,
+    MouseButton, MouseUpEvent, ParentElement, Pixels, Point, Render, SharedString,
+    StatefulInteractiveElement, Styled, Subscription, Task, WeakEntity, Window,
 };
 use gpui_component::button::{Button, ButtonVariants};
-use gpui_component::Root;
-use settings::constants::timing;
+use gpui_component::input::{InputEvent, InputState};
+use gpui_component::{Root, Sizable};
+use remote::{ForwardSpec, HostKey, HostKeyStatus, KnownHosts, PortForward, SftpClient, SshTarget};
+use settings::constants::{closed_tabs, timing};
+use settings::session::{LayoutSnapshot, TabGroupSnapshot, TabSnapshot, WindowSnapshot};
+use settings::{SerialConfig, TelnetConfig};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use terminal::reconnect::ReconnectTarget;
 #[cfg(not(test))]
 use terminal_view::TerminalExitEvent;
-use terminal_view::{TabBadge, TerminalPane};
+use terminal_view::{TabBadge, TerminalInputEvent, TerminalPane};
 use theme::terminal_colors;
 use uuid::Uuid;
 
@@ -55,6 +78,106 @@ enum PendingAction {
     ClosePane,
     CloseTab(usize),
     Quit,
+    /// First connection to a host: ask before trusting its key
+    TrustHostKey,
+    /// Host key changed or revoked: connection blocked, dismiss only
+    HostKeyMismatch,
+    /// Trusted key couldn't be written to known_hosts: dismiss only
+    HostKeySaveFailed,
+    /// Tried to close a pinned tab: says to unpin it first, dismiss only
+    PinnedTab,
+}
+
+/// A host key checked against `known_hosts` before connecting
+struct HostKeyCheck {
+    /// Name and port the key is recorded under, after applying the ssh config
+    known_host: (String, u16),
+    status: HostKeyStatus,
+    key: HostKey,
+    files: HostKeyFiles,
+}
+
+/// The known_hosts files behind a host key check, as the ssh config names them
+#[derive(Clone)]
+struct HostKeyFiles {
+    /// File holding the entry a changed or revoked key conflicts with
+    entry: PathBuf,
+    /// Where a trusted key is saved: the first `UserKnownHostsFile`
+    save_to: PathBuf,
+}
+
+/// Host key awaiting a decision in the confirmation dialog
+struct HostKeyPrompt {
+    target: SshTarget,
+    known_host: (String, u16),
+    key: HostKey,
+    status: HostKeyStatus,
+    files: HostKeyFiles,
+    /// Profile forwards to start once the session opens
+    forwards: Vec<ForwardSpec>,
+    /// Why the key couldn't be saved, once trusting it failed
+    save_error: Option<String>,
+    /// Cluster and member index this key is for, instead of a tab of its own
+    cluster: Option<(Uuid, usize)>,
+}
+
+/// A cluster member ready to open
+struct ClusterSession {
+    member: ClusterMember,
+    /// The connection, for a Telnet member
+    connection: Option<anyhow::Result<std::net::TcpStream>>,
+    /// Whether an SSH member's host key was checked first; `ssh` checks it otherwise
+    host_key_checked: bool,
+}
+
+/// A cluster waiting for host key decisions on some of its SSH members
+struct PendingCluster {
+    id: Uuid,
+    title: String,
+    /// `None` once a member's key is declined
+    members: Vec<Option<ClusterSession>>,
+    /// Host key prompts not yet answered
+    awaiting: usize,
+}
+
+impl HostKeyPrompt {
+    /// The confirmation this prompt shows as.
+    fn action(&self) -> PendingAction {
+        match (&self.save_error, &self.status) {
+            (Some(_), _) => PendingAction::HostKeySaveFailed,
+            (None, HostKeyStatus::Unknown) => PendingAction::TrustHostKey,
+            (None, _) => PendingAction::HostKeyMismatch,
+        }
+    }
+}
+
+/// What a name being edited in the tab bar belongs to
+#[derive(Clone, Copy, PartialEq)]
+enum RenameTarget {
+    Tab(Uuid),
+    /// Tab group, by index
+    Group(usize),
+}
+
+/// A tab or group name being edited in place in the tab bar
+struct TabRename {
+    target: RenameTarget,
+    input: Entity<InputState>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// Text and buttons for the confirmation dialog
+struct ConfirmDialog {
+    title: String,
+    message: String,
+    /// Key fingerprints, one per line
+    details: Vec<String>,
+    /// None for dismiss-only warnings
+    confirm_label: Option<&'static str>,
+    /// Confirm button uses danger styling
+    destructive: bool,
+    /// Loud styling; clicking outside does not dismiss
+    blocking: bool,
 }
 
 /// A single tab in the workspace
@@ -63,16 +186,77 @@ struct Tab {
     fallback_title: SharedString,
     panes: PaneNode,
     active_pane: Uuid,
+    /// SSH target if this tab was opened as a remote session
+    remote: Option<SshTarget>,
+    /// Whether the session's host key was checked before connecting; forwards
+    /// started from the tab check it the same way
+    host_key_checked: bool,
+    /// Pane shown alone at full size; zoom ends once another pane is active
+    zoomed: Option<Uuid>,
+    /// Terminal hovering over the split layout, once toggled on
+    floating: Option<FloatingPane>,
+    /// Name given by the user; shown instead of the pane's title
+    custom_title: Option<SharedString>,
+    /// Color marking the tab in the tab bar
+    color: Option<TabColor>,
+    /// Pinned tabs sit leftmost and can't be closed until unpinned
+    pinned: bool,
 }
 
 impl Tab {
+    /// A new tab showing `panes`, with its first pane active.
+    fn new(fallback_title: impl Into<SharedString>, panes: PaneNode) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            fallback_title: fallback_title.into(),
+            active_pane: panes.first_leaf_id(),
+            panes,
+            remote: None,
+            host_key_checked: false,
+            zoomed: None,
+            floating: None,
+            custom_title: None,
+            color: None,
+            pinned: false,
+        }
+    }
+
+    /// Every terminal in the tab, the floating one included.
+    fn terminals(&self) -> Vec<Entity<TerminalPane>> {
+        let mut terminals = layout_terminals(&self.panes);
+        terminals.extend(
+            self.floating
+                .as_ref()
+                .map(|floating| floating.terminal.clone()),
+        );
+        terminals
+    }
+
+    /// The tab's name without indicators: the user's name for it, else the
+    /// active pane's title, else the fallback.
+    fn base_title(&self, cx: &App) -> SharedString {
+        if let Some(title) = &self.custom_title {
+            return title.clone();
+        }
+        self.panes
+            .find_pane(self.active_pane)
+            .and_then(|pane| pane.title(cx))
+            .unwrap_or_else(|| self.fallback_title.clone())
+    }
+
+    /// The zoomed pane, if it's still the active one.
+    fn zoomed_pane(&self) -> Option<Uuid> {
+        self.zoomed.filter(|id| *id == self.active_pane)
+    }
+
     /// Get the display title for this tab (dynamic from pane or fallback)
     fn display_title(&self, cx: &App) -> SharedString {
-        let base_title = if let Some(pane) = self.panes.find_pane(self.active_pane) {
-            pane.title(cx)
-                .unwrap_or_else(|| self.fallback_title.clone())
+        let base_title = self.base_title(cx);
+
+        let base_title = if self.zoomed_pane().is_some() {
+            format!("[Z] {}", base_title).into()
         } else {
-            self.fallback_title.clone()
+            base_title
         };
 
         // Prepend recording indicator if the active pane is recording
@@ -101,16 +285,130 @@ impl Tab {
     }
 }
 
+/// The terminal panes of a layout.
+fn layout_terminals(panes: &PaneNode) -> Vec<Entity<TerminalPane>> {
+    panes
+        .all_panes()
+        .into_iter()
+        .filter_map(|(_, pane)| pane.as_terminal().cloned())
+        .collect()
+}
+
+/// A closed pane's place in its layout, and how to start it again
+enum ClosedLayout {
+    Pane {
+        cwd: Option<std::path::PathBuf>,
+        /// Remote session to reopen instead of a local shell
+        session: Option<ReconnectTarget>,
+        /// Buffer text to show above the new session
+        history: Option<String>,
+    },
+    Split {
+        direction: SplitDirection,
+        ratio: f32,
+        first: Box<ClosedLayout>,
+        second: Box<ClosedLayout>,
+    },
+}
+
+impl ClosedLayout {
+    /// Record `node`'s layout and sessions, with each pane's text if
+    /// `with_history`.
+    fn capture(node: &PaneNode, with_history: bool, cx: &App) -> Self {
+        match node {
+            PaneNode::Leaf { pane, .. } => Self::capture_pane(pane, with_history, cx),
+            PaneNode::Split {
+                direction,
+                first,
+                second,
+                ratio,
+            } => Self::Split {
+                direction: *direction,
+                ratio: *ratio,
+                first: Box::new(Self::capture(first, with_history, cx)),
+                second: Box::new(Self::capture(second, with_history, cx)),
+            },
+        }
+    }
+
+    fn capture_pane(pane: &PaneKind, with_history: bool, cx: &App) -> Self {
+        let terminal = pane.as_terminal().map(|terminal| terminal.read(cx));
+        Self::Pane {
+            cwd: pane.get_current_directory(cx),
+            session: terminal.and_then(|terminal| terminal.reconnect_target().cloned()),
+            history: terminal
+                .filter(|_| with_history)
+                .map(|terminal| terminal.buffer_text(closed_tabs::MAX_HISTORY_LINES))
+                .filter(|text| !text.is_empty()),
+        }
+    }
+}
+
+/// Whether closed tabs keep their text to show when reopened, from the
+/// applied config.
+struct ReopenWithScrollback(bool);
+
+impl Global for ReopenWithScrollback {}
+
+/// Set whether closed tabs keep their text, on startup and when the config
+/// is reloaded.
+pub fn set_reopen_with_scrollback(enabled: bool, cx: &mut App) {
+    cx.set_global(ReopenWithScrollback(enabled));
+}
+
+/// A closed tab, or a single closed pane, kept for Reopen Closed Tab
+struct ClosedTab {
+    /// Title when it closed, as listed in the command palette
+    title: SharedString,
+    custom_title: Option<SharedString>,
+    color: Option<TabColor>,
+    pinned: bool,
+    remote: Option<SshTarget>,
+    host_key_checked: bool,
+    layout: ClosedLayout,
+    /// For a closed pane, the tab it was in
+    pane_of: Option<Uuid>,
+}
+
+/// A named set of tabs within a window, such as "api" or "infra". The shown
+/// group's tabs live in [`Workspace::tabs`]; hidden groups hold theirs here,
+/// their processes still running.
+struct TabGroup {
+    name: SharedString,
+    tabs: Vec<Tab>,
+    active_tab: usize,
+}
+
+impl TabGroup {
+    fn new(name: impl Into<SharedString>) -> Self {
+        Self {
+            name: name.into(),
+            tabs: Vec::new(),
+            active_tab: 0,
+        }
+    }
+}
+
 /// The main workspace view containing the tab bar and terminal panes.
 pub struct Workspace {
     /// All open tabs, each containing a pane tree
     tabs: Vec<Tab>,
     /// Index of the currently active tab
     active_tab: usize,
+    /// Tab groups of this window; the shown one's tabs are `tabs`
+    groups: Vec<TabGroup>,
+    /// Index of the shown tab group
+    active_group: usize,
     /// Pending action requiring confirmation (shows dialog)
     pending_action: Option<PendingAction>,
     /// Process name for confirmation dialog
     pending_process_name: Option<String>,
+    /// Host key details for TrustHostKey / HostKeyMismatch / HostKeySaveFailed
+    pending_host_key: Option<HostKeyPrompt>,
+    /// Host key prompts waiting for the open confirmation to close
+    queued_host_keys: VecDeque<HostKeyPrompt>,
+    /// Clusters that open once their members' host keys are decided
+    pending_clusters: Vec<PendingCluster>,
     /// Last time we checked for exited panes (debounce)
     #[cfg_attr(test, allow(dead_code))]
     last_cleanup: std::time::Instant,
@@ -124,47 +422,215 @@ pub struct Workspace {
     cached_stats: SystemStats,
     /// Scratchpad (persistent notes overlay) state
     pub(crate) scratchpad: Option<ScratchpadState>,
+    /// Running SSH port forwards (stopped when dropped)
+    pub(crate) port_forwards: Vec<PortForward>,
+    /// Port forwarding panel state
+    pub(crate) port_forwards_panel: Option<PortForwardsState>,
+    /// Panes whose input is mirrored to each other
+    broadcast: BroadcastGroup,
+    /// Open Cluster panel state
+    pub(crate) cluster_panel: Option<ClusterPanelState>,
+    /// Tab or group whose name is being edited
+    renaming: Option<TabRename>,
     /// Command palette entity (Some when visible)
     command_palette: Option<Entity<CommandPalette>>,
     /// Subscription for command palette events (kept alive while palette is open)
     _command_palette_subscriptions: Vec<Subscription>,
+    /// Tab switcher entity (Some when visible)
+    tab_switcher: Option<Entity<TabSwitcher>>,
+    /// Subscription for tab switcher events (kept alive while it is open)
+    _tab_switcher_subscriptions: Vec<Subscription>,
+    /// Keybinding cheat sheet entity (Some when visible)
+    cheat_sheet: Option<Entity<CheatSheet>>,
+    /// Subscription for cheat sheet events (kept alive while it is open)
+    _cheat_sheet_subscriptions: Vec<Subscription>,
+    /// Pane ids, most recently used first
+    recent_panes: Vec<Uuid>,
+    /// Tab being dragged, to detach it if released outside the window
+    tab_drag: Option<Uuid>,
+    /// Closed tabs and panes, most recent first
+    closed_tabs: Vec<ClosedTab>,
+    /// Keys pressed so far of a multi-stroke key sequence
+    pending_keys: Vec<Keystroke>,
+    /// Cancels the pending key sequence when it times out
+    _pending_keys_timeout: Option<Task<()>>,
+    /// Exit and input subscriptions for each terminal in this window, dropped
+    /// when its tab moves to another window
+    terminal_subscriptions: Vec<(WeakEntity<TerminalPane>, Vec<Subscription>)>,
 }
 
 impl Workspace {
     /// Create a new workspace with a single tab containing one terminal pane.
     pub fn new(cx: &mut Context<Self>) -> Self {
         let terminal = cx.new(TerminalPane::new);
+        let panes = PaneNode::new_leaf(terminal.into());
 
-        // Subscribe to terminal exit events for immediate cleanup (non-test only)
-        #[cfg(not(test))]
-        cx.subscribe(&terminal, |this, _, _: &TerminalExitEvent, cx| {
-            this.force_cleanup(cx);
+        Self::with_tabs(vec![Tab::new("Terminal 1", panes)], cx)
+    }
+
+    /// Create a workspace around a tab moved in from another window.
+    fn from_tab(tab: Tab, cx: &mut Context<Self>) -> Self {
+        Self::with_tabs(vec![tab], cx)
+    }
+
+    fn with_tabs(tabs: Vec<Tab>, cx: &mut Context<Self>) -> Self {
+        // Tear down tunnels on quit; their ssh processes would otherwise outlive the app
+        cx.on_app_quit(|this, _cx| {
+            this.port_forwards.clear();
+            async {}
         })
         .detach();
 
-        let panes = PaneNode::new_leaf(terminal.into());
-        let active_pane = panes.first_leaf_id();
-
-        let tab = Tab {
-            id: Uuid::new_v4(),
-            fallback_title: "Terminal 1".into(),
-            panes,
-            active_pane,
-        };
+        let cached_titles = tabs.iter().map(|tab| tab.display_title(cx)).collect();
 
-        Self {
-            tabs: vec![tab],
+        let mut workspace = Self {
+            tabs,
             active_tab: 0,
+            groups: vec![TabGroup::new("Default")],
+            active_group: 0,
             pending_action: None,
             pending_process_name: None,
+            pending_host_key: None,
+            queued_host_keys: VecDeque::new(),
+            pending_clusters: Vec::new(),
             last_cleanup: std::time::Instant::now(),
-            cached_titles: vec!["Terminal 1".into()],
+            cached_titles,
             last_title_update: std::time::Instant::now(),
             last_saved_bounds: None,
             cached_stats: SystemStats::default(),
             scratchpad: None,
+            port_forwards: Vec::new(),
+            port_forwards_panel: None,
+            broadcast: BroadcastGroup::default(),
+            cluster_panel: None,
+            renaming: None,
             command_palette: None,
             _command_palette_subscriptions: Vec::new(),
+            tab_switcher: None,
+            _tab_switcher_subscriptions: Vec::new(),
+            cheat_sheet: None,
+            _cheat_sheet_subscriptions: Vec::new(),
+            recent_panes: Vec::new(),
+            tab_drag: None,
+            closed_tabs: Vec::new(),
+            pending_keys: Vec::new(),
+            _pending_keys_timeout: None,
+            terminal_subscriptions: Vec::new(),
+        };
+        let terminals: Vec<_> = workspace.tabs.iter().flat_map(Tab::terminals).collect();
+        workspace.watch_terminals(&terminals, cx);
+        workspace
+    }
+
+    /// Rebuild a window from a saved session, starting a shell in each
+    /// pane's saved directory.
+    pub(crate) fn restore(snapshot: &WindowSnapshot, cx: &mut Context<Self>) -> Self {
+        let mut groups = Vec::new();
+        let mut active_group = 0;
+        for (index, group) in snapshot.groups.iter().enumerate() {
+            if group.tabs.is_empty() {
+                continue;
+            }
+            if index == snapshot.active_group {
+                active_group = groups.len();
+            }
+            let tabs: Vec<Tab> = group
+                .tabs
+                .iter()
+                .enumerate()
+                .map(|(i, tab)| Self::restore_tab(i, tab, cx))
+                .collect();
+            groups.push(TabGroup {
+                name: group.name.clone().into(),
+                active_tab: group.active_tab.min(tabs.len() - 1),
+                tabs,
+            });
+        }
+        if groups.is_empty() {
+            return Self::new(cx);
+        }
+
+        let shown = &mut groups[active_group];
+        let tabs = std::mem::take(&mut shown.tabs);
+        let active_tab = shown.active_tab;
+        let mut workspace = Self::with_tabs(tabs, cx);
+        workspace.active_tab = active_tab;
+        let hidden: Vec<_> = groups
+            .iter()
+            .flat_map(|group| &group.tabs)
+            .flat_map(Tab::terminals)
+            .collect();
+        workspace.watch_terminals(&hidden, cx);
+        workspace.groups = groups;
+        workspace.active_group = active_group;
+        workspace
+    }
+
+    fn restore_tab(index: usize, snapshot: &TabSnapshot, cx: &mut Context<Self>) -> Tab {
+        let panes = Self::restore_layout(&snapshot.layout, cx);
+        let mut tab = Tab::new(format!("Terminal {}", index + 1), panes);
+        tab.custom_title = snapshot.title.clone().map(Into::into);
+        tab.color = snapshot.color.as_deref().and_then(TabColor::from_name);
+        tab.pinned = snapshot.pinned;
+        tab
+    }
+
+    fn restore_layout(layout: &LayoutSnapshot, cx: &mut Context<Self>) -> PaneNode {
+        match layout {
+            LayoutSnapshot::Pane { cwd } => {
+                // The directory may be gone since the session was saved
+                let cwd = cwd.clone().filter(|dir| dir.is_dir());
+                let terminal = cx.new(|cx| TerminalPane::new_in_dir(cx, cwd));
+                PaneNode::new_leaf(terminal.into())
+            }
+            LayoutSnapshot::Split {
+                horizontal,
+                ratio,
+                first,
+                second,
+            } => PaneNode::Split {
+                direction: if *horizontal {
+                    SplitDirection::Horizontal
+                } else {
+                    SplitDirection::Vertical
+                },
+                first: Box::new(Self::restore_layout(first, cx)),
+                second: Box::new(Self::restore_layout(second, cx)),
+                ratio: ratio.clamp(MIN_RATIO, 1.0 - MIN_RATIO),
+            },
+        }
+    }
+
+    /// This window's groups, tabs and split layouts, for session restore.
+    pub(crate) fn snapshot(&self, cx: &App) -> WindowSnapshot {
+        let groups = self
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(index, group)| {
+                let (tabs, active_tab) = if index == self.active_group {
+                    (&self.tabs, self.active_tab)
+                } else {
+                    (&group.tabs, group.active_tab)
+                };
+                TabGroupSnapshot {
+                    name: group.name.to_string(),
+                    tabs: tabs
+                        .iter()
+                        .map(|tab| TabSnapshot {
+                            title: tab.custom_title.as_ref().map(ToString::to_string),
+                            color: tab.color.map(|color| color.name().to_string()),
+                            pinned: tab.pinned,
+                            layout: layout_snapshot(&tab.panes, cx),
+                        })
+                        .collect(),
+                    active_tab,
+                }
+            })
+            .collect();
+        WindowSnapshot {
+            groups,
+            active_group: self.active_group,
         }
     }
 
@@ -207,6 +673,11 @@ impl Workspace {
         self.cached_titles.clone()
     }
 
+    /// Recompute tab titles on the next render.
+    fn invalidate_tab_titles(&mut self) {
+        self.last_title_update = std::time::Instant::now() - timing::TITLE_CACHE_TTL;
+    }
+
     /// Check if a tab has any running child processes
     fn tab_has_running_processes(&self, index: usize, cx: &App) -> bool {
         if let Some(tab) = self.tabs.get(index) {
@@ -231,28 +702,49 @@ impl Workspace {
         None
     }
 
-    /// Check if any tab has running processes
+    /// Every tab of every group, with its group, shown group's tabs first.
+    fn grouped_tabs(&self) -> impl Iterator<Item = (&TabGroup, &Tab)> {
+        let shown = &self.groups[self.active_group];
+        let hidden = self
+            .groups
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != self.active_group)
+            .flat_map(|(_, group)| group.tabs.iter().map(move |tab| (group, tab)));
+        self.tabs.iter().map(move |tab| (shown, tab)).chain(hidden)
+    }
+
+    /// Every tab of every group, shown group's tabs first.
+    fn all_tabs(&self) -> impl Iterator<Item = &Tab> {
+        self.grouped_tabs().map(|(_, tab)| tab)
+    }
+
+    /// Check if any tab in any group has running processes
     fn any_tab_has_running_processes(&self, cx: &App) -> bool {
-        for i in 0..self.tabs.len() {
-            if self.tab_has_running_processes(i, cx) {
-                return true;
-            }
-        }
-        false
+        self.all_tabs().any(|tab| {
+            tab.panes
+                .all_panes()
+                .iter()
+                .any(|(_, pane)| pane.has_running_processes(cx))
+        })
     }
 
-    /// Get the name of any running process across all tabs
+    /// Get the name of any running process across all tabs and groups
     fn get_any_running_process_name(&self, cx: &App) -> Option<String> {
-        for i in 0..self.tabs.len() {
-            if let Some(name) = self.get_tab_running_process_name(i, cx) {
-                return Some(name);
-            }
-        }
-        None
+        self.all_tabs().find_map(|tab| {
+            tab.panes
+                .all_panes()
+                .iter()
+                .find_map(|(_, pane)| pane.get_running_process_name(cx))
+        })
     }
 
     /// Request to close a tab (with confirmation if needed)
     fn request_close_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if self.is_pinned(index) {
+            self.show_pinned_notice(cx);
+            return;
+        }
         if self.tab_has_running_processes(index, cx) {
             self.pending_process_name = self.get_tab_running_process_name(index, cx);
             self.pending_action = Some(PendingAction::CloseTab(index));
@@ -277,229 +769,1744 @@ impl Workspace {
     fn confirm_pending_action(&mut self, cx: &mut Context<Self>) {
         if let Some(action) = self.pending_action.take() {
             self.pending_process_name = None;
+            let host_key = self.pending_host_key.take();
             match action {
                 PendingAction::ClosePane => self.do_close_pane(cx),
                 PendingAction::CloseTab(index) => self.close_tab(index, cx),
                 PendingAction::Quit => cx.quit(),
+                PendingAction::TrustHostKey => {
+                    if let Some(prompt) = host_key {
+                        self.accept_host_key(prompt, cx);
+                    }
+                }
+                PendingAction::PinnedTab => cx.notify(),
+                // Nothing to confirm: a mismatched key is never accepted from here
+                PendingAction::HostKeyMismatch | PendingAction::HostKeySaveFailed => {
+                    if let Some(member) = host_key.and_then(|prompt| prompt.cluster) {
+                        self.decide_cluster_member(member, false, cx);
+                    }
+                    cx.notify();
+                }
             }
         }
+        self.show_next_host_key_prompt(cx);
+    }
+
+    /// Save an accepted host key off the main thread, then connect.
+    fn accept_host_key(&mut self, mut prompt: HostKeyPrompt, cx: &mut Context<Self>) {
+        let (host, port) = prompt.known_host.clone();
+        let key = prompt.key.clone();
+        let path = prompt.files.save_to.clone();
+        let saved = cx.background_spawn(async move { trust_host_key(&path, &host, port, &key) });
+        cx.spawn(async move |this, cx| {
+            let result = saved.await;
+            this.update(cx, |this, cx| match result {
+                Ok(()) => match prompt.cluster {
+                    Some(member) => this.decide_cluster_member(member, true, cx),
+                    None => this.open_ssh_tab(&prompt.target, prompt.forwards, true, cx),
+                },
+                // ssh would refuse the unsaved key anyway; say why instead
+                Err(error) => {
+                    prompt.save_error = Some(error);
+                    this.show_host_key_prompt(prompt, cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
     }
 
     /// Cancel the pending action
     fn cancel_pending_action(&mut self, cx: &mut Context<Self>) {
         self.pending_action = None;
         self.pending_process_name = None;
+        if let Some(member) = self
+            .pending_host_key
+            .take()
+            .and_then(|prompt| prompt.cluster)
+        {
+            self.decide_cluster_member(member, false, cx);
+        }
+        self.show_next_host_key_prompt(cx);
         cx.notify();
     }
 
-    fn toggle_command_palette(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        if self.command_palette.is_some() {
-            self.dismiss_command_palette(cx);
-        } else {
-            let palette = cx.new(CommandPalette::new);
-
-            let sub = cx.subscribe_in(
-                &palette,
-                window,
-                |this, _palette, event: &CommandPaletteDismiss, window, cx| {
-                    let action = event.action.as_ref().map(|a| a.boxed_clone());
-                    this.dismiss_command_palette(cx);
-
-                    // Restore focus to active pane before dispatching so the
-                    // action finds its handler in the focus chain
-                    if let Some(tab) = this.tabs.get(this.active_tab) {
-                        if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
-                            window.focus(&pane.focus_handle(cx));
-                        }
-                    }
-
-                    if let Some(action) = action {
-                        window.dispatch_action(action, cx);
-                    }
-                },
-            );
-
-            self._command_palette_subscriptions = vec![sub];
-
-            let focus = palette.read(cx).focus_handle.clone();
-            window.focus(&focus);
-            self.command_palette = Some(palette);
+    /// Say that a pinned tab has to be unpinned before it closes.
+    fn show_pinned_notice(&mut self, cx: &mut Context<Self>) {
+        if self.pending_action.is_none() {
+            self.pending_process_name = None;
+            self.pending_action = Some(PendingAction::PinnedTab);
             cx.notify();
         }
     }
 
-    fn dismiss_command_palette(&mut self, cx: &mut Context<Self>) {
-        self.command_palette = None;
-        self._command_palette_subscriptions.clear();
+    /// Ask about a host key now, or once the open confirmation closes.
+    fn show_host_key_prompt(&mut self, prompt: HostKeyPrompt, cx: &mut Context<Self>) {
+        if self.pending_action.is_some() {
+            self.queued_host_keys.push_back(prompt);
+            return;
+        }
+        self.pending_process_name = None;
+        self.pending_action = Some(prompt.action());
+        self.pending_host_key = Some(prompt);
         cx.notify();
     }
 
-    /// Get the current working directory from the active pane of the active tab.
-    fn get_active_pane_cwd(&self, cx: &App) -> Option<std::path::PathBuf> {
-        let tab = self.tabs.get(self.active_tab)?;
-        let pane = tab.panes.find_pane(tab.active_pane)?;
-        pane.get_current_directory(cx)
+    /// Show the next queued host key prompt if nothing else is being confirmed.
+    fn show_next_host_key_prompt(&mut self, cx: &mut Context<Self>) {
+        if self.pending_action.is_none() {
+            if let Some(prompt) = self.queued_host_keys.pop_front() {
+                self.show_host_key_prompt(prompt, cx);
+            }
+        }
     }
 
-    fn new_tab(&mut self, cx: &mut Context<Self>) {
-        // Get working directory from the active pane (if any) for better UX
-        let working_dir = self.get_active_pane_cwd(cx);
+    /// Build the confirmation dialog for the pending action, if any.
+    fn confirm_dialog(&self) -> Option<ConfirmDialog> {
+        let action = self.pending_action?;
+        let dialog = match action {
+            PendingAction::ClosePane | PendingAction::CloseTab(_) | PendingAction::Quit => {
+                let action_text = match action {
+                    PendingAction::ClosePane => "close this pane",
+                    PendingAction::CloseTab(_) => "close this tab",
+                    _ => "quit",
+                };
+                let process_name = self
+                    .pending_process_name
+                    .clone()
+                    .unwrap_or_else(|| "a process".to_string());
+                ConfirmDialog {
+                    title: format!("\"{}\" is running", process_name),
+                    message: format!(
+                        "Are you sure you want to {}? The running process will be terminated.",
+                        action_text
+                    ),
+                    details: Vec::new(),
+                    confirm_label: Some(if action == PendingAction::Quit {
+                        "Quit"
+                    } else {
+                        "Close"
+                    }),
+                    destructive: true,
+                    blocking: false,
+                }
+            }
+            PendingAction::TrustHostKey => {
+                let prompt = self.pending_host_key.as_ref()?;
+                ConfirmDialog {
+                    title: format!("Unknown host {}", prompt.target.display_name()),
+                    message: format!(
+                        "The authenticity of host '{}' can't be established. Only continue if this \
+                         fingerprint matches the one you expect. The key will be saved to {}.",
+                        prompt.target.host,
+                        prompt.files.save_to.display()
+                    ),
+                    details: vec![format!(
+                        "{} {}",
+                        prompt.key.key_type,
+                        prompt.key.fingerprint()
+                    )],
+                    confirm_label: Some("Trust and Connect"),
+                    destructive: false,
+                    blocking: false,
+                }
+            }
+            PendingAction::HostKeyMismatch => {
+                let prompt = self.pending_host_key.as_ref()?;
+                let host = &prompt.target.host;
+                let (known_name, known_port) = &prompt.known_host;
+                let lookup_name =
+                    remote::known_hosts::host_name_for_lookup(known_name, *known_port);
+                let entry_file = prompt.files.entry.display();
+                // ssh-keygen edits ~/.ssh/known_hosts unless told otherwise
+                let remove_command =
+                    if KnownHosts::default_path().as_ref() == Some(&prompt.files.entry) {
+                        format!("ssh-keygen -R {}", lookup_name)
+                    } else {
+                        format!("ssh-keygen -f {} -R {}", entry_file, lookup_name)
+                    };
+                let mut details = vec![format!(
+                    "Offered: {} {}",
+                    prompt.key.key_type,
+                    prompt.key.fingerprint()
+                )];
+                let (title, message) = match &prompt.status {
+                    HostKeyStatus::Changed {
+                        line,
+                        known_fingerprint,
+                    } => {
+                        details.push(format!(
+                            "Known:   {} ({} line {})",
+                            known_fingerprint, entry_file, line
+                        ));
+                        (
+                            format!("WARNING: host key for {} has changed!", host),
+                            format!(
+                                "Someone could be eavesdropping on you right now (man-in-the-middle \
+                                 attack), or the host key has just been changed. The connection was \
+                                 blocked. If the change is expected, remove the old key with \
+                                 `{}` and connect again.",
+                                remove_command
+                            ),
+                        )
+                    }
+                    HostKeyStatus::Revoked { line } => (
+                        format!("WARNING: host key for {} has been revoked!", host),
+                        format!(
+                            "The key offered by '{}' is marked @revoked in {} (line {}). \
+                             The connection was blocked. Contact the server's administrator.",
+                            host, entry_file, line
+                        ),
+                    ),
+                    HostKeyStatus::Trusted | HostKeyStatus::Unknown => return None,
+                };
+                ConfirmDialog {
+                    title,
+                    message,
+                    details,
+                    confirm_label: None,
+                    destructive: false,
+                    blocking: true,
+                }
+            }
+            PendingAction::PinnedTab => ConfirmDialog {
+                title: "This tab is pinned".to_string(),
+                message: "Pinned tabs don't close by accident. Unpin the tab first to close it."
+                    .to_string(),
+                details: Vec::new(),
+                confirm_label: None,
+                destructive: false,
+                blocking: false,
+            },
+            PendingAction::HostKeySaveFailed => {
+                let prompt = self.pending_host_key.as_ref()?;
+                ConfirmDialog {
+                    title: format!(
+                        "Could not save the host key for {}",
+                        prompt.target.display_name()
+                    ),
+                    message: format!(
+                        "{}. The key was not trusted and no connection was opened. Check that \
+                         {} is writable and connect again.",
+                        prompt.save_error.as_deref().unwrap_or("Unknown error"),
+                        prompt.files.save_to.display()
+                    ),
+                    details: vec![format!(
+                        "{} {}",
+                        prompt.key.key_type,
+                        prompt.key.fingerprint()
+                    )],
+                    confirm_label: None,
+                    destructive: false,
+                    blocking: false,
+                }
+            }
+        };
+        Some(dialog)
+    }
 
-        let terminal = cx.new(|cx| TerminalPane::new_in_dir(cx, working_dir));
+    /// Connect to a profile by name, a serial device, a `telnet://` or
+    /// `tcp://` URL, or a `[user@]host[:port]` target.
+    ///
+    /// Profiles with a `host` also start their port forwards; profiles with
+    /// `[profiles.serial]` or `[profiles.telnet]` open those sessions instead.
+    pub fn connect(&mut self, destination: &str, cx: &mut Context<Self>) {
+        let config = settings::load_config();
+        if let Some(profile) = config.profiles.iter().find(|p| p.name == destination) {
+            if let Some(serial) = &profile.serial {
+                self.new_tab_with_serial(serial, cx);
+                return;
+            }
+            if let Some(telnet) = &profile.telnet {
+                self.connect_telnet(telnet.clone(), cx);
+                return;
+            }
+        }
 
-        // Subscribe to terminal exit events for immediate cleanup (non-test only)
-        #[cfg(not(test))]
-        cx.subscribe(&terminal, |this, _, _: &TerminalExitEvent, cx| {
-            this.force_cleanup(cx);
+        let profiles = crate::port_forwards::ssh_profiles(&config);
+        if let Some(profile) = profiles.into_iter().find(|p| p.name == destination) {
+            self.connect_ssh_with_forwards(profile.target, profile.forwards, cx);
+        } else if let Some(serial) = SerialConfig::parse(destination) {
+            self.new_tab_with_serial(&serial, cx);
+        } else if let Some(telnet) = TelnetConfig::parse(destination) {
+            self.connect_telnet(telnet, cx);
+        } else if let Some(target) = SshTarget::parse(destination) {
+            self.connect_ssh(target, cx);
+        } else {
+            tracing::error!(
+                "'{}' is not a profile, serial device, telnet:// or tcp:// URL, or SSH target",
+                destination
+            );
+        }
+    }
+
+    /// Open a Telnet or raw TCP session in a new tab once the socket connects.
+    ///
+    /// Connecting happens off the main thread; failures show in the tab.
+    pub fn connect_telnet(&mut self, telnet: TelnetConfig, cx: &mut Context<Self>) {
+        let connect_config = telnet.clone();
+        let connection =
+            cx.background_spawn(async move { terminal::telnet::connect(&connect_config) });
+
+        cx.spawn(async move |this, cx| {
+            let connection = connection.await;
+            this.update(cx, |this, cx| {
+                let title = telnet.display_name();
+                let terminal = cx.new(move |cx| {
+                    let mut pane = TerminalPane::new_telnet(cx, &telnet, connection);
+                    pane.set_reconnect_target(ReconnectTarget::Telnet(telnet), cx);
+                    pane
+                });
+                this.push_session_tab(terminal, title, cx);
+            })
+            .ok();
         })
         .detach();
+    }
 
-        let panes = PaneNode::new_leaf(terminal.into());
-        let active_pane = panes.first_leaf_id();
-        let tab_num = self.tabs.len() + 1;
+    /// Open a cluster in a new tab: one pane per member in a balanced grid,
+    /// with broadcast input on for the tab.
+    ///
+    /// `spec` is a profile group or a comma-separated member list (see
+    /// [`crate::cluster`]). Telnet members connect and SSH members' host keys
+    /// are checked off the main thread, all at once; the tab appears when
+    /// they're done and any untrusted keys have been asked about.
+    pub fn open_cluster(&mut self, spec: &str, cx: &mut Context<Self>) -> Result<(), String> {
+        let (title, members) = crate::cluster::resolve_cluster(spec, &settings::load_config())?;
+        tracing::info!(
+            "Opening cluster '{}': {}",
+            title,
+            members
+                .iter()
+                .map(ClusterMember::display_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
 
-        let tab = Tab {
-            id: Uuid::new_v4(),
-            fallback_title: format!("Terminal {}", tab_num).into(),
-            panes,
-            active_pane,
-        };
-        self.tabs.push(tab);
-        self.active_tab = self.tabs.len() - 1;
-        cx.notify();
+        let connections: Vec<_> = members
+            .iter()
+            .map(|member| match member {
+                ClusterMember::Telnet(config) => {
+                    let config = config.clone();
+                    Some(cx.background_spawn(async move { terminal::telnet::connect(&config) }))
+                }
+                _ => None,
+            })
+            .collect();
+        let host_keys: Vec<_> = members
+            .iter()
+            .map(|member| match member {
+                ClusterMember::Ssh(target) => Some(Self::verify_host_key(target.clone(), cx)),
+                _ => None,
+            })
+            .collect();
+
+        cx.spawn(async move |this, cx| {
+            let mut connected = Vec::with_capacity(connections.len());
+            for connection in connections {
+                connected.push(match connection {
+                    Some(task) => Some(task.await),
+                    None => None,
+                });
+            }
+            let mut verified = Vec::with_capacity(host_keys.len());
+            for check in host_keys {
+                verified.push(match check {
+                    Some(task) => Some(task.await),
+                    None => None,
+                });
+            }
+            this.update(cx, |this, cx| {
+                this.on_cluster_checked(title, members, connected, verified, cx);
+            })
+            .ok();
+        })
+        .detach();
+        Ok(())
     }
 
-    /// Create a new tab running a specific command.
-    pub fn new_tab_with_command(
+    /// Open a checked cluster, first asking about any SSH member whose host
+    /// key isn't trusted yet. Like a single session, a member that couldn't be
+    /// checked leaves it to `ssh` to check the key.
+    fn on_cluster_checked(
         &mut self,
-        command: &str,
-        args: &[&str],
-        title: &str,
+        title: String,
+        members: Vec<ClusterMember>,
+        connections: Vec<Option<anyhow::Result<std::net::TcpStream>>>,
+        host_keys: Vec<Option<anyhow::Result<Option<HostKeyCheck>>>>,
         cx: &mut Context<Self>,
     ) {
-        let cmd = command.to_string();
-        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-        let title_owned: SharedString = title.to_string().into();
+        let id = Uuid::new_v4();
+        let mut prompts = Vec::new();
+        let mut sessions = Vec::with_capacity(members.len());
+        for (index, ((member, connection), checked)) in members
+            .into_iter()
+            .zip(connections)
+            .zip(host_keys)
+            .enumerate()
+        {
+            let host_key_checked = matches!(checked, Some(Ok(Some(_))));
+            if let (ClusterMember::Ssh(target), Some(Ok(Some(check)))) = (&member, checked) {
+                if check.status != HostKeyStatus::Trusted {
+                    prompts.push(HostKeyPrompt {
+                        target: target.clone(),
+                        known_host: check.known_host,
+                        key: check.key,
+                        status: check.status,
+                        files: check.files,
+                        forwards: Vec::new(),
+                        save_error: None,
+                        cluster: Some((id, index)),
+                    });
+                }
+            }
+            sessions.push(ClusterSession {
+                member,
+                connection,
+                host_key_checked,
+            });
+        }
+        if prompts.is_empty() {
+            self.push_cluster_tab(title, sessions, cx);
+            return;
+        }
 
-        let terminal = cx.new(move |cx| {
-            let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-            TerminalPane::new_with_command(cx, &cmd, &args_refs)
+        self.pending_clusters.push(PendingCluster {
+            id,
+            title,
+            members: sessions.into_iter().map(Some).collect(),
+            awaiting: prompts.len(),
         });
+        for prompt in prompts {
+            self.show_host_key_prompt(prompt, cx);
+        }
+    }
 
-        #[cfg(not(test))]
-        cx.subscribe(&terminal, |this, _, _: &TerminalExitEvent, cx| {
-            this.force_cleanup(cx);
+    /// Record the answer to a cluster member's host key prompt. The cluster
+    /// opens once every prompt is answered, without the declined members.
+    fn decide_cluster_member(
+        &mut self,
+        (id, index): (Uuid, usize),
+        trusted: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(position) = self
+            .pending_clusters
+            .iter()
+            .position(|cluster| cluster.id == id)
+        else {
+            return;
+        };
+        let cluster = &mut self.pending_clusters[position];
+        if !trusted {
+            cluster.members[index] = None;
+        }
+        cluster.awaiting -= 1;
+        if cluster.awaiting > 0 {
+            return;
+        }
+
+        let cluster = self.pending_clusters.remove(position);
+        let sessions = cluster.members.into_iter().flatten().collect();
+        self.push_cluster_tab(cluster.title, sessions, cx);
+    }
+
+    /// Add a tab holding a grid of cluster sessions and broadcast to all of it.
+    fn push_cluster_tab(
+        &mut self,
+        title: String,
+        sessions: Vec<ClusterSession>,
+        cx: &mut Context<Self>,
+    ) {
+        let terminals: Vec<Entity<TerminalPane>> = sessions
+            .into_iter()
+            .map(|session| match session.member {
+                ClusterMember::Ssh(target) => cx.new(move |cx| {
+                    let ssh_args = if session.host_key_checked {
+                        target.ssh_args()
+                    } else {
+                        target.ssh_args_unverified()
+                    };
+                    let args: Vec<&str> = ssh_args.iter().map(String::as_str).collect();
+                    let mut pane = TerminalPane::new_with_command(cx, "ssh", &args);
+                    pane.set_reconnect_target(ReconnectTarget::Ssh { args: ssh_args }, cx);
+                    pane
+                }),
+                ClusterMember::Telnet(telnet) => cx.new(move |cx| {
+                    let connection = session
+                        .connection
+                        .unwrap_or_else(|| Err(anyhow::anyhow!("Not connected")));
+                    let mut pane = TerminalPane::new_telnet(cx, &telnet, connection);
+                    pane.set_reconnect_target(ReconnectTarget::Telnet(telnet), cx);
+                    pane
+                }),
+                ClusterMember::Command { program, args } => cx.new(move |cx| {
+                    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                    TerminalPane::new_with_command(cx, &program, &args)
+                }),
+            })
+            .collect();
+
+        self.watch_terminals(&terminals, cx);
+        let Some(panes) = PaneNode::grid(terminals.into_iter().map(PaneKind::from).collect())
+        else {
+            return;
+        };
+        let pane_ids: Vec<Uuid> = panes.all_panes().into_iter().map(|(id, _)| id).collect();
+
+        let tab = Tab::new(title, panes);
+        self.broadcast.toggle_tab(tab.id, &pane_ids);
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        self.sync_broadcast(cx);
+    }
+
+    /// Open an SSH session in a new tab, verifying the host key first.
+    ///
+    /// Keys are scanned off the main thread. A known key connects straight
+    /// away; an unknown key asks for confirmation; a changed or revoked key
+    /// blocks the connection. A host that can't be scanned (behind a proxy, or
+    /// unreachable) is left to `ssh` to check.
+    pub fn connect_ssh(&mut self, target: SshTarget, cx: &mut Context<Self>) {
+        self.connect_ssh_with_forwards(target, Vec::new(), cx);
+    }
+
+    /// Like [`Workspace::connect_ssh`], starting `forwards` once the session opens.
+    fn connect_ssh_with_forwards(
+        &mut self,
+        target: SshTarget,
+        forwards: Vec<ForwardSpec>,
+        cx: &mut Context<Self>,
+    ) {
+        let verification = Self::verify_host_key(target.clone(), cx);
+        cx.spawn(async move |this, cx| {
+            let result = verification.await;
+            this.update(cx, |this, cx| {
+                this.on_host_key_verified(target, forwards, result, cx)
+            })
+            .ok();
         })
         .detach();
+    }
+
+    /// Resolve `target` through the ssh config, then scan its host keys off
+    /// the main thread and check them against the known_hosts files the
+    /// config names. `None` if the host is behind a proxy `ssh-keyscan` can't
+    /// follow, or offered no keys.
+    fn verify_host_key(
+        target: SshTarget,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<Option<HostKeyCheck>>> {
+        cx.background_spawn(async move {
+            let host = remote::resolve_host(&target)?;
+            if host.proxied {
+                return anyhow::Ok(None);
+            }
+            let save_to = host
+                .trusted_keys_file()
+                .ok_or_else(|| anyhow::anyhow!("No UserKnownHostsFile to save keys to"))?
+                .to_path_buf();
+            let keys = remote::scan_host_keys(&host)?;
+            // Like ssh, skip files that can't be read
+            let known_hosts: Vec<KnownHosts> = host
+                .known_hosts_files()
+                .filter_map(|path| match KnownHosts::load(path) {
+                    Ok(known_hosts) => Some(known_hosts),
+                    Err(error) => {
+                        tracing::warn!("Cannot read {}: {}", path.display(), error);
+                        None
+                    }
+                })
+                .collect();
+            let (name, port) = host.known_hosts_name();
+            Ok(KnownHosts::verify_all(&known_hosts, name, port, &keys).map(
+                |(status, key, entry)| HostKeyCheck {
+                    known_host: (name.to_string(), port),
+                    status,
+                    key,
+                    files: HostKeyFiles {
+                        entry: entry.to_path_buf(),
+                        save_to,
+                    },
+                },
+            ))
+        })
+    }
+
+    /// Act on the outcome of a host key check.
+    fn on_host_key_verified(
+        &mut self,
+        target: SshTarget,
+        forwards: Vec<ForwardSpec>,
+        result: anyhow::Result<Option<HostKeyCheck>>,
+        cx: &mut Context<Self>,
+    ) {
+        let HostKeyCheck {
+            known_host,
+            status,
+            key,
+            files,
+        } = match result {
+            Ok(Some(check)) => check,
+            Ok(None) => {
+                tracing::info!(
+                    "Host key for {} not pre-checked; ssh will check it",
+                    target.display_name()
+                );
+                self.open_ssh_tab(&target, forwards, false, cx);
+                return;
+            }
+            Err(error) => {
+                // Host unreachable or ssh-keyscan missing. Leave the key to ssh, which
+                // either shows why it can't connect or asks about the key itself.
+                tracing::warn!("Host key pre-check failed: {:#}", error);
+                self.open_ssh_tab(&target, forwards, false, cx);
+                return;
+            }
+        };
+
+        match status {
+            HostKeyStatus::Trusted => {
+                self.open_ssh_tab(&target, forwards, true, cx);
+                return;
+            }
+            HostKeyStatus::Unknown => {}
+            HostKeyStatus::Changed { .. } | HostKeyStatus::Revoked { .. } => {
+                tracing::warn!(
+                    "Host key mismatch for {} ({}): connection blocked",
+                    target.display_name(),
+                    key.fingerprint()
+                );
+            }
+        }
+
+        let prompt = HostKeyPrompt {
+            target,
+            known_host,
+            key,
+            status,
+            files,
+            forwards,
+            save_error: None,
+            cluster: None,
+        };
+        self.show_host_key_prompt(prompt, cx);
+    }
+
+    /// Spawn `ssh` for a target in a new tab, then start its forwards. Unless
+    /// the host key was checked first, `ssh` checks it itself.
+    fn open_ssh_tab(
+        &mut self,
+        target: &SshTarget,
+        forwards: Vec<ForwardSpec>,
+        host_key_checked: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let ssh_args = if host_key_checked {
+            target.ssh_args()
+        } else {
+            target.ssh_args_unverified()
+        };
+        let args: Vec<&str> = ssh_args.iter().map(|s| s.as_str()).collect();
+        self.new_tab_with_command("ssh", &args, &target.display_name(), cx);
+        if let Some(tab) = self.tabs.last_mut() {
+            tab.remote = Some(target.clone());
+            tab.host_key_checked = host_key_checked;
+            if let Some(terminal) = tab
+                .panes
+                .find_pane(tab.active_pane)
+                .and_then(|pane| pane.as_terminal().cloned())
+            {
+                terminal.update(cx, |terminal, cx| {
+                    terminal.set_reconnect_target(ReconnectTarget::Ssh { args: ssh_args }, cx)
+                });
+            }
+        }
+        self.start_forwards(target, forwards, host_key_checked, cx);
+    }
+
+    /// Start port forwards through `target`, checking its host key like the
+    /// session did. Returns the first error, if any.
+    fn start_forwards(
+        &mut self,
+        target: &SshTarget,
+        specs: Vec<ForwardSpec>,
+        host_key_checked: bool,
+        cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let mut first_error = None;
+        for spec in specs {
+            let label = spec.to_string();
+            match PortForward::start(target.clone(), spec, host_key_checked) {
+                Ok(forward) => self.port_forwards.push(forward),
+                Err(error) => {
+                    tracing::error!("Failed to start forward {}: {:#}", label, error);
+                    first_error.get_or_insert_with(|| format!("{}: {:#}", label, error));
+                }
+            }
+        }
+        cx.notify();
+        first_error
+    }
+
+    /// Start the forwards of the named SSH profile.
+    pub(crate) fn start_profile_forwards(&mut self, name: &str, cx: &mut Context<Self>) {
+        let profiles = crate::port_forwards::ssh_profiles(&settings::load_config());
+        let Some(profile) = profiles.into_iter().find(|p| p.name == name) else {
+            tracing::warn!("SSH profile '{}' not found", name);
+            return;
+        };
+        // No session checked the key: ssh checks it, refusing unknown keys
+        let error = self.start_forwards(&profile.target, profile.forwards, false, cx);
+        if let Some(panel) = &mut self.port_forwards_panel {
+            panel.error = error.map(Into::into);
+        }
+    }
+
+    /// Stop a forward and remove it from the panel.
+    pub(crate) fn stop_forward(&mut self, id: u64, cx: &mut Context<Self>) {
+        // Dropping the forward kills its ssh process and relay
+        self.port_forwards.retain(|forward| forward.id() != id);
+        cx.notify();
+    }
+
+    /// SSH target of the active tab, if it is a remote session.
+    fn active_ssh_target(&self) -> Option<&SshTarget> {
+        self.tabs.get(self.active_tab)?.remote.as_ref()
+    }
+
+    /// Command palette entries for the open tabs, profiles, themes and
+    /// recordings, and for recently closed tabs.
+    fn palette_entries(&mut self, cx: &mut Context<Self>) -> Vec<CommandEntry> {
+        let mut entries: Vec<CommandEntry> = self
+            .get_tab_titles(cx)
+            .into_iter()
+            .enumerate()
+            .map(|(index, title)| {
+                CommandEntry::new(format!("Go to Tab: {}", title), GoToTab { index })
+            })
+            .collect();
+        entries.extend(settings::load_config().profiles.into_iter().map(|profile| {
+            CommandEntry::new(
+                format!("Open Profile: {}", profile.name),
+                NewTabWithProfile {
+                    profile: profile.name,
+                },
+            )
+        }));
+        entries.extend(theme::theme_names(cx).into_iter().map(|name| {
+            CommandEntry::new(
+                format!("Theme: {}", name),
+                SelectTheme {
+                    name: name.to_string(),
+                },
+            )
+        }));
+        entries.extend(recordings().into_iter().map(|path| {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            CommandEntry::new(
+                format!("Replay Recording: {}", file_name),
+                ReplayRecording { path },
+            )
+        }));
+        entries.extend(self.closed_tabs.iter().enumerate().map(|(index, closed)| {
+            let kind = if closed.pane_of.is_some() {
+                "Pane"
+            } else {
+                "Tab"
+            };
+            CommandEntry::new(
+                format!("Reopen Closed {}: {}", kind, closed.title),
+                ReopenClosedTab { index },
+            )
+        }));
+        entries
+    }
+
+    fn toggle_command_palette(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.command_palette.is_some() {
+            self.dismiss_command_palette(cx);
+        } else {
+            let extra = self.palette_entries(cx);
+            let working_dir = self.get_active_pane_cwd(cx);
+            let palette = cx.new(|cx| CommandPalette::new(extra, working_dir, window, cx));
+
+            let sub = cx.subscribe_in(
+                &palette,
+                window,
+                |this, _palette, event: &CommandPaletteDismiss, window, cx| {
+                    let action = event.action.as_ref().map(|a| a.boxed_clone());
+                    this.dismiss_command_palette(cx);
+
+                    // Restore focus to active pane before dispatching so the
+                    // action finds its handler in the focus chain
+                    if let Some(tab) = this.tabs.get(this.active_tab) {
+                        if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
+                            window.focus(&pane.focus_handle(cx));
+                        }
+                    }
+
+                    if let Some(action) = action {
+                        window.dispatch_action(action, cx);
+                    }
+                },
+            );
+
+            self._command_palette_subscriptions = vec![sub];
+
+            let focus = palette.read(cx).focus_handle.clone();
+            window.focus(&focus);
+            self.command_palette = Some(palette);
+            cx.notify();
+        }
+    }
+
+    fn dismiss_command_palette(&mut self, cx: &mut Context<Self>) {
+        self.command_palette = None;
+        self._command_palette_subscriptions.clear();
+        cx.notify();
+    }
+
+    fn toggle_tab_switcher(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.tab_switcher.is_some() {
+            self.dismiss_tab_switcher(cx);
+        } else {
+            self.note_active_pane();
+            let entries = self.switcher_entries(cx);
+            let switcher = cx.new(|cx| TabSwitcher::new(entries, cx));
+
+            let sub = cx.subscribe_in(
+                &switcher,
+                window,
+                |this, _switcher, event: &TabSwitcherDismiss, window, cx| {
+                    let target = event.target;
+                    this.dismiss_tab_switcher(cx);
+                    if let Some((tab_id, pane_id)) = target {
+                        this.switch_to_pane(tab_id, pane_id, cx);
+                    }
+
+                    if let Some(tab) = this.tabs.get(this.active_tab) {
+                        if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
+                            window.focus(&pane.focus_handle(cx));
+                        }
+                    }
+                },
+            );
+
+            self._tab_switcher_subscriptions = vec![sub];
+
+            let focus = switcher.read(cx).focus_handle.clone();
+            window.focus(&focus);
+            self.tab_switcher = Some(switcher);
+            cx.notify();
+        }
+    }
+
+    fn dismiss_tab_switcher(&mut self, cx: &mut Context<Self>) {
+        self.tab_switcher = None;
+        self._tab_switcher_subscriptions.clear();
+        cx.notify();
+    }
+
+    /// Show every effective keybinding, or hide the list.
+    fn toggle_cheat_sheet(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.cheat_sheet.is_some() {
+            self.dismiss_cheat_sheet(cx);
+            return;
+        }
+        let custom = settings::load_config().keybindings;
+        let cheat_sheet = cx.new(|cx| CheatSheet::new(&custom, cx));
+
+        let sub = cx.subscribe_in(
+            &cheat_sheet,
+            window,
+            |this, _cheat_sheet, _: &CheatSheetDismiss, window, cx| {
+                this.dismiss_cheat_sheet(cx);
+                if let Some(tab) = this.tabs.get(this.active_tab) {
+                    if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
+                        window.focus(&pane.focus_handle(cx));
+                    }
+                }
+            },
+        );
+        self._cheat_sheet_subscriptions = vec![sub];
+
+        let focus = cheat_sheet.read(cx).focus_handle.clone();
+        window.focus(&focus);
+        self.cheat_sheet = Some(cheat_sheet);
+        cx.notify();
+    }
+
+    fn dismiss_cheat_sheet(&mut self, cx: &mut Context<Self>) {
+        self.cheat_sheet = None;
+        self._cheat_sheet_subscriptions.clear();
+        cx.notify();
+    }
+
+    /// Move the active pane to the front of the most-recently-used list.
+    fn note_active_pane(&mut self) {
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let active = tab.active_pane;
+        if self.recent_panes.first() == Some(&active) {
+            return;
+        }
+        let open: Vec<Uuid> = self
+            .all_tabs()
+            .flat_map(|tab| tab.panes.all_panes())
+            .map(|(id, _)| id)
+            .collect();
+        self.recent_panes
+            .retain(|id| *id != active && open.contains(id));
+        self.recent_panes.insert(0, active);
+    }
+
+    /// Every pane of every tab for the switcher, most recently used first;
+    /// panes never focused follow in tab order. With several groups, titles
+    /// start with the group's name.
+    fn switcher_entries(&self, cx: &App) -> Vec<SwitcherEntry> {
+        let show_group = self.groups.len() > 1;
+        let mut entries: Vec<SwitcherEntry> = self
+            .grouped_tabs()
+            .flat_map(|(group, tab)| {
+                let tab_title = if show_group {
+                    format!("{} \u{203A} {}", group.name, tab.base_title(cx))
+                } else {
+                    tab.base_title(cx).to_string()
+                };
+                let panes = tab.panes.all_panes();
+                let split = panes.len() > 1;
+                panes.into_iter().map(move |(pane_id, pane)| {
+                    let title = match pane.title(cx).filter(|_| split) {
+                        Some(pane_title) => format!("{} \u{203A} {}", tab_title, pane_title),
+                        None => tab_title.clone(),
+                    };
+                    let host = pane
+                        .remote_host(cx)
+                        .or_else(|| tab.remote.as_ref().map(|target| target.host.clone()));
+                    SwitcherEntry {
+                        tab: tab.id,
+                        pane: pane_id,
+                        title,
+                        process: pane.get_running_process_name(cx),
+                        cwd: pane.working_directory(cx),
+                        host,
+                    }
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| {
+            self.recent_panes
+                .iter()
+                .position(|id| *id == entry.pane)
+                .unwrap_or(usize::MAX)
+        });
+        entries
+    }
+
+    /// Activate the tab `tab_id` and its pane `pane_id`, showing the tab's
+    /// group if it's hidden.
+    fn switch_to_pane(&mut self, tab_id: Uuid, pane_id: Uuid, cx: &mut Context<Self>) {
+        if !self.tabs.iter().any(|tab| tab.id == tab_id) {
+            if let Some(group) = self
+                .groups
+                .iter()
+                .position(|group| group.tabs.iter().any(|tab| tab.id == tab_id))
+            {
+                self.switch_group(group, cx);
+            }
+        }
+        let Some(index) = self.tabs.iter().position(|tab| tab.id == tab_id) else {
+            return;
+        };
+        self.switch_tab(index, cx);
+        if self.tabs[index].panes.find_pane(pane_id).is_some() {
+            self.set_active_pane(pane_id, cx);
+        }
+        self.note_active_pane();
+    }
+
+    /// Get the current working directory from the active pane of the active tab.
+    fn get_active_pane_cwd(&self, cx: &App) -> Option<std::path::PathBuf> {
+        let tab = self.tabs.get(self.active_tab)?;
+        let pane = tab.panes.find_pane(tab.active_pane)?;
+        pane.get_current_directory(cx)
+    }
+
+    fn new_tab(&mut self, cx: &mut Context<Self>) {
+        // Get working directory from the active pane (if any) for better UX
+        let working_dir = self.get_active_pane_cwd(cx);
+        self.new_tab_in_dir(working_dir, cx);
+    }
+
+    /// Open a shell tab starting in `working_dir`.
+    fn new_tab_in_dir(&mut self, working_dir: Option<std::path::PathBuf>, cx: &mut Context<Self>) {
+        let terminal = cx.new(|cx| TerminalPane::new_in_dir(cx, working_dir));
+
+        self.watch_terminal(&terminal, cx);
 
         let panes = PaneNode::new_leaf(terminal.into());
-        let active_pane = panes.first_leaf_id();
+        let tab_num = self.tabs.len() + 1;
+
+        let tab = Tab::new(format!("Terminal {}", tab_num), panes);
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        cx.notify();
+    }
+
+    /// Create a new tab running a specific command.
+    pub fn new_tab_with_command(
+        &mut self,
+        command: &str,
+        args: &[&str],
+        title: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let cmd = command.to_string();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let title_owned: SharedString = title.to_string().into();
+
+        let terminal = cx.new(move |cx| {
+            let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+            TerminalPane::new_with_command(cx, &cmd, &args_refs)
+        });
+
+        self.watch_terminal(&terminal, cx);
+
+        let panes = PaneNode::new_leaf(terminal.into());
+
+        let tab = Tab::new(title_owned, panes);
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        cx.notify();
+    }
+
+    /// Run a shell command line in the active pane's directory, in a new tab
+    /// titled after it, a split, or the tab's floating pane (a split if the
+    /// tab already has one). The pane stays open after the command exits and
+    /// shows its exit code.
+    pub fn run_command(
+        &mut self,
+        command_line: &str,
+        target: RunTarget,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // The shell tabs run, as a login shell, so the command finds the same PATH
+        let (shell, args) = terminal::shell_command(command_line);
+        let working_dir = self.get_active_pane_cwd(cx);
+        let terminal = cx.new(move |cx| {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let mut pane = TerminalPane::new_command_in_dir(cx, &shell, &args, working_dir);
+            pane.set_hold_on_exit(true);
+            pane
+        });
+        self.watch_terminal(&terminal, cx);
+
+        // The tab's floating pane is never replaced, idle shell or not; the
+        // command opens in a split instead
+        let has_floating = self
+            .tabs
+            .get(self.active_tab)
+            .is_some_and(|tab| tab.floating.is_some());
+        let target = match target {
+            RunTarget::Floating if has_floating => RunTarget::Split,
+            target => target,
+        };
+
+        let tab = self.tabs.get_mut(self.active_tab);
+        match (target, tab) {
+            (RunTarget::Split, Some(tab)) => {
+                let pane: PaneKind = terminal.clone().into();
+                if let Some(pane_id) =
+                    tab.panes
+                        .split(tab.active_pane, SplitDirection::Horizontal, pane)
+                {
+                    tab.active_pane = pane_id;
+                    window.focus(&terminal.read(cx).focus_handle);
+                }
+                self.sync_broadcast(cx);
+            }
+            (RunTarget::Floating, Some(tab)) => {
+                window.focus(&terminal.read(cx).focus_handle);
+                tab.floating = Some(FloatingPane::new(terminal));
+            }
+            _ => {
+                let tab = Tab::new(
+                    command_line.to_string(),
+                    PaneNode::new_leaf(terminal.into()),
+                );
+                self.tabs.push(tab);
+                self.active_tab = self.tabs.len() - 1;
+            }
+        }
+        cx.notify();
+    }
+
+    /// Open a tab listing the merged config and the file each value came
+    /// from (`humanssh --effective-config`), held open after it prints.
+    fn show_effective_config(&mut self, cx: &mut Context<Self>) {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe.to_string_lossy().to_string(),
+            Err(e) => {
+                tracing::warn!("Cannot find the humanssh executable: {}", e);
+                return;
+            }
+        };
+        let terminal = cx.new(move |cx| {
+            let mut pane =
+                TerminalPane::new_command_in_dir(cx, &exe, &["--effective-config"], None);
+            pane.set_hold_on_exit(true);
+            pane
+        });
+        self.watch_terminal(&terminal, cx);
+        self.tabs.push(Tab::new(
+            "Effective Config",
+            PaneNode::new_leaf(terminal.into()),
+        ));
+        self.active_tab = self.tabs.len() - 1;
+        cx.notify();
+    }
+
+    /// Create a new tab attached to a serial console.
+    pub fn new_tab_with_serial(&mut self, serial: &SerialConfig, cx: &mut Context<Self>) {
+        let title = serial.display_name();
+        let serial = serial.clone();
+        let terminal = cx.new(move |cx| TerminalPane::new_serial(cx, &serial));
+        self.push_session_tab(terminal, title, cx);
+    }
+
+    /// Add a tab holding a serial or network session pane, closing it when
+    /// the session ends.
+    fn push_session_tab(
+        &mut self,
+        terminal: Entity<TerminalPane>,
+        title: String,
+        cx: &mut Context<Self>,
+    ) {
+        self.watch_terminal(&terminal, cx);
+
+        let panes = PaneNode::new_leaf(terminal.into());
+
+        let tab = Tab::new(title, panes);
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        cx.notify();
+    }
+
+    /// Subscribe to a new terminal pane: clean it up when its process exits
+    /// (non-test only) and mirror its input while it broadcasts.
+    fn watch_terminal(&mut self, terminal: &Entity<TerminalPane>, cx: &mut Context<Self>) {
+        // Closed terminals' subscriptions do nothing; drop them as we go
+        self.terminal_subscriptions
+            .retain(|(terminal, _)| terminal.upgrade().is_some());
+        let subscriptions = vec![
+            #[cfg(not(test))]
+            cx.subscribe(terminal, |this, _, _: &TerminalExitEvent, cx| {
+                this.force_cleanup(cx);
+            }),
+            cx.subscribe(terminal, |this, source, event: &TerminalInputEvent, cx| {
+                this.broadcast_input(&source, event, cx);
+            }),
+        ];
+        self.terminal_subscriptions
+            .push((terminal.downgrade(), subscriptions));
+    }
+
+    fn watch_terminals(&mut self, terminals: &[Entity<TerminalPane>], cx: &mut Context<Self>) {
+        for terminal in terminals {
+            self.watch_terminal(terminal, cx);
+        }
+    }
+
+    /// Mirror input from `source` into every other pane of the broadcast group.
+    fn broadcast_input(
+        &mut self,
+        source: &Entity<TerminalPane>,
+        event: &TerminalInputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        let members = self.broadcast_members();
+        if !members.contains(source) {
+            return;
+        }
+        for terminal in members.iter().filter(|terminal| *terminal != source) {
+            terminal.update(cx, |terminal, cx| {
+                match event {
+                    TerminalInputEvent::Keys(keys) => terminal.send_input(keys),
+                    TerminalInputEvent::Paste(text) => terminal.paste_text(text),
+                }
+                cx.notify();
+            });
+        }
+    }
+
+    /// Terminal panes currently in the broadcast group, across all tabs.
+    fn broadcast_members(&self) -> Vec<Entity<TerminalPane>> {
+        self.all_tabs()
+            .flat_map(|tab| {
+                tab.panes
+                    .all_panes()
+                    .into_iter()
+                    .filter(|(id, _)| self.broadcast.includes(tab.id, *id))
+                    .filter_map(|(_, pane)| pane.as_terminal().cloned())
+            })
+            .collect()
+    }
+
+    /// Push group membership to the panes so they show the broadcast border.
+    fn sync_broadcast(&mut self, cx: &mut Context<Self>) {
+        for tab in &self.tabs {
+            for (id, pane) in tab.panes.all_panes() {
+                let Some(terminal) = pane.as_terminal() else {
+                    continue;
+                };
+                let broadcasting = self.broadcast.includes(tab.id, id);
+                if terminal.read(cx).is_broadcasting() != broadcasting {
+                    terminal.update(cx, |terminal, cx| {
+                        terminal.set_broadcasting(broadcasting, cx);
+                    });
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    /// Broadcast input to every pane of the active tab, or stop.
+    fn toggle_broadcast_tab(&mut self, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let panes: Vec<Uuid> = tab
+            .panes
+            .all_panes()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let on = self.broadcast.toggle_tab(tab.id, &panes);
+        tracing::info!(tab = %tab.id, on, "Toggled tab broadcast");
+        self.sync_broadcast(cx);
+    }
+
+    /// Add the active pane to the broadcast group, or leave it out.
+    fn toggle_broadcast_pane(&mut self, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let on = self.broadcast.toggle_pane(tab.id, tab.active_pane);
+        tracing::info!(pane = %tab.active_pane, on, "Toggled pane broadcast");
+        self.sync_broadcast(cx);
+    }
+
+    /// Stop broadcasting in all tabs.
+    fn stop_broadcast(&mut self, cx: &mut Context<Self>) {
+        self.broadcast.clear();
+        self.sync_broadcast(cx);
+    }
+
+    /// Open a replay tab for the most recent .cast recording.
+    fn open_replay(&mut self, cx: &mut Context<Self>) {
+        match recordings().into_iter().next() {
+            Some(path) => self.open_replay_file(path, cx),
+            None => tracing::info!("No recordings found"),
+        }
+    }
+
+    /// Open a replay tab for the recording at `path`.
+    fn open_replay_file(&mut self, path: std::path::PathBuf, cx: &mut Context<Self>) {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Replay".to_string());
+
+        tracing::info!("Opening replay: {:?}", path);
+
+        let replay_path = path;
+        let terminal = cx.new(move |cx| {
+            TerminalPane::new_replay(cx, replay_path).unwrap_or_else(|error| {
+                tracing::error!("Failed to open replay: {}", error);
+                TerminalPane::new(cx)
+            })
+        });
+
+        let panes = PaneNode::new_leaf(terminal.into());
+
+        let tab = Tab::new(format!("Replay: {}", file_name), panes);
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+        cx.notify();
+    }
+
+    fn close_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if self.is_pinned(index) {
+            tracing::info!("Tab {} is pinned; unpin it to close", index + 1);
+            return;
+        }
+        self.remember_closed_tab(index, cx);
+        if self.tabs.len() <= 1 {
+            self.close_group(cx);
+            return;
+        }
+
+        self.tabs.remove(index);
+
+        if self.active_tab >= self.tabs.len() {
+            self.active_tab = self.tabs.len() - 1;
+        } else if self.active_tab > index {
+            self.active_tab -= 1;
+        }
+
+        cx.notify();
+    }
+
+    fn switch_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.tabs.len() {
+            self.active_tab = index;
+            cx.notify();
+        }
+    }
+
+    fn next_tab(&mut self, cx: &mut Context<Self>) {
+        self.active_tab = (self.active_tab + 1) % self.tabs.len();
+        cx.notify();
+    }
+
+    fn prev_tab(&mut self, cx: &mut Context<Self>) {
+        if self.active_tab == 0 {
+            self.active_tab = self.tabs.len() - 1;
+        } else {
+            self.active_tab -= 1;
+        }
+        cx.notify();
+    }
+
+    /// Whether the tab at `index` is pinned.
+    fn is_pinned(&self, index: usize) -> bool {
+        self.tabs.get(index).is_some_and(|tab| tab.pinned)
+    }
+
+    /// Pin the active tab (moving it after the other pinned tabs), or unpin
+    /// it (moving it to the first unpinned slot).
+    fn toggle_pin_tab(&mut self, cx: &mut Context<Self>) {
+        if self.active_tab >= self.tabs.len() {
+            return;
+        }
+        let mut tab = self.tabs.remove(self.active_tab);
+        tab.pinned = !tab.pinned;
+        let slot = self.tabs.iter().take_while(|tab| tab.pinned).count();
+        self.tabs.insert(slot, tab);
+        self.active_tab = slot;
+        self.invalidate_tab_titles();
+        cx.notify();
+    }
+
+    /// Move the active tab one place left or right. Pinned and unpinned tabs
+    /// don't cross.
+    fn move_active_tab(&mut self, right: bool, cx: &mut Context<Self>) {
+        let from = self.active_tab;
+        let to = if right {
+            from + 1
+        } else {
+            match from.checked_sub(1) {
+                Some(to) => to,
+                None => return,
+            }
+        };
+        if to >= self.tabs.len() || self.tabs[from].pinned != self.tabs[to].pinned {
+            return;
+        }
+        self.tabs.swap(from, to);
+        self.active_tab = to;
+        self.invalidate_tab_titles();
+        cx.notify();
+    }
+
+    /// Remember tab `index` as it closes, for Reopen Closed Tab.
+    fn remember_closed_tab(&mut self, index: usize, cx: &App) {
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+        let with_history = cx
+            .try_global::<ReopenWithScrollback>()
+            .is_none_or(|reopen| reopen.0);
+        let closed = ClosedTab {
+            title: tab.base_title(cx),
+            custom_title: tab.custom_title.clone(),
+            color: tab.color,
+            pinned: tab.pinned,
+            remote: tab.remote.clone(),
+            host_key_checked: tab.host_key_checked,
+            layout: ClosedLayout::capture(&tab.panes, with_history, cx),
+            pane_of: None,
+        };
+        self.remember_closed(closed);
+    }
+
+    /// Remember the active tab's active pane as it closes.
+    fn remember_closed_pane(&mut self, cx: &App) {
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let Some(pane) = tab.panes.find_pane(tab.active_pane) else {
+            return;
+        };
+        let with_history = cx
+            .try_global::<ReopenWithScrollback>()
+            .is_none_or(|reopen| reopen.0);
+        let closed = ClosedTab {
+            title: pane.title(cx).unwrap_or_else(|| tab.base_title(cx)),
+            custom_title: None,
+            color: None,
+            pinned: false,
+            remote: None,
+            host_key_checked: false,
+            layout: ClosedLayout::capture_pane(&pane, with_history, cx),
+            pane_of: Some(tab.id),
+        };
+        self.remember_closed(closed);
+    }
+
+    fn remember_closed(&mut self, closed: ClosedTab) {
+        self.closed_tabs.insert(0, closed);
+        self.closed_tabs
+            .truncate(settings::constants::closed_tabs::MAX_REMEMBERED);
+    }
+
+    /// Reopen closed tab `index` (0 is the most recent) with its layout,
+    /// title and color, starting its sessions again. A closed pane goes back
+    /// beside the active pane of its old tab while that tab is open.
+    fn reopen_closed_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index >= self.closed_tabs.len() {
+            return;
+        }
+        let closed = self.closed_tabs.remove(index);
+        let panes = Self::reopen_layout(&closed.layout, cx);
+        self.watch_terminals(&layout_terminals(&panes), cx);
+        let old_tab = closed
+            .pane_of
+            .and_then(|id| self.tabs.iter().position(|tab| tab.id == id));
+
+        match old_tab {
+            Some(tab_index) => {
+                let pane_id = panes.first_leaf_id();
+                let tab = &mut self.tabs[tab_index];
+                if tab
+                    .panes
+                    .insert(tab.active_pane, SplitDirection::Horizontal, panes)
+                {
+                    tab.active_pane = pane_id;
+                }
+                self.active_tab = tab_index;
+                self.invalidate_tab_titles();
+                cx.notify();
+            }
+            None => {
+                let mut tab = Tab::new(closed.title, panes);
+                tab.custom_title = closed.custom_title;
+                tab.color = closed.color;
+                tab.pinned = closed.pinned;
+                tab.remote = closed.remote;
+                tab.host_key_checked = closed.host_key_checked;
+                self.insert_tab(tab, cx);
+            }
+        }
+    }
+
+    /// Build a closed layout again: remote panes reconnect, the rest start a
+    /// shell in their old directory, each below its old text.
+    fn reopen_layout(layout: &ClosedLayout, cx: &mut Context<Self>) -> PaneNode {
+        match layout {
+            ClosedLayout::Pane {
+                cwd,
+                session,
+                history,
+            } => {
+                let terminal = match session {
+                    Some(target) => {
+                        let target = target.clone();
+                        cx.new(|cx| TerminalPane::new_unconnected(cx, target))
+                    }
+                    None => {
+                        // The directory may be gone since the pane closed
+                        let cwd = cwd.clone().filter(|dir| dir.is_dir());
+                        cx.new(|cx| TerminalPane::new_in_dir(cx, cwd))
+                    }
+                };
+                if let Some(history) = history {
+                    terminal.read(cx).show_history(history);
+                }
+                if session.is_some() {
+                    terminal.update(cx, |terminal, cx| terminal.reconnect(cx));
+                }
+                PaneNode::new_leaf(terminal.into())
+            }
+            ClosedLayout::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => PaneNode::Split {
+                direction: *direction,
+                first: Box::new(Self::reopen_layout(first, cx)),
+                second: Box::new(Self::reopen_layout(second, cx)),
+                ratio: *ratio,
+            },
+        }
+    }
 
-        let tab = Tab {
-            id: Uuid::new_v4(),
-            fallback_title: title_owned,
-            panes,
-            active_pane,
-        };
-        self.tabs.push(tab);
-        self.active_tab = self.tabs.len() - 1;
+    /// Show tab group `index`. The group being hidden keeps its tabs, their
+    /// layout and its active tab; its processes keep running.
+    fn switch_group(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index == self.active_group || index >= self.groups.len() {
+            return;
+        }
+        self.renaming = None;
+        let shown = &mut self.groups[self.active_group];
+        shown.tabs = std::mem::take(&mut self.tabs);
+        shown.active_tab = self.active_tab;
+        let group = &mut self.groups[index];
+        self.tabs = std::mem::take(&mut group.tabs);
+        self.active_tab = group.active_tab;
+        self.active_group = index;
+        self.invalidate_tab_titles();
         cx.notify();
     }
 
-    /// Open a replay tab for the most recent .cast recording.
-    fn open_replay(&mut self, cx: &mut Context<Self>) {
-        let recordings_dir = match terminal::recording::recordings_directory() {
-            Ok(dir) => dir,
-            Err(error) => {
-                tracing::warn!("Cannot determine recordings directory: {}", error);
-                return;
-            }
-        };
+    fn next_group(&mut self, cx: &mut Context<Self>) {
+        self.switch_group((self.active_group + 1) % self.groups.len(), cx);
+    }
 
-        let mut entries: Vec<_> = match std::fs::read_dir(&recordings_dir) {
-            Ok(reader) => reader
-                .filter_map(|entry| entry.ok())
-                .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "cast"))
-                .collect(),
-            Err(error) => {
-                tracing::info!("No recordings directory: {}", error);
-                return;
-            }
-        };
+    fn prev_group(&mut self, cx: &mut Context<Self>) {
+        let count = self.groups.len();
+        self.switch_group((self.active_group + count - 1) % count, cx);
+    }
 
-        entries.sort_by_key(|entry| {
-            std::cmp::Reverse(entry.metadata().ok().and_then(|m| m.modified().ok()))
-        });
+    /// Add a tab group holding one shell, started in the active pane's
+    /// directory, and show it.
+    fn new_group(&mut self, cx: &mut Context<Self>) {
+        let working_dir = self.get_active_pane_cwd(cx);
+        self.groups
+            .push(TabGroup::new(format!("Group {}", self.groups.len() + 1)));
+        self.switch_group(self.groups.len() - 1, cx);
+        self.new_tab_in_dir(working_dir, cx);
+    }
+
+    /// Name group `index`. An empty name keeps the old one.
+    fn set_group_name(&mut self, index: usize, name: &str, cx: &mut Context<Self>) {
+        let name = name.trim();
+        if let Some(group) = self.groups.get_mut(index).filter(|_| !name.is_empty()) {
+            group.name = name.to_string().into();
+            cx.notify();
+        }
+    }
 
-        let Some(path) = entries.first().map(|entry| entry.path()) else {
-            tracing::info!("No recordings found in {:?}", recordings_dir);
+    /// The shown group lost its last tab: drop the group and show a
+    /// neighbour, or close the window if it was the only group.
+    fn close_group(&mut self, cx: &mut Context<Self>) {
+        if self.groups.len() <= 1 {
+            // Close the window, or quit if it's the last one
+            crate::windows::close_window(&cx.entity(), cx);
             return;
+        }
+        let closing = self.active_group;
+        self.tabs.clear();
+        let neighbour = if closing + 1 < self.groups.len() {
+            closing + 1
+        } else {
+            closing - 1
         };
+        self.switch_group(neighbour, cx);
+        self.groups.remove(closing);
+        if self.active_group > closing {
+            self.active_group -= 1;
+        }
+    }
 
-        let file_name = path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Replay".to_string());
-
-        tracing::info!("Opening replay: {:?}", path);
-
-        let replay_path = path;
-        let terminal = cx.new(move |cx| {
-            TerminalPane::new_replay(cx, replay_path).unwrap_or_else(|error| {
-                tracing::error!("Failed to open replay: {}", error);
-                TerminalPane::new(cx)
-            })
+    /// Take tab `index` out of this window, to hand it to another. Its panes
+    /// leave this window's broadcast group.
+    fn take_tab(&mut self, index: usize, cx: &mut Context<Self>) -> Option<Tab> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        if self
+            .renaming
+            .as_ref()
+            .is_some_and(|r| r.target == RenameTarget::Tab(tab.id))
+        {
+            self.renaming = None;
+        }
+        if self.active_tab > index || self.active_tab >= self.tabs.len() {
+            self.active_tab = self.active_tab.saturating_sub(1);
+        }
+        for terminal in layout_terminals(&tab.panes) {
+            terminal.update(cx, |terminal, cx| terminal.set_broadcasting(false, cx));
+        }
+        // The window it moves to subscribes to its terminals instead
+        let terminals = tab.terminals();
+        self.terminal_subscriptions.retain(|(watched, _)| {
+            !terminals
+                .iter()
+                .any(|terminal| terminal.entity_id() == watched.entity_id())
         });
+        self.invalidate_tab_titles();
+        cx.notify();
+        Some(tab)
+    }
 
-        let panes = PaneNode::new_leaf(terminal.into());
-        let active_pane = panes.first_leaf_id();
+    /// Add a tab from another window and make it active. Its processes keep
+    /// running.
+    fn adopt_tab(&mut self, tab: Tab, cx: &mut Context<Self>) {
+        self.watch_terminals(&tab.terminals(), cx);
+        self.insert_tab(tab, cx);
+    }
 
-        let tab = Tab {
-            id: Uuid::new_v4(),
-            fallback_title: format!("Replay: {}", file_name).into(),
-            panes,
-            active_pane,
+    /// Add `tab` and make it active: after the pinned tabs if it's pinned,
+    /// else at the end.
+    fn insert_tab(&mut self, tab: Tab, cx: &mut Context<Self>) {
+        let slot = if tab.pinned {
+            self.tabs.iter().take_while(|tab| tab.pinned).count()
+        } else {
+            self.tabs.len()
         };
-        self.tabs.push(tab);
-        self.active_tab = self.tabs.len() - 1;
+        self.tabs.insert(slot, tab);
+        self.active_tab = slot;
+        self.invalidate_tab_titles();
         cx.notify();
     }
 
-    fn close_tab(&mut self, index: usize, cx: &mut Context<Self>) {
-        if self.tabs.len() <= 1 {
-            // Last tab - quit the app
-            cx.quit();
+    /// A tab dragged out of the window was released at `position` (window
+    /// coordinates): move it into the window under the mouse, or into a new
+    /// window of its own.
+    fn drop_tab_outside(
+        &mut self,
+        tab_id: Uuid,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(index) = self.tabs.iter().position(|tab| tab.id == tab_id) else {
             return;
+        };
+        let screen_position = window.bounds().origin + position;
+        match crate::windows::workspace_at(screen_position, window.window_handle(), cx) {
+            Some((handle, target)) => {
+                let Some(tab) = self.take_tab(index, cx) else {
+                    return;
+                };
+                target.update(cx, |workspace, cx| workspace.adopt_tab(tab, cx));
+                handle
+                    .update(cx, |_, window, _| window.activate_window())
+                    .ok();
+            }
+            // A lone tab already has a window of its own
+            None if self.tabs.len() > 1 || self.groups.len() > 1 => {
+                let Some(tab) = self.take_tab(index, cx) else {
+                    return;
+                };
+                let workspace = cx.new(|cx| Workspace::from_tab(tab, cx));
+                let opened = crate::windows::open_workspace_window(
+                    cx,
+                    Some(screen_position),
+                    |_window, _cx| workspace.clone(),
+                );
+                if let Err(error) = opened {
+                    tracing::error!("Cannot detach tab: {}", error);
+                    // Keep the tab rather than lose its sessions
+                    if let Some(tab) =
+                        workspace.update(cx, |workspace, cx| workspace.take_tab(0, cx))
+                    {
+                        self.adopt_tab(tab, cx);
+                    }
+                }
+            }
+            None => {}
         }
-
-        self.tabs.remove(index);
-
-        if self.active_tab >= self.tabs.len() {
-            self.active_tab = self.tabs.len() - 1;
-        } else if self.active_tab > index {
-            self.active_tab -= 1;
+        if self.tabs.is_empty() {
+            self.close_group(cx);
         }
+    }
 
-        cx.notify();
+    /// Color the active tab, or clear its color.
+    fn set_tab_color(&mut self, color: Option<TabColor>, cx: &mut Context<Self>) {
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.color = color;
+            cx.notify();
+        }
     }
 
-    fn switch_tab(&mut self, index: usize, cx: &mut Context<Self>) {
-        if index < self.tabs.len() {
-            self.active_tab = index;
+    /// Name the tab at `index`, overriding titles set by its panes. An empty
+    /// name goes back to the pane's title.
+    fn set_tab_title(&mut self, index: usize, name: &str, cx: &mut Context<Self>) {
+        if let Some(tab) = self.tabs.get_mut(index) {
+            let name = name.trim();
+            tab.custom_title = (!name.is_empty()).then(|| name.to_string().into());
+            self.invalidate_tab_titles();
             cx.notify();
         }
     }
 
-    fn next_tab(&mut self, cx: &mut Context<Self>) {
-        self.active_tab = (self.active_tab + 1) % self.tabs.len();
+    /// Edit the active tab's name in place in the tab bar.
+    fn start_rename_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let target = RenameTarget::Tab(tab.id);
+        let current = tab.base_title(cx);
+        self.start_rename(target, current, window, cx);
+    }
+
+    /// Edit the shown group's name in place in the tab bar.
+    fn start_rename_group(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let current = self.groups[self.active_group].name.clone();
+        self.start_rename(RenameTarget::Group(self.active_group), current, window, cx);
+    }
+
+    fn start_rename(
+        &mut self,
+        target: RenameTarget,
+        current: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let input = cx.new(|cx| InputState::new(window, cx).default_value(current));
+        let subscription = cx.subscribe_in(
+            &input,
+            window,
+            |this, _input, event: &InputEvent, _window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    this.finish_rename_tab(true, cx);
+                }
+            },
+        );
+        input.read(cx).focus_handle(cx).focus(window);
+        self.renaming = Some(TabRename {
+            target,
+            input,
+            _subscriptions: vec![subscription],
+        });
         cx.notify();
     }
 
-    fn prev_tab(&mut self, cx: &mut Context<Self>) {
-        if self.active_tab == 0 {
-            self.active_tab = self.tabs.len() - 1;
-        } else {
-            self.active_tab -= 1;
+    /// Stop editing a tab or group name, keeping the new name if `save`.
+    pub(crate) fn finish_rename_tab(&mut self, save: bool, cx: &mut Context<Self>) {
+        let Some(rename) = self.renaming.take() else {
+            return;
+        };
+        if save {
+            let name = rename.input.read(cx).value().to_string();
+            match rename.target {
+                RenameTarget::Tab(tab_id) => {
+                    if let Some(index) = self.tabs.iter().position(|tab| tab.id == tab_id) {
+                        self.set_tab_title(index, &name, cx);
+                    }
+                }
+                RenameTarget::Group(index) => self.set_group_name(index, &name, cx),
+            }
         }
         cx.notify();
     }
@@ -514,16 +2521,12 @@ impl Workspace {
         // Get working directory from the active pane before creating new terminal
         let working_dir = self.get_active_pane_cwd(cx);
 
-        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+        if self.tabs.get(self.active_tab).is_some() {
             let new_terminal = cx.new(|cx| TerminalPane::new_in_dir(cx, working_dir));
 
-            // Subscribe to terminal exit events for immediate cleanup (non-test only)
-            #[cfg(not(test))]
-            cx.subscribe(&new_terminal, |this, _, _: &TerminalExitEvent, cx| {
-                this.force_cleanup(cx);
-            })
-            .detach();
+            self.watch_terminal(&new_terminal, cx);
 
+            let tab = &mut self.tabs[self.active_tab];
             let new_pane: PaneKind = new_terminal.clone().into();
             if let Some(new_pane_id) = tab.panes.split(tab.active_pane, direction, new_pane) {
                 // Set the new pane as active
@@ -531,10 +2534,194 @@ impl Workspace {
                 // Focus the new terminal
                 new_terminal.read(cx).focus_handle.focus(window);
             }
+        }
+        // A split in a broadcasting tab joins the group
+        self.sync_broadcast(cx);
+    }
+
+    /// Move the divider next to the active pane.
+    fn resize_active_pane(&mut self, action: &ResizePane, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        let (direction, forward) = pane_axis(action.direction);
+        let delta = if forward {
+            action.amount
+        } else {
+            -action.amount
+        };
+        if tab.panes.resize(tab.active_pane, direction, delta) {
+            cx.notify();
+        }
+    }
+
+    /// Set a split's ratio while its divider is dragged.
+    pub(crate) fn set_split_ratio(&mut self, second_id: Uuid, ratio: f32, cx: &mut Context<Self>) {
+        if !ratio.is_finite() {
+            return;
+        }
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            if tab.panes.set_ratio(second_id, ratio) {
+                cx.notify();
+            }
+        }
+    }
+
+    /// Give every pane in the active tab an equal share of its split.
+    fn equalize_panes(&mut self, cx: &mut Context<Self>) {
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.panes.equalize();
             cx.notify();
         }
     }
 
+    /// Focus the pane next to the active one on screen.
+    fn focus_pane_in_direction(
+        &mut self,
+        direction: PaneDirection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        let (axis, forward) = pane_axis(direction);
+        let Some(target) = tab.panes.neighbor(tab.active_pane, axis, forward) else {
+            return;
+        };
+        if let Some(pane) = tab.panes.find_pane(target) {
+            let focus = pane.focus_handle(cx);
+            self.set_active_pane(target, cx);
+            window.focus(&focus);
+        }
+    }
+
+    /// Swap the active pane with its neighbour on screen. Focus stays with
+    /// the active pane.
+    fn swap_active_pane(&mut self, direction: PaneDirection, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        let (axis, forward) = pane_axis(direction);
+        if let Some(target) = tab.panes.neighbor(tab.active_pane, axis, forward) {
+            if tab.panes.swap(tab.active_pane, target) {
+                cx.notify();
+            }
+        }
+    }
+
+    /// Rotate the panes in the active pane's row or column.
+    fn rotate_panes(&mut self, cx: &mut Context<Self>) {
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            if tab.panes.rotate(tab.active_pane) {
+                cx.notify();
+            }
+        }
+    }
+
+    /// Take the active pane out of its split and give it a tab of its own.
+    fn move_pane_to_new_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        let pane_id = tab.active_pane;
+        let Some(pane) = tab.panes.find_pane(pane_id) else {
+            return;
+        };
+        // A lone pane already has its tab
+        let Some((sibling_id, _)) = tab.panes.remove(pane_id) else {
+            return;
+        };
+        tab.active_pane = sibling_id;
+
+        let tab_num = self.tabs.len() + 1;
+        let panes = PaneNode::Leaf {
+            id: pane_id,
+            pane: pane.clone(),
+        };
+        self.tabs
+            .push(Tab::new(format!("Terminal {}", tab_num), panes));
+        self.active_tab = self.tabs.len() - 1;
+        self.finish_pane_move(&pane, window, cx);
+    }
+
+    /// Move the active pane beside the active pane of tab `index`. Moving a
+    /// tab's only pane closes that tab.
+    fn move_pane_to_tab(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let source = self.active_tab;
+        if index == source || index >= self.tabs.len() {
+            return;
+        }
+        let tab = &mut self.tabs[source];
+        let pane_id = tab.active_pane;
+        let Some(pane) = tab.panes.find_pane(pane_id) else {
+            return;
+        };
+        let target = if let Some((sibling_id, _)) = tab.panes.remove(pane_id) {
+            tab.active_pane = sibling_id;
+            index
+        } else {
+            self.tabs.remove(source);
+            if index > source {
+                index - 1
+            } else {
+                index
+            }
+        };
+
+        let tab = &mut self.tabs[target];
+        let node = PaneNode::Leaf {
+            id: pane_id,
+            pane: pane.clone(),
+        };
+        if tab
+            .panes
+            .insert(tab.active_pane, SplitDirection::Horizontal, node)
+        {
+            tab.active_pane = pane_id;
+        }
+        self.active_tab = target;
+        self.finish_pane_move(&pane, window, cx);
+    }
+
+    /// Focus a pane that changed tabs and bring its tab's broadcast state
+    /// and title along.
+    fn finish_pane_move(&mut self, pane: &PaneKind, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus(&pane.focus_handle(cx));
+        self.invalidate_tab_titles();
+        self.sync_broadcast(cx);
+    }
+
+    /// Open a file browser beside the active pane.
+    ///
+    /// In an SSH tab the browser lists the remote host over SFTP; otherwise it
+    /// lists the local filesystem. Either way it follows the active terminal's
+    /// working directory.
+    fn open_file_browser(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let source = match self.active_ssh_target() {
+            Some(target) => BrowserSource::Remote(SftpClient::new(target.clone())),
+            None => BrowserSource::Local,
+        };
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        let linked = tab
+            .panes
+            .find_pane(tab.active_pane)
+            .and_then(|pane| pane.as_terminal().cloned());
+        let browser = cx.new(|cx| FileBrowserPane::new(source, linked.as_ref(), cx));
+
+        let new_pane: PaneKind = browser.clone().into();
+        if let Some(new_pane_id) =
+            tab.panes
+                .split(tab.active_pane, SplitDirection::Horizontal, new_pane)
+        {
+            tab.active_pane = new_pane_id;
+            browser.read(cx).focus_handle(cx).focus(window);
+        }
+        cx.notify();
+    }
+
     /// Move a tab's pane content into a split of the given target pane.
     ///
     /// The source tab's pane tree becomes one side of the split in the current tab.
@@ -586,22 +2773,135 @@ impl Workspace {
             }
         }
 
-        // Invalidate title cache
-        self.last_title_update =
-            std::time::Instant::now() - settings::constants::timing::TITLE_CACHE_TTL;
+        self.invalidate_tab_titles();
         cx.notify();
     }
 
     /// Set the active pane within the current tab
     pub fn set_active_pane(&mut self, pane_id: Uuid, cx: &mut Context<Self>) {
         if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            // Focusing another pane restores the layout
+            if tab.active_pane != pane_id {
+                tab.zoomed = None;
+            }
+            if let Some(floating) = &mut tab.floating {
+                floating.focused = false;
+            }
             tab.active_pane = pane_id;
             cx.notify();
         }
-    }
-
-    /// Request to close the active pane (with confirmation if needed)
-    fn request_close_pane(&mut self, cx: &mut Context<Self>) {
+    }
+
+    /// Show the active tab's floating terminal (starting its shell on first
+    /// use), focus it if it's shown but unfocused, or hide it.
+    fn toggle_floating_pane(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let working_dir = self.get_active_pane_cwd(cx);
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        if tab.floating.is_none() {
+            let terminal = cx.new(|cx| TerminalPane::new_in_dir(cx, working_dir));
+            self.watch_terminal(&terminal, cx);
+            self.tabs[self.active_tab].floating = Some(FloatingPane::new(terminal));
+        } else if let Some(floating) = &mut self.tabs[self.active_tab].floating {
+            let show = !(floating.visible && floating.focused);
+            floating.visible = show;
+            floating.focused = show;
+        }
+        let tab = &self.tabs[self.active_tab];
+        let focus = match tab.floating.as_ref().filter(|floating| floating.focused) {
+            Some(floating) => Some(floating.terminal.read(cx).focus_handle.clone()),
+            None => tab
+                .panes
+                .find_pane(tab.active_pane)
+                .map(|pane| pane.focus_handle(cx)),
+        };
+        if let Some(focus) = focus {
+            window.focus(&focus);
+        }
+        cx.notify();
+    }
+
+    /// Give keyboard focus to the active tab's floating terminal.
+    pub(crate) fn focus_floating_pane(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(floating) = self
+            .tabs
+            .get_mut(self.active_tab)
+            .and_then(|tab| tab.floating.as_mut())
+        else {
+            return;
+        };
+        if !floating.focused {
+            floating.focused = true;
+            window.focus(&floating.terminal.read(cx).focus_handle);
+            cx.notify();
+        }
+    }
+
+    /// The active tab's floating terminal, if it's shown and has focus.
+    fn focused_floating_pane(&self) -> Option<&FloatingPane> {
+        self.tabs
+            .get(self.active_tab)?
+            .floating
+            .as_ref()
+            .filter(|floating| floating.visible && floating.focused)
+    }
+
+    /// Start moving or resizing the floating terminal.
+    pub(crate) fn begin_floating_drag(&mut self, position: Point<Pixels>) {
+        if let Some(floating) = self
+            .tabs
+            .get_mut(self.active_tab)
+            .and_then(|tab| tab.floating.as_mut())
+        {
+            floating.begin_drag(position);
+        }
+    }
+
+    /// Show the active pane alone at full size, or restore the layout.
+    fn toggle_pane_zoom(&mut self, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        tab.zoomed = if tab.zoomed_pane().is_some() {
+            None
+        } else if matches!(tab.panes, PaneNode::Split { .. }) {
+            Some(tab.active_pane)
+        } else {
+            // A lone pane is already full size
+            return;
+        };
+        self.invalidate_tab_titles();
+        cx.notify();
+    }
+
+    /// Request to close the active pane (with confirmation if needed)
+    fn request_close_pane(&mut self, cx: &mut Context<Self>) {
+        // A focused floating terminal closes first, leaving the layout alone
+        if let Some(floating) = self.focused_floating_pane() {
+            let terminal = floating.terminal.read(cx);
+            let running = terminal
+                .has_running_processes()
+                .then(|| terminal.get_running_process_name());
+            match running {
+                Some(process_name) => {
+                    self.pending_process_name = process_name;
+                    self.pending_action = Some(PendingAction::ClosePane);
+                    cx.notify();
+                }
+                None => self.do_close_pane(cx),
+            }
+            return;
+        }
+        // Closing a pinned tab's last pane would close the tab
+        if self
+            .tabs
+            .get(self.active_tab)
+            .is_some_and(|tab| tab.pinned && matches!(tab.panes, PaneNode::Leaf { .. }))
+        {
+            self.show_pinned_notice(cx);
+            return;
+        }
         if let Some(tab) = self.tabs.get(self.active_tab) {
             if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
                 if pane.has_running_processes(cx) {
@@ -617,14 +2917,79 @@ impl Workspace {
         self.do_close_pane(cx);
     }
 
-    /// Returns true if any overlay (scratchpad, command palette, or confirmation dialog)
-    /// is active, meaning workspace actions should be suppressed.
+    /// Returns true if any overlay (scratchpad, port forwards, command palette, or
+    /// confirmation dialog) is active, meaning workspace actions should be suppressed.
     fn has_active_overlay(&self) -> bool {
         self.scratchpad.as_ref().is_some_and(|sp| sp.visible)
+            || self
+                .port_forwards_panel
+                .as_ref()
+                .is_some_and(|pf| pf.visible)
+            || self.cluster_panel.as_ref().is_some_and(|cp| cp.visible)
+            || self.renaming.is_some()
             || self.command_palette.is_some()
+            || self.tab_switcher.is_some()
+            || self.cheat_sheet.is_some()
             || self.pending_action.is_some()
     }
 
+    /// Match a key press against the configured key sequences. Keys that
+    /// start or continue a sequence are kept from the terminal; any other key
+    /// ends a pending sequence and is typed as usual.
+    fn handle_key_sequence(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.has_active_overlay() {
+            return;
+        }
+        if !self.pending_keys.is_empty() && event.keystroke.key == "escape" {
+            self.cancel_key_sequence(cx);
+            cx.stop_propagation();
+            return;
+        }
+        let Some(sequences) = cx.try_global::<KeySequences>() else {
+            return;
+        };
+        let timeout = sequences.timeout_ms;
+        match sequences.step(
+            &self.pending_keys,
+            &event.keystroke,
+            &window.context_stack(),
+        ) {
+            SequenceStep::Complete(action) => {
+                self.cancel_key_sequence(cx);
+                cx.stop_propagation();
+                window.dispatch_action(action, cx);
+            }
+            SequenceStep::Pending => {
+                self.pending_keys.push(event.keystroke.clone());
+                cx.stop_propagation();
+                self._pending_keys_timeout = (timeout > 0).then(|| {
+                    cx.spawn(async move |this, cx| {
+                        cx.background_executor()
+                            .timer(std::time::Duration::from_millis(timeout))
+                            .await;
+                        let _ = this.update(cx, |this, cx| this.cancel_key_sequence(cx));
+                    })
+                });
+                cx.notify();
+            }
+            SequenceStep::Unbound => self.cancel_key_sequence(cx),
+        }
+    }
+
+    /// Forget the keys of a pending key sequence.
+    fn cancel_key_sequence(&mut self, cx: &mut Context<Self>) {
+        if !self.pending_keys.is_empty() {
+            self.pending_keys.clear();
+            self._pending_keys_timeout = None;
+            cx.notify();
+        }
+    }
+
     /// Ensure the scratchpad exists, creating it on first call.
     pub(crate) fn ensure_scratchpad(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.scratchpad.is_some() {
@@ -686,23 +3051,340 @@ impl Workspace {
         }
     }
 
-    /// Actually close the active pane (or tab if only one pane, or quit if last tab)
-    fn do_close_pane(&mut self, cx: &mut Context<Self>) {
-        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-            let pane_count = tab.panes.all_panes().len();
+    /// Ensure the port forwarding panel exists, creating it on first call.
+    pub(crate) fn ensure_port_forwards_panel(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.port_forwards_panel.is_some() {
+            return;
+        }
 
-            if pane_count <= 1 {
-                // Last pane in tab - close the tab
-                self.close_tab(self.active_tab, cx);
-            } else {
-                // Remove the pane and focus its sibling
-                if let Some((sibling_id, _)) = tab.panes.remove(tab.active_pane) {
-                    tab.active_pane = sibling_id;
+        let input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("L 8080:localhost:80 [user@host] — Enter to start")
+        });
+        let subscription =
+            cx.subscribe_in(&input, window, |this, _, event: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    this.add_forward_from_input(window, cx);
+                }
+            });
+
+        self.port_forwards_panel = Some(PortForwardsState {
+            input,
+            visible: false,
+            error: None,
+            profiles: Vec::new(),
+            _subscriptions: vec![subscription],
+            _refresh: None,
+        });
+    }
+
+    /// Toggle the port forwarding panel.
+    fn toggle_port_forwards(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.ensure_port_forwards_panel(window, cx);
+
+        let Some(panel) = &mut self.port_forwards_panel else {
+            return;
+        };
+        if !panel.toggle() {
+            self.hide_port_forwards(window, cx);
+            return;
+        }
+
+        panel.error = None;
+        panel.profiles = crate::port_forwards::ssh_profiles(&settings::load_config());
+        // Re-render while visible so byte counters stay live
+        panel._refresh = Some(cx.spawn(async move |this, cx| loop {
+            cx.background_executor().timer(REFRESH_INTERVAL).await;
+            let visible = this
+                .update(cx, |this, cx| {
                     cx.notify();
+                    this.port_forwards_panel
+                        .as_ref()
+                        .is_some_and(|pf| pf.visible)
+                })
+                .unwrap_or(false);
+            if !visible {
+                break;
+            }
+        }));
+        let focus = panel.input.read(cx).focus_handle(cx).clone();
+        focus.focus(window);
+        cx.notify();
+    }
+
+    /// Hide the port forwarding panel and return focus to the workspace.
+    pub(crate) fn hide_port_forwards(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(panel) = &mut self.port_forwards_panel {
+            panel.visible = false;
+            panel._refresh = None;
+            if let Some(tab) = self.tabs.get(self.active_tab) {
+                if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
+                    let pane_focus = pane.focus_handle(cx);
+                    pane_focus.focus(window);
+                }
+            }
+            cx.notify();
+        }
+    }
+
+    /// Start a forward written like `L 8080:localhost:80 [user@host]`.
+    /// Returns the error, if any.
+    fn start_forward_request(&mut self, request: &str, cx: &mut Context<Self>) -> Option<String> {
+        match crate::port_forwards::parse_forward_request(request, self.active_ssh_target()) {
+            Ok((target, spec)) => {
+                // Through the active session, the way it checked the key
+                let host_key_checked = self.tabs.get(self.active_tab).is_some_and(|tab| {
+                    tab.host_key_checked && tab.remote.as_ref() == Some(&target)
+                });
+                self.start_forwards(&target, vec![spec], host_key_checked, cx)
+            }
+            Err(error) => Some(error),
+        }
+    }
+
+    /// Start a forward from the command palette or a keybinding. If it
+    /// fails, open the panel with the error and the forward to fix.
+    fn add_port_forward(&mut self, request: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(error) = self.start_forward_request(request, cx) else {
+            return;
+        };
+        if !self
+            .port_forwards_panel
+            .as_ref()
+            .is_some_and(|pf| pf.visible)
+        {
+            self.toggle_port_forwards(window, cx);
+        }
+        if let Some(panel) = &mut self.port_forwards_panel {
+            panel.input.update(cx, |input, cx| {
+                input.set_value(request.to_string(), window, cx)
+            });
+            panel.error = Some(error.into());
+        }
+        cx.notify();
+    }
+
+    /// Start the forward typed into the panel input.
+    fn add_forward_from_input(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(panel) = &self.port_forwards_panel else {
+            return;
+        };
+        let text = panel.input.read(cx).value().to_string();
+
+        let error = self.start_forward_request(&text, cx);
+
+        if let Some(panel) = &mut self.port_forwards_panel {
+            if error.is_none() {
+                panel
+                    .input
+                    .update(cx, |input, cx| input.set_value("", window, cx));
+            }
+            panel.error = error.map(Into::into);
+        }
+        cx.notify();
+    }
+
+    /// Ensure the Open Cluster panel exists, creating it on first call.
+    pub(crate) fn ensure_cluster_panel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.cluster_panel.is_some() {
+            return;
+        }
+
+        let input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder(
+                "Group, or hosts: web1, deploy@web2, telnet://sw1, !htop — Enter to open",
+            )
+        });
+        let subscription = cx.subscribe_in(
+            &input,
+            window,
+            |this, input, event: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    let spec = input.read(cx).value().to_string();
+                    this.open_cluster_from_panel(&spec, window, cx);
+                }
+            },
+        );
+
+        self.cluster_panel = Some(ClusterPanelState {
+            input,
+            visible: false,
+            error: None,
+            groups: Vec::new(),
+            _subscriptions: vec![subscription],
+        });
+    }
+
+    /// Toggle the Open Cluster panel.
+    fn toggle_cluster_panel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.ensure_cluster_panel(window, cx);
+
+        let Some(panel) = &mut self.cluster_panel else {
+            return;
+        };
+        if !panel.toggle() {
+            self.hide_cluster_panel(window, cx);
+            return;
+        }
+
+        panel.error = None;
+        panel.groups = crate::cluster::cluster_groups(&settings::load_config());
+        let focus = panel.input.read(cx).focus_handle(cx).clone();
+        focus.focus(window);
+        cx.notify();
+    }
+
+    /// Hide the Open Cluster panel and return focus to the workspace.
+    pub(crate) fn hide_cluster_panel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(panel) = &mut self.cluster_panel {
+            panel.visible = false;
+            if let Some(tab) = self.tabs.get(self.active_tab) {
+                if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
+                    let pane_focus = pane.focus_handle(cx);
+                    pane_focus.focus(window);
+                }
+            }
+            cx.notify();
+        }
+    }
+
+    /// Open the cluster named or listed in the panel, closing the panel on success.
+    pub(crate) fn open_cluster_from_panel(
+        &mut self,
+        spec: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match self.open_cluster(spec, cx) {
+            Ok(()) => {
+                if let Some(panel) = &mut self.cluster_panel {
+                    panel
+                        .input
+                        .update(cx, |input, cx| input.set_value("", window, cx));
+                    panel.error = None;
+                }
+                self.hide_cluster_panel(window, cx);
+            }
+            Err(error) => {
+                if let Some(panel) = &mut self.cluster_panel {
+                    panel.error = Some(error.into());
                 }
+                cx.notify();
             }
         }
     }
+
+    /// Actually close the active pane (or tab if only one pane, or quit if last tab)
+    fn do_close_pane(&mut self, cx: &mut Context<Self>) {
+        if self.focused_floating_pane().is_some() {
+            self.tabs[self.active_tab].floating = None;
+            cx.notify();
+            return;
+        }
+        let Some(tab) = self.tabs.get(self.active_tab) else {
+            return;
+        };
+        if tab.panes.all_panes().len() <= 1 {
+            // Last pane in tab - close the tab
+            self.close_tab(self.active_tab, cx);
+            return;
+        }
+
+        self.remember_closed_pane(cx);
+        // Remove the pane and focus its sibling
+        let tab = &mut self.tabs[self.active_tab];
+        if let Some((sibling_id, _)) = tab.panes.remove(tab.active_pane) {
+            tab.active_pane = sibling_id;
+            cx.notify();
+        }
+    }
+}
+
+/// The `.cast` files in the recordings directory, newest first.
+fn recordings() -> Vec<std::path::PathBuf> {
+    let recordings_dir = match terminal::recording::recordings_directory() {
+        Ok(dir) => dir,
+        Err(error) => {
+            tracing::warn!("Cannot determine recordings directory: {}", error);
+            return Vec::new();
+        }
+    };
+
+    let mut entries: Vec<_> = match std::fs::read_dir(&recordings_dir) {
+        Ok(reader) => reader
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "cast"))
+            .collect(),
+        Err(error) => {
+            tracing::debug!("No recordings directory: {}", error);
+            return Vec::new();
+        }
+    };
+
+    entries.sort_by_key(|entry| {
+        std::cmp::Reverse(entry.metadata().ok().and_then(|m| m.modified().ok()))
+    });
+    entries.into_iter().map(|entry| entry.path()).collect()
+}
+
+/// Split layout and pane directories of `node`, for session restore.
+fn layout_snapshot(node: &PaneNode, cx: &App) -> LayoutSnapshot {
+    match node {
+        PaneNode::Leaf { pane, .. } => LayoutSnapshot::Pane {
+            cwd: pane.get_current_directory(cx),
+        },
+        PaneNode::Split {
+            direction,
+            first,
+            second,
+            ratio,
+        } => LayoutSnapshot::Split {
+            horizontal: *direction == SplitDirection::Horizontal,
+            ratio: *ratio,
+            first: Box::new(layout_snapshot(first, cx)),
+            second: Box::new(layout_snapshot(second, cx)),
+        },
+    }
+}
+
+/// Theme color for a tab color.
+fn tab_color(color: TabColor, cx: &App) -> gpui::Hsla {
+    let colors = terminal_colors(cx);
+    match color {
+        TabColor::Red => colors.red,
+        TabColor::Yellow => colors.yellow,
+        TabColor::Green => colors.green,
+        TabColor::Cyan => colors.cyan,
+        TabColor::Blue => colors.blue,
+        TabColor::Magenta => colors.magenta,
+    }
+}
+
+/// The split axis a direction moves along, and whether it moves toward the
+/// second side (right or down).
+fn pane_axis(direction: PaneDirection) -> (SplitDirection, bool) {
+    match direction {
+        PaneDirection::Left => (SplitDirection::Horizontal, false),
+        PaneDirection::Right => (SplitDirection::Horizontal, true),
+        PaneDirection::Up => (SplitDirection::Vertical, false),
+        PaneDirection::Down => (SplitDirection::Vertical, true),
+    }
+}
+
+/// Record a host key the user accepted in the known_hosts file at `path`
+/// (hashed, like OpenSSH's `HashKnownHosts yes`). Returns why it couldn't be
+/// saved.
+fn trust_host_key(path: &Path, host: &str, port: u16, key: &HostKey) -> Result<(), String> {
+    let result =
+        KnownHosts::load(path).and_then(|mut known_hosts| known_hosts.add(host, port, key, true));
+    result.map_err(|error| {
+        tracing::error!("Failed to save host key for {}:{}: {}", host, port, error);
+        format!("Writing {} failed: {}", path.display(), error)
+    })
 }
 
 impl Workspace {
@@ -732,19 +3414,45 @@ impl Workspace {
     }
 
     /// Internal: perform the actual cleanup
-    #[cfg(not(test))]
     fn do_cleanup_exited_panes(&mut self, cx: &mut Context<Self>) {
         let mut tabs_to_remove: Vec<usize> = Vec::new();
 
         for (tab_idx, tab) in self.tabs.iter_mut().enumerate() {
+            // A floating shell that exits just goes away; the next toggle starts a new one
+            if tab
+                .floating
+                .as_ref()
+                .is_some_and(|floating| floating.terminal.read(cx).has_exited())
+            {
+                tab.floating = None;
+            }
+
             // Phase 1: Collect all exited pane IDs (avoid TOCTOU race)
             let panes = tab.panes.all_panes();
             let total_panes = panes.len();
-            let exited_pane_ids: Vec<uuid::Uuid> = panes
+            let mut exited_pane_ids: Vec<uuid::Uuid> = panes
                 .iter()
                 .filter(|(_, pane)| pane.has_exited(cx))
                 .map(|(id, _)| *id)
                 .collect();
+
+            // A pinned tab never closes on its own: its last pane stays,
+            // showing how its process ended
+            if tab.pinned && exited_pane_ids.len() >= total_panes {
+                let keep = if exited_pane_ids.contains(&tab.active_pane) {
+                    tab.active_pane
+                } else {
+                    tab.panes.first_leaf_id()
+                };
+                exited_pane_ids.retain(|id| *id != keep);
+                if let Some(terminal) = tab
+                    .panes
+                    .find_pane(keep)
+                    .and_then(|pane| pane.as_terminal().cloned())
+                {
+                    terminal.update(cx, |terminal, cx| terminal.hold_after_exit(cx));
+                }
+            }
             let exited_count = exited_pane_ids.len();
 
             // Phase 2: Remove all exited panes, tracking sibling for focus
@@ -779,8 +3487,7 @@ impl Workspace {
                     self.active_tab = self.tabs.len().saturating_sub(1);
                 }
             } else {
-                // Last tab - quit the app
-                cx.quit();
+                self.close_group(cx);
             }
         }
     }
@@ -794,17 +3501,34 @@ impl Render for Workspace {
         // Save window bounds if changed
         self.maybe_save_window_bounds(window);
 
+        // Remember the active pane for the tab switcher's ordering
+        self.note_active_pane();
+
         // Focus the active pane (unless an overlay is focused)
         let scratchpad_focused = self
             .scratchpad
             .as_ref()
             .is_some_and(|sp| sp.visible && sp.input.read(cx).focus_handle(cx).is_focused(window));
-        let command_palette_focused = self.command_palette.is_some();
+        let command_palette_focused = self.command_palette.is_some()
+            || self.tab_switcher.is_some()
+            || self.cheat_sheet.is_some();
+        let panel_visible = self
+            .port_forwards_panel
+            .as_ref()
+            .is_some_and(|pf| pf.visible)
+            || self.cluster_panel.as_ref().is_some_and(|cp| cp.visible)
+            || self.renaming.is_some();
 
-        if !scratchpad_focused && !command_palette_focused {
+        if !scratchpad_focused && !command_palette_focused && !panel_visible {
             if let Some(tab) = self.tabs.get(self.active_tab) {
-                if let Some(pane) = tab.panes.find_pane(tab.active_pane) {
-                    let pane_focus = pane.focus_handle(cx);
+                let pane_focus = match tab.floating.as_ref().filter(|f| f.visible && f.focused) {
+                    Some(floating) => Some(floating.terminal.read(cx).focus_handle.clone()),
+                    None => tab
+                        .panes
+                        .find_pane(tab.active_pane)
+                        .map(|pane| pane.focus_handle(cx)),
+                };
+                if let Some(pane_focus) = pane_focus {
                     if !pane_focus.is_focused(window) {
                         window.focus(&pane_focus);
                     }
@@ -837,6 +3561,7 @@ impl Render for Workspace {
         let muted = colors.muted;
         let tab_active_bg = colors.tab_active;
         let red = colors.red;
+        let accent = colors.accent;
 
         let active_tab_idx = self.active_tab;
         let tab_count = self.tabs.len();
@@ -844,14 +3569,55 @@ impl Render for Workspace {
         // Pre-compute tab titles (dynamic from terminal or fallback)
         let tab_titles = self.get_tab_titles(cx);
 
+        let reconnect_status = self
+            .tabs
+            .get(self.active_tab)
+            .and_then(|tab| tab.panes.find_pane(tab.active_pane))
+            .and_then(|pane| pane.reconnect_status(cx));
+
+        // Keys pressed so far of a key sequence, and what can follow them
+        let (pending_keys, key_hints) = match cx.try_global::<KeySequences>() {
+            Some(sequences) if !self.pending_keys.is_empty() => {
+                let contexts = window.context_stack();
+                (
+                    sequences.describe(&self.pending_keys, &contexts),
+                    sequences.hints(&self.pending_keys, &contexts),
+                )
+            }
+            _ => (None, Vec::new()),
+        };
+
         div()
             .size_full()
             .bg(background)
             .flex()
             .flex_col()
+            // Track tab drags so one released outside the window can be detached
+            .on_drag_move(cx.listener(|this, event: &DragMoveEvent<TabDrag>, _window, cx| {
+                let index = event.drag(cx).index;
+                this.tab_drag = this.tabs.get(index).map(|tab| tab.id);
+            }))
+            .capture_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                this.handle_key_sequence(event, window, cx);
+            }))
+            .capture_any_mouse_up(cx.listener(|this, _: &MouseUpEvent, _window, _cx| {
+                this.tab_drag = None;
+            }))
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|this, event: &MouseUpEvent, window, cx| {
+                    if let Some(tab_id) = this.tab_drag.take() {
+                        this.drop_tab_outside(tab_id, event.position, window, cx);
+                    }
+                }),
+            )
             .on_action(cx.listener(|_this, _: &OpenSettings, _window, _cx| {
                 crate::settings_opener::open_config_file();
             }))
+            .on_action(cx.listener(|this, _: &ShowEffectiveConfig, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.show_effective_config(cx);
+            }))
             .on_action(cx.listener(|this, _: &Quit, _window, cx| {
                 this.request_quit(cx);
             }))
@@ -862,9 +3628,46 @@ impl Render for Workspace {
             .on_action(cx.listener(|this, _: &ToggleScratchpad, window, cx| {
                 this.toggle_scratchpad(window, cx);
             }))
+            .on_action(cx.listener(|this, _: &TogglePortForwards, window, cx| {
+                this.toggle_port_forwards(window, cx);
+            }))
+            .on_action(cx.listener(|this, action: &AddPortForward, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.add_port_forward(&action.forward, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &OpenFileBrowser, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.open_file_browser(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &OpenCluster, window, cx| {
+                this.toggle_cluster_panel(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleBroadcastTab, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.toggle_broadcast_tab(cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleBroadcastPane, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.toggle_broadcast_pane(cx);
+            }))
+            .on_action(cx.listener(|this, _: &StopBroadcast, _window, cx| {
+                this.stop_broadcast(cx);
+            }))
             .on_action(cx.listener(|this, _: &ToggleCommandPalette, window, cx| {
                 this.toggle_command_palette(window, cx);
             }))
+            .on_action(cx.listener(|this, _: &ToggleTabSwitcher, window, cx| {
+                if this.command_palette.is_some() {
+                    return;
+                }
+                this.toggle_tab_switcher(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleKeybindings, window, cx| {
+                if this.command_palette.is_some() || this.tab_switcher.is_some() {
+                    return;
+                }
+                this.toggle_cheat_sheet(window, cx);
+            }))
             .on_action(cx.listener(|this, _: &NewTab, _window, cx| {
                 if this.has_active_overlay() { return; }
                 this.new_tab(cx);
@@ -873,25 +3676,125 @@ impl Render for Workspace {
                 if this.has_active_overlay() { return; }
                 this.open_replay(cx);
             }))
+            .on_action(cx.listener(|this, action: &ReplayRecording, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.open_replay_file(action.path.clone(), cx);
+            }))
             .on_action(cx.listener(|this, _: &NextTab, _window, cx| {
                 if this.has_active_overlay() { return; }
-                this.next_tab(cx);
+                this.next_tab(cx);
+            }))
+            .on_action(cx.listener(|this, _: &PrevTab, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.prev_tab(cx);
+            }))
+            .on_action(cx.listener(|this, _: &SplitVertical, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.split_pane(SplitDirection::Horizontal, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &SplitHorizontal, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.split_pane(SplitDirection::Vertical, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ClosePane, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.request_close_pane(cx);
+            }))
+            .on_action(cx.listener(|this, action: &ResizePane, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.resize_active_pane(action, cx);
+            }))
+            .on_action(cx.listener(|this, _: &EqualizePanes, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.equalize_panes(cx);
+            }))
+            .on_action(cx.listener(|this, _: &RenameTab, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.start_rename_tab(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &TogglePinTab, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.toggle_pin_tab(cx);
+            }))
+            .on_action(cx.listener(|this, action: &SetTabColor, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.set_tab_color(action.color, cx);
+            }))
+            .on_action(cx.listener(|this, _: &MoveTabLeft, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.move_active_tab(false, cx);
+            }))
+            .on_action(cx.listener(|this, _: &MoveTabRight, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.move_active_tab(true, cx);
+            }))
+            .on_action(cx.listener(|this, action: &GoToTab, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.switch_tab(action.index, cx);
+            }))
+            .on_action(cx.listener(|this, _: &GoToLastTab, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.switch_tab(this.tabs.len().saturating_sub(1), cx);
+            }))
+            .on_action(cx.listener(|this, action: &NewTabWithProfile, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.connect(&action.profile, cx);
+            }))
+            .on_action(cx.listener(|this, action: &RunCommand, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.run_command(&action.command, action.target, window, cx);
+            }))
+            .on_action(cx.listener(|this, action: &ReopenClosedTab, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.reopen_closed_tab(action.index, cx);
+            }))
+            .on_action(cx.listener(|this, _: &NewTabGroup, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.new_group(cx);
+            }))
+            .on_action(cx.listener(|this, _: &NextTabGroup, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.next_group(cx);
+            }))
+            .on_action(cx.listener(|this, _: &PrevTabGroup, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.prev_group(cx);
+            }))
+            .on_action(cx.listener(|this, action: &GoToTabGroup, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.switch_group(action.index, cx);
+            }))
+            .on_action(cx.listener(|this, _: &RenameTabGroup, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.start_rename_group(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleFloatingPane, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.toggle_floating_pane(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &TogglePaneZoom, _window, cx| {
+                if this.has_active_overlay() { return; }
+                this.toggle_pane_zoom(cx);
+            }))
+            .on_action(cx.listener(|this, action: &FocusPane, window, cx| {
+                if this.has_active_overlay() { return; }
+                this.focus_pane_in_direction(action.direction, window, cx);
             }))
-            .on_action(cx.listener(|this, _: &PrevTab, _window, cx| {
+            .on_action(cx.listener(|this, action: &SwapPane, _window, cx| {
                 if this.has_active_overlay() { return; }
-                this.prev_tab(cx);
+                this.swap_active_pane(action.direction, cx);
             }))
-            .on_action(cx.listener(|this, _: &SplitVertical, window, cx| {
+            .on_action(cx.listener(|this, _: &RotatePanes, _window, cx| {
                 if this.has_active_overlay() { return; }
-                this.split_pane(SplitDirection::Horizontal, window, cx);
+                this.rotate_panes(cx);
             }))
-            .on_action(cx.listener(|this, _: &SplitHorizontal, window, cx| {
+            .on_action(cx.listener(|this, _: &MovePaneToNewTab, window, cx| {
                 if this.has_active_overlay() { return; }
-                this.split_pane(SplitDirection::Vertical, window, cx);
+                this.move_pane_to_new_tab(window, cx);
             }))
-            .on_action(cx.listener(|this, _: &ClosePane, _window, cx| {
+            .on_action(cx.listener(|this, action: &MovePaneToTab, window, cx| {
                 if this.has_active_overlay() { return; }
-                this.request_close_pane(cx);
+                this.move_pane_to_tab(action.index, window, cx);
             }))
             .on_action(cx.listener(|this, _: &FocusNextPane, window, cx| {
                 if this.has_active_overlay() { return; }
@@ -957,6 +3860,60 @@ impl Render for Workspace {
                     .flex()
                     .pl(px(settings::constants::tab_bar::LEFT_PADDING))
                     .pr(px(settings::constants::tab_bar::RIGHT_PADDING))
+                    // Tab groups, once there's more than one
+                    .when(self.groups.len() > 1, |d| {
+                        d.children(self.groups.iter().enumerate().map(|(i, group)| {
+                            let is_active = i == self.active_group;
+                            div()
+                                .id(ElementId::Name(format!("tab-group-{}", i).into()))
+                                .h(px(38.0))
+                                .px_2()
+                                .flex()
+                                .items_center()
+                                .cursor_pointer()
+                                .text_xs()
+                                .border_r_1()
+                                .border_color(border_color)
+                                .when(is_active, |d| {
+                                    d.text_color(foreground).font_weight(FontWeight::SEMIBOLD)
+                                })
+                                .when(!is_active, |d| {
+                                    d.text_color(muted).hover(|d| d.bg(tab_active_bg))
+                                })
+                                .on_click(cx.listener(
+                                    move |this, event: &ClickEvent, window, cx| {
+                                        this.switch_group(i, cx);
+                                        if event.click_count() >= 2 {
+                                            this.start_rename_group(window, cx);
+                                        }
+                                    },
+                                ))
+                                .map(|d| match self
+                                    .renaming
+                                    .as_ref()
+                                    .filter(|r| r.target == RenameTarget::Group(i))
+                                {
+                                    Some(rename) => d.child(
+                                        div()
+                                            .w(px(100.0))
+                                            .on_key_down(cx.listener(
+                                                |this, event: &KeyDownEvent, _window, cx| {
+                                                    if event.keystroke.key == "escape" {
+                                                        this.finish_rename_tab(false, cx);
+                                                    }
+                                                },
+                                            ))
+                                            .child(
+                                                gpui_component::input::Input::new(&rename.input)
+                                                    .appearance(false)
+                                                    .bordered(false)
+                                                    .xsmall(),
+                                            ),
+                                    ),
+                                    None => d.child(group.name.clone()),
+                                })
+                        }))
+                    })
                     // Tabs - stuck together, no gaps
                     .children(self.tabs.iter().enumerate().zip(tab_titles).map(|((i, tab), title)| {
                         let is_active = i == active_tab_idx;
@@ -992,7 +3949,12 @@ impl Render for Workspace {
                             .on_drop(cx.listener(move |this, drag: &TabDrag, _window, cx| {
                                 let from = drag.index;
                                 let to = i;
-                                if from != to && from < this.tabs.len() && to < this.tabs.len() {
+                                // Pinned tabs only trade places with each other
+                                if from != to
+                                    && from < this.tabs.len()
+                                    && to < this.tabs.len()
+                                    && this.tabs[from].pinned == this.tabs[to].pinned
+                                {
                                     this.tabs.swap(from, to);
                                     // Follow the active tab through the swap
                                     if this.active_tab == from {
@@ -1000,15 +3962,29 @@ impl Render for Workspace {
                                     } else if this.active_tab == to {
                                         this.active_tab = from;
                                     }
-                                    // Invalidate title cache
-                                    this.last_title_update = std::time::Instant::now()
-                                        - settings::constants::timing::TITLE_CACHE_TTL;
+                                    this.invalidate_tab_titles();
                                     cx.notify();
                                 }
                             }))
-                            .on_click(cx.listener(move |this, _: &ClickEvent, _window, cx| {
+                            .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
                                 this.switch_tab(i, cx);
+                                if event.click_count() >= 2 {
+                                    this.start_rename_tab(window, cx);
+                                }
                             }))
+                            .relative()
+                            // Color stripe along the top edge
+                            .when_some(tab.color.map(|color| tab_color(color, cx)), |d, color| {
+                                d.child(
+                                    div()
+                                        .absolute()
+                                        .top_0()
+                                        .left_0()
+                                        .right_0()
+                                        .h(px(2.0))
+                                        .bg(color),
+                                )
+                            })
                             .child({
                                 let badge = tab.panes.find_pane(tab.active_pane)
                                     .map(|pane_kind| pane_kind.badge(cx))
@@ -1029,15 +4005,46 @@ impl Render for Workspace {
                                             .text_color(badge_color)
                                             .child(badge_text),
                                     )
-                                    .child(
-                                        div()
-                                            .text_sm()
-                                            .overflow_hidden()
-                                            .whitespace_nowrap()
-                                            .child(title),
-                                    )
+                                    .map(|d| match self.renaming.as_ref().filter(|r| r.target == RenameTarget::Tab(tab_id)) {
+                                        Some(rename) => d.child(
+                                            div()
+                                                .on_key_down(cx.listener(
+                                                    |this, event: &KeyDownEvent, _window, cx| {
+                                                        if event.keystroke.key == "escape" {
+                                                            this.finish_rename_tab(false, cx);
+                                                        }
+                                                    },
+                                                ))
+                                                .child(
+                                                    gpui_component::input::Input::new(&rename.input)
+                                                        .appearance(false)
+                                                        .bordered(false)
+                                                        .xsmall(),
+                                                ),
+                                        ),
+                                        None => d.child(
+                                            div()
+                                                .text_sm()
+                                                .overflow_hidden()
+                                                .whitespace_nowrap()
+                                                .child(title),
+                                        ),
+                                    })
                             })
-                            .child(
+                            // Pinned tabs show a pin where the close button was
+                            .when(tab.pinned, |d| {
+                                d.child(
+                                    div()
+                                        .w(px(18.0))
+                                        .ml_2()
+                                        .flex()
+                                        .justify_center()
+                                        .text_size(px(10.0))
+                                        .text_color(muted)
+                                        .child("\u{25C6}"),
+                                )
+                            })
+                            .when(!tab.pinned, |d| d.child(
                                 div()
                                     .id(ElementId::Name(format!("close-{}", tab_id).into()))
                                     .w(px(18.0))
@@ -1057,7 +4064,7 @@ impl Render for Workspace {
                                         ))
                                     })
                                     .child("×"),
-                            )
+                            ))
                     }))
                     // New tab cell
                     .child(
@@ -1079,19 +4086,58 @@ impl Render for Workspace {
                     // Spacer
                     .child(div().flex_1())
             )
+            // Problems found in config.toml on the last reload
+            .children(crate::config_check::render_config_banner(cx))
             .child(
-                // Pane content
+                // Pane content, with the floating terminal over it
                 div()
+                    .id("pane-area")
                     .flex_1()
                     .w_full()
                     .h_full()
+                    .relative()
                     .overflow_hidden()
+                    .on_drag_move(cx.listener(
+                        |this, event: &DragMoveEvent<FloatingDrag>, _window, cx| {
+                            let resize = event.drag(cx).resize;
+                            if let Some(floating) = this
+                                .tabs
+                                .get_mut(this.active_tab)
+                                .and_then(|tab| tab.floating.as_mut())
+                            {
+                                floating.drag_to(event.event.position, event.bounds, resize);
+                                cx.notify();
+                            }
+                        },
+                    ))
                     .children(self.tabs.get(self.active_tab).map(|tab| {
-                        crate::pane_group_view::render_pane_tree(&tab.panes, tab.active_pane, window, cx)
+                        // A zoomed pane renders alone; the tree stays as it was
+                        let zoomed = tab.zoomed_pane().and_then(|id| {
+                            let pane = tab.panes.find_pane(id)?;
+                            Some(PaneNode::Leaf { id, pane })
+                        });
+                        crate::pane_group_view::render_pane_tree(
+                            zoomed.as_ref().unwrap_or(&tab.panes),
+                            tab.active_pane,
+                            window,
+                            cx,
+                        )
                     }))
+                    .children(
+                        self.tabs
+                            .get(self.active_tab)
+                            .and_then(|tab| tab.floating.as_ref())
+                            .filter(|floating| floating.visible)
+                            .map(|floating| crate::floating::render_floating_pane(floating, cx)),
+                    ),
             )
             // Status bar
-            .child(render_status_bar(&self.cached_stats, cx))
+            .child(render_status_bar(
+                &self.cached_stats,
+                reconnect_status,
+                pending_keys,
+                cx,
+            ))
             // Scratchpad overlay
             .when(
                 self.scratchpad.as_ref().is_some_and(|sp| sp.visible),
@@ -1132,47 +4178,84 @@ impl Render for Workspace {
                     )
                 },
             )
+            // Port forwarding panel overlay
+            .when_some(
+                self.port_forwards_panel.as_ref().filter(|pf| pf.visible),
+                |d, panel| {
+                    d.child(
+                        div()
+                            .id("port-forwards-backdrop")
+                            .absolute()
+                            .inset_0()
+                            .bg(hsla(0.0, 0.0, 0.0, 0.4))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.hide_port_forwards(window, cx);
+                            })),
+                    )
+                    .child(crate::port_forwards::render_port_forwards_panel(
+                        panel,
+                        &self.port_forwards,
+                        cx,
+                    ))
+                },
+            )
+            // Open Cluster panel overlay
+            .when_some(
+                self.cluster_panel.as_ref().filter(|cp| cp.visible),
+                |d, panel| {
+                    d.child(
+                        div()
+                            .id("cluster-backdrop")
+                            .absolute()
+                            .inset_0()
+                            .bg(hsla(0.0, 0.0, 0.0, 0.4))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.hide_cluster_panel(window, cx);
+                            })),
+                    )
+                    .child(crate::cluster::render_cluster_panel(panel, cx))
+                },
+            )
             // Confirmation dialog overlay
-            .when(self.pending_action.is_some(), |d| {
-                let action_text = match self.pending_action {
-                    Some(PendingAction::ClosePane) => "close this pane",
-                    Some(PendingAction::CloseTab(_)) => "close this tab",
-                    Some(PendingAction::Quit) => "quit",
-                    None => "",
-                };
-                let process_name = self.pending_process_name.clone().unwrap_or_else(|| "a process".to_string());
-
-                let confirm_label = if self.pending_action == Some(PendingAction::Quit) { "Quit" } else { "Close" };
+            .when_some(self.confirm_dialog(), |d, dialog| {
+                let blocking = dialog.blocking;
+                let title_color = if blocking { red } else { foreground };
+                let modal_border = if blocking { red } else { hsla(0.0, 0.0, 0.25, 1.0) };
+                let modal_width = if dialog.details.is_empty() { 420.0 } else { 520.0 };
+                let cancel_label = if dialog.confirm_label.is_some() { "Cancel" } else { "Close" };
                 d.child(
                     // Backdrop
                     div()
                         .id("confirm-backdrop")
                         .absolute()
                         .inset_0()
-                        .bg(hsla(0.0, 0.0, 0.0, 0.6))
+                        .bg(hsla(0.0, 0.0, 0.0, if blocking { 0.75 } else { 0.6 }))
                         .flex()
                         .items_center()
                         .justify_center()
                         .child(
-                            // Modal container (clickable to cancel)
+                            // Modal container (clickable to cancel, unless blocking)
                             div()
                                 .id("confirm-modal-container")
                                 .size_full()
                                 .absolute()
-                                .on_click(cx.listener(|this, _, _, cx| {
-                                    this.cancel_pending_action(cx);
-                                }))
+                                .when(!blocking, |d| {
+                                    d.on_click(cx.listener(|this, _, _, cx| {
+                                        this.cancel_pending_action(cx);
+                                    }))
+                                })
                         )
                         .child(
                             // Modal
                             div()
                                 .bg(hsla(0.0, 0.0, 0.12, 1.0))
                                 .border_1()
-                                .border_color(hsla(0.0, 0.0, 0.25, 1.0))
+                                .when(blocking, |d| d.border_2())
+                                .border_color(modal_border)
                                 .rounded_xl()
                                 .shadow_lg()
                                 .p_6()
-                                .w(px(420.0))
+                                .w(px(modal_width))
                                 .flex()
                                 .flex_col()
                                 .gap_5()
@@ -1181,8 +4264,8 @@ impl Render for Workspace {
                                     div()
                                         .text_base()
                                         .font_weight(FontWeight::SEMIBOLD)
-                                        .text_color(foreground)
-                                        .child(format!("\"{}\" is running", process_name))
+                                        .text_color(title_color)
+                                        .child(dialog.title)
                                 )
                                 .child(
                                     // Message
@@ -1190,8 +4273,23 @@ impl Render for Workspace {
                                         .text_sm()
                                         .text_color(muted)
                                         .line_height(px(20.0))
-                                        .child(format!("Are you sure you want to {}? The running process will be terminated.", action_text))
+                                        .child(dialog.message)
                                 )
+                                .when(!dialog.details.is_empty(), |d| {
+                                    // Key fingerprints
+                                    d.child(
+                                        div()
+                                            .flex()
+                                            .flex_col()
+                                            .gap_1()
+                                            .p_3()
+                                            .rounded(px(6.0))
+                                            .bg(hsla(0.0, 0.0, 0.08, 1.0))
+                                            .text_sm()
+                                            .text_color(foreground)
+                                            .children(dialog.details.into_iter().map(|line| div().child(line)))
+                                    )
+                                })
                                 .child(
                                     // Buttons
                                     div()
@@ -1201,20 +4299,23 @@ impl Render for Workspace {
                                         .mt_2()
                                         .child(
                                             Button::new("cancel-btn")
-                                                .label("Cancel")
+                                                .label(cancel_label)
                                                 .ghost()
                                                 .on_click(cx.listener(|this, _, _, cx| {
                                                     this.cancel_pending_action(cx);
                                                 }))
                                         )
-                                        .child(
-                                            Button::new("confirm-btn")
-                                                .label(confirm_label)
-                                                .danger()
-                                                .on_click(cx.listener(|this, _, _, cx| {
-                                                    this.confirm_pending_action(cx);
-                                                }))
-                                        )
+                                        .when_some(dialog.confirm_label, |d, confirm_label| {
+                                            d.child(
+                                                Button::new("confirm-btn")
+                                                    .label(confirm_label)
+                                                    .when(dialog.destructive, |b| b.danger())
+                                                    .when(!dialog.destructive, |b| b.primary())
+                                                    .on_click(cx.listener(|this, _, _, cx| {
+                                                        this.confirm_pending_action(cx);
+                                                    }))
+                                            )
+                                        })
                                 )
                         )
                 )
@@ -1223,6 +4324,43 @@ impl Render for Workspace {
             .when_some(self.command_palette.clone(), |d, palette| {
                 d.child(palette)
             })
+            // Tab switcher overlay
+            .when_some(self.tab_switcher.clone(), |d, switcher| d.child(switcher))
+            // Keybinding cheat sheet overlay
+            .when_some(self.cheat_sheet.clone(), |d, cheat_sheet| d.child(cheat_sheet))
+            // Keys that can follow a pending key sequence
+            .when(!key_hints.is_empty(), |d| {
+                d.child(
+                    div()
+                        .absolute()
+                        .right(px(12.0))
+                        .bottom(px(settings::constants::status_bar::HEIGHT + 8.0))
+                        .min_w(px(180.0))
+                        .p(px(8.0))
+                        .bg(hsla(0.0, 0.0, 0.10, 0.95))
+                        .border_1()
+                        .border_color(hsla(0.0, 0.0, 0.25, 1.0))
+                        .rounded(px(6.0))
+                        .shadow_lg()
+                        .flex()
+                        .flex_col()
+                        .gap(px(2.0))
+                        .text_xs()
+                        .children(key_hints.into_iter().map(|(keys, label)| {
+                            div()
+                                .flex()
+                                .gap(px(12.0))
+                                .child(
+                                    div()
+                                        .min_w(px(48.0))
+                                        .text_color(accent)
+                                        .font_weight(FontWeight::SEMIBOLD)
+                                        .child(keys),
+                                )
+                                .child(div().text_color(foreground).child(label))
+                        })),
+                )
+            })
             // Dialog layer - must be rendered for dialogs to appear
             .children(Root::render_dialog_layer(window, cx))
     }
@@ -1235,7 +4373,7 @@ impl Render for Workspace {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gpui::TestAppContext;
+    use gpui::{TestAppContext, VisualContext};
 
     /// Initialize test context with required globals (theme, etc.)
     fn init_test_context(cx: &mut TestAppContext) {
@@ -1544,113 +4682,476 @@ mod tests {
         });
     }
 
-    // ========================================================================
-    // Focus Management Tests
-    // ========================================================================
-
+    // ========================================================================
+    // Focus Management Tests
+    // ========================================================================
+
+    #[gpui::test]
+    fn test_set_active_pane(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        // Get the first pane's ID (workspace starts with one pane)
+        let first_pane_id = cx.read(|app| {
+            let ws = workspace.read(app);
+            let tab = &ws.tabs[0];
+            tab.panes.first_leaf_id()
+        });
+
+        // Set active pane (should work even with just one pane)
+        workspace.update(cx, |ws, cx| {
+            ws.set_active_pane(first_pane_id, cx);
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            let tab = &ws.tabs[0];
+            assert_eq!(
+                tab.active_pane, first_pane_id,
+                "Active pane should be set to first pane"
+            );
+        });
+    }
+
+    // ========================================================================
+    // Confirmation Dialog Tests
+    // ========================================================================
+
+    #[gpui::test]
+    fn test_cancel_pending_action(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        // Set up a pending action manually
+        workspace.update(cx, |ws, cx| {
+            ws.pending_action = Some(PendingAction::Quit);
+            ws.pending_process_name = Some("test_process".to_string());
+            cx.notify();
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert!(ws.pending_action.is_some(), "Should have pending action");
+            assert!(
+                ws.pending_process_name.is_some(),
+                "Should have process name"
+            );
+        });
+
+        // Cancel the pending action
+        workspace.update(cx, |ws, cx| {
+            ws.cancel_pending_action(cx);
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert!(
+                ws.pending_action.is_none(),
+                "Pending action should be cleared"
+            );
+            assert!(
+                ws.pending_process_name.is_none(),
+                "Process name should be cleared"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_pending_action_states(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        // Test ClosePane action
+        workspace.update(cx, |ws, _| {
+            ws.pending_action = Some(PendingAction::ClosePane);
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert_eq!(ws.pending_action, Some(PendingAction::ClosePane));
+        });
+
+        // Test CloseTab action
+        workspace.update(cx, |ws, _| {
+            ws.pending_action = Some(PendingAction::CloseTab(0));
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert_eq!(ws.pending_action, Some(PendingAction::CloseTab(0)));
+        });
+
+        // Test Quit action
+        workspace.update(cx, |ws, _| {
+            ws.pending_action = Some(PendingAction::Quit);
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert_eq!(ws.pending_action, Some(PendingAction::Quit));
+        });
+    }
+
+    // ========================================================================
+    // Host Key Verification Tests
+    // ========================================================================
+
+    fn test_host_key() -> HostKey {
+        HostKey::new(
+            "ssh-ed25519",
+            "AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl",
+        )
+    }
+
+    fn test_host_key_files() -> HostKeyFiles {
+        HostKeyFiles {
+            entry: PathBuf::from("/home/me/.ssh/known_hosts"),
+            save_to: PathBuf::from("/home/me/.ssh/known_hosts"),
+        }
+    }
+
+    fn test_host_key_check(status: HostKeyStatus) -> HostKeyCheck {
+        HostKeyCheck {
+            known_host: ("example.com".to_string(), 22),
+            status,
+            key: test_host_key(),
+            files: test_host_key_files(),
+        }
+    }
+
+    #[gpui::test]
+    fn test_unknown_host_key_prompts_for_trust(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        workspace.update(cx, |ws, cx| {
+            let target = SshTarget::parse("me@example.com").unwrap();
+            ws.on_host_key_verified(
+                target,
+                Vec::new(),
+                Ok(Some(test_host_key_check(HostKeyStatus::Unknown))),
+                cx,
+            );
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert_eq!(ws.pending_action, Some(PendingAction::TrustHostKey));
+            assert_eq!(ws.tabs.len(), 1, "No session until the key is trusted");
+
+            let dialog = ws.confirm_dialog().expect("dialog should be shown");
+            assert_eq!(dialog.confirm_label, Some("Trust and Connect"));
+            assert!(!dialog.blocking);
+            assert_eq!(
+                dialog.details,
+                vec!["ssh-ed25519 SHA256:+DiY3wvvV6TuJJhbpZisF/zLDA0zPMSvHdkr4UvCOqU".to_string()]
+            );
+        });
+
+        workspace.update(cx, |ws, cx| ws.cancel_pending_action(cx));
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert!(ws.pending_action.is_none());
+            assert!(ws.pending_host_key.is_none(), "Prompt should be cleared");
+            assert_eq!(ws.tabs.len(), 1, "Cancelling must not connect");
+        });
+    }
+
+    #[gpui::test]
+    fn test_changed_host_key_blocks_connection(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        workspace.update(cx, |ws, cx| {
+            let target = SshTarget::parse("example.com").unwrap();
+            let status = HostKeyStatus::Changed {
+                line: 7,
+                known_fingerprint: "SHA256:old".to_string(),
+            };
+            ws.on_host_key_verified(
+                target,
+                Vec::new(),
+                Ok(Some(test_host_key_check(status))),
+                cx,
+            );
+        });
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert_eq!(ws.pending_action, Some(PendingAction::HostKeyMismatch));
+            assert!(ws.has_active_overlay());
+
+            let dialog = ws.confirm_dialog().expect("warning should be shown");
+            assert!(dialog.blocking);
+            assert_eq!(
+                dialog.confirm_label, None,
+                "Changed keys cannot be accepted"
+            );
+            assert!(dialog.title.contains("has changed"));
+            assert!(dialog.details[1].contains("SHA256:old"));
+            assert!(dialog.details[1].contains("line 7"));
+        });
+
+        // Even a direct confirm only dismisses the warning
+        workspace.update(cx, |ws, cx| ws.confirm_pending_action(cx));
+
+        cx.read(|app| {
+            let ws = workspace.read(app);
+            assert!(ws.pending_action.is_none());
+            assert!(ws.pending_host_key.is_none());
+            assert_eq!(ws.tabs.len(), 1, "No session for a mismatched key");
+        });
+    }
+
+    #[gpui::test]
+    fn test_host_key_dialogs_name_the_configured_files(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+        let files = HostKeyFiles {
+            entry: PathBuf::from("/etc/ssh/ssh_known_hosts"),
+            save_to: PathBuf::from("/work/keys/known_hosts"),
+        };
+
+        workspace.update(cx, |ws, cx| {
+            let mut check = test_host_key_check(HostKeyStatus::Unknown);
+            check.files = files.clone();
+            let target = SshTarget::parse("example.com").unwrap();
+            ws.on_host_key_verified(target, Vec::new(), Ok(Some(check)), cx);
+            let dialog = ws.confirm_dialog().unwrap();
+            assert!(dialog.message.contains("saved to /work/keys/known_hosts"));
+            ws.cancel_pending_action(cx);
+
+            let mut check = test_host_key_check(HostKeyStatus::Changed {
+                line: 3,
+                known_fingerprint: "SHA256:old".to_string(),
+            });
+            check.files = files;
+            let target = SshTarget::parse("example.com").unwrap();
+            ws.on_host_key_verified(target, Vec::new(), Ok(Some(check)), cx);
+            let dialog = ws.confirm_dialog().unwrap();
+            assert!(dialog.details[1].contains("/etc/ssh/ssh_known_hosts line 3"));
+            assert!(dialog
+                .message
+                .contains("ssh-keygen -f /etc/ssh/ssh_known_hosts -R example.com"));
+        });
+    }
+
+    #[gpui::test]
+    fn test_host_key_prompts_wait_for_open_confirmation(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        workspace.update(cx, |ws, cx| {
+            ws.pending_action = Some(PendingAction::Quit);
+            ws.pending_process_name = Some("vim".to_string());
+
+            let target = SshTarget::parse("example.com").unwrap();
+            ws.on_host_key_verified(
+                target.clone(),
+                Vec::new(),
+                Ok(Some(test_host_key_check(HostKeyStatus::Unknown))),
+                cx,
+            );
+            let status = HostKeyStatus::Revoked { line: 2 };
+            ws.on_host_key_verified(
+                target,
+                Vec::new(),
+                Ok(Some(test_host_key_check(status))),
+                cx,
+            );
+            assert_eq!(
+                ws.pending_action,
+                Some(PendingAction::Quit),
+                "Host key prompts must not replace another confirmation"
+            );
+            assert_eq!(ws.pending_process_name.as_deref(), Some("vim"));
+            assert_eq!(ws.queued_host_keys.len(), 2);
+
+            // Each queued prompt shows in turn as the one before it closes
+            ws.cancel_pending_action(cx);
+            assert_eq!(ws.pending_action, Some(PendingAction::TrustHostKey));
+            ws.cancel_pending_action(cx);
+            assert_eq!(ws.pending_action, Some(PendingAction::HostKeyMismatch));
+            ws.confirm_pending_action(cx);
+            assert!(ws.pending_action.is_none());
+            assert!(ws.queued_host_keys.is_empty());
+        });
+    }
+
     #[gpui::test]
-    fn test_set_active_pane(cx: &mut TestAppContext) {
+    fn test_host_key_save_failure_is_shown(cx: &mut TestAppContext) {
         init_test_context(cx);
         let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
 
-        // Get the first pane's ID (workspace starts with one pane)
-        let first_pane_id = cx.read(|app| {
-            let ws = workspace.read(app);
-            let tab = &ws.tabs[0];
-            tab.panes.first_leaf_id()
-        });
-
-        // Set active pane (should work even with just one pane)
         workspace.update(cx, |ws, cx| {
-            ws.set_active_pane(first_pane_id, cx);
+            let prompt = HostKeyPrompt {
+                target: SshTarget::parse("example.com").unwrap(),
+                known_host: ("example.com".to_string(), 22),
+                key: test_host_key(),
+                status: HostKeyStatus::Unknown,
+                files: test_host_key_files(),
+                forwards: Vec::new(),
+                save_error: Some("Writing known_hosts failed: read-only file system".to_string()),
+                cluster: None,
+            };
+            ws.show_host_key_prompt(prompt, cx);
         });
 
         cx.read(|app| {
             let ws = workspace.read(app);
-            let tab = &ws.tabs[0];
-            assert_eq!(
-                tab.active_pane, first_pane_id,
-                "Active pane should be set to first pane"
-            );
+            assert_eq!(ws.pending_action, Some(PendingAction::HostKeySaveFailed));
+            assert_eq!(ws.tabs.len(), 1, "An unsaved key must not connect");
+
+            let dialog = ws.confirm_dialog().expect("error should be shown");
+            assert_eq!(dialog.confirm_label, None);
+            assert!(dialog.message.contains("read-only file system"));
         });
     }
 
-    // ========================================================================
-    // Confirmation Dialog Tests
-    // ========================================================================
-
     #[gpui::test]
-    fn test_cancel_pending_action(cx: &mut TestAppContext) {
+    fn test_cluster_waits_for_host_key_decisions(cx: &mut TestAppContext) {
         init_test_context(cx);
         let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
 
-        // Set up a pending action manually
         workspace.update(cx, |ws, cx| {
-            ws.pending_action = Some(PendingAction::Quit);
-            ws.pending_process_name = Some("test_process".to_string());
-            cx.notify();
-        });
-
-        cx.read(|app| {
-            let ws = workspace.read(app);
-            assert!(ws.pending_action.is_some(), "Should have pending action");
-            assert!(
-                ws.pending_process_name.is_some(),
-                "Should have process name"
+            let members = vec![
+                ClusterMember::Ssh(SshTarget::parse("web1").unwrap()),
+                ClusterMember::Ssh(SshTarget::parse("web2").unwrap()),
+                ClusterMember::Command {
+                    program: "true".to_string(),
+                    args: Vec::new(),
+                },
+            ];
+            let host_keys = vec![
+                Some(Ok(Some(test_host_key_check(HostKeyStatus::Unknown)))),
+                Some(Ok(Some(test_host_key_check(HostKeyStatus::Revoked {
+                    line: 2,
+                })))),
+                None,
+            ];
+            let connections = vec![None, None, None];
+            ws.on_cluster_checked("web".to_string(), members, connections, host_keys, cx);
+            assert_eq!(
+                ws.tabs.len(),
+                1,
+                "The cluster waits for its host key prompts"
             );
-        });
+            assert_eq!(ws.pending_action, Some(PendingAction::TrustHostKey));
+            assert_eq!(ws.queued_host_keys.len(), 1);
 
-        // Cancel the pending action
-        workspace.update(cx, |ws, cx| {
             ws.cancel_pending_action(cx);
-        });
+            assert_eq!(ws.pending_action, Some(PendingAction::HostKeyMismatch));
+            assert_eq!(ws.tabs.len(), 1);
 
-        cx.read(|app| {
-            let ws = workspace.read(app);
-            assert!(
-                ws.pending_action.is_none(),
-                "Pending action should be cleared"
+            ws.confirm_pending_action(cx);
+            assert!(ws.pending_action.is_none());
+            assert!(ws.pending_clusters.is_empty());
+            assert_eq!(
+                ws.tabs.len(),
+                2,
+                "The cluster opens once every key is decided"
             );
-            assert!(
-                ws.pending_process_name.is_none(),
-                "Process name should be cleared"
+            assert_eq!(
+                ws.tabs[1].panes.all_panes().len(),
+                1,
+                "Hosts whose key wasn't trusted are left out"
             );
         });
     }
 
     #[gpui::test]
-    fn test_pending_action_states(cx: &mut TestAppContext) {
+    fn test_port_forwards_panel_toggle(cx: &mut TestAppContext) {
         init_test_context(cx);
-        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
 
-        // Test ClosePane action
-        workspace.update(cx, |ws, _| {
-            ws.pending_action = Some(PendingAction::ClosePane);
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.toggle_port_forwards(window, cx);
+            assert!(ws.port_forwards_panel.as_ref().is_some_and(|pf| pf.visible));
+            assert!(ws.has_active_overlay());
+
+            ws.toggle_port_forwards(window, cx);
+            assert!(!ws.port_forwards_panel.as_ref().is_some_and(|pf| pf.visible));
+            assert!(!ws.has_active_overlay());
         });
+    }
 
-        cx.read(|app| {
-            let ws = workspace.read(app);
-            assert_eq!(ws.pending_action, Some(PendingAction::ClosePane));
+    #[gpui::test]
+    fn test_open_file_browser_splits_active_pane(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.open_file_browser(window, cx);
+
+            let tab = &ws.tabs[ws.active_tab];
+            assert_eq!(tab.panes.all_panes().len(), 2);
+            let pane = tab.panes.find_pane(tab.active_pane).unwrap();
+            assert!(matches!(pane, PaneKind::FileBrowser(_)));
+            assert!(pane.as_terminal().is_none());
+            assert!(!pane.has_running_processes(cx));
+            assert!(!pane.has_exited(cx));
+            assert_eq!(pane.progress(cx), terminal::ProgressState::Hidden);
         });
+    }
 
-        // Test CloseTab action
-        workspace.update(cx, |ws, _| {
-            ws.pending_action = Some(PendingAction::CloseTab(0));
+    #[gpui::test]
+    fn test_port_forward_without_ssh_tab_reports_error(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.toggle_port_forwards(window, cx);
+            let input = ws.port_forwards_panel.as_ref().unwrap().input.clone();
+            input.update(cx, |input, cx| {
+                input.set_value("L 8080:db:5432", window, cx)
+            });
+            ws.add_forward_from_input(window, cx);
+
+            let panel = ws.port_forwards_panel.as_ref().unwrap();
+            assert!(panel
+                .error
+                .as_ref()
+                .is_some_and(|e| e.contains("No SSH session")));
+            assert!(ws.port_forwards.is_empty());
         });
+    }
 
-        cx.read(|app| {
-            let ws = workspace.read(app);
-            assert_eq!(ws.pending_action, Some(PendingAction::CloseTab(0)));
+    #[gpui::test]
+    fn test_add_port_forward_action_shows_error_in_panel(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.add_port_forward("L 8080:db:5432", window, cx);
+
+            let panel = ws.port_forwards_panel.as_ref().expect("panel opened");
+            assert!(panel.visible);
+            assert!(panel
+                .error
+                .as_ref()
+                .is_some_and(|e| e.contains("No SSH session")));
+            assert_eq!(panel.input.read(cx).value().as_ref(), "L 8080:db:5432");
+            assert!(ws.port_forwards.is_empty());
         });
+    }
+
+    #[gpui::test]
+    fn test_process_confirm_dialog_text(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, _vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
 
-        // Test Quit action
         workspace.update(cx, |ws, _| {
-            ws.pending_action = Some(PendingAction::Quit);
+            ws.pending_action = Some(PendingAction::CloseTab(0));
+            ws.pending_process_name = Some("htop".to_string());
         });
 
         cx.read(|app| {
-            let ws = workspace.read(app);
-            assert_eq!(ws.pending_action, Some(PendingAction::Quit));
+            let dialog = workspace.read(app).confirm_dialog().unwrap();
+            assert_eq!(dialog.title, "\"htop\" is running");
+            assert!(dialog.message.contains("close this tab"));
+            assert_eq!(dialog.confirm_label, Some("Close"));
+            assert!(dialog.destructive);
         });
     }
 
@@ -1815,6 +5316,541 @@ mod tests {
         });
     }
 
+    /// Split ratios in depth-first order.
+    fn split_ratios(node: &PaneNode) -> Vec<f32> {
+        match node {
+            PaneNode::Leaf { .. } => Vec::new(),
+            PaneNode::Split {
+                first,
+                second,
+                ratio,
+                ..
+            } => {
+                let mut ratios = vec![*ratio];
+                ratios.extend(split_ratios(first));
+                ratios.extend(split_ratios(second));
+                ratios
+            }
+        }
+    }
+
+    #[gpui::test]
+    fn test_resize_and_equalize_panes(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            // Left | (top / bottom), bottom active
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            ws.split_pane(SplitDirection::Vertical, window, cx);
+            assert_eq!(split_ratios(&ws.tabs[0].panes), vec![0.5, 0.5]);
+
+            // Keyboard: each direction moves the nearest divider across it
+            ws.resize_active_pane(&ResizePane::step(PaneDirection::Left), cx);
+            ws.resize_active_pane(&ResizePane::step(PaneDirection::Down), cx);
+            let ratios = split_ratios(&ws.tabs[0].panes);
+            assert!((ratios[0] - 0.45).abs() < 1e-6, "{:?}", ratios);
+            assert!((ratios[1] - 0.55).abs() < 1e-6, "{:?}", ratios);
+
+            // Dragging clamps to the minimum share
+            let right_id = ws.tabs[0].panes.all_panes()[1].0;
+            ws.set_split_ratio(right_id, 0.0, cx);
+            assert_eq!(
+                split_ratios(&ws.tabs[0].panes)[0],
+                crate::pane_group::MIN_RATIO
+            );
+            ws.set_split_ratio(right_id, f32::NAN, cx);
+            assert_eq!(
+                split_ratios(&ws.tabs[0].panes)[0],
+                crate::pane_group::MIN_RATIO
+            );
+
+            // Equalize gives the left pane and the right column equal width
+            ws.equalize_panes(cx);
+            assert_eq!(split_ratios(&ws.tabs[0].panes), vec![0.5, 0.5]);
+        });
+    }
+
+    fn pane_ids(node: &PaneNode) -> Vec<Uuid> {
+        node.all_panes().into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[gpui::test]
+    fn test_directional_focus_swap_and_rotate(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            // left | (top / bottom), bottom active
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            ws.split_pane(SplitDirection::Vertical, window, cx);
+            let [left, top, bottom] = pane_ids(&ws.tabs[0].panes)[..] else {
+                panic!("expected three panes");
+            };
+            assert_eq!(ws.tabs[0].active_pane, bottom);
+
+            ws.focus_pane_in_direction(PaneDirection::Up, window, cx);
+            assert_eq!(ws.tabs[0].active_pane, top);
+            ws.focus_pane_in_direction(PaneDirection::Left, window, cx);
+            assert_eq!(ws.tabs[0].active_pane, left);
+            // Nothing further left: focus stays put
+            ws.focus_pane_in_direction(PaneDirection::Left, window, cx);
+            assert_eq!(ws.tabs[0].active_pane, left);
+
+            // Swapping moves the pane, and focus with it
+            ws.swap_active_pane(PaneDirection::Right, cx);
+            assert_eq!(pane_ids(&ws.tabs[0].panes), vec![top, left, bottom]);
+            assert_eq!(ws.tabs[0].active_pane, left);
+
+            // Rotating the right column swaps its two panes
+            ws.rotate_panes(cx);
+            assert_eq!(pane_ids(&ws.tabs[0].panes), vec![top, bottom, left]);
+        });
+    }
+
+    #[gpui::test]
+    fn test_move_pane_between_tabs(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            let moved = ws.tabs[0].active_pane;
+
+            ws.move_pane_to_new_tab(window, cx);
+            assert_eq!(ws.tabs.len(), 2);
+            assert_eq!(ws.active_tab, 1);
+            assert_eq!(pane_ids(&ws.tabs[1].panes), vec![moved]);
+            assert_eq!(ws.tabs[0].panes.all_panes().len(), 1);
+
+            // A lone pane stays where it is
+            ws.move_pane_to_new_tab(window, cx);
+            assert_eq!(ws.tabs.len(), 2);
+
+            // Moving a tab's only pane merges it into the target and closes the tab
+            let stay = ws.tabs[0].active_pane;
+            ws.move_pane_to_tab(0, window, cx);
+            assert_eq!(ws.tabs.len(), 1);
+            assert_eq!(ws.active_tab, 0);
+            assert_eq!(pane_ids(&ws.tabs[0].panes), vec![stay, moved]);
+            assert_eq!(ws.tabs[0].active_pane, moved);
+
+            // Out of range and the current tab are no-ops
+            ws.move_pane_to_tab(0, window, cx);
+            ws.move_pane_to_tab(5, window, cx);
+            assert_eq!(ws.tabs[0].panes.all_panes().len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_toggle_pane_zoom(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            // A lone pane doesn't zoom
+            ws.toggle_pane_zoom(cx);
+            assert_eq!(ws.tabs[0].zoomed_pane(), None);
+
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            let [left, right] = pane_ids(&ws.tabs[0].panes)[..] else {
+                panic!("expected two panes");
+            };
+            ws.toggle_pane_zoom(cx);
+            assert_eq!(ws.tabs[0].zoomed_pane(), Some(right));
+            assert!(ws.tabs[0].display_title(cx).starts_with("[Z] "));
+            // The layout is kept while zoomed
+            assert_eq!(pane_ids(&ws.tabs[0].panes), vec![left, right]);
+
+            ws.toggle_pane_zoom(cx);
+            assert_eq!(ws.tabs[0].zoomed_pane(), None);
+
+            // Focusing another pane unzooms, and coming back doesn't re-zoom
+            ws.toggle_pane_zoom(cx);
+            ws.set_active_pane(left, cx);
+            assert_eq!(ws.tabs[0].zoomed_pane(), None);
+            ws.set_active_pane(right, cx);
+            assert_eq!(ws.tabs[0].zoomed_pane(), None);
+            assert!(!ws.tabs[0].display_title(cx).starts_with("[Z] "));
+        });
+    }
+
+    #[gpui::test]
+    fn test_tab_rename_pin_color_and_move(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, _window, cx| {
+            ws.new_tab(cx);
+            ws.new_tab(cx);
+            let ids: Vec<Uuid> = ws.tabs.iter().map(|tab| tab.id).collect();
+
+            // A custom name wins over the pane title until cleared
+            let fallback = ws.tabs[2].base_title(cx);
+            ws.set_tab_title(2, "  logs  ", cx);
+            assert_eq!(ws.tabs[2].base_title(cx), "logs");
+            ws.set_tab_title(2, "", cx);
+            assert_eq!(ws.tabs[2].base_title(cx), fallback);
+
+            ws.set_tab_color(Some(TabColor::Green), cx);
+            assert_eq!(ws.tabs[2].color, Some(TabColor::Green));
+            ws.set_tab_color(None, cx);
+            assert_eq!(ws.tabs[2].color, None);
+
+            // Pinning moves the tab to the front and keeps it open
+            ws.toggle_pin_tab(cx);
+            assert_eq!(ws.tabs[0].id, ids[2]);
+            assert_eq!(ws.active_tab, 0);
+            ws.close_tab(0, cx);
+            ws.request_close_tab(0, cx);
+            assert_eq!(ws.tabs.len(), 3);
+
+            // Pinned and unpinned tabs don't swap places
+            ws.move_active_tab(true, cx);
+            assert_eq!(ws.tabs[0].id, ids[2]);
+            ws.switch_tab(1, cx);
+            ws.move_active_tab(false, cx);
+            assert_eq!(ws.active_tab, 1);
+            ws.move_active_tab(true, cx);
+            assert_eq!(ws.active_tab, 2);
+            assert_eq!(
+                ws.tabs.iter().map(|tab| tab.id).collect::<Vec<_>>(),
+                vec![ids[2], ids[1], ids[0]]
+            );
+
+            // Unpinning returns it to the first unpinned slot
+            ws.switch_tab(0, cx);
+            ws.toggle_pin_tab(cx);
+            assert!(!ws.tabs[0].pinned);
+            ws.close_tab(0, cx);
+            assert_eq!(ws.tabs.len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_pinned_tab_outlives_its_process(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, _window, cx| {
+            // A command that can't start leaves its pane exited straight away
+            ws.new_tab_with_command("humanssh-test-missing-command", &[], "pinned", cx);
+            ws.toggle_pin_tab(cx);
+            ws.new_tab_with_command("humanssh-test-missing-command", &[], "loose", cx);
+            assert_eq!(ws.tabs.len(), 3);
+
+            ws.do_cleanup_exited_panes(cx);
+            assert_eq!(ws.tabs.len(), 2, "The unpinned tab closes");
+            assert!(ws.tabs[0].pinned);
+            let pane = ws.tabs[0].panes.find_pane(ws.tabs[0].active_pane).unwrap();
+            assert!(!pane.has_exited(cx), "The pinned tab's pane is held open");
+
+            // Closing it asks to unpin first
+            ws.switch_tab(0, cx);
+            ws.request_close_pane(cx);
+            assert_eq!(ws.pending_action, Some(PendingAction::PinnedTab));
+            let dialog = ws.confirm_dialog().unwrap();
+            assert!(dialog.message.contains("Unpin"));
+            assert_eq!(dialog.confirm_label, None);
+            ws.cancel_pending_action(cx);
+
+            ws.request_close_tab(0, cx);
+            assert_eq!(ws.pending_action, Some(PendingAction::PinnedTab));
+            ws.confirm_pending_action(cx);
+            assert_eq!(ws.tabs.len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_tab_switcher_orders_by_recent_use(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            let first_tab = ws.tabs[0].id;
+            let first_pane = ws.tabs[0].active_pane;
+            ws.note_active_pane();
+            ws.new_tab(cx);
+            ws.note_active_pane();
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            ws.note_active_pane();
+            let [left, right] = pane_ids(&ws.tabs[1].panes)[..] else {
+                panic!("expected two panes");
+            };
+
+            let order: Vec<Uuid> = ws.switcher_entries(cx).iter().map(|e| e.pane).collect();
+            assert_eq!(order, vec![right, left, first_pane]);
+
+            // Jumping back to the first tab puts it at the front
+            ws.switch_to_pane(first_tab, first_pane, cx);
+            assert_eq!(ws.active_tab, 0);
+            let order: Vec<Uuid> = ws.switcher_entries(cx).iter().map(|e| e.pane).collect();
+            assert_eq!(order, vec![first_pane, right, left]);
+
+            ws.switch_to_pane(ws.tabs[1].id, left, cx);
+            assert_eq!((ws.active_tab, ws.tabs[1].active_pane), (1, left));
+            assert_eq!(ws.recent_panes[..2], [left, first_pane]);
+        });
+    }
+
+    #[gpui::test]
+    fn test_move_tab_between_windows(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (source, _) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+        let (target, _) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        let (tab_id, terminal) = source.update(cx, |ws, cx| {
+            ws.new_tab(cx);
+            let tab = &ws.tabs[1];
+            let pane = tab.panes.find_pane(tab.active_pane).expect("active pane");
+            (tab.id, pane.as_terminal().cloned().expect("terminal pane"))
+        });
+
+        let tab = source.update(cx, |ws, cx| {
+            let tab = ws.take_tab(1, cx).expect("tab taken");
+            assert_eq!(ws.tabs.len(), 1);
+            assert_eq!(ws.active_tab, 0);
+            // Only the new window handles its input and exit from now on
+            assert_eq!(ws.terminal_subscriptions.len(), 1);
+            tab
+        });
+
+        target.update(cx, |ws, cx| {
+            ws.adopt_tab(tab, cx);
+            assert_eq!(ws.tabs.len(), 2);
+            assert_eq!(ws.active_tab, 1);
+            assert_eq!(ws.tabs[1].id, tab_id);
+            // The same session moves over, not a new one
+            let pane = ws.tabs[1].panes.find_pane(ws.tabs[1].active_pane);
+            assert!(pane.and_then(|pane| pane.as_terminal().cloned()) == Some(terminal.clone()));
+            assert!(!terminal.read(cx).has_exited());
+            assert_eq!(ws.terminal_subscriptions.len(), 2);
+        });
+
+        // Moving it back subscribes once again, not twice
+        let tab = target.update(cx, |ws, cx| ws.take_tab(1, cx).expect("tab taken"));
+        source.update(cx, |ws, cx| {
+            ws.adopt_tab(tab, cx);
+            let watched = ws
+                .terminal_subscriptions
+                .iter()
+                .filter(|(watched, _)| watched.entity_id() == terminal.entity_id())
+                .count();
+            assert_eq!(watched, 1);
+        });
+    }
+
+    #[gpui::test]
+    fn test_tab_groups_keep_their_tabs(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, _window, cx| {
+            ws.new_tab(cx);
+            ws.switch_tab(0, cx);
+            let default_tabs: Vec<Uuid> = ws.tabs.iter().map(|tab| tab.id).collect();
+
+            // A new group starts with one shell of its own
+            ws.new_group(cx);
+            assert_eq!(ws.groups.len(), 2);
+            assert_eq!(ws.active_group, 1);
+            assert_eq!(ws.tabs.len(), 1);
+            let api_tab = ws.tabs[0].id;
+            let api_pane = ws.tabs[0].active_pane;
+            ws.set_group_name(1, " api ", cx);
+            assert_eq!(ws.groups[1].name, "api");
+            ws.set_group_name(1, "", cx);
+            assert_eq!(ws.groups[1].name, "api");
+
+            // Switching back brings back the group's tabs and active tab
+            ws.prev_group(cx);
+            assert_eq!(ws.active_group, 0);
+            assert_eq!(
+                ws.tabs.iter().map(|tab| tab.id).collect::<Vec<_>>(),
+                default_tabs
+            );
+            assert_eq!(ws.active_tab, 0);
+            assert_eq!(ws.groups[1].tabs.len(), 1);
+
+            // Hidden tabs are listed in the switcher under their group's name
+            let entries = ws.switcher_entries(cx);
+            assert_eq!(entries.len(), 3);
+            assert!(entries
+                .iter()
+                .any(|entry| entry.tab == api_tab && entry.title.starts_with("api")));
+            ws.switch_to_pane(api_tab, api_pane, cx);
+            assert_eq!(ws.active_group, 1);
+            assert_eq!(ws.tabs[0].id, api_tab);
+
+            // Closing a group's last tab drops the group, not the window
+            ws.close_tab(0, cx);
+            assert_eq!(ws.groups.len(), 1);
+            assert_eq!(ws.active_group, 0);
+            assert_eq!(
+                ws.tabs.iter().map(|tab| tab.id).collect::<Vec<_>>(),
+                default_tabs
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_snapshot_restores_groups_and_splits(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            ws.set_tab_title(0, "logs", cx);
+            ws.set_tab_color(Some(TabColor::Red), cx);
+            ws.toggle_pin_tab(cx);
+            ws.new_group(cx);
+            ws.set_group_name(1, "infra", cx);
+
+            let snapshot = ws.snapshot(cx);
+            assert_eq!(snapshot.active_group, 1);
+            let logs = &snapshot.groups[0].tabs[0];
+            assert_eq!(logs.title.as_deref(), Some("logs"));
+            assert_eq!(logs.color.as_deref(), Some("red"));
+            assert!(logs.pinned);
+            assert!(matches!(
+                logs.layout,
+                LayoutSnapshot::Split {
+                    horizontal: true,
+                    ..
+                }
+            ));
+
+            let restored = cx.new(|cx| Workspace::restore(&snapshot, cx));
+            let restored = restored.read(cx);
+            assert_eq!(restored.active_group, 1);
+            assert_eq!(restored.groups[1].name, "infra");
+            assert_eq!(restored.tabs.len(), 1);
+            let tab = &restored.groups[0].tabs[0];
+            assert_eq!(tab.custom_title, Some("logs".into()));
+            assert_eq!(tab.color, Some(TabColor::Red));
+            assert!(tab.pinned);
+            assert_eq!(split_ratios(&tab.panes), vec![0.5]);
+        });
+    }
+
+    #[gpui::test]
+    fn test_reopen_closed_tab_and_pane(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.new_tab(cx);
+            ws.split_pane(SplitDirection::Horizontal, window, cx);
+            ws.set_tab_title(1, "logs", cx);
+            ws.set_tab_color(Some(TabColor::Blue), cx);
+            ws.close_tab(1, cx);
+            assert_eq!(ws.tabs.len(), 1);
+            assert_eq!(ws.closed_tabs.len(), 1);
+
+            // The tab comes back with its layout, name and color
+            ws.reopen_closed_tab(0, cx);
+            assert_eq!(ws.tabs.len(), 2);
+            assert_eq!(ws.active_tab, 1);
+            let tab = &ws.tabs[1];
+            assert_eq!(tab.custom_title, Some("logs".into()));
+            assert_eq!(tab.color, Some(TabColor::Blue));
+            assert_eq!(split_ratios(&tab.panes), vec![0.5]);
+            assert!(ws.closed_tabs.is_empty());
+
+            // A closed pane goes back into the tab it was in
+            ws.do_close_pane(cx);
+            assert_eq!(pane_ids(&ws.tabs[1].panes).len(), 1);
+            assert_eq!(ws.closed_tabs[0].pane_of, Some(ws.tabs[1].id));
+            ws.reopen_closed_tab(0, cx);
+            assert_eq!(pane_ids(&ws.tabs[1].panes).len(), 2);
+            assert_eq!(ws.tabs.len(), 2);
+
+            // Nothing left to reopen
+            ws.reopen_closed_tab(0, cx);
+            assert_eq!(ws.tabs.len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_run_command_targets(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.run_command("true", RunTarget::Tab, window, cx);
+            assert_eq!(ws.tabs.len(), 2);
+            assert_eq!(ws.active_tab, 1);
+            assert_eq!(ws.tabs[1].fallback_title.as_ref(), "true");
+
+            ws.run_command("false", RunTarget::Split, window, cx);
+            assert_eq!(pane_ids(&ws.tabs[1].panes).len(), 2);
+
+            ws.run_command("ls", RunTarget::Floating, window, cx);
+            let floating = ws.tabs[1].floating.as_ref().expect("floating pane created");
+            assert!(floating.visible && floating.focused);
+            // Held open after the command exits
+            assert!(!floating.terminal.read(cx).has_exited());
+        });
+    }
+
+    #[gpui::test]
+    fn test_run_command_keeps_existing_floating_shell(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.toggle_floating_pane(window, cx);
+            let shell = ws.tabs[0].floating.as_ref().unwrap().terminal.clone();
+
+            ws.run_command("ls", RunTarget::Floating, window, cx);
+            let floating = ws.tabs[0].floating.as_ref().expect("floating shell kept");
+            assert!(floating.terminal == shell);
+            // The command went to a split instead
+            assert_eq!(pane_ids(&ws.tabs[0].panes).len(), 2);
+            assert!(ws.closed_tabs.is_empty());
+        });
+    }
+
+    #[gpui::test]
+    fn test_floating_pane_toggle_keeps_shell(cx: &mut TestAppContext) {
+        init_test_context(cx);
+        let (workspace, vcx) = cx.add_window_view(|_window, cx| Workspace::new(cx));
+
+        vcx.update_window_entity(&workspace, |ws, window, cx| {
+            ws.toggle_floating_pane(window, cx);
+            let floating = ws.tabs[0].floating.as_ref().expect("floating pane created");
+            assert!(floating.visible && floating.focused);
+            let terminal = floating.terminal.clone();
+
+            // Hidden, not closed: the same shell comes back
+            ws.toggle_floating_pane(window, cx);
+            assert!(!ws.tabs[0].floating.as_ref().unwrap().visible);
+            ws.toggle_floating_pane(window, cx);
+            let floating = ws.tabs[0].floating.as_ref().unwrap();
+            assert!(floating.visible);
+            assert!(floating.terminal == terminal);
+
+            // Clicking a tiled pane takes focus; toggling gives it back first
+            let pane = ws.tabs[0].active_pane;
+            ws.set_active_pane(pane, cx);
+            assert!(!ws.tabs[0].floating.as_ref().unwrap().focused);
+            ws.toggle_floating_pane(window, cx);
+            let floating = ws.tabs[0].floating.as_ref().unwrap();
+            assert!(floating.visible && floating.focused);
+
+            // Closing while it has focus ends the floating shell, not the pane
+            ws.request_close_pane(cx);
+            assert!(ws.tabs[0].floating.is_none());
+            assert_eq!(ws.tabs.len(), 1);
+
+            // Each tab has its own
+            ws.new_tab(cx);
+            assert!(ws.tabs[1].floating.is_none());
+            // The split layout underneath is untouched
+            assert_eq!(ws.tabs[0].panes.all_panes().len(), 1);
+        });
+    }
+
     // ========================================================================
     // Concurrency Tests - Tab Operations Thread Safety
     // ========================================================================